@@ -856,8 +856,25 @@ export class CanvasManager {
             this.currentRings = originalRings;
           }
           
-          // Generate layout - this recomputes tile types based on pre-constraints
-          wasmModule.generate_layout();
+          // Generate layout - collapse only the tiles of the chunks being generated,
+          // so tiles of chunks generated earlier stay as they are and new tiles fit against them
+          const maskHexCoords = forceRecompute ? allHexCoords : newHexCoords;
+          const maskHexes: Array<{ q: number; r: number }> = [];
+          for (const hexKey of maskHexCoords) {
+            const parts = hexKey.split(',');
+            if (parts.length === 2) {
+              const q = Number.parseInt(parts[0] ?? '0', 10);
+              const r = Number.parseInt(parts[1] ?? '0', 10);
+              if (!Number.isNaN(q) && !Number.isNaN(r)) {
+                maskHexes.push({ q, r });
+              }
+            }
+          }
+          if (!wasmModule.generate_layout(undefined, undefined, undefined, JSON.stringify(maskHexes))) {
+            // Pre-constraints that break the adjacency rules cannot be collapsed; apply them as they are
+            this.log('Masked layout generation failed, applying pre-constraints directly', 'warning');
+            wasmModule.generate_layout();
+          }
           
           if (this.logFn) {
             if (forceRecompute) {
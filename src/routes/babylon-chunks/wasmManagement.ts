@@ -50,6 +50,9 @@ export class WasmManager {
       if (!('get_stats' in moduleUnknown) || typeof moduleUnknown.get_stats !== 'function') {
         throw new Error(`Module missing 'get_stats' export. Available: ${moduleKeys.join(', ')}`);
       }
+      if (!('set_seed' in moduleUnknown) || typeof moduleUnknown.set_seed !== 'function') {
+        throw new Error(`Module missing 'set_seed' export. Available: ${moduleKeys.join(', ')}`);
+      }
       if (!('generate_voronoi_regions' in moduleUnknown) || typeof moduleUnknown.generate_voronoi_regions !== 'function') {
         throw new Error(`Module missing 'generate_voronoi_regions' export. Available: ${moduleKeys.join(', ')}`);
       }
@@ -136,6 +139,7 @@ export class WasmManager {
     const setPreConstraintValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'set_pre_constraint') : getProperty(exports, 'set_pre_constraint');
     const clearPreConstraintsValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'clear_pre_constraints') : getProperty(exports, 'clear_pre_constraints');
     const getStatsValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'get_stats') : getProperty(exports, 'get_stats');
+    const setSeedValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'set_seed') : getProperty(exports, 'set_seed');
     const generateVoronoiRegionsValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'generate_voronoi_regions') : getProperty(exports, 'generate_voronoi_regions');
     const validateRoadConnectivityValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'validate_road_connectivity') : getProperty(exports, 'validate_road_connectivity');
     const hexAstarValue = this.wasmModuleRecord ? getProperty(this.wasmModuleRecord, 'hex_astar') : getProperty(exports, 'hex_astar');
@@ -172,6 +176,9 @@ export class WasmManager {
     if (typeof getStatsValue !== 'function') {
       missingExports.push('get_stats (function)');
     }
+    if (typeof setSeedValue !== 'function') {
+      missingExports.push('set_seed (function)');
+    }
     if (typeof generateVoronoiRegionsValue !== 'function') {
       missingExports.push('generate_voronoi_regions (function)');
     }
@@ -237,6 +244,7 @@ export class WasmManager {
     const setPreConstraintFunc = setPreConstraintValue;
     const clearPreConstraintsFunc = clearPreConstraintsValue;
     const getStatsFunc = getStatsValue;
+    const setSeedFunc = setSeedValue;
     const generateVoronoiRegionsFunc = generateVoronoiRegionsValue;
     const validateRoadConnectivityFunc = validateRoadConnectivityValue;
     const hexAstarFunc = hexAstarValue;
@@ -262,6 +270,7 @@ export class WasmManager {
       typeof setPreConstraintFunc !== 'function' ||
       typeof clearPreConstraintsFunc !== 'function' ||
       typeof getStatsFunc !== 'function' ||
+      typeof setSeedFunc !== 'function' ||
       typeof generateVoronoiRegionsFunc !== 'function' ||
       typeof validateRoadConnectivityFunc !== 'function' ||
       typeof hexAstarFunc !== 'function' ||
@@ -288,9 +297,10 @@ export class WasmManager {
     // We wrap them in functions with proper types to avoid type assertions
     return {
       memory,
-      generate_layout: (): void => {
-        // eslint-disable-next-line @typescript-eslint/no-unsafe-call
-        generateLayoutFunc();
+      generate_layout: (max_layer?: number, center_q?: number, center_r?: number, mask_json?: string): boolean => {
+        // eslint-disable-next-line @typescript-eslint/no-unsafe-call, @typescript-eslint/no-unsafe-assignment
+        const result = generateLayoutFunc(max_layer, center_q, center_r, mask_json);
+        return typeof result === 'boolean' ? result : false;
       },
       get_tile_at: (x: number, y: number): number => {
         // eslint-disable-next-line @typescript-eslint/no-unsafe-call, @typescript-eslint/no-unsafe-return
@@ -314,6 +324,10 @@ export class WasmManager {
         const result = getStatsFunc();
         return typeof result === 'string' ? result : '{}';
       },
+      set_seed: (seed: bigint): void => {
+        // eslint-disable-next-line @typescript-eslint/no-unsafe-call
+        setSeedFunc(seed);
+      },
       generate_voronoi_regions: (
         max_layer: number,
         center_q: number,
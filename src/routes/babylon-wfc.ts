@@ -111,6 +111,9 @@ const getInitWasm = async (): Promise<unknown> => {
     if (!('get_stats' in moduleUnknown) || typeof moduleUnknown.get_stats !== 'function') {
       throw new Error(`Module missing 'get_stats' export. Available: ${moduleKeys.join(', ')}`);
     }
+    if (!('set_seed' in moduleUnknown) || typeof moduleUnknown.set_seed !== 'function') {
+      throw new Error(`Module missing 'set_seed' export. Available: ${moduleKeys.join(', ')}`);
+    }
     if (!('generate_voronoi_regions' in moduleUnknown) || typeof moduleUnknown.generate_voronoi_regions !== 'function') {
       throw new Error(`Module missing 'generate_voronoi_regions' export. Available: ${moduleKeys.join(', ')}`);
     }
@@ -263,6 +266,9 @@ function validateBabylonWfcModule(exports: unknown): WasmModuleBabylonWfc | null
     if (typeof wasmModuleRecord.get_stats !== 'function') {
       missingExports.push('get_stats (function)');
     }
+    if (typeof wasmModuleRecord.set_seed !== 'function') {
+      missingExports.push('set_seed (function)');
+    }
     if (typeof wasmModuleRecord.generate_voronoi_regions !== 'function') {
       missingExports.push('generate_voronoi_regions (function)');
     }
@@ -299,6 +305,7 @@ function validateBabylonWfcModule(exports: unknown): WasmModuleBabylonWfc | null
   const setPreConstraintFunc = wasmModuleRecord.set_pre_constraint;
   const clearPreConstraintsFunc = wasmModuleRecord.clear_pre_constraints;
   const getStatsFunc = wasmModuleRecord.get_stats;
+  const setSeedFunc = wasmModuleRecord.set_seed;
   const generateVoronoiRegionsFunc = wasmModuleRecord.generate_voronoi_regions;
   const validateRoadConnectivityFunc = wasmModuleRecord.validate_road_connectivity;
   const hexAstarFunc = wasmModuleRecord.hex_astar;
@@ -312,6 +319,7 @@ function validateBabylonWfcModule(exports: unknown): WasmModuleBabylonWfc | null
     typeof setPreConstraintFunc !== 'function' ||
     typeof clearPreConstraintsFunc !== 'function' ||
     typeof getStatsFunc !== 'function' ||
+    typeof setSeedFunc !== 'function' ||
     typeof generateVoronoiRegionsFunc !== 'function' ||
     typeof validateRoadConnectivityFunc !== 'function' ||
     typeof hexAstarFunc !== 'function' ||
@@ -352,6 +360,10 @@ function validateBabylonWfcModule(exports: unknown): WasmModuleBabylonWfc | null
       const result = getStatsFunc();
       return typeof result === 'string' ? result : '{}';
     },
+    set_seed: (seed: bigint): void => {
+      // eslint-disable-next-line @typescript-eslint/no-unsafe-call
+      setSeedFunc(seed);
+    },
     generate_voronoi_regions: (
       max_layer: number,
       center_q: number,
@@ -2285,6 +2297,13 @@ function constraintsToPreConstraints(
     addLogEntry(`Generating Voronoi regions: ${forestSeeds} forest, ${waterSeeds} water, ${grassSeeds} grass seeds`, 'info');
   }
 
+  // Fresh world seed per generation: each run differs, and a logged seed reproduces it
+  const worldSeed = crypto.getRandomValues(new BigUint64Array(1))[0];
+  WASM_BABYLON_WFC.wasmModule.set_seed(worldSeed);
+  if (addLogEntry !== null) {
+    addLogEntry(`World seed: ${worldSeed.toString()}`, 'info');
+  }
+
   const voronoiJson = WASM_BABYLON_WFC.wasmModule.generate_voronoi_regions(
     maxLayer,
     centerQ,
@@ -3028,10 +3047,6 @@ export const init = async (): Promise<void> => {
     }
     
     WASM_BABYLON_WFC.wasmModule = wasmModule;
-    
-    // Set up JavaScript random function for WASM
-    const globalObj: { [key: string]: unknown } = globalThis;
-    globalObj.js_random = (): number => Math.random();
   } catch (error) {
     if (errorEl) {
       const errorMsg = error instanceof Error ? error.message : 'Unknown error';
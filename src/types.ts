@@ -281,6 +281,7 @@ export interface WasmModuleBabylonWfc {
   clear_pre_constraints(): void;
   clear_layout(): void;
   get_stats(): string;
+  set_seed(seed: bigint): void;
   generate_voronoi_regions(
     max_layer: number,
     center_q: number,
@@ -316,6 +317,11 @@ export interface WasmModuleBabylonWfc {
  * WASM module interface for babylon-chunks (extends WasmModuleBabylonWfc with version info)
  */
 export interface WasmModuleBabylonChunks extends WasmModuleBabylonWfc {
+  /**
+   * Apply the pre-constraints; with max_layer or mask_json, also fill that area with WFC.
+   * Returns false if the area or mask could not be collapsed.
+   */
+  generate_layout(max_layer?: number, center_q?: number, center_r?: number, mask_json?: string): boolean;
   get_wasm_version(): string;
   calculate_chunk_radius(rings: number): number;
   calculate_chunk_neighbors(center_q: number, center_r: number, rings: number): string;
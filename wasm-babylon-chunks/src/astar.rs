@@ -2,91 +2,243 @@
 
 use wasm_bindgen::prelude::*;
 use std::collections::{HashMap, HashSet, BinaryHeap};
-use crate::types::AStarNode;
-use crate::hex_utils::{get_hex_neighbors, parse_valid_terrain_json, axial_to_cube, cube_distance, hex_distance};
-
-/// Hex A* pathfinding between two road tiles
-/// Returns path length, or -1 if unreachable
-/// Only considers road tiles as valid path nodes
-/// 
-/// Algorithm matches Python example:
-/// - Uses f_cost = g_cost + h_cost for priority
-/// - g_cost is path cost from start (uniform cost of 1 per step)
-/// - h_cost is hex distance heuristic
-/// - Explores nodes with lowest f_cost first
-pub fn hex_astar_path(
+use crate::types::{AStarNode, WeightedNode};
+use crate::influence::INFLUENCE_MAPS;
+use crate::hex_utils::{get_hex_neighbors, parse_valid_terrain_json, parse_path_json, axial_to_cube, cube_distance, hex_distance, hex_line, hex_set_from_flat, hexes_to_flat, CUBE_DIRECTIONS};
+use crate::connectivity::{components_json, Connectivity};
+use crate::movement::movement_profile;
+use crate::state::WFC_STATE;
+use crate::errors::{envelope, parse_hex_set_param, parse_hex_values_param, profile_param, unknown_handle, ApiError, ErrorCode};
+use crate::json::parse_json;
+
+/// Hex A* search over a set of valid terrain hexes
+/// Shared by hex_astar and the generators that need paths internally
+/// (avoids serializing terrain to JSON just to call the exported function).
+/// 
+/// @returns Path from start to goal (both included), or None if unreachable
+pub fn find_path(
     start_q: i32,
     start_r: i32,
     goal_q: i32,
     goal_r: i32,
-    roads: &HashSet<(i32, i32)>,
-) -> i32 {
-    // Check if start and goal are roads
-    if !roads.contains(&(start_q, start_r)) || !roads.contains(&(goal_q, goal_r)) {
-        return -1;
-    }
+    valid_terrain: &HashSet<(i32, i32)>,
+) -> Option<Vec<(i32, i32)>> {
+    search_path(start_q, start_r, goal_q, goal_r, valid_terrain, false, f64::INFINITY).map(|(path, _)| path)
+}
 
-    // If start equals goal, path length is 0
+/// Hex A* search that can fall back to a partial path
+/// With `allow_partial`, an unreachable (or invalid) goal yields the path to the explored
+/// hex closest to the goal (ties broken by fewer steps) instead of None.
+/// Hexes that can't lie on a path of at most `max_cost` steps are never explored
+/// (f64::INFINITY = no limit), so an unreachable goal can't flood the whole terrain.
+/// 
+/// @returns Path from start (included) and whether it reaches the goal, or None if no path
+pub fn search_path(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain: &HashSet<(i32, i32)>,
+    allow_partial: bool,
+    max_cost: f64,
+) -> Option<(Vec<(i32, i32)>, bool)> {
+    // Check if start and goal are in valid terrain
+    if !valid_terrain.contains(&(start_q, start_r)) {
+        return None;
+    }
+    if !allow_partial && !valid_terrain.contains(&(goal_q, goal_r)) {
+        return None;
+    }
+    
+    // If start equals goal, return path with single node
     if start_q == goal_q && start_r == goal_r {
-        return 0;
+        return Some((vec![(start_q, start_r)], true));
     }
-
-    // Calculate heuristic (hex distance) - now using correct formula
-    let h_start = hex_distance(start_q, start_r, goal_q, goal_r);
-
+    
+    // Convert goal to cube for distance calculation (matches TypeScript)
+    let goal_cube = axial_to_cube(goal_q, goal_r);
+    
+    // Calculate heuristic function (cube distance)
+    let heuristic = |q: i32, r: i32| -> i32 {
+        let cube = axial_to_cube(q, r);
+        cube_distance(cube, goal_cube)
+    };
+    
+    // Initialize A* data structures
+    let h_start = heuristic(start_q, start_r);
     let mut open_set = BinaryHeap::new();
     let mut closed_set = HashSet::new();
     let mut g_scores: HashMap<(i32, i32), i32> = HashMap::new();
-
+    let mut parents: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    
+    // Start node (parent is itself to mark as root)
     open_set.push(AStarNode::new(start_q, start_r, 0, h_start, start_q, start_r));
     g_scores.insert((start_q, start_r), 0);
-
+    
+    // Closest explored hex to the goal as (h, g, hex), for partial paths
+    let mut closest = (h_start, 0, (start_q, start_r));
+    
     while let Some(current) = open_set.pop() {
         let current_key = (current.q, current.r);
-
+        
         // Skip if already processed (duplicate in open_set)
         if closed_set.contains(&current_key) {
             continue;
         }
-
+        
         closed_set.insert(current_key);
-
+        
         // Check if we reached the goal
         if current.q == goal_q && current.r == goal_r {
-            return current.g;
+            return Some((reconstruct_path(&parents, (start_q, start_r), current_key), true));
         }
-
-        // Explore neighbors - get all 6 hex neighbors
+        
+        let h_current = heuristic(current.q, current.r);
+        if (h_current, current.g) < (closest.0, closest.1) {
+            closest = (h_current, current.g, current_key);
+        }
+        
+        // Explore neighbors
         let neighbors = get_hex_neighbors(current.q, current.r);
         for (nq, nr) in neighbors {
             let neighbor_key = (nq, nr);
-
-            // Skip if not a road (obstacle check)
-            if !roads.contains(&neighbor_key) {
+            
+            // Skip if not in valid terrain
+            if !valid_terrain.contains(&neighbor_key) {
                 continue;
             }
-
+            
             // Skip if already closed
             if closed_set.contains(&neighbor_key) {
                 continue;
             }
-
+            
             // Calculate tentative g score (uniform cost of 1 per step)
             let tentative_g = current.g + 1;
-
-            // Check if this is a better path (matches Python: if neighbor not in g_cost or tentative_g < g_cost[neighbor])
+            
+            // Skip if every path through the neighbor would exceed the budget
+            let h = heuristic(nq, nr);
+            if (tentative_g + h) as f64 > max_cost {
+                continue;
+            }
+            
+            // Check if this is a better path
             let current_g = g_scores.get(&neighbor_key).copied().unwrap_or(i32::MAX);
             if tentative_g < current_g {
                 // This path to neighbor is better - record it
                 g_scores.insert(neighbor_key, tentative_g);
-                let h = hex_distance(nq, nr, goal_q, goal_r);
+                parents.insert(neighbor_key, (current.q, current.r));
                 open_set.push(AStarNode::new(nq, nr, tentative_g, h, current.q, current.r));
             }
         }
     }
-
+    
     // No path found
-    -1
+    if allow_partial {
+        Some((reconstruct_path(&parents, (start_q, start_r), closest.2), false))
+    } else {
+        None
+    }
+}
+
+/// Follow parent pointers from `end` back to `start`
+/// @returns Path from start to end (both included)
+fn reconstruct_path(
+    parents: &HashMap<(i32, i32), (i32, i32)>,
+    start: (i32, i32),
+    end: (i32, i32),
+) -> Vec<(i32, i32)> {
+    let mut path = vec![end];
+    let mut node_key = end;
+    while node_key != start {
+        match parents.get(&node_key) {
+            Some(parent_key) => {
+                node_key = *parent_key;
+                path.push(node_key);
+            }
+            None => {
+                // No parent means a broken chain; anchor the path at start anyway
+                path.push(start);
+                break;
+            }
+        }
+    }
+    
+    // Reverse path to get start-to-goal order
+    path.reverse();
+    path
+}
+
+
+/// Search valid terrain with a registered movement profile
+/// Shared by the exported entry points that take an optional profile name.
+/// 
+/// @returns Path, its cost and whether it reaches the goal, or None if the profile is
+///   unknown or there is no path
+fn search_with_profile<F>(
+    profile_name: &str,
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    extra_cost: F,
+    allow_partial: bool,
+    max_cost: f64,
+) -> Option<WeightedPath>
+where
+    F: Fn((i32, i32)) -> f64,
+{
+    let profile = movement_profile(profile_name)?;
+    let state = WFC_STATE.lock().unwrap();
+    profile.search(&state, start, goal, Some(valid_terrain), extra_cost, allow_partial, max_cost)
+}
+
+/// Soft avoidance penalties from a `[{"q":0,"r":0,"value":5},...]` parameter, skipping malformed entries
+fn parse_penalties(penalties_json: Option<String>) -> HashMap<(i32, i32), f64> {
+    penalties_json
+        .and_then(|json| parse_json(&json))
+        .map(|value| value.as_hex_values())
+        .unwrap_or_default()
+}
+
+/// Optional arguments shared by the exported hex_astar variants
+struct SearchOptions {
+    /// Movement profile name (None = uniform steps)
+    profile: Option<String>,
+    /// Fall back to a path toward the closest explored hex
+    allow_partial: bool,
+    /// Path cost budget (f64::INFINITY = no limit)
+    max_cost: f64,
+    /// Extra cost for entering a hex
+    penalties: HashMap<(i32, i32), f64>,
+}
+
+impl SearchOptions {
+    fn new(profile: Option<String>, allow_partial: bool, max_cost: Option<f64>) -> Self {
+        SearchOptions {
+            profile,
+            allow_partial,
+            max_cost: max_cost.filter(|cost| !cost.is_nan()).unwrap_or(f64::INFINITY),
+            penalties: HashMap::new(),
+        }
+    }
+
+    fn penalty(&self, hex: (i32, i32)) -> f64 {
+        self.penalties.get(&hex).copied().unwrap_or(0.0).max(0.0)
+    }
+}
+
+/// Path search shared by the exported hex_astar variants
+/// 
+/// @returns Path and whether it reaches the goal, or None if there is no path (or the profile is unknown)
+fn search(start: (i32, i32), goal: (i32, i32), valid_terrain: &HashSet<(i32, i32)>, options: &SearchOptions) -> Option<(Vec<(i32, i32)>, bool)> {
+    let (allow_partial, max_cost) = (options.allow_partial, options.max_cost);
+    match &options.profile {
+        Some(name) => search_with_profile(name, start, goal, valid_terrain, |hex| options.penalty(hex), allow_partial, max_cost)
+            .map(|(path, _, complete)| (path, complete)),
+        None if options.penalties.is_empty() => search_path(start.0, start.1, goal.0, goal.1, valid_terrain, allow_partial, max_cost),
+        None => search_path_weighted(start, goal, valid_terrain, |_, to| 1.0 + options.penalty(to), allow_partial, max_cost)
+            .map(|(path, _, complete)| (path, complete)),
+    }
 }
 
 /// Hex A* pathfinding that returns full path
@@ -102,7 +254,15 @@ pub fn hex_astar_path(
 /// @param goal_q - Goal q coordinate (axial)
 /// @param goal_r - Goal r coordinate (axial)
 /// @param valid_terrain_json - JSON string with array of valid terrain coordinates: [{"q":0,"r":0},...]
-/// @returns JSON string with path array [{"q":0,"r":0},...] or "null" if no path found
+/// @param profile - Optional movement profile name (see register_movement_profile); with a profile,
+///   steps are priced by the profile and valid hexes it cannot enter are skipped
+/// @param max_cost - Optional path cost budget (steps, or profile cost in units of its cheapest tile);
+///   the search gives up once no path within it can exist instead of exploring all connected terrain
+/// @param penalties_json - Optional soft avoidance: JSON array of extra costs for entering hexes
+///   [{"q":0,"r":0,"value":5},...] (e.g. enemy zones, construction sites); paths route around them
+///   when the detour is cheaper but still pass through if there is no alternative
+/// @returns JSON string with path array [{"q":0,"r":0},...] or "null" if no path found (or unknown profile)
+#[allow(clippy::too_many_arguments)]
 #[wasm_bindgen]
 pub fn hex_astar(
     start_q: i32,
@@ -110,122 +270,526 @@ pub fn hex_astar(
     goal_q: i32,
     goal_r: i32,
     valid_terrain_json: String,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties_json: Option<String>,
 ) -> String {
     // Parse valid terrain from JSON
     let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let mut options = SearchOptions::new(profile, false, max_cost);
+    options.penalties = parse_penalties(penalties_json);
     
-    // Check if start and goal are in valid terrain
-    if !valid_terrain.contains(&(start_q, start_r)) || !valid_terrain.contains(&(goal_q, goal_r)) {
-        return "null".to_string();
+    match search((start_q, start_r), (goal_q, goal_r), &valid_terrain, &options) {
+        Some((path, _)) => {
+            // Build JSON string
+            let mut json_parts = Vec::new();
+            for (q, r) in path {
+                json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
+            }
+            format!("[{}]", json_parts.join(","))
+        }
+        None => "null".to_string(),
     }
-    
-    // If start equals goal, return path with single node
-    if start_q == goal_q && start_r == goal_r {
-        return format!(r#"[{{"q":{},"r":{}}}]"#, start_q, start_r);
+}
+
+/// Hex A* pathfinding over a flat valid terrain buffer
+/// 
+/// Same search as hex_astar, without building and re-parsing JSON for the
+/// terrain or the path (long paths make that the dominant cost).
+/// 
+/// @param start_q - Start q coordinate (axial)
+/// @param start_r - Start r coordinate (axial)
+/// @param goal_q - Goal q coordinate (axial)
+/// @param goal_r - Goal r coordinate (axial)
+/// @param valid_terrain - Int32Array of interleaved valid terrain coordinates: [q0, r0, q1, r1, ...]
+/// @param profile - Optional movement profile name (see hex_astar)
+/// @param max_cost - Optional path cost budget (see hex_astar)
+/// @param penalties - Optional Float64Array of soft avoidance costs, 3 values per hex: [q0, r0, cost0, ...]
+///   (see hex_astar; a trailing incomplete triple is ignored)
+/// @returns Int32Array of interleaved path coordinates from start to goal (both included); empty if
+///          no path found (or unknown profile)
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_buf(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain: &[i32],
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties: Option<Vec<f64>>,
+) -> Vec<i32> {
+    let valid_terrain = hex_set_from_flat(valid_terrain);
+    let mut options = SearchOptions::new(profile, false, max_cost);
+    options.penalties = penalties.unwrap_or_default()
+        .chunks_exact(3)
+        .map(|entry| ((entry[0] as i32, entry[1] as i32), entry[2]))
+        .collect();
+    search((start_q, start_r), (goal_q, goal_r), &valid_terrain, &options)
+        .map(|(path, _)| hexes_to_flat(&path))
+        .unwrap_or_default()
+}
+
+/// hex_astar with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json" (malformed valid terrain), "unknown_profile" or "no_path" (also when no path
+///   fits within max_cost); malformed penalties are "invalid_json"
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_result(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain_json: String,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties_json: Option<String>,
+) -> String {
+    let options = SearchOptions::new(profile, false, max_cost);
+    envelope(checked_search((start_q, start_r), (goal_q, goal_r), &valid_terrain_json, penalties_json.as_deref(), options).map(|(path, _)| {
+        let json_parts: Vec<String> = path.iter()
+            .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+            .collect();
+        format!("[{}]", json_parts.join(","))
+    }))
+}
+
+/// Validate the inputs of a path search and run it, for the `_result` variants
+/// 
+/// @returns Path and whether it reaches the goal
+fn checked_search(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain_json: &str,
+    penalties_json: Option<&str>,
+    mut options: SearchOptions,
+) -> Result<(Vec<(i32, i32)>, bool), ApiError> {
+    let valid_terrain = parse_hex_set_param(valid_terrain_json, "valid_terrain_json")?;
+    if let Some(json) = penalties_json {
+        options.penalties = parse_hex_values_param(json, "penalties_json")?;
+    }
+    if let Some(name) = &options.profile {
+        profile_param(name)?;
     }
+    search(start, goal, &valid_terrain, &options).ok_or_else(|| no_path_error(start, goal))
+}
+
+/// Hex A* pathfinding that falls back to a partial path
+/// 
+/// **Learning Point**: When the goal is walled off, hex_astar returns "null" and an
+/// NPC has nowhere to go. This variant keeps track of the explored hex closest to
+/// the goal and, if the goal is never reached, returns the path to that hex instead
+/// (flagged as incomplete), so the NPC walks up to the blockade.
+/// 
+/// @param start_q - Start q coordinate (axial)
+/// @param start_r - Start r coordinate (axial)
+/// @param goal_q - Goal q coordinate (axial)
+/// @param goal_r - Goal r coordinate (axial); may lie outside the valid terrain
+/// @param valid_terrain_json - JSON string with array of valid terrain coordinates: [{"q":0,"r":0},...]
+/// @param profile - Optional movement profile name (see hex_astar)
+/// @param max_cost - Optional path cost budget (see hex_astar); if the goal can't be reached within it,
+///   the partial path leads to the closest hex that can
+/// @param penalties_json - Optional soft avoidance costs (see hex_astar)
+/// @returns JSON object {"path":[{"q":0,"r":0},...],"complete":false}, or "null" if the start is not valid terrain
+///   (or the profile is unknown)
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_partial(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain_json: String,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties_json: Option<String>,
+) -> String {
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let mut options = SearchOptions::new(profile, true, max_cost);
+    options.penalties = parse_penalties(penalties_json);
     
-    // Convert goal to cube for distance calculation (matches TypeScript)
-    let goal_cube = axial_to_cube(goal_q, goal_r);
+    match search((start_q, start_r), (goal_q, goal_r), &valid_terrain, &options) {
+        Some((path, complete)) => {
+            let json_parts: Vec<String> = path.iter()
+                .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+                .collect();
+            format!(r#"{{"path":[{}],"complete":{}}}"#, json_parts.join(","), complete)
+        }
+        None => "null".to_string(),
+    }
+}
+
+/// hex_astar_partial with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":{"path":[...],"complete":false},"error":null}, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json", "unknown_profile" or "no_path" (the start is not valid terrain)
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_partial_result(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain_json: String,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties_json: Option<String>,
+) -> String {
+    let options = SearchOptions::new(profile, true, max_cost);
+    envelope(checked_search((start_q, start_r), (goal_q, goal_r), &valid_terrain_json, penalties_json.as_deref(), options).map(|(path, complete)| {
+        let json_parts: Vec<String> = path.iter()
+            .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+            .collect();
+        format!(r#"{{"path":[{}],"complete":{}}}"#, json_parts.join(","), complete)
+    }))
+}
+
+/// Hex A* search with per-step costs
+/// `step_cost(from, to)` returns the cost of entering `to` from `from`; it must be at
+/// least 1.0 so the hex-distance heuristic stays admissible.
+/// 
+/// @returns Path from start to goal (both included) and its total cost, or None if unreachable
+pub fn find_path_weighted<F>(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    step_cost: F,
+) -> Option<(Vec<(i32, i32)>, f64)>
+where
+    F: Fn((i32, i32), (i32, i32)) -> f64,
+{
+    search_path_weighted(start, goal, valid_terrain, step_cost, false, f64::INFINITY).map(|(path, cost, _)| (path, cost))
+}
+
+/// Path, its total cost and whether it reaches the goal
+pub type WeightedPath = (Vec<(i32, i32)>, f64, bool);
+
+/// Weighted hex A* that can fall back to a partial path
+/// Same contract as find_path_weighted; a step costing f64::INFINITY is not allowed.
+/// With `allow_partial`, an unreachable (or invalid) goal yields the path to the explored
+/// hex closest to the goal (ties broken by lower cost) instead of None.
+/// Hexes that can't lie on a path costing at most `max_cost` are never explored
+/// (f64::INFINITY = no limit).
+/// 
+/// @returns Path from start (included), its total cost and whether it reaches the goal,
+///   or None if no path
+pub fn search_path_weighted<F>(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    step_cost: F,
+    allow_partial: bool,
+    max_cost: f64,
+) -> Option<WeightedPath>
+where
+    F: Fn((i32, i32), (i32, i32)) -> f64,
+{
+    if !valid_terrain.contains(&start) {
+        return None;
+    }
+    if !allow_partial && !valid_terrain.contains(&goal) {
+        return None;
+    }
     
-    // Calculate heuristic function (cube distance)
-    let heuristic = |q: i32, r: i32| -> i32 {
-        let cube = axial_to_cube(q, r);
-        cube_distance(cube, goal_cube)
-    };
+    let heuristic = |(q, r): (i32, i32)| hex_distance(q, r, goal.0, goal.1) as f64;
     
-    // Initialize A* data structures
-    let h_start = heuristic(start_q, start_r);
     let mut open_set = BinaryHeap::new();
     let mut closed_set = HashSet::new();
-    let mut g_scores: HashMap<(i32, i32), i32> = HashMap::new();
+    let mut g_scores: HashMap<(i32, i32), f64> = HashMap::new();
     let mut parents: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
     
-    // Start node (parent is itself to mark as root)
-    open_set.push(AStarNode::new(start_q, start_r, 0, h_start, start_q, start_r));
-    g_scores.insert((start_q, start_r), 0);
+    open_set.push(WeightedNode { q: start.0, r: start.1, g: 0.0, f: heuristic(start) });
+    g_scores.insert(start, 0.0);
+    
+    // Closest explored hex to the goal as (h, g, hex), for partial paths
+    let mut closest = (heuristic(start), 0.0, start);
     
     while let Some(current) = open_set.pop() {
         let current_key = (current.q, current.r);
         
         // Skip if already processed (duplicate in open_set)
-        if closed_set.contains(&current_key) {
+        if !closed_set.insert(current_key) {
             continue;
         }
         
-        closed_set.insert(current_key);
+        if current_key == goal {
+            return Some((reconstruct_path(&parents, start, goal), current.g, true));
+        }
         
-        // Check if we reached the goal
-        if current.q == goal_q && current.r == goal_r {
-            // Reconstruct path by following parent pointers
-            let mut path: Vec<(i32, i32)> = Vec::new();
-            let mut node_key = (goal_q, goal_r);
-            
-            // Follow parent pointers from goal to start
-            loop {
-                path.push(node_key);
-                
-                // Get parent for this node
-                if let Some(parent_key) = parents.get(&node_key) {
-                    // If parent is the start, add it and break
-                    if parent_key.0 == start_q && parent_key.1 == start_r {
-                        path.push((start_q, start_r));
-                        break;
-                    }
-                    node_key = *parent_key;
-                } else {
-                    // No parent in map means we're at start (shouldn't happen in normal flow)
-                    // But handle it just in case
-                    if node_key.0 != start_q || node_key.1 != start_r {
-                        path.push((start_q, start_r));
-                    }
-                    break;
-                }
-            }
-            
-            // Reverse path to get start-to-goal order
-            path.reverse();
-            
-            // Build JSON string
-            let mut json_parts = Vec::new();
-            for (q, r) in path {
-                json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
-            }
-            
-            return format!("[{}]", json_parts.join(","));
+        let h_current = heuristic(current_key);
+        if h_current < closest.0 || (h_current == closest.0 && current.g < closest.1) {
+            closest = (h_current, current.g, current_key);
         }
         
-        // Explore neighbors
-        let neighbors = get_hex_neighbors(current.q, current.r);
-        for (nq, nr) in neighbors {
-            let neighbor_key = (nq, nr);
-            
-            // Skip if not in valid terrain
-            if !valid_terrain.contains(&neighbor_key) {
+        for neighbor in get_hex_neighbors(current.q, current.r) {
+            if !valid_terrain.contains(&neighbor) || closed_set.contains(&neighbor) {
                 continue;
             }
             
-            // Skip if already closed
-            if closed_set.contains(&neighbor_key) {
+            let cost = step_cost(current_key, neighbor);
+            if !cost.is_finite() {
                 continue;
             }
-            
-            // Calculate tentative g score (uniform cost of 1 per step)
-            let tentative_g = current.g + 1;
-            
-            // Check if this is a better path
-            let current_g = g_scores.get(&neighbor_key).copied().unwrap_or(i32::MAX);
+            let tentative_g = current.g + cost;
+            let f = tentative_g + heuristic(neighbor);
+            if f > max_cost {
+                continue;
+            }
+            let current_g = g_scores.get(&neighbor).copied().unwrap_or(f64::INFINITY);
             if tentative_g < current_g {
-                // This path to neighbor is better - record it
-                g_scores.insert(neighbor_key, tentative_g);
-                parents.insert(neighbor_key, (current.q, current.r));
-                let h = heuristic(nq, nr);
-                open_set.push(AStarNode::new(nq, nr, tentative_g, h, current.q, current.r));
+                g_scores.insert(neighbor, tentative_g);
+                parents.insert(neighbor, current_key);
+                open_set.push(WeightedNode {
+                    q: neighbor.0,
+                    r: neighbor.1,
+                    g: tentative_g,
+                    f,
+                });
             }
         }
     }
     
-    // No path found
-    "null".to_string()
+    if allow_partial {
+        Some((reconstruct_path(&parents, start, closest.2), closest.1, false))
+    } else {
+        None
+    }
+}
+
+/// Hex and the CUBE_DIRECTIONS index it was entered with (6 = the start, which has no heading yet)
+type HeadingState = ((i32, i32), usize);
+
+/// Search node for find_path_with_turns, ordered as a min-heap by f
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TurnNode {
+    hex: (i32, i32),
+    heading: usize,
+    g: f64,
+    f: f64,
+}
+
+impl Eq for TurnNode {}
+
+impl Ord for TurnNode {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Reverse order for min-heap
+        other.f.total_cmp(&self.f)
+            .then_with(|| other.hex.cmp(&self.hex))
+            .then_with(|| other.heading.cmp(&self.heading))
+    }
+}
+
+impl PartialOrd for TurnNode {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Weighted hex A* that also charges for changing heading
+/// 
+/// **Learning Point**: A plain hex A* has many equally short paths and picks one
+/// that zigzags between two directions. Charging `turn_penalty` per 60° of heading
+/// change (a 120° turn costs twice, a U-turn three times) makes long straight runs
+/// and gentle curves cheaper. The cost now depends on how a hex was entered, so the
+/// search runs over (hex, heading) states; hex distance stays an admissible heuristic
+/// because turns only add cost.
+/// 
+/// @returns Path from start to goal (both included) and its total cost, or None if unreachable
+pub fn find_path_with_turns<F>(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    step_cost: F,
+    turn_penalty: f64,
+) -> Option<(Vec<(i32, i32)>, f64)>
+where
+    F: Fn((i32, i32), (i32, i32)) -> f64,
+{
+    if !valid_terrain.contains(&start) || !valid_terrain.contains(&goal) {
+        return None;
+    }
+    let heuristic = |(q, r): (i32, i32)| hex_distance(q, r, goal.0, goal.1) as f64;
+    let turn_penalty = turn_penalty.max(0.0);
+    let start_state: HeadingState = (start, CUBE_DIRECTIONS.len());
+
+    let mut open_set = BinaryHeap::new();
+    let mut closed_set = HashSet::new();
+    let mut g_scores: HashMap<HeadingState, f64> = HashMap::new();
+    let mut parents: HashMap<HeadingState, HeadingState> = HashMap::new();
+    open_set.push(TurnNode { hex: start, heading: start_state.1, g: 0.0, f: heuristic(start) });
+    g_scores.insert(start_state, 0.0);
+
+    while let Some(current) = open_set.pop() {
+        let state = (current.hex, current.heading);
+        if !closed_set.insert(state) {
+            continue;
+        }
+        if current.hex == goal {
+            let mut path = vec![goal];
+            let mut node = state;
+            while let Some(&parent) = parents.get(&node) {
+                path.push(parent.0);
+                node = parent;
+            }
+            path.reverse();
+            return Some((path, current.g));
+        }
+
+        for (heading, offset) in CUBE_DIRECTIONS.iter().enumerate() {
+            let neighbor = (current.hex.0 + offset.q, current.hex.1 + offset.r);
+            if !valid_terrain.contains(&neighbor) || closed_set.contains(&(neighbor, heading)) {
+                continue;
+            }
+            let cost = step_cost(current.hex, neighbor);
+            if !cost.is_finite() {
+                continue;
+            }
+            let turn = if current.heading < CUBE_DIRECTIONS.len() {
+                let difference = current.heading.abs_diff(heading);
+                difference.min(6 - difference) as f64
+            } else {
+                0.0
+            };
+            let tentative_g = current.g + cost + turn * turn_penalty;
+            if tentative_g < g_scores.get(&(neighbor, heading)).copied().unwrap_or(f64::INFINITY) {
+                g_scores.insert((neighbor, heading), tentative_g);
+                parents.insert((neighbor, heading), state);
+                open_set.push(TurnNode { hex: neighbor, heading, g: tentative_g, f: tentative_g + heuristic(neighbor) });
+            }
+        }
+    }
+    None
+}
+
+/// Turn-penalized road path with uniform steps or a movement profile's costs
+/// 
+/// @returns Path from start to goal (both included), or None if unreachable (or the profile is unknown)
+fn turning_road_path(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    profile: Option<String>,
+    turn_penalty: f64,
+) -> Option<Vec<(i32, i32)>> {
+    match profile {
+        Some(name) => {
+            let profile = movement_profile(&name)?;
+            let state = WFC_STATE.lock().unwrap();
+            let scale = profile.min_cost();
+            find_path_with_turns(start, goal, valid_terrain, |from, to| {
+                profile.step_cost(state.get_tile(from.0, from.1), state.get_tile(to.0, to.1))
+                    .map(|cost| cost / scale)
+                    .unwrap_or(f64::INFINITY)
+            }, turn_penalty)
+        }
+        None => find_path_with_turns(start, goal, valid_terrain, |_, _| 1.0, turn_penalty),
+    }
+    .map(|(path, _)| path)
+}
+
+/// Danger-aware hex A* that adds influence map values to step costs
+/// 
+/// **Learning Point**: Entering a hex costs 1 + danger_weight * influence(hex), so
+/// caravans take longer but safer routes when the detour is cheaper than the danger.
+/// Negative influence values are treated as 0 to keep the heuristic admissible.
+/// 
+/// @param start_q - Start q coordinate (axial)
+/// @param start_r - Start r coordinate (axial)
+/// @param goal_q - Goal q coordinate (axial)
+/// @param goal_r - Goal r coordinate (axial)
+/// @param valid_terrain_json - JSON string with array of valid terrain coordinates: [{"q":0,"r":0},...]
+/// @param influence_map_handle - Handle returned by create_influence_map
+/// @param danger_weight - Multiplier applied to influence values
+/// @param profile - Optional movement profile name (see hex_astar); danger is added on top of
+///   the profile's step cost
+/// @returns JSON string with path array [{"q":0,"r":0},...] or "null" if no path found (or unknown handle or profile)
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn hex_astar_danger_aware(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain_json: String,
+    influence_map_handle: u32,
+    danger_weight: f64,
+    profile: Option<String>,
+) -> String {
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    danger_aware_path((start_q, start_r), (goal_q, goal_r), &valid_terrain, influence_map_handle, danger_weight, profile)
+        .map(|path| path_json(&path))
+        .unwrap_or_else(|_| "null".to_string())
+}
+
+/// hex_astar_danger_aware with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json", "unknown_handle", "unknown_profile" or "no_path"
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn hex_astar_danger_aware_result(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain_json: String,
+    influence_map_handle: u32,
+    danger_weight: f64,
+    profile: Option<String>,
+) -> String {
+    envelope(parse_hex_set_param(&valid_terrain_json, "valid_terrain_json")
+        .and_then(|valid_terrain| danger_aware_path(
+            (start_q, start_r),
+            (goal_q, goal_r),
+            &valid_terrain,
+            influence_map_handle,
+            danger_weight,
+            profile,
+        ))
+        .map(|path| path_json(&path)))
+}
+
+/// Danger-weighted search shared by hex_astar_danger_aware and its `_result` variant
+fn danger_aware_path(
+    start: (i32, i32),
+    goal: (i32, i32),
+    valid_terrain: &HashSet<(i32, i32)>,
+    influence_map_handle: u32,
+    danger_weight: f64,
+    profile: Option<String>,
+) -> Result<Vec<(i32, i32)>, ApiError> {
+    let maps = INFLUENCE_MAPS.lock().unwrap();
+    let influence = maps.get(influence_map_handle)
+        .ok_or_else(|| unknown_handle("influence map", influence_map_handle))?;
+    
+    let danger = |hex: (i32, i32)| danger_weight.max(0.0) * influence.get(&hex).copied().unwrap_or(0.0).max(0.0);
+    
+    let path = match profile {
+        Some(name) => {
+            profile_param(&name)?;
+            search_with_profile(&name, start, goal, valid_terrain, danger, false, f64::INFINITY)
+                .map(|(path, _, _)| path)
+        }
+        None => find_path_weighted(start, goal, valid_terrain, |_, to| 1.0 + danger(to))
+            .map(|(path, _)| path),
+    };
+    path.ok_or_else(|| no_path_error(start, goal))
+}
+
+/// The error for endpoints that no path connects
+fn no_path_error(start: (i32, i32), goal: (i32, i32)) -> ApiError {
+    ApiError::new(ErrorCode::NoPath, format!("no path from ({}, {}) to ({}, {})", start.0, start.1, goal.0, goal.1))
+}
+
+/// Format a path as a `[{"q":0,"r":0},...]` JSON array
+fn path_json(path: &[(i32, i32)]) -> String {
+    let json_parts: Vec<String> = path.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    format!("[{}]", json_parts.join(","))
 }
 
 /// Build a path between two road points using A* pathfinding
@@ -237,6 +801,9 @@ pub fn hex_astar(
 /// @param end_q - End q coordinate (axial)
 /// @param end_r - End r coordinate (axial)
 /// @param valid_terrain_json - JSON string with array of valid terrain coordinates: [{"q":0,"r":0},...]
+/// @param profile - Optional movement profile name (see hex_astar)
+/// @param turn_penalty - Optional extra cost per 60° of heading change (see find_path_with_turns); favors
+///   long straight segments and gentle curves over zigzags (default 0 = plain A*)
 /// @returns JSON string with path array excluding start, including end, or "null" if no path found
 #[wasm_bindgen]
 pub fn build_path_between_roads(
@@ -245,9 +812,22 @@ pub fn build_path_between_roads(
     end_q: i32,
     end_r: i32,
     valid_terrain_json: String,
+    profile: Option<String>,
+    turn_penalty: Option<f64>,
 ) -> String {
+    if let Some(turn_penalty) = turn_penalty.filter(|penalty| *penalty > 0.0) {
+        let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+        return match turning_road_path((start_q, start_r), (end_q, end_r), &valid_terrain, profile, turn_penalty) {
+            Some(path) if path.len() > 1 => {
+                let json_parts: Vec<String> = path[1..].iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+                format!("[{}]", json_parts.join(","))
+            }
+            _ => "null".to_string(),
+        };
+    }
+    
     // Call hex_astar to get full path
-    let full_path_json = hex_astar(start_q, start_r, end_q, end_r, valid_terrain_json);
+    let full_path_json = hex_astar(start_q, start_r, end_q, end_r, valid_terrain_json, profile, None, None);
     
     // If no path, return null
     if full_path_json == "null" || full_path_json.is_empty() {
@@ -333,110 +913,177 @@ pub fn build_path_between_roads(
     format!("[{}]", json_parts.join(","))
 }
 
-/// Validate that all road tiles are reachable from each other using A* pathfinding
+/// build_path_between_roads with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":[{"q":1,"r":0},...],"error":null} with the hexes after the start
+///   ([] when start and end coincide), or {"ok":null,"error":{"code","message"}} with code "invalid_json",
+///   "unknown_profile" or "no_path"
+#[wasm_bindgen]
+pub fn build_path_between_roads_result(
+    start_q: i32,
+    start_r: i32,
+    end_q: i32,
+    end_r: i32,
+    valid_terrain_json: String,
+    profile: Option<String>,
+    turn_penalty: Option<f64>,
+) -> String {
+    let (start, end) = ((start_q, start_r), (end_q, end_r));
+    let path = match turn_penalty.filter(|penalty| *penalty > 0.0) {
+        Some(turn_penalty) => parse_hex_set_param(&valid_terrain_json, "valid_terrain_json").and_then(|valid_terrain| {
+            if let Some(name) = &profile {
+                profile_param(name)?;
+            }
+            turning_road_path(start, end, &valid_terrain, profile, turn_penalty).ok_or_else(|| no_path_error(start, end))
+        }),
+        None => checked_search(start, end, &valid_terrain_json, None, SearchOptions::new(profile, false, None))
+            .map(|(path, _)| path),
+    };
+    envelope(path.map(|path| path_json(&path[1..])))
+}
+
+/// Build a path between two road points from a flat valid terrain buffer
 /// 
-/// Uses transitive property: if all roads are reachable from one source road,
-/// then all pairs have paths (by transitivity: A->B and B->C implies A->C).
+/// Same result as build_path_between_roads (without a profile), without building
+/// and re-parsing JSON.
 /// 
-/// @param roads_json - JSON string with array of road coordinates: [{"q":0,"r":0},{"q":1,"r":0},...]
-/// @returns true if all roads are reachable from source, false otherwise
+/// @param start_q - Start q coordinate (axial)
+/// @param start_r - Start r coordinate (axial)
+/// @param end_q - End q coordinate (axial)
+/// @param end_r - End r coordinate (axial)
+/// @param valid_terrain - Int32Array of interleaved valid terrain coordinates: [q0, r0, q1, r1, ...]
+/// @param turn_penalty - Optional extra cost per 60° of heading change (see build_path_between_roads)
+/// @returns Int32Array of interleaved path coordinates excluding start, including end; empty if no path
 #[wasm_bindgen]
-pub fn validate_road_connectivity(roads_json: String) -> bool {
-    // Parse roads from JSON
-    // Simple JSON parsing without serde to keep WASM size small
-    let mut roads: Vec<(i32, i32)> = Vec::new();
-    
-    // Remove whitespace and brackets
-    let trimmed = roads_json.trim();
-    if trimmed.is_empty() || trimmed == "[]" {
-        return true; // Empty roads is trivially connected
+pub fn build_path_between_roads_buf(
+    start_q: i32,
+    start_r: i32,
+    end_q: i32,
+    end_r: i32,
+    valid_terrain: &[i32],
+    turn_penalty: Option<f64>,
+) -> Vec<i32> {
+    let valid_terrain = hex_set_from_flat(valid_terrain);
+    let path = match turn_penalty.filter(|penalty| *penalty > 0.0) {
+        Some(turn_penalty) => turning_road_path((start_q, start_r), (end_q, end_r), &valid_terrain, None, turn_penalty),
+        None => find_path(start_q, start_r, end_q, end_r, &valid_terrain),
+    };
+    match path {
+        Some(path) if path.len() > 1 => hexes_to_flat(&path[1..]),
+        _ => Vec::new(),
     }
+}
 
-    // Simple JSON parsing: find all {"q":X,"r":Y} patterns
-    // This is a simplified parser that handles the expected format: [{"q":0,"r":0},...]
-    let mut i = 0;
-    let chars: Vec<char> = trimmed.chars().collect();
-    while i < chars.len() {
-        // Look for opening brace
-        if chars[i] == '{' {
-            let mut q_value: Option<i32> = None;
-            let mut r_value: Option<i32> = None;
-            
-            i += 1;
-            while i < chars.len() && chars[i] != '}' {
-                // Look for "q" or "r" followed by colon and number
-                if i + 3 < chars.len() && chars[i] == '"' && chars[i + 1] == 'q' && chars[i + 2] == '"' {
-                    i += 3;
-                    // Skip colon and whitespace
-                    while i < chars.len() && (chars[i] == ':' || chars[i] == ' ' || chars[i] == '\t') {
-                        i += 1;
-                    }
-                    // Parse number
-                    if i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '-') {
-                        let start = i;
-                        i += 1;
-                        while i < chars.len() && chars[i].is_ascii_digit() {
-                            i += 1;
-                        }
-                        let num_str: String = chars[start..i].iter().collect();
-                        if let Ok(num) = num_str.parse::<i32>() {
-                            q_value = Some(num);
-                        }
-                    }
-                } else if i + 3 < chars.len() && chars[i] == '"' && chars[i + 1] == 'r' && chars[i + 2] == '"' {
-                    i += 3;
-                    // Skip colon and whitespace
-                    while i < chars.len() && (chars[i] == ':' || chars[i] == ' ' || chars[i] == '\t') {
-                        i += 1;
-                    }
-                    // Parse number
-                    if i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '-') {
-                        let start = i;
-                        i += 1;
-                        while i < chars.len() && chars[i].is_ascii_digit() {
-                            i += 1;
-                        }
-                        let num_str: String = chars[start..i].iter().collect();
-                        if let Ok(num) = num_str.parse::<i32>() {
-                            r_value = Some(num);
-                        }
-                    }
-                } else {
-                    i += 1;
-                }
-            }
-            
-            if let (Some(q), Some(r)) = (q_value, r_value) {
-                roads.push((q, r));
-            }
-        }
-        i += 1;
+/// Shorten a hex path by replacing detours with straight lines wherever the line stays in `open`
+/// The path must not be empty.
+pub fn straighten_path(path: &[(i32, i32)], open: impl Fn((i32, i32)) -> bool) -> Vec<(i32, i32)> {
+    let mut straight = vec![path[0]];
+    let mut from = 0;
+    while from < path.len() - 1 {
+        // Furthest later path hex visible from the current one
+        let to = (from + 1..path.len())
+            .rev()
+            .find(|&to| hex_line(path[from], path[to]).into_iter().all(&open))
+            .unwrap_or(from + 1);
+        straight.extend(hex_line(path[from], path[to]).into_iter().skip(1));
+        from = to;
     }
+    straight
+}
 
-    if roads.is_empty() {
-        return true;
+/// Smooth a path by pulling it straight wherever the terrain allows
+/// 
+/// **Learning Point**: A* on a hex grid returns one of many equally short paths,
+/// often a staircase that hugs obstacles. String pulling walks the path and, from
+/// each kept hex, jumps to the furthest later hex whose straight hex line stays on
+/// valid terrain, replacing the detour with that line. The result is never longer
+/// than the input and keeps its endpoints, so roads and NPC routes read as straight
+/// runs with turns only where something is in the way.
+/// 
+/// @param path_json - JSON array of path hexes: [{"q":0,"r":0},...] (e.g. a hex_astar result)
+/// @param valid_terrain_json - JSON string with array of valid terrain coordinates: [{"q":0,"r":0},...]
+/// @returns JSON array of the smoothed path [{"q":0,"r":0},...]; an empty path gives "[]"
+#[wasm_bindgen]
+pub fn smooth_path(path_json: String, valid_terrain_json: String) -> String {
+    let path = parse_path_json(&path_json);
+    if path.is_empty() {
+        return "[]".to_string();
     }
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let smoothed = straighten_path(&path, |hex| valid_terrain.contains(&hex));
+    let json_parts: Vec<String> = smoothed.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    format!("[{}]", json_parts.join(","))
+}
 
-    if roads.len() == 1 {
-        // Single road - check if it has at least one road neighbor
-        // For single road, we consider it valid (can't check neighbors without more context)
-        return true;
-    }
+/// Validate that all road tiles are connected to each other
+/// 
+/// **Learning Point**: Connectivity only needs one BFS labelling pass (the same one
+/// Connectivity uses): the roads are connected exactly when they form a single
+/// component. This replaces the old A* from the source to every other road, which
+/// was O(n² log n) and slow on networks with thousands of road hexes.
+/// 
+/// Use diagnose_road_connectivity to find out which segments are disconnected.
+/// 
+/// @param roads_json - JSON string with array of road coordinates: [{"q":0,"r":0},{"q":1,"r":0},...]
+/// @returns true if all roads are connected, false otherwise
+#[wasm_bindgen]
+pub fn validate_road_connectivity(roads_json: String) -> bool {
+    let roads = parse_valid_terrain_json(&roads_json);
+    roads_connected(&roads)
+}
 
-    // Convert to HashSet for O(1) lookups
-    let roads_set: HashSet<(i32, i32)> = roads.iter().cloned().collect();
+/// Validate road connectivity from a flat coordinate buffer
+/// 
+/// Same check as validate_road_connectivity without building and parsing JSON.
+/// 
+/// @param road_coords - Int32Array of interleaved coordinates: [q0, r0, q1, r1, ...] (a trailing odd value is ignored)
+/// @returns true if all roads are connected, false otherwise
+#[wasm_bindgen]
+pub fn validate_road_connectivity_buf(road_coords: &[i32]) -> bool {
+    let roads = hex_set_from_flat(road_coords);
+    roads_connected(&roads)
+}
 
-    // Use first road as source
-    let source = roads[0];
+/// Diagnose road connectivity: which disconnected pieces the road network falls into
+/// 
+/// **Learning Point**: A bare "not connected" doesn't say what to fix. The same
+/// labelling pass that answers the yes/no question also yields every component, so
+/// the largest one can be treated as the main network and the others as the
+/// segments that still need a connecting road.
+/// 
+/// @param roads_json - JSON string with array of road coordinates: [{"q":0,"r":0},{"q":1,"r":0},...]
+/// @returns JSON object {"connected":false,"componentCount":2,"components":[{"id":0,"size":40,"hexes":[{"q":0,"r":0},...]},...]}
+///   with components largest first (see compute_connected_components); an empty network is connected with no components
+#[wasm_bindgen]
+pub fn diagnose_road_connectivity(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+    connectivity_report(&roads)
+}
 
-    // Check if all other roads are reachable from source using A*
-    for road in roads.iter().skip(1) {
-        let path_length = hex_astar_path(source.0, source.1, road.0, road.1, &roads_set);
-        if path_length == -1 {
-            return false; // Unreachable road found
-        }
-    }
+/// Diagnose road connectivity from a flat coordinate buffer
+/// 
+/// @param road_coords - Int32Array of interleaved coordinates: [q0, r0, q1, r1, ...] (a trailing odd value is ignored)
+/// @returns Same JSON object as diagnose_road_connectivity
+#[wasm_bindgen]
+pub fn diagnose_road_connectivity_buf(road_coords: &[i32]) -> String {
+    let roads = hex_set_from_flat(road_coords);
+    connectivity_report(&roads)
+}
 
-    true // All roads reachable from source
+fn connectivity_report(roads: &HashSet<(i32, i32)>) -> String {
+    let components = Connectivity::from_hexes(roads).components();
+    format!(
+        r#"{{"connected":{},"componentCount":{},"components":{}}}"#,
+        components.len() <= 1,
+        components.len(),
+        components_json(&components),
+    )
 }
 
+/// True if every hex in the set is reachable from every other through the set
+/// Empty and single-hex sets are trivially connected
+pub fn roads_connected(roads: &HashSet<(i32, i32)>) -> bool {
+    Connectivity::from_hexes(roads).component_count() <= 1
+}
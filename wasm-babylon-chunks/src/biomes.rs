@@ -0,0 +1,150 @@
+//! Climate fields and biome classification
+//!
+//! **Learning Point**: Voronoi regions give every hex a type, but nothing about the
+//! placement is physical. Real biomes follow climate: a Whittaker diagram places
+//! them by temperature and rainfall. Here both are fields over the grid: moisture
+//! is noise blended with closeness to water (coasts and lakeshores are wet), and
+//! temperature is noise that drops with height above the waterline. A small table
+//! of temperature and moisture bands then maps each hex's climate to a biome and
+//! the tile type it is drawn with, so forests gather along rivers and deserts sit
+//! far from water without any hand-placed seeds.
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashSet;
+use crate::elevation::ELEVATION;
+use crate::hex_utils::{generate_hex_grid, hex_distance_field, hex_to_world, UNIT_HEX_SIZE};
+use crate::json::{escape_json_string, parse_json_object, JsonValue};
+use crate::noise::fractal_noise_2d;
+use crate::types::TileType;
+
+/// Noise seed offsets so the temperature and moisture fields are independent
+const MOISTURE_SEED_OFFSET: u64 = 0x6d6f;
+const TEMPERATURE_SEED_OFFSET: u64 = 0x7465;
+
+/// One row of the biome table: a named biome and the climate range it covers
+struct BiomeRule {
+    name: String,
+    tile: TileType,
+    temperature: (f64, f64),
+    moisture: (f64, f64),
+}
+
+impl BiomeRule {
+    fn new(name: &str, tile: TileType, temperature: (f64, f64), moisture: (f64, f64)) -> Self {
+        BiomeRule { name: name.to_string(), tile, temperature, moisture }
+    }
+
+    /// Read {"name":"desert","tileType":0,"temperature":{"min":0.6},"moisture":{"max":0.3}}
+    ///
+    /// tileType is an id or a tile name; missing bounds are open. None without a name or a known tile.
+    fn from_json(rule: &JsonValue) -> Option<Self> {
+        let name = rule.get("name")?.as_str()?;
+        let tile = match rule.get("tileType")? {
+            JsonValue::String(tile_name) => TileType::from_name(tile_name)?,
+            value => TileType::from_i32(value.as_i32()?)?,
+        };
+        let band = |key: &str| {
+            let range = rule.get(key);
+            let bound = |end: &str, default: f64| range.and_then(|r| r.get(end)).and_then(|value| value.as_f64()).unwrap_or(default);
+            (bound("min", f64::NEG_INFINITY), bound("max", f64::INFINITY))
+        };
+        Some(BiomeRule::new(name, tile, band("temperature"), band("moisture")))
+    }
+
+    fn matches(&self, temperature: f64, moisture: f64) -> bool {
+        temperature >= self.temperature.0 && temperature < self.temperature.1
+            && moisture >= self.moisture.0 && moisture < self.moisture.1
+    }
+}
+
+/// Default Whittaker-style table, checked top to bottom (cold to hot, wet before dry)
+fn default_biome_table() -> Vec<BiomeRule> {
+    let any = (f64::NEG_INFINITY, f64::INFINITY);
+    vec![
+        BiomeRule::new("tundra", TileType::Grass, (f64::NEG_INFINITY, 0.35), any),
+        BiomeRule::new("taiga", TileType::Forest, (f64::NEG_INFINITY, 0.45), (0.5, f64::INFINITY)),
+        BiomeRule::new("shrubland", TileType::Grass, (f64::NEG_INFINITY, 0.45), any),
+        BiomeRule::new("temperateForest", TileType::Forest, (f64::NEG_INFINITY, 0.58), (0.45, f64::INFINITY)),
+        BiomeRule::new("grassland", TileType::Grass, (f64::NEG_INFINITY, 0.58), any),
+        BiomeRule::new("rainforest", TileType::Forest, any, (0.6, f64::INFINITY)),
+        BiomeRule::new("savanna", TileType::Grass, any, (0.35, f64::INFINITY)),
+        BiomeRule::new("desert", TileType::Grass, any, any),
+    ]
+}
+
+/// Generate moisture and temperature fields and classify each hex into a biome
+///
+/// Water comes from the elevation layer (hexes below waterLevel, see generate_elevation)
+/// and from the optional "water" list. Water hexes are returned as biome "water".
+/// Moisture is half noise and half closeness to water (1 at the shore, 0 at
+/// moistureRange hexes or more); without any water it is noise alone. Temperature is
+/// noise minus lapseRate times the height above waterLevel. Both are clamped to 0-1.
+///
+/// @param max_layer - Grid radius in rings around the center
+/// @param center_q - Center q coordinate
+/// @param center_r - Center r coordinate
+/// @param params_json - JSON object (all optional):
+///   {"seed":1,"frequency":0.06,"octaves":3,"waterLevel":0.35,"moistureRange":8,"lapseRate":1,
+///    "water":[{"q":0,"r":0},...],
+///    "biomes":[{"name":"desert","tileType":"grass","temperature":{"min":0.6},"moisture":{"max":0.3}},...]}
+///   biomes replaces the default table; the first matching row wins, hexes matching none become grassland
+/// @returns JSON array in grid order, usable as pre-constraints:
+///   [{"q":0,"r":0,"tileType":0,"biome":"savanna","temperature":0.62,"moisture":0.41},...]
+#[wasm_bindgen]
+pub fn generate_biomes(max_layer: i32, center_q: i32, center_r: i32, params_json: String) -> String {
+    let params = parse_json_object(&params_json);
+    let number = |key: &str, default: f64| params.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+    let seed = number("seed", 0.0) as u64;
+    let frequency = number("frequency", 0.06);
+    let octaves = number("octaves", 3.0).max(1.0) as u32;
+    let water_level = number("waterLevel", 0.35);
+    let moisture_range = number("moistureRange", 8.0).max(1.0);
+    let lapse_rate = number("lapseRate", 1.0);
+    let table: Vec<BiomeRule> = match params.get("biomes").and_then(|value| value.as_array()) {
+        Some(rules) => rules.iter().filter_map(BiomeRule::from_json).collect(),
+        None => default_biome_table(),
+    };
+
+    let hexes = generate_hex_grid(max_layer, center_q, center_r);
+    let elevation = ELEVATION.lock().unwrap();
+    let mut water: HashSet<(i32, i32)> = params.get("water").map(|value| value.as_hex_list()).unwrap_or_default().into_iter().collect();
+    water.extend(hexes.iter()
+        .map(|hex| (hex.q, hex.r))
+        .filter(|hex| elevation.get(hex).is_some_and(|height| *height < water_level)));
+    let shore_distance = hex_distance_field(&water, moisture_range.ceil() as i32);
+
+    let noise01 = |x: f64, y: f64, offset: u64| {
+        (fractal_noise_2d(x * frequency, y * frequency, seed.wrapping_add(offset), octaves) + 1.0) / 2.0
+    };
+
+    let parts: Vec<String> = hexes.iter()
+        .map(|hex| {
+            let (x, y) = hex_to_world(hex.q as f64, hex.r as f64, UNIT_HEX_SIZE);
+            let height = elevation.get(&(hex.q, hex.r)).copied();
+            let wetness = noise01(x, y, MOISTURE_SEED_OFFSET);
+            let moisture = if water.is_empty() {
+                wetness
+            } else {
+                let distance = shore_distance.get(&(hex.q, hex.r)).map(|d| *d as f64).unwrap_or(moisture_range);
+                (wetness + (1.0 - distance.min(moisture_range) / moisture_range)) / 2.0
+            };
+            let altitude = height.map(|height| (height - water_level).max(0.0)).unwrap_or(0.0);
+            let temperature = (noise01(x, y, TEMPERATURE_SEED_OFFSET) - lapse_rate * altitude).clamp(0.0, 1.0);
+            let moisture = moisture.clamp(0.0, 1.0);
+
+            let (biome, tile) = if water.contains(&(hex.q, hex.r)) {
+                ("water", TileType::Water)
+            } else {
+                table.iter()
+                    .find(|rule| rule.matches(temperature, moisture))
+                    .map(|rule| (rule.name.as_str(), rule.tile))
+                    .unwrap_or(("grassland", TileType::Grass))
+            };
+            format!(
+                r#"{{"q":{},"r":{},"tileType":{},"biome":"{}","temperature":{:.3},"moisture":{:.3}}}"#,
+                hex.q, hex.r, tile.id(), escape_json_string(biome), temperature, moisture
+            )
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
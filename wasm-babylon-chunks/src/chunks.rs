@@ -1,7 +1,14 @@
 /// Chunk management module
 
 use wasm_bindgen::prelude::*;
-use crate::hex_utils::{parse_valid_terrain_json, hex_distance};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+use crate::hex_utils::{parse_valid_terrain_json, hex_distance, generate_hex_grid, get_hex_neighbors, super_hex_center};
+use crate::json::{parse_json, parse_json_object};
+use crate::state::WFC_STATE;
+use crate::types::TileType;
+use crate::rng::{SeededRng, hash_values};
+use crate::errors::{envelope, parse_hex_set_param, ApiError, ErrorCode};
 
 /// Calculate chunk radius for distance threshold calculations
 /// The chunk radius is the distance from chunk center to the outer boundary
@@ -13,19 +20,8 @@ pub fn calculate_chunk_radius(rings: i32) -> i32 {
     rings
 }
 
-/// Calculate chunk neighbor positions using offset vector rotation
-/// Returns exactly 6 neighbor hex coordinates, one in each of the 6 directions
-/// 
-/// Uses the offset vector (rings, rings+1) for rings>0, or (1, 0) for rings=0, and rotates
-/// it 60 degrees clockwise 6 times. This ensures chunks are packed without gaps - 
-/// each direction has exactly one neighbor. The outer boundaries of adjacent chunks touch.
-/// 
-/// @param center_q - Center q coordinate
-/// @param center_r - Center r coordinate
-/// @param rings - Number of rings per chunk
-/// @returns JSON string with array of 6 neighbor coordinates: [{"q":0,"r":0},...]
-#[wasm_bindgen]
-pub fn calculate_chunk_neighbors(center_q: i32, center_r: i32, rings: i32) -> String {
+/// Centers of the 6 chunks surrounding a chunk, in calculate_chunk_neighbors order
+pub fn chunk_neighbor_centers(center_q: i32, center_r: i32, rings: i32) -> Vec<(i32, i32)> {
     let mut neighbors = Vec::new();
     
     // Base offset vector: (rings, rings+1) for rings>0, or (1, 0) for rings=0
@@ -60,6 +56,24 @@ pub fn calculate_chunk_neighbors(center_q: i32, center_r: i32, rings: i32) -> St
         current_r = next_r;
     }
     
+    neighbors
+}
+
+/// Calculate chunk neighbor positions using offset vector rotation
+/// Returns exactly 6 neighbor hex coordinates, one in each of the 6 directions
+/// 
+/// Uses the offset vector (rings, rings+1) for rings>0, or (1, 0) for rings=0, and rotates
+/// it 60 degrees clockwise 6 times. This ensures chunks are packed without gaps - 
+/// each direction has exactly one neighbor. The outer boundaries of adjacent chunks touch.
+/// 
+/// @param center_q - Center q coordinate
+/// @param center_r - Center r coordinate
+/// @param rings - Number of rings per chunk
+/// @returns JSON string with array of 6 neighbor coordinates: [{"q":0,"r":0},...]
+#[wasm_bindgen]
+pub fn calculate_chunk_neighbors(center_q: i32, center_r: i32, rings: i32) -> String {
+    let neighbors = chunk_neighbor_centers(center_q, center_r, rings);
+    
     // Convert to JSON
     let mut json_parts = Vec::new();
     for (q, r) in neighbors {
@@ -124,6 +138,40 @@ pub fn find_nearest_neighbor_chunk(
     }
 }
 
+/// find_nearest_neighbor_chunk with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":{"neighbor":{"q":0,"r":0},"distance":1,"isInstantiated":true},"error":null}, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument" (negative rings)
+#[wasm_bindgen]
+pub fn find_nearest_neighbor_chunk_result(
+    current_chunk_q: i32,
+    current_chunk_r: i32,
+    current_tile_q: i32,
+    current_tile_r: i32,
+    rings: i32,
+    existing_chunks_json: String,
+) -> String {
+    if let Err(error) = parse_hex_set_param(&existing_chunks_json, "existing_chunks_json").and(check_rings(rings)) {
+        return envelope(Err(error));
+    }
+    envelope(Ok(find_nearest_neighbor_chunk(
+        current_chunk_q,
+        current_chunk_r,
+        current_tile_q,
+        current_tile_r,
+        rings,
+        existing_chunks_json,
+    )))
+}
+
+/// Reject a negative rings parameter, for the `_result` variants
+fn check_rings(rings: i32) -> Result<(), ApiError> {
+    if rings < 0 {
+        return Err(ApiError::new(ErrorCode::InvalidArgument, format!("rings must be 0 or more, got {}", rings)));
+    }
+    Ok(())
+}
+
 /// Disable chunks that are more than max_distance away from the current chunk
 /// All chunks, including the origin chunk, are subject to the distance threshold
 /// 
@@ -309,3 +357,329 @@ pub fn calculate_chunk_for_tile(
     }
 }
 
+/// calculate_chunk_for_tile with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":{"q":0,"r":0},"error":null}, {"ok":null,"error":null} if no chunk contains
+///   the tile, or {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument" (negative rings)
+#[wasm_bindgen]
+pub fn calculate_chunk_for_tile_result(
+    tile_q: i32,
+    tile_r: i32,
+    rings: i32,
+    chunk_positions_json: String,
+) -> String {
+    if let Err(error) = parse_hex_set_param(&chunk_positions_json, "chunk_positions_json").and(check_rings(rings)) {
+        return envelope(Err(error));
+    }
+    envelope(Ok(calculate_chunk_for_tile(tile_q, tile_r, rings, chunk_positions_json)))
+}
+
+
+/// Adjacent hexes on either side of a chunk border
+pub type BorderPair = ((i32, i32), (i32, i32));
+
+/// Pairs of adjacent hexes straddling the border between two chunks
+/// Each pair is (hex in chunk a, hex in chunk b), sorted so both sides see the same order
+pub fn chunk_border_pairs(a: (i32, i32), b: (i32, i32), rings: i32) -> Vec<BorderPair> {
+    let mut pairs = Vec::new();
+    for hex in generate_hex_grid(rings, a.0, a.1) {
+        if hex_distance(hex.q, hex.r, a.0, a.1) != rings {
+            continue;
+        }
+        for neighbor in get_hex_neighbors(hex.q, hex.r) {
+            if hex_distance(neighbor.0, neighbor.1, b.0, b.1) <= rings {
+                pairs.push(((hex.q, hex.r), neighbor));
+            }
+        }
+    }
+    pairs.sort();
+    pairs
+}
+
+/// Feature that crosses a chunk border
+#[derive(Clone, Copy)]
+enum BorderFeature {
+    River,
+    Highway,
+}
+
+impl BorderFeature {
+    fn salt(self) -> i64 {
+        match self {
+            BorderFeature::River => 1,
+            BorderFeature::Highway => 2,
+        }
+    }
+}
+
+/// Decide whether a feature crosses the border between two chunks and where
+/// The key is order-independent, so both chunks derive the same answer.
+///
+/// @param pairs - chunk_border_pairs(lower, higher, rings)
+/// @param avoid - Crossing already taken by another feature; no hex may be shared with it
+/// @returns The chosen border pair, or None
+fn border_crossing(
+    world_seed: u32,
+    lower: (i32, i32),
+    higher: (i32, i32),
+    feature: BorderFeature,
+    probability: f64,
+    pairs: &[BorderPair],
+    avoid: Option<BorderPair>,
+) -> Option<BorderPair> {
+    let key = [lower.0 as i64, lower.1 as i64, higher.0 as i64, higher.1 as i64, feature.salt()];
+    let mut rng = SeededRng::new(hash_values(world_seed as u64, &key));
+    if pairs.is_empty() || rng.next_f64() >= probability {
+        return None;
+    }
+    let start = rng.next_below(pairs.len());
+    // Walk along the border from the drawn pair until one is free of the avoided hexes
+    (0..pairs.len())
+        .map(|offset| pairs[(start + offset) % pairs.len()])
+        .find(|(a, b)| match avoid {
+            Some((avoid_a, avoid_b)) => *a != avoid_a && *b != avoid_b,
+            None => true,
+        })
+}
+
+/// Generate border contracts for a chunk: where rivers and highways cross each edge
+///
+/// **Learning Point**: Chunks are generated independently, so features that run
+/// across chunk borders need an agreement both sides can compute alone. Each
+/// border's decision is hashed from the world seed and the two chunk centers
+/// (order-independent), so a chunk and its neighbor always derive the same
+/// crossing hexes, whichever of them is generated first.
+///
+/// @param world_seed - World seed shared by every chunk
+/// @param center_q - Chunk center q coordinate
+/// @param center_r - Chunk center r coordinate
+/// @param rings - Number of rings per chunk
+/// @param params_json - Optional JSON object: {"riverProbability":0.25,"highwayProbability":0.35}
+/// @returns JSON array with one entry per edge, in calculate_chunk_neighbors order:
+///   [{"direction":0,"neighbor":{"q":3,"r":4},
+///     "river":{"hex":{"q":1,"r":2},"neighborHex":{"q":1,"r":3}} or null,
+///     "highway":{"hex":{...},"neighborHex":{...}} or null},...]
+///   hex lies in this chunk, neighborHex is the adjacent hex in the neighboring chunk
+#[wasm_bindgen]
+pub fn generate_border_contracts(
+    world_seed: u32,
+    center_q: i32,
+    center_r: i32,
+    rings: i32,
+    params_json: String,
+) -> String {
+    let params = parse_json_object(&params_json);
+    let river_probability = params.get("riverProbability").and_then(|value| value.as_f64()).unwrap_or(0.25);
+    let highway_probability = params.get("highwayProbability").and_then(|value| value.as_f64()).unwrap_or(0.35);
+
+    let center = (center_q, center_r);
+    let rings = rings.max(0);
+    let mut edge_parts = Vec::new();
+
+    for (direction, neighbor) in chunk_neighbor_centers(center_q, center_r, rings).into_iter().enumerate() {
+        // Always compute from the lower chunk's side so both chunks see identical pairs
+        let (lower, higher) = if center < neighbor { (center, neighbor) } else { (neighbor, center) };
+        let pairs = chunk_border_pairs(lower, higher, rings);
+
+        let river = border_crossing(world_seed, lower, higher, BorderFeature::River, river_probability, &pairs, None);
+        let highway = border_crossing(world_seed, lower, higher, BorderFeature::Highway, highway_probability, &pairs, river);
+
+        let crossing_json = |crossing: Option<BorderPair>| match crossing {
+            Some((lower_hex, higher_hex)) => {
+                let (own, other) = if lower == center { (lower_hex, higher_hex) } else { (higher_hex, lower_hex) };
+                format!(
+                    r#"{{"hex":{{"q":{},"r":{}}},"neighborHex":{{"q":{},"r":{}}}}}"#,
+                    own.0, own.1, other.0, other.1
+                )
+            }
+            None => "null".to_string(),
+        };
+
+        edge_parts.push(format!(
+            r#"{{"direction":{},"neighbor":{{"q":{},"r":{}}},"river":{},"highway":{}}}"#,
+            direction,
+            neighbor.0,
+            neighbor.1,
+            crossing_json(river),
+            crossing_json(highway)
+        ));
+    }
+
+    format!("[{}]", edge_parts.join(","))
+}
+
+/// Summarize chunks for a strategic world map
+///
+/// **Learning Point**: The overview only needs a handful of numbers per chunk, so
+/// they are aggregated here from the grid instead of shipping every tile to JS.
+/// The dominant biome is the most common natural tile (grass, forest or water);
+/// roads and buildings are man-made and reported separately as road density and
+/// the settlement flag.
+///
+/// @param chunks_json - JSON array of chunk centers: [{"q":0,"r":0},...]
+/// @param rings - Number of rings per chunk
+/// @returns JSON array in input order (tiles missing from the grid are ignored):
+///   [{"q":0,"r":0,"tiles":37,"biome":3,"roadDensity":0.108,"settlement":true,"waterPercent":12.5},...]
+///   biome is a TileType id, or -1 for a chunk without natural tiles
+#[wasm_bindgen]
+pub fn build_world_overview(chunks_json: String, rings: i32) -> String {
+    let chunks = parse_json(&chunks_json)
+        .map(|value| value.as_hex_list())
+        .unwrap_or_default();
+    let state = WFC_STATE.lock().unwrap();
+
+    let mut json_parts = Vec::new();
+    for (center_q, center_r) in chunks {
+        // Counts per tile type, indexed by the TileType id
+        let mut counts = [0usize; TileType::COUNT];
+        for hex in generate_hex_grid(rings.max(0), center_q, center_r) {
+            if let Some(tile) = state.get_tile(hex.q, hex.r) {
+                counts[tile.index()] += 1;
+            }
+        }
+        let total: usize = counts.iter().sum();
+
+        // Ties resolve to the first type in this list
+        let biome = [TileType::Grass, TileType::Forest, TileType::Water]
+            .into_iter()
+            .filter(|tile| counts[tile.index()] > 0)
+            .max_by_key(|tile| (counts[tile.index()], std::cmp::Reverse(tile.id())))
+            .map(|tile| tile.id())
+            .unwrap_or(-1);
+        let fraction = |tile: TileType| {
+            if total == 0 { 0.0 } else { counts[tile.index()] as f64 / total as f64 }
+        };
+
+        json_parts.push(format!(
+            r#"{{"q":{},"r":{},"tiles":{},"biome":{},"roadDensity":{:.3},"settlement":{},"waterPercent":{:.1}}}"#,
+            center_q,
+            center_r,
+            total,
+            biome,
+            fraction(TileType::Road),
+            counts[TileType::Building.index()] > 0,
+            fraction(TileType::Water) * 100.0
+        ));
+    }
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Chunk bookkeeping kept on the WASM side
+///
+/// **Learning Point**: disable_distant_chunks and find_nearest_neighbor_chunk need
+/// the full chunk list serialized from JS on every call. ChunkManager keeps chunk
+/// positions and enabled flags itself, so each frame JS only sends the player
+/// position and gets back what changed. Chunks sit on the same lattice as
+/// calculate_chunk_neighbors, anchored at the origin chunk (0, 0).
+///
+/// Used from JS as an object:
+///   const manager = new ChunkManager(rings, maxDistance); const deltas = JSON.parse(manager.update(q, r));
+#[wasm_bindgen]
+pub struct ChunkManager {
+    rings: i32,
+    max_distance: i32,
+    chunks: HashMap<(i32, i32), bool>,
+    current_chunk: Option<(i32, i32)>,
+}
+
+#[wasm_bindgen]
+impl ChunkManager {
+    /// Create a manager for chunks of `rings` rings, keeping chunks within `max_distance` enabled
+    #[wasm_bindgen(constructor)]
+    pub fn new(rings: i32, max_distance: i32) -> ChunkManager {
+        ChunkManager {
+            rings: rings.max(0),
+            max_distance: max_distance.max(0),
+            chunks: HashMap::new(),
+            current_chunk: None,
+        }
+    }
+
+    /// Register a chunk that JS already instantiated
+    pub fn add_chunk(&mut self, q: i32, r: i32, enabled: bool) {
+        self.chunks.insert((q, r), enabled);
+    }
+
+    /// Forget a chunk (e.g. after JS disposed it)
+    ///
+    /// @returns true if the chunk was registered
+    pub fn remove_chunk(&mut self, q: i32, r: i32) -> bool {
+        self.chunks.remove(&(q, r)).is_some()
+    }
+
+    /// Whether a registered chunk is currently enabled (false for unknown chunks)
+    pub fn is_enabled(&self, q: i32, r: i32) -> bool {
+        self.chunks.get(&(q, r)).copied().unwrap_or(false)
+    }
+
+    /// Number of registered chunks
+    pub fn chunk_count(&self) -> u32 {
+        self.chunks.len() as u32
+    }
+
+    /// Update for a new player position and return the deltas
+    ///
+    /// Chunks farther than max_distance (center to center) from the player's chunk are
+    /// disabled, registered chunks within it are enabled, and missing chunks within it
+    /// are reported for loading. Chunks in toLoad are registered as enabled right away,
+    /// so they are reported only once.
+    ///
+    /// @param player_q - Hex q coordinate of the player
+    /// @param player_r - Hex r coordinate of the player
+    /// @returns JSON object: {"currentChunk":{"q":0,"r":0},"chunkChanged":true,"toEnable":[...],"toDisable":[...],"toLoad":[...]}
+    pub fn update(&mut self, player_q: i32, player_r: i32) -> String {
+        let current = super_hex_center(player_q, player_r, self.rings);
+        let chunk_changed = self.current_chunk != Some(current);
+        self.current_chunk = Some(current);
+
+        let mut to_enable = Vec::new();
+        let mut to_disable = Vec::new();
+        for (chunk, enabled) in self.chunks.iter_mut() {
+            let in_range = hex_distance(current.0, current.1, chunk.0, chunk.1) <= self.max_distance;
+            if in_range && !*enabled {
+                *enabled = true;
+                to_enable.push(*chunk);
+            } else if !in_range && *enabled {
+                *enabled = false;
+                to_disable.push(*chunk);
+            }
+        }
+
+        // Walk the chunk lattice outward from the current chunk to find missing chunks in range
+        let mut to_load = Vec::new();
+        let mut visited: HashSet<(i32, i32)> = HashSet::new();
+        let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
+        visited.insert(current);
+        queue.push_back(current);
+        while let Some(chunk) = queue.pop_front() {
+            if let Entry::Vacant(entry) = self.chunks.entry(chunk) {
+                entry.insert(true);
+                to_load.push(chunk);
+            }
+            for neighbor in chunk_neighbor_centers(chunk.0, chunk.1, self.rings) {
+                let in_range = hex_distance(current.0, current.1, neighbor.0, neighbor.1) <= self.max_distance;
+                if in_range && visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        to_enable.sort();
+        to_disable.sort();
+        let list_json = |chunks: &[(i32, i32)]| {
+            let parts: Vec<String> = chunks.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+            format!("[{}]", parts.join(","))
+        };
+
+        format!(
+            r#"{{"currentChunk":{{"q":{},"r":{}}},"chunkChanged":{},"toEnable":{},"toDisable":{},"toLoad":{}}}"#,
+            current.0,
+            current.1,
+            chunk_changed,
+            list_json(&to_enable),
+            list_json(&to_disable),
+            list_json(&to_load)
+        )
+    }
+}
@@ -0,0 +1,223 @@
+//! Incremental hex connectivity module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::hex_utils::{get_hex_neighbors, parse_valid_terrain_json};
+
+/// Connected components of a hex set, kept up to date as hexes are added and removed
+///
+/// **Learning Point**: Every hex carries a component label. Adding a hex can only
+/// merge components, so the smaller components are relabelled into the largest
+/// (each hex is relabelled O(log n) times overall). Removing a hex can split its
+/// component, so only that component is re-flooded from the removed hex's
+/// neighbors; the rest of the network is untouched.
+///
+/// Used from JS as an object:
+///   const net = new Connectivity(); net.build(json); net.connected(0, 0, 5, 2);
+#[wasm_bindgen]
+pub struct Connectivity {
+    labels: HashMap<(i32, i32), u32>,
+    members: HashMap<u32, Vec<(i32, i32)>>,
+    next_label: u32,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Connectivity {
+    /// Create an empty connectivity structure
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Connectivity {
+        Connectivity {
+            labels: HashMap::new(),
+            members: HashMap::new(),
+            next_label: 0,
+        }
+    }
+
+    /// Replace the contents with a new hex set
+    ///
+    /// @param hexes_json - JSON array of hex coordinates: [{"q":0,"r":0},...]
+    pub fn build(&mut self, hexes_json: String) {
+        let hexes = parse_valid_terrain_json(&hexes_json);
+        self.rebuild(&hexes);
+    }
+
+    /// True if both hexes are in the set and in the same component
+    pub fn connected(&self, a_q: i32, a_r: i32, b_q: i32, b_r: i32) -> bool {
+        match (self.labels.get(&(a_q, a_r)), self.labels.get(&(b_q, b_r))) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Number of connected components
+    pub fn component_count(&self) -> u32 {
+        self.members.len() as u32
+    }
+
+    /// Number of hexes in the set
+    pub fn hex_count(&self) -> u32 {
+        self.labels.len() as u32
+    }
+
+    /// Add a hex, merging any components it touches
+    ///
+    /// @returns false if the hex was already present
+    pub fn add_hex(&mut self, q: i32, r: i32) -> bool {
+        if self.labels.contains_key(&(q, r)) {
+            return false;
+        }
+
+        let mut touching: Vec<u32> = get_hex_neighbors(q, r)
+            .iter()
+            .filter_map(|neighbor| self.labels.get(neighbor).copied())
+            .collect();
+        touching.sort();
+        touching.dedup();
+
+        // Keep the largest touching component and fold the others into it
+        let target = match touching.iter().max_by_key(|label| self.members[label].len()) {
+            Some(&label) => label,
+            None => self.fresh_label(),
+        };
+        for label in touching {
+            if label != target {
+                let moved = self.members.remove(&label).unwrap_or_default();
+                for hex in &moved {
+                    self.labels.insert(*hex, target);
+                }
+                self.members.entry(target).or_default().extend(moved);
+            }
+        }
+
+        self.labels.insert((q, r), target);
+        self.members.entry(target).or_default().push((q, r));
+        true
+    }
+
+    /// Remove a hex, splitting its component if it was the only link
+    ///
+    /// @returns false if the hex was not present
+    pub fn remove_hex(&mut self, q: i32, r: i32) -> bool {
+        let label = match self.labels.remove(&(q, r)) {
+            Some(label) => label,
+            None => return false,
+        };
+        let old_members = self.members.remove(&label).unwrap_or_default();
+        let remaining: HashSet<(i32, i32)> = old_members.into_iter().filter(|hex| *hex != (q, r)).collect();
+
+        // Re-flood the old component from each neighbor of the removed hex
+        let mut assigned: HashSet<(i32, i32)> = HashSet::new();
+        for start in get_hex_neighbors(q, r) {
+            if !remaining.contains(&start) || assigned.contains(&start) {
+                continue;
+            }
+            let new_label = self.fresh_label();
+            let mut component = Vec::new();
+            let mut queue = VecDeque::new();
+            assigned.insert(start);
+            queue.push_back(start);
+            while let Some(hex) = queue.pop_front() {
+                component.push(hex);
+                self.labels.insert(hex, new_label);
+                for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                    if remaining.contains(&neighbor) && assigned.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            self.members.insert(new_label, component);
+        }
+        true
+    }
+}
+
+impl Connectivity {
+    /// Build from a hex set already in memory
+    pub fn from_hexes(hexes: &HashSet<(i32, i32)>) -> Connectivity {
+        let mut connectivity = Connectivity::new();
+        connectivity.rebuild(hexes);
+        connectivity
+    }
+
+    /// Members of every component, largest first (ties by lowest hex), each sorted by (r, q)
+    pub fn components(&self) -> Vec<Vec<(i32, i32)>> {
+        let mut components: Vec<Vec<(i32, i32)>> = self.members.values()
+            .map(|members| {
+                let mut members = members.clone();
+                members.sort_by_key(|&(q, r)| (r, q));
+                members
+            })
+            .collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].1.cmp(&b[0].1).then(a[0].0.cmp(&b[0].0))));
+        components
+    }
+
+    fn rebuild(&mut self, hexes: &HashSet<(i32, i32)>) {
+        self.labels.clear();
+        self.members.clear();
+        self.next_label = 0;
+
+        let mut sorted: Vec<(i32, i32)> = hexes.iter().cloned().collect();
+        sorted.sort();
+        for start in sorted {
+            if self.labels.contains_key(&start) {
+                continue;
+            }
+            let label = self.fresh_label();
+            let mut component = Vec::new();
+            let mut queue = VecDeque::new();
+            self.labels.insert(start, label);
+            queue.push_back(start);
+            while let Some(hex) = queue.pop_front() {
+                component.push(hex);
+                for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                    if hexes.contains(&neighbor) && !self.labels.contains_key(&neighbor) {
+                        self.labels.insert(neighbor, label);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            self.members.insert(label, component);
+        }
+    }
+
+    fn fresh_label(&mut self) -> u32 {
+        let label = self.next_label;
+        self.next_label = self.next_label.wrapping_add(1);
+        label
+    }
+}
+
+/// Label the connected components of a hex set in one pass
+///
+/// **Learning Point**: A single BFS labelling answers "is this road network one piece",
+/// "which landmasses are islands" or "which pockets of terrain can't be reached" at
+/// once: every hex gets a component, and the small components are the ones to fix.
+///
+/// @param tiles_json - JSON array of hex coordinates: [{"q":0,"r":0},...]
+/// @returns JSON array of components, largest first:
+///   [{"id":0,"size":12,"hexes":[{"q":0,"r":0},...]},...] where id is the index in the array and
+///   hexes are sorted by r, then q
+#[wasm_bindgen]
+pub fn compute_connected_components(tiles_json: String) -> String {
+    let hexes = parse_valid_terrain_json(&tiles_json);
+    components_json(&Connectivity::from_hexes(&hexes).components())
+}
+
+/// JSON array of components: [{"id":0,"size":12,"hexes":[{"q":0,"r":0},...]},...] (id = array index)
+pub fn components_json(components: &[Vec<(i32, i32)>]) -> String {
+    let parts: Vec<String> = components.iter()
+        .enumerate()
+        .map(|(id, members)| {
+            let hexes: Vec<String> = members.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+            format!(r#"{{"id":{},"size":{},"hexes":[{}]}}"#, id, members.len(), hexes.join(","))
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
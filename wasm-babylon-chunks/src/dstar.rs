@@ -0,0 +1,286 @@
+//! Incremental replanning (D* Lite) over the grid
+//!
+//! **Learning Point**: When a road is built or a building blocks a hex, most of an
+//! agent's previous search is still valid. D* Lite searches backwards from the goal
+//! and keeps its cost estimates (g) and one-step lookahead values (rhs) between
+//! calls. A changed hex only makes itself and its neighbors inconsistent, and the
+//! next replan repairs just the part of the search those changes can affect,
+//! instead of starting A* from scratch for every active agent.
+
+use wasm_bindgen::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::hex_utils::{get_hex_neighbors, hex_distance};
+use crate::movement::{movement_profile, MovementProfile};
+use crate::state::{WfcState, WFC_STATE};
+use crate::errors::{envelope, ApiError, ErrorCode};
+
+/// D* Lite priority: (min(g, rhs) + h + km, min(g, rhs)), compared lexicographically
+type Key = (f64, f64);
+
+fn key_less(a: Key, b: Key) -> bool {
+    a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)) == Ordering::Less
+}
+
+/// Priority queue entry (min-heap by key)
+struct QueueEntry {
+    key: Key,
+    hex: (i32, i32),
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reverse order for min-heap
+        other.key.0.total_cmp(&self.key.0)
+            .then_with(|| other.key.1.total_cmp(&self.key.1))
+            .then_with(|| other.hex.cmp(&self.hex))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Persistent path between a moving agent and a fixed goal over the current grid
+///
+/// Tile changes are reported with notify_tile_changed (e.g. from poll_tile_changes)
+/// and take effect on the next replan.
+///
+/// Used from JS as an object:
+///   const session = new PathSession(0, 0, 20, -5, "walk"); session.replan();
+///   session.notify_tile_changed(4, 1); session.move_to(1, 0); session.replan();
+#[wasm_bindgen]
+pub struct PathSession {
+    start: (i32, i32),
+    goal: (i32, i32),
+    /// Start at the previous replan (km grows by the distance moved since)
+    last_start: (i32, i32),
+    /// None if the profile name was unknown
+    profile: Option<MovementProfile>,
+    scale: f64,
+    km: f64,
+    g: HashMap<(i32, i32), f64>,
+    rhs: HashMap<(i32, i32), f64>,
+    open_set: BinaryHeap<QueueEntry>,
+    /// Current key of every hex in the open set (heap entries with another key are stale)
+    queued: HashMap<(i32, i32), Key>,
+    pending: HashSet<(i32, i32)>,
+    expanded: u32,
+}
+
+impl PathSession {
+    fn g(&self, hex: (i32, i32)) -> f64 {
+        self.g.get(&hex).copied().unwrap_or(f64::INFINITY)
+    }
+
+    fn rhs(&self, hex: (i32, i32)) -> f64 {
+        self.rhs.get(&hex).copied().unwrap_or(f64::INFINITY)
+    }
+
+    /// Cost of stepping from one hex to a neighbor (f64::INFINITY = not allowed)
+    fn cost(&self, state: &WfcState, from: (i32, i32), to: (i32, i32)) -> f64 {
+        let Some(profile) = &self.profile else {
+            return f64::INFINITY;
+        };
+        match profile.step_cost(state.get_tile(from.0, from.1), state.get_tile(to.0, to.1)) {
+            Some(cost) => cost / self.scale,
+            None => f64::INFINITY,
+        }
+    }
+
+    fn key(&self, hex: (i32, i32)) -> Key {
+        let best = self.g(hex).min(self.rhs(hex));
+        (best + hex_distance(self.start.0, self.start.1, hex.0, hex.1) as f64 + self.km, best)
+    }
+
+    fn queue(&mut self, hex: (i32, i32)) {
+        let key = self.key(hex);
+        self.queued.insert(hex, key);
+        self.open_set.push(QueueEntry { key, hex });
+    }
+
+    /// Smallest valid entry of the open set, dropping stale ones
+    fn top(&mut self) -> Option<(Key, (i32, i32))> {
+        while let Some(entry) = self.open_set.peek() {
+            if self.queued.get(&entry.hex) == Some(&entry.key) {
+                return Some((entry.key, entry.hex));
+            }
+            self.open_set.pop();
+        }
+        None
+    }
+
+    fn update_vertex(&mut self, state: &WfcState, hex: (i32, i32)) {
+        if hex != self.goal {
+            let best = get_hex_neighbors(hex.0, hex.1)
+                .into_iter()
+                .map(|next| self.cost(state, hex, next) + self.g(next))
+                .fold(f64::INFINITY, f64::min);
+            self.rhs.insert(hex, best);
+        }
+        self.queued.remove(&hex);
+        if self.g(hex) != self.rhs(hex) {
+            self.queue(hex);
+        }
+    }
+
+    fn compute_shortest_path(&mut self, state: &WfcState) {
+        while let Some((old_key, hex)) = self.top() {
+            let start_key = self.key(self.start);
+            if !key_less(old_key, start_key) && self.rhs(self.start) == self.g(self.start) {
+                break;
+            }
+            self.expanded += 1;
+            let new_key = self.key(hex);
+            if key_less(old_key, new_key) {
+                self.queue(hex);
+            } else if self.g(hex) > self.rhs(hex) {
+                self.queued.remove(&hex);
+                self.g.insert(hex, self.rhs(hex));
+                for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                    self.update_vertex(state, neighbor);
+                }
+            } else {
+                self.g.insert(hex, f64::INFINITY);
+                self.update_vertex(state, hex);
+                for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                    self.update_vertex(state, neighbor);
+                }
+            }
+        }
+    }
+
+    /// Repair the search after changes and follow it from the start
+    ///
+    /// @returns Path from start to goal (both included), or None if unreachable
+    pub fn plan(&mut self, state: &WfcState) -> Option<Vec<(i32, i32)>> {
+        self.expanded = 0;
+        self.profile.as_ref()?;
+        // Queued keys were computed against the old start; km keeps them lower bounds
+        self.km += hex_distance(self.last_start.0, self.last_start.1, self.start.0, self.start.1) as f64;
+        self.last_start = self.start;
+        if !self.pending.is_empty() {
+            let mut changed: Vec<(i32, i32)> = self.pending.drain().collect();
+            changed.sort();
+            for hex in changed {
+                // Edges into and out of the hex changed cost
+                self.update_vertex(state, hex);
+                for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                    self.update_vertex(state, neighbor);
+                }
+            }
+        }
+        self.compute_shortest_path(state);
+
+        if !self.g(self.start).is_finite() {
+            return None;
+        }
+        let mut path = vec![self.start];
+        let mut current = self.start;
+        while current != self.goal {
+            let next = get_hex_neighbors(current.0, current.1)
+                .into_iter()
+                .map(|next| (next, self.cost(state, current, next) + self.g(next)))
+                .filter(|(_, total)| total.is_finite())
+                .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))?;
+            current = next.0;
+            path.push(current);
+            // A consistent search never loops; guard against an unreported grid change
+            if path.len() > self.g.len() + 1 {
+                return None;
+            }
+        }
+        Some(path)
+    }
+}
+
+#[wasm_bindgen]
+impl PathSession {
+    /// Create a session between an agent and a goal
+    ///
+    /// @param profile - Optional movement profile name (default "walk"); with an unknown profile
+    ///   every replan returns "null"
+    #[wasm_bindgen(constructor)]
+    pub fn new(start_q: i32, start_r: i32, goal_q: i32, goal_r: i32, profile: Option<String>) -> PathSession {
+        let profile = movement_profile(profile.as_deref().unwrap_or("walk"));
+        let goal = (goal_q, goal_r);
+        let mut session = PathSession {
+            start: (start_q, start_r),
+            goal,
+            last_start: (start_q, start_r),
+            scale: profile.as_ref().map(|profile| profile.min_cost()).unwrap_or(1.0),
+            profile,
+            km: 0.0,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            open_set: BinaryHeap::new(),
+            queued: HashMap::new(),
+            pending: HashSet::new(),
+            expanded: 0,
+        };
+        session.rhs.insert(goal, 0.0);
+        session.queue(goal);
+        session
+    }
+
+    /// Report that the tile at a hex changed (it is re-read on the next replan)
+    pub fn notify_tile_changed(&mut self, q: i32, r: i32) {
+        self.pending.insert((q, r));
+    }
+
+    /// Move the agent; the next replan continues from here
+    pub fn move_to(&mut self, q: i32, r: i32) {
+        self.start = (q, r);
+    }
+
+    /// Bring the path up to date with reported changes
+    ///
+    /// The first call runs a full backward search; later calls only repair what
+    /// notified changes (and agent moves) affect.
+    ///
+    /// @returns JSON array [{"q":0,"r":0},...] from the agent to the goal (both included), or "null"
+    ///   if the goal is unreachable
+    pub fn replan(&mut self) -> String {
+        let state = WFC_STATE.lock().unwrap();
+        match self.plan(&state) {
+            Some(path) => {
+                let parts: Vec<String> = path.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+                format!("[{}]", parts.join(","))
+            }
+            None => "null".to_string(),
+        }
+    }
+
+    /// replan with a structured result (see the errors module)
+    ///
+    /// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or
+    ///   {"ok":null,"error":{"code":"no_path","message":"..."}} if the goal is unreachable
+    pub fn replan_result(&mut self) -> String {
+        let state = WFC_STATE.lock().unwrap();
+        let (start, goal) = (self.start, self.goal);
+        envelope(self.plan(&state)
+            .map(|path| {
+                let parts: Vec<String> = path.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+                format!("[{}]", parts.join(","))
+            })
+            .ok_or_else(|| ApiError::new(
+                ErrorCode::NoPath,
+                format!("no path from ({}, {}) to ({}, {})", start.0, start.1, goal.0, goal.1),
+            )))
+    }
+
+    /// Number of hexes expanded by the last replan (a measure of how much work it repaired)
+    pub fn last_expansions(&self) -> u32 {
+        self.expanded
+    }
+}
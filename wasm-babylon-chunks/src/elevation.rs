@@ -0,0 +1,428 @@
+//! Per-hex elevation layer and contour extraction module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+use crate::state::{TileLayer, WFC_STATE};
+use crate::types::TileType;
+use crate::json::{parse_json, parse_json_object, JsonValue};
+use crate::errors::{envelope, number_field, parse_object_param, string_field, ApiError, ErrorCode};
+use crate::hex_utils::{generate_hex_grid, get_hex_neighbors, hex_to_world, hexes_in_world_rect, CUBE_DIRECTIONS, UNIT_HEX_SIZE};
+use crate::noise::fractal_noise_2d;
+
+/// Global elevation layer: height per hex (thread-safe)
+///
+/// Hexes without a value have no elevation data (consumers treat them as 0 or skip them).
+pub static ELEVATION: LazyLock<Mutex<HashMap<(i32, i32), f64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Most contour levels traced in one call (guards against a tiny interval)
+const MAX_CONTOUR_LEVELS: i64 = 10_000;
+
+/// Replace the elevation layer
+///
+/// @param elevation_json - JSON array of heights: [{"q":0,"r":0,"value":1.5},...]
+/// @returns Number of hexes with elevation
+#[wasm_bindgen]
+pub fn set_elevation(elevation_json: String) -> u32 {
+    let values = parse_json(&elevation_json)
+        .map(|value| value.as_hex_values())
+        .unwrap_or_default();
+    let mut elevation = ELEVATION.lock().unwrap();
+    *elevation = values;
+    elevation.len() as u32
+}
+
+/// Get the elevation at a hex
+///
+/// @returns Height at the hex, or 0 if it has no elevation data
+#[wasm_bindgen]
+pub fn get_elevation(q: i32, r: i32) -> f64 {
+    ELEVATION.lock().unwrap().get(&(q, r)).copied().unwrap_or(0.0)
+}
+
+/// Remove all elevation data
+#[wasm_bindgen]
+pub fn clear_elevation() {
+    ELEVATION.lock().unwrap().clear();
+}
+
+/// Fill the elevation layer with fractal noise over a hexagonal grid
+///
+/// **Learning Point**: Noise is sampled at each hex center in a plane where
+/// neighbouring centers are exactly one unit apart (axial coordinates are skewed,
+/// so sampling at (q, r) directly would stretch the terrain along one diagonal).
+/// Frequency is in noise cycles per hex step: about 0.05 gives continent-sized
+/// landforms, 0.2 gives hills. Octaves add detail at doubling frequency on top.
+/// Heights are the noise remapped to 0-1 (most fall between 0.3 and 0.7). They are
+/// not stretched per grid, so separately generated neighbouring grids with the same
+/// seed meet seamlessly.
+///
+/// @param max_layer - Grid radius in rings around the center
+/// @param center_q - Center q coordinate
+/// @param center_r - Center r coordinate
+/// @param octaves - Noise octaves (at least 1)
+/// @param frequency - Noise cycles per hex step of the first octave
+/// @param seed - Noise seed; the same seed and parameters always give the same terrain
+/// @returns Number of hexes with elevation (the layer is replaced)
+#[wasm_bindgen]
+pub fn generate_elevation(max_layer: i32, center_q: i32, center_r: i32, octaves: u32, frequency: f64, seed: u32) -> u32 {
+    let values: HashMap<(i32, i32), f64> = generate_hex_grid(max_layer, center_q, center_r)
+        .iter()
+        .map(|hex| {
+            let (x, y) = hex_to_world(hex.q as f64, hex.r as f64, UNIT_HEX_SIZE);
+            let noise = fractal_noise_2d(x * frequency, y * frequency, seed as u64, octaves);
+            ((hex.q, hex.r), ((noise + 1.0) / 2.0).clamp(0.0, 1.0))
+        })
+        .collect();
+    let mut elevation = ELEVATION.lock().unwrap();
+    *elevation = values;
+    elevation.len() as u32
+}
+
+/// Export the whole elevation layer
+///
+/// @returns Float32Array with three values per hex, sorted by (q, r): [q0, r0, height0, q1, r1, height1, ...]
+#[wasm_bindgen]
+pub fn get_elevation_layer() -> Vec<f32> {
+    let elevation = ELEVATION.lock().unwrap();
+    let mut hexes: Vec<(i32, i32)> = elevation.keys().cloned().collect();
+    hexes.sort();
+
+    let mut values = Vec::with_capacity(hexes.len() * 3);
+    for hex in hexes {
+        values.push(hex.0 as f32);
+        values.push(hex.1 as f32);
+        values.push(elevation[&hex] as f32);
+    }
+    values
+}
+
+/// Bilinearly sample a row-major grid of values at fractional (column, row)
+fn sample_bilinear(values: &[f32], width: usize, height: usize, column: f64, row: f64) -> f64 {
+    let column = column.clamp(0.0, (width - 1) as f64);
+    let row = row.clamp(0.0, (height - 1) as f64);
+    let (c0, r0) = (column.floor() as usize, row.floor() as usize);
+    let (c1, r1) = ((c0 + 1).min(width - 1), (r0 + 1).min(height - 1));
+    let (tc, tr) = (column - c0 as f64, row - r0 as f64);
+    let at = |c: usize, r: usize| values[r * width + c] as f64;
+    let top = at(c0, r0) + (at(c1, r0) - at(c0, r0)) * tc;
+    let bottom = at(c0, r1) + (at(c1, r1) - at(c0, r1)) * tc;
+    top + (bottom - top) * tr
+}
+
+/// Sample an external heightmap onto the hex grid
+///
+/// **Learning Point**: The image is stretched over a world-space rectangle, and
+/// each hex whose center falls inside it takes the bilinearly interpolated height
+/// at that center, so the result does not depend on how image pixels line up with
+/// hexes. With a water and/or forest level the heights are also quantized into
+/// tiles (below waterLevel = Water, at or above forestLevel = Forest, otherwise
+/// Grass), written as pre-constraints by default so generation grows around the
+/// imported terrain, or straight into the grid.
+///
+/// @param heights - Float32Array of width * height samples, row-major; row 0 lies at minZ, column 0 at minX
+/// @param width - Heightmap width in samples
+/// @param height - Heightmap height in samples
+/// @param world_bounds_json - JSON object: {"minX":-50,"minZ":-50,"maxX":50,"maxZ":50}
+/// @param params_json - JSON object (all optional):
+///   {"hexSize":1.34,"heightScale":1,"waterLevel":0.3,"forestLevel":0.7,"layer":"constraint"|"grid"}
+///   levels are compared with scaled heights; without either level no tiles are written
+/// @returns Number of hexes that received elevation, or -1 if the sizes or bounds are invalid
+#[wasm_bindgen]
+pub fn import_heightmap(heights: &[f32], width: u32, height: u32, world_bounds_json: String, params_json: String) -> i32 {
+    let bounds = parse_json_object(&world_bounds_json);
+    let params = parse_json_object(&params_json);
+    apply_heightmap(heights, width, height, &bounds, &params).unwrap_or(-1)
+}
+
+/// import_heightmap with a structured result (see the errors module)
+///
+/// Unlike import_heightmap, a parameter of the wrong type is an error instead of falling back to its default.
+///
+/// @returns JSON envelope: {"ok":N,"error":null} with the number of hexes that received elevation, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument"
+#[wasm_bindgen]
+pub fn import_heightmap_result(heights: &[f32], width: u32, height: u32, world_bounds_json: String, params_json: String) -> String {
+    let checked = || {
+        let bounds = parse_object_param(&world_bounds_json, "world_bounds_json")?;
+        for key in ["minX", "minZ", "maxX", "maxZ"] {
+            number_field(&bounds, key, "world_bounds_json")?;
+        }
+        let params = parse_object_param(&params_json, "params_json")?;
+        for key in ["hexSize", "heightScale", "waterLevel", "forestLevel"] {
+            number_field(&params, key, "params_json")?;
+        }
+        string_field(&params, "layer", "params_json")?;
+        apply_heightmap(heights, width, height, &bounds, &params)
+    };
+    envelope(checked().map(|count| count.to_string()))
+}
+
+/// Import shared by import_heightmap and its `_result` variant
+fn apply_heightmap(heights: &[f32], width: u32, height: u32, bounds: &JsonValue, params: &JsonValue) -> Result<i32, ApiError> {
+    let invalid = |message: &str| ApiError::new(ErrorCode::InvalidArgument, message);
+    let (width, height) = (width as usize, height as usize);
+    if width == 0 || height == 0 || width.checked_mul(height) != Some(heights.len()) {
+        return Err(invalid("heights must hold width * height samples (both non-zero)"));
+    }
+    let bound = |key: &str| bounds.get(key).and_then(|value| value.as_f64());
+    let (Some(min_x), Some(min_z), Some(max_x), Some(max_z)) = (bound("minX"), bound("minZ"), bound("maxX"), bound("maxZ")) else {
+        return Err(invalid("world_bounds_json: minX, minZ, maxX and maxZ are required"));
+    };
+    if min_x >= max_x || min_z >= max_z {
+        return Err(invalid("world_bounds_json: the bounds are empty"));
+    }
+
+    let number = |key: &str| params.get(key).and_then(|value| value.as_f64());
+    let hex_size = number("hexSize").unwrap_or(1.34);
+    let height_scale = number("heightScale").unwrap_or(1.0);
+    let water_level = number("waterLevel");
+    let forest_level = number("forestLevel");
+    let Some(layer) = TileLayer::from_name(params.get("layer").and_then(|value| value.as_str()).unwrap_or("constraint")) else {
+        return Err(invalid(r#"params_json.layer: expected "constraint" or "grid""#));
+    };
+    if hex_size <= 0.0 {
+        return Err(invalid("params_json.hexSize: must be positive"));
+    }
+
+    let hexes = hexes_in_world_rect(min_x, min_z, max_x, max_z, hex_size);
+    let mut elevation = ELEVATION.lock().unwrap();
+    let mut state = WFC_STATE.lock().unwrap();
+    for &(q, r) in &hexes {
+        let (x, z) = hex_to_world(q as f64, r as f64, hex_size);
+        let column = (x - min_x) / (max_x - min_x) * (width - 1) as f64;
+        let row = (z - min_z) / (max_z - min_z) * (height - 1) as f64;
+        let value = sample_bilinear(heights, width, height, column, row) * height_scale;
+        elevation.insert((q, r), value);
+
+        if water_level.is_some() || forest_level.is_some() {
+            let tile = if water_level.is_some_and(|level| value < level) {
+                TileType::Water
+            } else if forest_level.is_some_and(|level| value >= level) {
+                TileType::Forest
+            } else {
+                TileType::Grass
+            };
+            state.set_layer_tile(layer, q, r, tile);
+        }
+    }
+    Ok(hexes.len() as i32)
+}
+
+/// Steepest height difference from each hex to a neighbour with elevation
+///
+/// Slope is measured per hex step; hexes with no neighbouring data have slope 0.
+pub fn slope_map(elevation: &HashMap<(i32, i32), f64>) -> HashMap<(i32, i32), f64> {
+    elevation.iter()
+        .map(|(&(q, r), &height)| {
+            let slope = get_hex_neighbors(q, r).into_iter()
+                .filter_map(|neighbor| elevation.get(&neighbor))
+                .map(|neighbor_height| (height - neighbor_height).abs())
+                .fold(0.0, f64::max);
+            ((q, r), slope)
+        })
+        .collect()
+}
+
+/// Compute the slope of every hex in the elevation layer
+///
+/// **Learning Point**: A hex's slope is the largest absolute height difference to
+/// any of its six neighbours, i.e. the steepest way off the hex. Building placement
+/// wants flat ground (low slope), road routing can add it as a step penalty, and
+/// rendering it as a heatmap shows where erosion cuts deepest.
+///
+/// @returns Float32Array with three values per hex, sorted by (q, r): [q0, r0, slope0, q1, r1, slope1, ...]
+///   slope is the height difference per hex step; hexes with no neighbouring data have slope 0
+#[wasm_bindgen]
+pub fn compute_slope_map() -> Vec<f32> {
+    let slopes = slope_map(&ELEVATION.lock().unwrap());
+    let mut hexes: Vec<(i32, i32)> = slopes.keys().cloned().collect();
+    hexes.sort();
+
+    let mut values = Vec::with_capacity(hexes.len() * 3);
+    for hex in hexes {
+        values.push(hex.0 as f32);
+        values.push(hex.1 as f32);
+        values.push(slopes[&hex] as f32);
+    }
+    values
+}
+
+/// Find hex edges where the elevation drops by more than a threshold
+///
+/// **Learning Point**: Slope is a property of a hex, but a cliff is a property of
+/// an edge: a plateau hex can have a sheer face on one side and a gentle ramp on
+/// another. Reporting the edge with its direction tells the renderer which side
+/// gets a cliff mesh, and tells pathfinding exactly which step to forbid while the
+/// ramp stays usable.
+///
+/// @param threshold - Smallest height difference across an edge that counts as a cliff
+/// @returns JSON array of cliff edges sorted by q, r, then direction, each listed once from its upper hex:
+///   [{"q":0,"r":0,"direction":3,"drop":1.25},...]
+///   where direction (0-5, see CUBE_DIRECTIONS) points from the upper hex to the lower neighbour
+#[wasm_bindgen]
+pub fn detect_cliffs(threshold: f64) -> String {
+    let elevation = ELEVATION.lock().unwrap();
+    let mut hexes: Vec<(i32, i32)> = elevation.keys().cloned().collect();
+    hexes.sort();
+
+    let mut parts = Vec::new();
+    for (q, r) in hexes {
+        let height = elevation[&(q, r)];
+        for (direction, offset) in CUBE_DIRECTIONS.iter().enumerate() {
+            let Some(&lower) = elevation.get(&(q + offset.q, r + offset.r)) else {
+                continue;
+            };
+            let drop = height - lower;
+            if drop > 0.0 && drop > threshold {
+                parts.push(format!(r#"{{"q":{},"r":{},"direction":{},"drop":{}}}"#, q, r, direction, drop));
+            }
+        }
+    }
+    format!("[{}]", parts.join(","))
+}
+
+/// Edge of the dual triangulation, identified by its two hex centers (sorted)
+type ContourEdge = ((i32, i32), (i32, i32));
+
+fn contour_edge(a: (i32, i32), b: (i32, i32)) -> ContourEdge {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// One traced contour line, as crossing edges in order
+struct ContourLine {
+    edges: Vec<ContourEdge>,
+    closed: bool,
+}
+
+/// Chain contour segments into polylines
+///
+/// Every crossing edge is shared by at most two triangles, so each edge touches at
+/// most two segments. Chains are started from edges touching one segment (lines
+/// that run off the data) first; whatever is left forms closed loops.
+fn join_segments(segments: &[(ContourEdge, ContourEdge)]) -> Vec<ContourLine> {
+    let mut by_edge: BTreeMap<ContourEdge, Vec<usize>> = BTreeMap::new();
+    for (index, (a, b)) in segments.iter().enumerate() {
+        by_edge.entry(*a).or_default().push(index);
+        by_edge.entry(*b).or_default().push(index);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let walk = |start: ContourEdge, used: &mut Vec<bool>| -> Option<ContourLine> {
+        let mut edges = vec![start];
+        let mut current = start;
+        while let Some(&index) = by_edge[&current].iter().find(|index| !used[**index]) {
+            used[index] = true;
+            let (a, b) = segments[index];
+            current = if a == current { b } else { a };
+            edges.push(current);
+        }
+        if edges.len() < 2 {
+            return None;
+        }
+        let closed = edges.len() > 2 && edges.first() == edges.last();
+        if closed {
+            edges.pop();
+        }
+        Some(ContourLine { edges, closed })
+    };
+
+    let mut lines = Vec::new();
+    let open_ends: Vec<ContourEdge> = by_edge.iter()
+        .filter(|(_, indices)| indices.len() == 1)
+        .map(|(edge, _)| *edge)
+        .collect();
+    for edge in open_ends {
+        lines.extend(walk(edge, &mut used));
+    }
+    for index in 0..segments.len() {
+        if !used[index] {
+            lines.extend(walk(segments[index].0, &mut used));
+        }
+    }
+    lines
+}
+
+/// Trace contour lines of the elevation layer at fixed height intervals
+///
+/// **Learning Point**: Hex centers form a triangle mesh (every hex corner is shared
+/// by three hexes, whose centers make a triangle). Marching triangles walks that
+/// mesh: a contour at height h crosses a triangle edge whenever one end is below h
+/// and the other is not, at the linearly interpolated point along the edge. Each
+/// triangle a level passes through gets exactly one segment, and segments on
+/// neighbouring triangles share their crossing edge, so they chain into polylines.
+/// Only triangles whose three hexes all have elevation are traced, so lines end at
+/// the edge of the data instead of dropping to 0.
+///
+/// @param interval - Height between contour levels (levels are multiples of it)
+/// @param hex_size - Hex size used by the renderer (see batch_hex_to_world)
+/// @returns JSON array of polylines in world space:
+///   [{"level":2.0,"closed":true,"points":[{"x":0.0,"z":0.0},...]},...]
+///   a closed line does not repeat its first point; "[]" if interval is not positive
+#[wasm_bindgen]
+pub fn extract_contours(interval: f64, hex_size: f64) -> String {
+    if interval <= 0.0 || !interval.is_finite() {
+        return "[]".to_string();
+    }
+    let elevation = ELEVATION.lock().unwrap();
+    let mut hexes: Vec<(i32, i32)> = elevation.keys().cloned().collect();
+    hexes.sort();
+
+    // Triangles to the right of each hex; together they cover the mesh exactly once
+    let mut triangles = Vec::new();
+    for &(q, r) in &hexes {
+        for corners in [[(q, r), (q + 1, r - 1), (q + 1, r)], [(q, r), (q + 1, r), (q, r + 1)]] {
+            if corners.iter().all(|hex| elevation.contains_key(hex)) {
+                triangles.push(corners);
+            }
+        }
+    }
+    let height = |hex: &(i32, i32)| elevation[hex];
+
+    let (low, high) = hexes.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), hex| {
+        (low.min(height(hex)), high.max(height(hex)))
+    });
+    if triangles.is_empty() || !low.is_finite() || !high.is_finite() {
+        return "[]".to_string();
+    }
+    let first_level = (low / interval).ceil() as i64;
+    let last_level = ((high / interval).floor() as i64).min(first_level + MAX_CONTOUR_LEVELS - 1);
+
+    let mut json_parts = Vec::new();
+    for step in first_level..=last_level {
+        let level = step as f64 * interval;
+        let mut segments = Vec::new();
+        for corners in &triangles {
+            let crossings: Vec<ContourEdge> = [(0, 1), (1, 2), (2, 0)].iter()
+                .filter(|(a, b)| (height(&corners[*a]) >= level) != (height(&corners[*b]) >= level))
+                .map(|(a, b)| contour_edge(corners[*a], corners[*b]))
+                .collect();
+            if let [a, b] = crossings[..] {
+                segments.push((a, b));
+            }
+        }
+
+        for line in join_segments(&segments) {
+            let mut points: Vec<String> = line.edges.iter()
+                .map(|(a, b)| {
+                    let t = (level - height(a)) / (height(b) - height(a));
+                    let q = a.0 as f64 + (b.0 - a.0) as f64 * t;
+                    let r = a.1 as f64 + (b.1 - a.1) as f64 * t;
+                    let (x, z) = hex_to_world(q, r, hex_size);
+                    format!(r#"{{"x":{:.4},"z":{:.4}}}"#, x, z)
+                })
+                .collect();
+            // A level that passes exactly through a hex center crosses several edges at that point
+            points.dedup();
+            if line.closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            json_parts.push(format!(
+                r#"{{"level":{},"closed":{},"points":[{}]}}"#,
+                level, line.closed, points.join(",")
+            ));
+        }
+    }
+
+    format!("[{}]", json_parts.join(","))
+}
@@ -0,0 +1,219 @@
+//! Structured API errors and result envelopes
+//!
+//! **Learning Point**: The original API signals failure with sentinels: "null" for
+//! no path, -1 for an empty hex, and silently empty sets for malformed JSON, so a
+//! caller cannot tell "no path exists" from "I sent broken input". The `_result`
+//! variants of the main functions validate their inputs strictly and always return
+//! an envelope:
+//!
+//!   {"ok":<result>,"error":null}
+//!   {"ok":null,"error":{"code":"invalid_json","message":"valid_terrain_json: expected an array"}}
+//!
+//! The sentinel-returning functions are kept unchanged for compatibility.
+//!
+//! Variants exist for:
+//!
+//! - Pathfinders: hex_astar, hex_astar_partial, hex_astar_danger_aware,
+//!   build_path_between_roads, find_sea_route, find_flight_path, and the D* Lite and
+//!   HPA* handles' replan_result and find_path_result
+//! - Lookups: get_tile_at, get_entropy_at, find_nearest_tile_of_type,
+//!   random_walkable_hex, find_nearest_neighbor_chunk, calculate_chunk_for_tile
+//! - Setters and importers: set_pre_constraint (and its _area and _ring forms),
+//!   set_adjacency_rules, set_tile_quotas, register_tile_set, learn_rules_from_example,
+//!   import_heightmap, import_tilemap_image, rasterize_polygon, fill_depressions
+//! - Everything else: query, generate_road_network_growing_tree, reservoir_sample,
+//!   generate_distance_field, generate_flow_field
+//!
+//! A lookup that finds nothing returns {"ok":null,"error":null}; an unknown handle is
+//! an "unknown_handle" error.
+//!
+//! The map_ handle functions and the HexMap class mirror the global functions on a
+//! map of their own and keep the sentinels; map_get_tile_at_result is the one
+//! exception, since -1 there also hides an unknown handle.
+
+use std::collections::{HashMap, HashSet};
+use crate::json::{escape_json_string, parse_json, JsonValue};
+use crate::movement::{movement_profile, MovementProfile};
+use crate::types::TileType;
+
+/// Machine-readable error category
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// A JSON parameter is not valid JSON or does not have the expected shape
+    InvalidJson,
+    /// A parameter is out of range (unknown tile type, bad expression, ...)
+    InvalidArgument,
+    /// A named movement profile is not registered
+    UnknownProfile,
+    /// The input is fine but no path connects the endpoints
+    NoPath,
+    /// The input is fine but the hex holds no tile
+    NoTile,
+    /// A map, field or session handle does not exist (or was freed)
+    UnknownHandle,
+}
+
+impl ErrorCode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidJson => "invalid_json",
+            ErrorCode::InvalidArgument => "invalid_argument",
+            ErrorCode::UnknownProfile => "unknown_profile",
+            ErrorCode::NoPath => "no_path",
+            ErrorCode::NoTile => "no_tile",
+            ErrorCode::UnknownHandle => "unknown_handle",
+        }
+    }
+}
+
+/// An error with a code for programs and a message for people
+#[derive(Clone, Debug)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ApiError { code, message: message.into() }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(r#"{{"code":"{}","message":"{}"}}"#, self.code.name(), escape_json_string(&self.message))
+    }
+}
+
+/// Wrap a result (already formatted as JSON) in an {"ok","error"} envelope
+pub fn envelope(result: Result<String, ApiError>) -> String {
+    match result {
+        Ok(value) => format!(r#"{{"ok":{},"error":null}}"#, value),
+        Err(error) => format!(r#"{{"ok":null,"error":{}}}"#, error.to_json()),
+    }
+}
+
+/// Parse a JSON parameter, naming it in the error
+pub fn parse_json_param(json: &str, param: &str) -> Result<JsonValue, ApiError> {
+    parse_json(json).ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, format!("{}: not valid JSON", param)))
+}
+
+/// Parse a JSON object parameter, treating empty input as `{}` (see parse_json_object)
+pub fn parse_object_param(json: &str, param: &str) -> Result<JsonValue, ApiError> {
+    if json.trim().is_empty() {
+        return Ok(JsonValue::Object(Vec::new()));
+    }
+    match parse_json_param(json, param)? {
+        value @ JsonValue::Object(_) => Ok(value),
+        _ => Err(ApiError::new(ErrorCode::InvalidJson, format!("{}: expected an object", param))),
+    }
+}
+
+/// Read an optional number field of an object parameter
+pub fn number_field(object: &JsonValue, key: &str, param: &str) -> Result<Option<f64>, ApiError> {
+    object.get(key)
+        .map(|value| value.as_f64().ok_or_else(|| ApiError::new(
+            ErrorCode::InvalidJson,
+            format!("{}.{}: expected a number", param, key),
+        )))
+        .transpose()
+}
+
+/// Read an optional string field of an object parameter
+pub fn string_field<'a>(object: &'a JsonValue, key: &str, param: &str) -> Result<Option<&'a str>, ApiError> {
+    object.get(key)
+        .map(|value| value.as_str().ok_or_else(|| ApiError::new(
+            ErrorCode::InvalidJson,
+            format!("{}.{}: expected a string", param, key),
+        )))
+        .transpose()
+}
+
+/// Parse a `[{"q":0,"r":0},...]` parameter, rejecting anything malformed
+pub fn parse_hex_set_param(json: &str, param: &str) -> Result<HashSet<(i32, i32)>, ApiError> {
+    hex_set_from_value(&parse_json_param(json, param)?, param)
+}
+
+/// Read an already parsed `[{"q":0,"r":0},...]` value (see parse_hex_set_param)
+pub fn hex_set_from_value(value: &JsonValue, param: &str) -> Result<HashSet<(i32, i32)>, ApiError> {
+    let items = value.as_array()
+        .ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, format!("{}: expected an array", param)))?;
+    items.iter()
+        .enumerate()
+        .map(|(index, item)| item.as_hex().ok_or_else(|| ApiError::new(
+            ErrorCode::InvalidJson,
+            format!("{}[{}]: expected {{\"q\":integer,\"r\":integer}}", param, index),
+        )))
+        .collect()
+}
+
+/// Parse a `[{"q":0,"r":0,"value":1.5},...]` parameter, rejecting anything malformed
+pub fn parse_hex_values_param(json: &str, param: &str) -> Result<HashMap<(i32, i32), f64>, ApiError> {
+    hex_values_from_value(&parse_json_param(json, param)?, param)
+}
+
+/// Read an already parsed `[{"q":0,"r":0,"value":1.5},...]` value (see parse_hex_values_param)
+pub fn hex_values_from_value(value: &JsonValue, param: &str) -> Result<HashMap<(i32, i32), f64>, ApiError> {
+    let items = value.as_array()
+        .ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, format!("{}: expected an array", param)))?;
+    items.iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let entry = item.as_hex().zip(item.get("value").and_then(JsonValue::as_f64));
+            entry.ok_or_else(|| ApiError::new(
+                ErrorCode::InvalidJson,
+                format!("{}[{}]: expected {{\"q\":integer,\"r\":integer,\"value\":number}}", param, index),
+            ))
+        })
+        .collect()
+}
+
+/// Convert a tile type id parameter, naming the valid range in the error
+pub fn tile_type_param(tile_type: i32) -> Result<TileType, ApiError> {
+    TileType::from_i32(tile_type).ok_or_else(|| ApiError::new(
+        ErrorCode::InvalidArgument,
+        format!("unknown tile type {} (expected 0-{} or a registered id)", tile_type, TileType::ALL.len() - 1),
+    ))
+}
+
+/// Look up a movement profile parameter by name
+pub fn profile_param(name: &str) -> Result<MovementProfile, ApiError> {
+    movement_profile(name).ok_or_else(|| unknown_profile(name))
+}
+
+/// The error for a movement profile name that is not registered
+pub fn unknown_profile(name: &str) -> ApiError {
+    ApiError::new(ErrorCode::UnknownProfile, format!("no movement profile named \"{}\"", name))
+}
+
+/// The error for a handle that is not in its registry
+pub fn unknown_handle(kind: &str, handle: u32) -> ApiError {
+    ApiError::new(ErrorCode::UnknownHandle, format!("unknown {} handle {}", kind, handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_wraps_results_and_errors() {
+        assert_eq!(envelope(Ok("[1]".to_string())), r#"{"ok":[1],"error":null}"#);
+        let error = ApiError::new(ErrorCode::NoPath, "no \"path\"");
+        assert_eq!(envelope(Err(error)), r#"{"ok":null,"error":{"code":"no_path","message":"no \"path\""}}"#);
+    }
+
+    #[test]
+    fn hex_set_param_names_the_bad_entry() {
+        assert_eq!(parse_hex_set_param(r#"[{"q":0,"r":1}]"#, "p").unwrap(), HashSet::from([(0, 1)]));
+        let error = parse_hex_set_param(r#"[{"q":0,"r":1},{"q":"x","r":1}]"#, "p").unwrap_err();
+        assert_eq!((error.code, error.message.as_str()), (ErrorCode::InvalidJson, r#"p[1]: expected {"q":integer,"r":integer}"#));
+        assert_eq!(parse_hex_set_param("{}", "p").unwrap_err().message, "p: expected an array");
+    }
+
+    #[test]
+    fn object_param_treats_empty_input_as_empty_object() {
+        assert_eq!(parse_object_param("  ", "p").unwrap(), JsonValue::Object(Vec::new()));
+        assert_eq!(parse_object_param("[]", "p").unwrap_err().code, ErrorCode::InvalidJson);
+        let object = parse_object_param(r#"{"n":"1"}"#, "p").unwrap();
+        assert_eq!(number_field(&object, "n", "p").unwrap_err().message, "p.n: expected a number");
+        assert_eq!(number_field(&object, "m", "p").unwrap(), None);
+    }
+}
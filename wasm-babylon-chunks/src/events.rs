@@ -0,0 +1,66 @@
+//! Tile change events module
+
+use wasm_bindgen::prelude::*;
+use crate::state::WFC_STATE;
+
+/// Enable or disable recording of tile changes
+///
+/// **Learning Point**: Generation and edit calls mutate the grid wholesale, and the
+/// renderer (or audio) only cares about tiles that actually changed. While tracking
+/// is on, every mutation is logged per hex, keeping the first old type and the latest
+/// new type, so a regenerate that reproduces the same tile yields no event at all.
+/// JS polls the batch after each call instead of diffing snapshots. Tracking is off
+/// by default; disabling it discards pending changes.
+///
+/// @param enabled - true to start recording, false to stop
+#[wasm_bindgen]
+pub fn set_change_tracking(enabled: bool) {
+    WFC_STATE.lock().unwrap().set_change_tracking(enabled);
+}
+
+/// Take all tile changes recorded since the last poll
+///
+/// @returns JSON array sorted by layer and coordinates (type -1 means no tile):
+///   [{"q":0,"r":0,"layer":"grid","oldType":0,"newType":2},...]
+///   layer is "grid" (generated tiles) or "constraint" (pre-constraints)
+#[wasm_bindgen]
+pub fn poll_tile_changes() -> String {
+    let changes = WFC_STATE.lock().unwrap().take_changes();
+
+    let json_parts: Vec<String> = changes.iter()
+        .map(|change| {
+            format!(
+                r#"{{"q":{},"r":{},"layer":"{}","oldType":{},"newType":{}}}"#,
+                change.hex.0,
+                change.hex.1,
+                change.layer.name(),
+                change.old.map(|tile| tile.id()).unwrap_or(-1),
+                change.new.map(|tile| tile.id()).unwrap_or(-1)
+            )
+        })
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Take all recorded tile changes as a flat buffer
+/// Same events as poll_tile_changes without building JSON.
+///
+/// @returns Int32Array of 5 values per change: [q, r, layer, oldType, newType, ...]
+///   layer is 0 for the grid and 1 for pre-constraints; type -1 means no tile
+#[wasm_bindgen]
+pub fn poll_tile_changes_buf() -> Vec<i32> {
+    let changes = WFC_STATE.lock().unwrap().take_changes();
+
+    let mut buffer = Vec::with_capacity(changes.len() * 5);
+    for change in changes {
+        buffer.extend_from_slice(&[
+            change.hex.0,
+            change.hex.1,
+            change.layer as i32,
+            change.old.map(|tile| tile.id()).unwrap_or(-1),
+            change.new.map(|tile| tile.id()).unwrap_or(-1),
+        ]);
+    }
+    buffer
+}
@@ -0,0 +1,162 @@
+//! Farmland plots, crops and row orientation module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::hex_utils::{get_hex_neighbors, hex_to_world, hexes_within_distance, UNIT_HEX_SIZE};
+use crate::json::{escape_json_string, parse_json_object};
+use crate::noise::value_noise_2d;
+use crate::rng::hash_values;
+
+/// A contiguous farmland plot
+pub struct FarmPlot {
+    pub hexes: Vec<(i32, i32)>,
+    /// Index into the crop list
+    pub crop: usize,
+    /// Row direction in degrees in the world x/z plane, in [0, 180)
+    pub row_angle: f64,
+    /// True if the rows follow road frontage, false if they follow the regional direction
+    pub road_aligned: bool,
+}
+
+/// Split farmland into contiguous plots of at most max_size hexes
+///
+/// Plots grow breadth-first from the smallest unassigned hex, so they are compact
+/// and the split is deterministic.
+pub fn partition_plots(farmland: &HashSet<(i32, i32)>, max_size: usize) -> Vec<Vec<(i32, i32)>> {
+    let mut sorted: Vec<(i32, i32)> = farmland.iter().cloned().collect();
+    sorted.sort();
+    let mut assigned: HashSet<(i32, i32)> = HashSet::new();
+    let mut plots = Vec::new();
+
+    for start in sorted {
+        if assigned.contains(&start) {
+            continue;
+        }
+        let mut plot = Vec::new();
+        let mut queue = VecDeque::new();
+        assigned.insert(start);
+        queue.push_back(start);
+        while let Some(hex) = queue.pop_front() {
+            plot.push(hex);
+            if plot.len() + queue.len() >= max_size {
+                continue;
+            }
+            for neighbor in get_hex_neighbors(hex.0, hex.1) {
+                if plot.len() + queue.len() >= max_size {
+                    break;
+                }
+                if farmland.contains(&neighbor) && assigned.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        plot.sort();
+        plots.push(plot);
+    }
+    plots
+}
+
+/// Row angle along a plot's road frontage, or None if it touches no road
+///
+/// **Learning Point**: Each plot-to-road edge has a normal pointing at the road;
+/// rows run along the road, i.e. perpendicular to that normal. Angles that differ
+/// by 180° describe the same rows, so the normals are averaged on doubled angles
+/// (where 0° and 180° coincide) and halved afterwards.
+fn frontage_angle(state: &WfcState, plot: &[(i32, i32)]) -> Option<f64> {
+    let (mut sum_x, mut sum_y, mut edges) = (0.0, 0.0, 0);
+    for &(q, r) in plot {
+        for (nq, nr) in get_hex_neighbors(q, r) {
+            if state.get_tile(nq, nr) == Some(TileType::Road) {
+                let (x, z) = hex_to_world((nq - q) as f64, (nr - r) as f64, UNIT_HEX_SIZE);
+                let doubled = 2.0 * z.atan2(x);
+                sum_x += doubled.cos();
+                sum_y += doubled.sin();
+                edges += 1;
+            }
+        }
+    }
+    if edges == 0 || (sum_x == 0.0 && sum_y == 0.0) {
+        return None;
+    }
+    let normal = sum_y.atan2(sum_x).to_degrees() / 2.0;
+    Some((normal + 90.0).rem_euclid(180.0))
+}
+
+/// Regional prevailing row direction at a hex (smoothly varying across the map)
+fn regional_angle(hex: (i32, i32), seed: u64, scale: f64) -> f64 {
+    let (x, z) = hex_to_world(hex.0 as f64, hex.1 as f64, UNIT_HEX_SIZE);
+    ((value_noise_2d(x * scale, z * scale, seed) + 1.0) * 90.0).rem_euclid(180.0)
+}
+
+/// Assign crops and row orientation to farmland around the road network
+///
+/// **Learning Point**: Farmland is grass within a few hexes of a road, split into
+/// small contiguous plots. Each plot gets one crop (hashed from the seed and the
+/// plot's first hex, so a plot keeps its crop when unrelated parts of the map
+/// change) and one row direction: along its road frontage when it has one,
+/// otherwise a regional prevailing direction from low-frequency noise, so
+/// neighbouring back fields line up the way real field systems do.
+///
+/// @param params_json - JSON object (all optional):
+///   {"seed":0,"crops":["wheat","barley","corn"],"farmDistance":3,"maxPlotSize":8,"regionScale":0.05}
+/// @returns JSON object:
+///   {"crops":["wheat","barley","corn"],
+///    "tiles":[{"q":0,"r":0,"plot":0,"crop":1,"rowAngle":60.0,"alignment":"road"|"region"},...]}
+///   rowAngle is in degrees in the world x/z plane (0 = +x), in [0, 180)
+#[wasm_bindgen]
+pub fn assign_farm_plots(params_json: String) -> String {
+    let params = parse_json_object(&params_json);
+    let seed = params.get("seed").and_then(|value| value.as_f64()).unwrap_or(0.0) as u64;
+    let mut crops: Vec<String> = params.get("crops")
+        .and_then(|value| value.as_array())
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    if crops.is_empty() {
+        crops = vec!["wheat".to_string(), "barley".to_string(), "corn".to_string()];
+    }
+    let farm_distance = params.get("farmDistance").and_then(|value| value.as_i32()).unwrap_or(3).max(1);
+    let max_plot_size = params.get("maxPlotSize").and_then(|value| value.as_i32()).unwrap_or(8).max(1) as usize;
+    let region_scale = params.get("regionScale").and_then(|value| value.as_f64()).unwrap_or(0.05);
+
+    let state = WFC_STATE.lock().unwrap();
+    let roads: HashSet<(i32, i32)> = state.grid_tiles()
+        .filter(|(_, tile)| *tile == TileType::Road)
+        .map(|(hex, _)| hex)
+        .collect();
+    let near_roads = hexes_within_distance(&roads, farm_distance);
+    let farmland: HashSet<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile)| *tile == TileType::Grass && near_roads.contains(hex))
+        .map(|(hex, _)| hex)
+        .collect();
+
+    let plots: Vec<FarmPlot> = partition_plots(&farmland, max_plot_size)
+        .into_iter()
+        .map(|hexes| {
+            let anchor = hexes[0];
+            let crop = (hash_values(seed, &[anchor.0 as i64, anchor.1 as i64]) % crops.len() as u64) as usize;
+            let (row_angle, road_aligned) = match frontage_angle(&state, &hexes) {
+                Some(angle) => (angle, true),
+                None => (regional_angle(anchor, seed.wrapping_add(1), region_scale), false),
+            };
+            FarmPlot { hexes, crop, row_angle, road_aligned }
+        })
+        .collect();
+
+    let mut tile_parts = Vec::new();
+    for (index, plot) in plots.iter().enumerate() {
+        let alignment = if plot.road_aligned { "road" } else { "region" };
+        for (q, r) in &plot.hexes {
+            tile_parts.push(format!(
+                r#"{{"q":{},"r":{},"plot":{},"crop":{},"rowAngle":{:.1},"alignment":"{}"}}"#,
+                q, r, index, plot.crop, plot.row_angle, alignment
+            ));
+        }
+    }
+    let crop_parts: Vec<String> = crops.iter()
+        .map(|crop| format!("\"{}\"", escape_json_string(crop)))
+        .collect();
+
+    format!(r#"{{"crops":[{}],"tiles":[{}]}}"#, crop_parts.join(","), tile_parts.join(","))
+}
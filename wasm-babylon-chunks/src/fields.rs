@@ -0,0 +1,297 @@
+//! Distance fields over hex terrain
+//!
+//! **Learning Point**: A* answers one question (start to goal) per search. When many
+//! agents share a destination, or the question is "how far is every hex from town",
+//! one Dijkstra pass from the destination answers it for every hex at once; a single
+//! field can then be read by any number of agents.
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::types::WeightedNode;
+use crate::hex_utils::{get_hex_neighbors, parse_valid_terrain_json, CUBE_DIRECTIONS};
+use crate::json::parse_json;
+use crate::movement::{movement_profile, MovementProfile};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::errors::{envelope, parse_hex_set_param, parse_object_param, profile_param, unknown_profile, ApiError, ErrorCode};
+
+/// Cost-distance from the nearest source for every traversable hex reachable from one
+///
+/// Multi-source Dijkstra: every source starts at distance 0. `step_cost(from, to)` is
+/// the cost of entering `to` from `from`; f64::INFINITY forbids the step. Sources
+/// outside the traversable set are ignored.
+pub fn distance_field<F>(
+    sources: &[(i32, i32)],
+    traversable: &HashSet<(i32, i32)>,
+    step_cost: F,
+) -> HashMap<(i32, i32), f64>
+where
+    F: Fn((i32, i32), (i32, i32)) -> f64,
+{
+    let mut distances: HashMap<(i32, i32), f64> = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    for &source in sources {
+        if traversable.contains(&source) && distances.insert(source, 0.0).is_none() {
+            open_set.push(WeightedNode { q: source.0, r: source.1, g: 0.0, f: 0.0 });
+        }
+    }
+
+    while let Some(current) = open_set.pop() {
+        let current_key = (current.q, current.r);
+        // Skip stale heap entries
+        if current.g > distances[&current_key] {
+            continue;
+        }
+        for neighbor in get_hex_neighbors(current.q, current.r) {
+            if !traversable.contains(&neighbor) {
+                continue;
+            }
+            let cost = step_cost(current_key, neighbor);
+            if !cost.is_finite() {
+                continue;
+            }
+            let tentative = current.g + cost;
+            if tentative < distances.get(&neighbor).copied().unwrap_or(f64::INFINITY) {
+                distances.insert(neighbor, tentative);
+                open_set.push(WeightedNode { q: neighbor.0, r: neighbor.1, g: tentative, f: tentative });
+            }
+        }
+    }
+
+    distances
+}
+
+/// Step count from the nearest start for every traversable hex reachable within max_depth steps
+///
+/// Multi-source BFS; starts outside the traversable set are ignored. A negative
+/// max_depth means no limit.
+pub fn reachable_within(
+    starts: &[(i32, i32)],
+    traversable: &HashSet<(i32, i32)>,
+    max_depth: i32,
+) -> HashMap<(i32, i32), i32> {
+    let mut depths: HashMap<(i32, i32), i32> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &start in starts {
+        if traversable.contains(&start) && depths.insert(start, 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some((q, r)) = queue.pop_front() {
+        let depth = depths[&(q, r)];
+        if max_depth >= 0 && depth >= max_depth {
+            continue;
+        }
+        for neighbor in get_hex_neighbors(q, r) {
+            if traversable.contains(&neighbor) && !depths.contains_key(&neighbor) {
+                depths.insert(neighbor, depth + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    depths
+}
+
+/// Distance field with uniform step costs or a movement profile's costs
+///
+/// @returns The field, or None if the profile is unknown
+fn profile_distance_field(
+    sources: &[(i32, i32)],
+    traversable: &HashSet<(i32, i32)>,
+    profile: Option<String>,
+) -> Option<HashMap<(i32, i32), f64>> {
+    match profile {
+        Some(name) => {
+            let profile = movement_profile(&name)?;
+            let state = WFC_STATE.lock().unwrap();
+            let scale = profile.min_cost();
+            Some(distance_field(sources, traversable, |from, to| {
+                profile.step_cost(state.get_tile(from.0, from.1), state.get_tile(to.0, to.1))
+                    .map(|cost| cost / scale)
+                    .unwrap_or(f64::INFINITY)
+            }))
+        }
+        None => Some(distance_field(sources, traversable, |_, _| 1.0)),
+    }
+}
+
+/// Generate a Dijkstra map: the cost-distance from every reachable hex to the nearest source
+///
+/// **Learning Point**: Following the field downhill from any hex traces a shortest
+/// path to the nearest source, so enemy AI can steer toward the player without an A*
+/// search per agent, and following it uphill flees. Reading the largest values finds
+/// spawn points "far from town".
+///
+/// @param sources_json - JSON array of source hexes: [{"q":0,"r":0},...] (non-traversable ones are ignored)
+/// @param traversable_json - JSON array of traversable hexes: [{"q":0,"r":0},...]
+/// @param profile - Optional movement profile name (see register_movement_profile); without one every
+///   step costs 1, with one steps are priced by the profile (in units of its cheapest tile) and hexes it
+///   cannot enter are unreachable
+/// @returns JSON array sorted by r, then q: [{"q":0,"r":0,"distance":0},...], or "null" if the
+///   profile is unknown
+#[wasm_bindgen]
+pub fn generate_distance_field(sources_json: String, traversable_json: String, profile: Option<String>) -> String {
+    let sources = parse_json(&sources_json).map(|value| value.as_hex_list()).unwrap_or_default();
+    let traversable = parse_valid_terrain_json(&traversable_json);
+    match profile_distance_field(&sources, &traversable, profile) {
+        Some(field) => distance_field_json(field),
+        None => "null".to_string(),
+    }
+}
+
+/// generate_distance_field with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0,"distance":0},...],"error":null}, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json" or "unknown_profile"
+#[wasm_bindgen]
+pub fn generate_distance_field_result(sources_json: String, traversable_json: String, profile: Option<String>) -> String {
+    let checked = || {
+        let mut sources: Vec<(i32, i32)> = parse_hex_set_param(&sources_json, "sources_json")?.into_iter().collect();
+        sources.sort();
+        let traversable = parse_hex_set_param(&traversable_json, "traversable_json")?;
+        let name = profile.clone().unwrap_or_default();
+        profile_distance_field(&sources, &traversable, profile)
+            .map(distance_field_json)
+            .ok_or_else(|| unknown_profile(&name))
+    };
+    envelope(checked())
+}
+
+/// Format a distance field sorted by r, then q
+fn distance_field_json(field: HashMap<(i32, i32), f64>) -> String {
+    let mut entries: Vec<((i32, i32), f64)> = field.into_iter().collect();
+    entries.sort_by_key(|&((q, r), _)| (r, q));
+    let parts: Vec<String> = entries.iter()
+        .map(|((q, r), distance)| format!(r#"{{"q":{},"r":{},"distance":{}}}"#, q, r, distance))
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Find every hex reachable from a set of start hexes within a number of steps
+///
+/// **Learning Point**: With uniform step costs a breadth-first search visits hexes
+/// in order of distance, so one pass from all starts at once gives the whole
+/// movement range (e.g. the hexes a unit can reach this turn) with no priority queue.
+///
+/// @param start_set_json - JSON array of start hexes: [{"q":0,"r":0},...] (non-traversable ones are ignored)
+/// @param traversable_json - JSON array of traversable hexes: [{"q":0,"r":0},...]
+/// @param max_depth - Maximum number of steps (negative = unlimited)
+/// @returns JSON array sorted by r, then q: [{"q":0,"r":0,"depth":0},...] (starts have depth 0)
+#[wasm_bindgen]
+pub fn compute_reachable(start_set_json: String, traversable_json: String, max_depth: i32) -> String {
+    let starts = parse_json(&start_set_json).map(|value| value.as_hex_list()).unwrap_or_default();
+    let traversable = parse_valid_terrain_json(&traversable_json);
+
+    let mut entries: Vec<((i32, i32), i32)> = reachable_within(&starts, &traversable, max_depth).into_iter().collect();
+    entries.sort_by_key(|&((q, r), _)| (r, q));
+    let parts: Vec<String> = entries.iter()
+        .map(|((q, r), depth)| format!(r#"{{"q":{},"r":{},"depth":{}}}"#, q, r, depth))
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Best direction toward a goal for every grid hex that can reach it
+///
+/// Runs Dijkstra backwards from the goal (a step toward the goal costs the entry
+/// cost of the hex stepped into), then points each hex at the neighbor minimizing
+/// entry cost plus remaining distance. Ties go to the lowest direction index.
+///
+/// @returns CUBE_DIRECTIONS index per hex (-1 at the goal); empty if the goal can't be entered
+pub fn flow_field(state: &WfcState, goal: (i32, i32), profile: &MovementProfile) -> HashMap<(i32, i32), i32> {
+    let passable = profile.passable_hexes(state);
+    let entry_cost = |hex: (i32, i32)| profile.step_cost(None, state.get_tile(hex.0, hex.1)).unwrap_or(f64::INFINITY);
+    // Expanding from `from` to `to` means an agent at `to` steps into `from`
+    let distances = distance_field(&[goal], &passable, |from, _| entry_cost(from));
+
+    let mut directions = HashMap::new();
+    for (&(q, r), &distance) in &distances {
+        if (q, r) == goal {
+            directions.insert(goal, -1);
+            continue;
+        }
+        let best = CUBE_DIRECTIONS.iter()
+            .enumerate()
+            .filter_map(|(direction, offset)| {
+                let neighbor = (q + offset.q, r + offset.r);
+                let remaining = distances.get(&neighbor)?;
+                Some((direction, entry_cost(neighbor) + remaining))
+            })
+            .filter(|(_, total)| *total <= distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        if let Some((direction, _)) = best {
+            directions.insert((q, r), direction as i32);
+        }
+    }
+    directions
+}
+
+/// Generate a flow field toward a goal over the current grid
+///
+/// **Learning Point**: Instead of one A* search per agent, every hex stores the
+/// direction of its next step toward the goal. Hundreds of agents heading for the
+/// same place then just look up the hex they stand on and move one step, and the
+/// field is only rebuilt when the goal or the map changes.
+///
+/// @param goal_q - Goal q coordinate (axial)
+/// @param goal_r - Goal r coordinate (axial)
+/// @param cost_table_json - Entry cost per tile name; unlisted tiles are impassable: {"road":1,"grass":3}
+/// @param profile - Optional movement profile name (see register_movement_profile); when given its
+///   costs and allowed layers are used instead of cost_table_json
+/// @returns JSON array sorted by r, then q: [{"q":0,"r":0,"direction":2},...] where direction is the
+///   CUBE_DIRECTIONS index (0-5) of the next step, -1 at the goal; hexes that can't reach the goal are
+///   left out, and the array is empty if the goal itself can't be entered; "null" if the profile is unknown
+#[wasm_bindgen]
+pub fn generate_flow_field(goal_q: i32, goal_r: i32, cost_table_json: String, profile: Option<String>) -> String {
+    let profile = match profile {
+        Some(name) => match movement_profile(&name) {
+            Some(profile) => profile,
+            None => return "null".to_string(),
+        },
+        None => MovementProfile::from_json(&cost_table_json, "[]", "[]"),
+    };
+    flow_field_json((goal_q, goal_r), &profile)
+}
+
+/// generate_flow_field with a structured result (see the errors module)
+///
+/// Unlike generate_flow_field, an unknown tile name or a non-positive cost in cost_table_json is an
+/// error instead of being skipped.
+///
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0,"direction":2},...],"error":null}, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json", "invalid_argument" or "unknown_profile"
+#[wasm_bindgen]
+pub fn generate_flow_field_result(goal_q: i32, goal_r: i32, cost_table_json: String, profile: Option<String>) -> String {
+    let checked = || {
+        let profile = match profile {
+            Some(name) => profile_param(&name)?,
+            None => {
+                let table = parse_object_param(&cost_table_json, "cost_table_json")?;
+                for (name, cost) in table.as_object().unwrap_or_default() {
+                    if TileType::from_name(name).is_none() {
+                        return Err(ApiError::new(ErrorCode::InvalidArgument, format!("cost_table_json.{}: unknown tile name", name)));
+                    }
+                    if !cost.as_f64().is_some_and(|cost| cost.is_finite() && cost > 0.0) {
+                        return Err(ApiError::new(ErrorCode::InvalidArgument, format!("cost_table_json.{}: expected a positive number", name)));
+                    }
+                }
+                MovementProfile::from_json(&cost_table_json, "[]", "[]")
+            }
+        };
+        Ok(flow_field_json((goal_q, goal_r), &profile))
+    };
+    envelope(checked())
+}
+
+/// Flow field over the current grid, sorted by r, then q
+fn flow_field_json(goal: (i32, i32), profile: &MovementProfile) -> String {
+    let state = WFC_STATE.lock().unwrap();
+
+    let mut entries: Vec<((i32, i32), i32)> = flow_field(&state, goal, profile).into_iter().collect();
+    entries.sort_by_key(|&((q, r), _)| (r, q));
+    let parts: Vec<String> = entries.iter()
+        .map(|((q, r), direction)| format!(r#"{{"q":{},"r":{},"direction":{}}}"#, q, r, direction))
+        .collect();
+    format!("[{}]", parts.join(","))
+}
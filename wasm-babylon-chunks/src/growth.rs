@@ -0,0 +1,256 @@
+//! Agent-based city growth simulation module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::hex_utils::{get_hex_neighbors, hex_distance_field};
+use crate::json::parse_json_object;
+use crate::rng::SeededRng;
+
+/// Tunable weights for the growth simulation
+#[derive(Clone, Debug)]
+pub struct GrowthParams {
+    pub agent_count: usize,
+    /// Weight of road frontage (share of neighbors that are road) in building desirability
+    pub road_access_weight: f64,
+    /// Weight of water proximity in building desirability
+    pub water_weight: f64,
+    /// Distance at which water stops adding desirability
+    pub water_range: i32,
+    /// Minimum desirability for an agent to build instead of extending a road
+    pub build_threshold: f64,
+    /// Chance per tick that an agent extends its road even when it could build
+    pub extend_chance: f64,
+    /// Road hexes an agent wanders along before acting
+    pub wander_steps: usize,
+    /// Road hexes new agents start on (empty = any road hex)
+    pub spawn: Vec<(i32, i32)>,
+}
+
+impl Default for GrowthParams {
+    fn default() -> Self {
+        GrowthParams {
+            agent_count: 4,
+            road_access_weight: 1.0,
+            water_weight: 0.5,
+            water_range: 4,
+            build_threshold: 0.3,
+            extend_chance: 0.25,
+            wander_steps: 3,
+            spawn: Vec::new(),
+        }
+    }
+}
+
+impl GrowthParams {
+    pub fn from_json(params_json: &str) -> Self {
+        let params = parse_json_object(params_json);
+        let defaults = GrowthParams::default();
+        let number = |key: &str, default: f64| params.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+        GrowthParams {
+            agent_count: number("agents", defaults.agent_count as f64).max(0.0) as usize,
+            road_access_weight: number("roadAccessWeight", defaults.road_access_weight),
+            water_weight: number("waterWeight", defaults.water_weight),
+            water_range: number("waterRange", defaults.water_range as f64).max(1.0) as i32,
+            build_threshold: number("buildThreshold", defaults.build_threshold),
+            extend_chance: number("extendChance", defaults.extend_chance).clamp(0.0, 1.0),
+            wander_steps: number("wanderSteps", defaults.wander_steps as f64).max(0.0) as usize,
+            spawn: params.get("spawn").map(|value| value.as_hex_list()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Tiles placed during one tick
+#[derive(Clone, Debug, Default)]
+pub struct GrowthDelta {
+    pub roads: Vec<(i32, i32)>,
+    pub buildings: Vec<(i32, i32)>,
+}
+
+/// Persistent simulation state: developer agents living on the road network
+pub struct GrowthSim {
+    params: GrowthParams,
+    rng: SeededRng,
+    /// Current road hex of each agent
+    agents: Vec<(i32, i32)>,
+    tick: u32,
+}
+
+impl GrowthSim {
+    pub fn new(seed: u64, params: GrowthParams) -> Self {
+        GrowthSim {
+            params,
+            rng: SeededRng::new(seed),
+            agents: Vec::new(),
+            tick: 0,
+        }
+    }
+
+    /// Building desirability of a grass hex: road frontage plus water proximity
+    fn desirability(&self, state: &WfcState, hex: (i32, i32), water_distance: &HashMap<(i32, i32), i32>) -> f64 {
+        let neighbors = get_hex_neighbors(hex.0, hex.1);
+        let road_neighbors = neighbors.iter()
+            .filter(|(q, r)| state.get_tile(*q, *r) == Some(TileType::Road))
+            .count();
+        if road_neighbors == 0 {
+            return 0.0;
+        }
+        let road_access = road_neighbors as f64 / 6.0;
+        let water = match water_distance.get(&hex) {
+            Some(distance) => 1.0 - *distance as f64 / (self.params.water_range + 1) as f64,
+            None => 0.0,
+        };
+        road_access * self.params.road_access_weight + water * self.params.water_weight
+    }
+
+    /// Run one tick, applying placements to the grid
+    pub fn step(&mut self, state: &mut WfcState) -> GrowthDelta {
+        self.tick += 1;
+        let mut delta = GrowthDelta::default();
+
+        let mut roads: Vec<(i32, i32)> = state.grid_tiles()
+            .filter(|(_, tile)| *tile == TileType::Road)
+            .map(|(hex, _)| hex)
+            .collect();
+        if roads.is_empty() {
+            return delta;
+        }
+        roads.sort();
+
+        // Respawn agents whose road disappeared, and top up to the configured count,
+        // on the spawn hexes that are road (or anywhere on the network without any)
+        let road_set: HashSet<(i32, i32)> = roads.iter().cloned().collect();
+        self.agents.retain(|hex| road_set.contains(hex));
+        let mut spawns: Vec<(i32, i32)> = self.params.spawn.iter()
+            .filter(|hex| road_set.contains(*hex))
+            .cloned()
+            .collect();
+        spawns.sort();
+        spawns.dedup();
+        if spawns.is_empty() {
+            spawns = roads.clone();
+        }
+        while self.agents.len() < self.params.agent_count {
+            let spawn = spawns[self.rng.next_below(spawns.len())];
+            self.agents.push(spawn);
+        }
+
+        let water: HashSet<(i32, i32)> = state.grid_tiles()
+            .filter(|(_, tile)| *tile == TileType::Water)
+            .map(|(hex, _)| hex)
+            .collect();
+        let water_distance = hex_distance_field(&water, self.params.water_range);
+
+        for agent_index in 0..self.agents.len() {
+            // Wander along the road network
+            let mut position = self.agents[agent_index];
+            for _ in 0..self.params.wander_steps {
+                let next: Vec<(i32, i32)> = get_hex_neighbors(position.0, position.1)
+                    .into_iter()
+                    .filter(|(q, r)| state.get_tile(*q, *r) == Some(TileType::Road))
+                    .collect();
+                if next.is_empty() {
+                    break;
+                }
+                position = next[self.rng.next_below(next.len())];
+            }
+            self.agents[agent_index] = position;
+
+            // Grass hexes next to the agent, best building spot first (ties broken by coordinates)
+            let mut candidates: Vec<((i32, i32), f64)> = get_hex_neighbors(position.0, position.1)
+                .into_iter()
+                .filter(|(q, r)| state.get_tile(*q, *r) == Some(TileType::Grass))
+                .map(|hex| (hex, self.desirability(state, hex, &water_distance)))
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+            // A lone road hex (e.g. a freshly founded center) grows a street before
+            // any building, so houses can't box it in
+            let lone = !get_hex_neighbors(position.0, position.1)
+                .iter()
+                .any(|(q, r)| state.get_tile(*q, *r) == Some(TileType::Road));
+            let (best, score) = candidates[0];
+            let extend = lone || score < self.params.build_threshold || self.rng.next_f64() < self.params.extend_chance;
+            if !extend {
+                state.insert_tile(best.0, best.1, TileType::Building);
+                delta.buildings.push(best);
+                continue;
+            }
+
+            // Extend into the grass hex touching the fewest roads, so streets stay one hex wide
+            let road_count = |hex: &(i32, i32)| get_hex_neighbors(hex.0, hex.1)
+                .iter()
+                .filter(|(q, r)| state.get_tile(*q, *r) == Some(TileType::Road))
+                .count();
+            let min_roads = candidates.iter().map(|(hex, _)| road_count(hex)).min().unwrap_or(0);
+            let options: Vec<(i32, i32)> = candidates.iter()
+                .map(|(hex, _)| *hex)
+                .filter(|hex| road_count(hex) == min_roads)
+                .collect();
+            let target = options[self.rng.next_below(options.len())];
+            state.insert_tile(target.0, target.1, TileType::Road);
+            delta.roads.push(target);
+            self.agents[agent_index] = target;
+        }
+
+        delta
+    }
+}
+
+/// Global growth simulation (thread-safe)
+pub static GROWTH_SIM: LazyLock<Mutex<GrowthSim>> = LazyLock::new(|| Mutex::new(GrowthSim::new(0, GrowthParams::default())));
+
+/// Reset the growth simulation with new settings
+///
+/// @param seed - Random seed
+/// @param params_json - JSON object (all optional):
+///   {"agents":4,"roadAccessWeight":1,"waterWeight":0.5,"waterRange":4,"buildThreshold":0.3,"extendChance":0.25,"wanderSteps":3,
+///    "spawn":[{"q":0,"r":0},...]}
+///   agents start on the spawn hexes that are road; without any they start anywhere on the road network
+#[wasm_bindgen]
+pub fn configure_growth(seed: u32, params_json: String) {
+    *GROWTH_SIM.lock().unwrap() = GrowthSim::new(seed as u64, GrowthParams::from_json(&params_json));
+}
+
+/// Advance the city growth simulation on the current grid
+///
+/// **Learning Point**: Instead of generating a finished city once, developer agents
+/// live on the road network and act every tick: each wanders a few road hexes, then
+/// either builds on the most desirable grass hex next to it (desirability = road
+/// frontage + water proximity) or, if nothing there is worth building on, extends
+/// the road into fresh grass. Cities therefore densify along good streets and sprawl
+/// outward when they run out of space, and the game can call this between frames.
+/// Agents and the random stream persist between calls; configure_growth resets them.
+///
+/// @param ticks - Number of ticks to simulate
+/// @returns JSON array with one delta per tick:
+///   [{"tick":1,"roads":[{"q":0,"r":0}],"buildings":[{"q":1,"r":0}]},...]
+///   ticks place nothing while the grid has no roads
+#[wasm_bindgen]
+pub fn simulate_growth(ticks: u32) -> String {
+    let mut sim = GROWTH_SIM.lock().unwrap();
+    let mut state = WFC_STATE.lock().unwrap();
+
+    let hexes_json = |hexes: &[(i32, i32)]| {
+        let parts: Vec<String> = hexes.iter()
+            .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+            .collect();
+        format!("[{}]", parts.join(","))
+    };
+
+    let mut tick_parts = Vec::new();
+    for _ in 0..ticks {
+        let delta = sim.step(&mut state);
+        tick_parts.push(format!(
+            r#"{{"tick":{},"roads":{},"buildings":{}}}"#,
+            sim.tick, hexes_json(&delta.roads), hexes_json(&delta.buildings)
+        ));
+    }
+
+    format!("[{}]", tick_parts.join(","))
+}
@@ -0,0 +1,449 @@
+//! Independent map states: the HexMap class and the handle-based map registry
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::errors::{envelope, unknown_handle, ApiError, ErrorCode};
+use crate::layout::{
+    adjacency_violations_json, apply_adjacency_rule, apply_adjacency_rules, apply_tile_quotas, apply_tile_weight,
+    begin_wfc_run, layout_default, layout_wfc, regenerate_area, pre_constrain_area, pre_constrain_ring, quota_report_json,
+    stats_json, step_wfc_run, wfc_run_entropy, wfc_run_remaining,
+};
+use crate::query::{parse_query, query_hexes};
+use crate::learning::{example_stats, learn_rules};
+use crate::hex_utils::{hex_list_from_flat, parse_valid_terrain_json};
+use crate::utils::{nearest_tile_json, nearest_tile_of_type};
+
+/// A hex map with its own grid and pre-constraints
+///
+/// **Learning Point**: The free functions (generate_layout, get_tile_at, ...) all
+/// work on the single global WFC_STATE, so a page can only hold one map at a time.
+/// A HexMap owns a WfcState instead; JS can keep as many as it likes (overworld,
+/// interiors, previews) and they never see each other's tiles. Generators that
+/// only exist as free functions can still run on a HexMap by swapping it in as
+/// the global state with swap_with_global, and swapping it back out afterwards.
+///
+/// Used from JS as an object:
+///   const map = new HexMap(); map.set_pre_constraint(0, 0, 2); map.generate_layout(); map.get_tile_at(0, 0);
+///   map.free() releases the WASM memory when the map is no longer needed.
+#[wasm_bindgen]
+pub struct HexMap {
+    state: WfcState,
+}
+
+impl Default for HexMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl HexMap {
+    /// Create an empty map
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> HexMap {
+        HexMap { state: WfcState::new() }
+    }
+
+    /// Set a pre-constraint (see the set_pre_constraint function)
+    ///
+    /// @returns true if set, false if the tile type is invalid
+    pub fn set_pre_constraint(&mut self, q: i32, r: i32, tile_type: i32) -> bool {
+        match TileType::from_i32(tile_type) {
+            Some(tile) => self.state.set_pre_constraint(q, r, tile),
+            None => false,
+        }
+    }
+
+    /// Pre-constrain a filled hexagon (see the set_pre_constraint_area function)
+    ///
+    /// @returns Number of hexes constrained, or -1 if the tile type or radius is invalid
+    pub fn set_pre_constraint_area(&mut self, center_q: i32, center_r: i32, radius: i32, tile_type: i32) -> i32 {
+        pre_constrain_area(&mut self.state, center_q, center_r, radius, tile_type).unwrap_or(-1)
+    }
+
+    /// Pre-constrain a ring (see the set_pre_constraint_ring function)
+    ///
+    /// @returns Number of hexes constrained, or -1 if the tile type or radius is invalid
+    pub fn set_pre_constraint_ring(&mut self, center_q: i32, center_r: i32, radius: i32, tile_type: i32) -> i32 {
+        pre_constrain_ring(&mut self.state, center_q, center_r, radius, tile_type).unwrap_or(-1)
+    }
+
+    /// Remove all pre-constraints
+    pub fn clear_pre_constraints(&mut self) {
+        self.state.clear_pre_constraints();
+    }
+
+    /// Apply the pre-constraints, filling an area or mask with WFC if given (see the generate_layout function)
+    ///
+    /// @param mask_coords - Optional interleaved hex coordinates to generate instead of the area: [q0, r0, q1, r1, ...]
+    /// @returns true if the pre-constraints were applied or the area (or mask) was fully collapsed
+    pub fn generate_layout(&mut self, max_layer: Option<i32>, center_q: Option<i32>, center_r: Option<i32>, mask_coords: Option<Vec<i32>>) -> bool {
+        let mask = mask_coords.map(|coords| hex_list_from_flat(&coords));
+        layout_default(&mut self.state, max_layer, (center_q.unwrap_or(0), center_r.unwrap_or(0)), mask)
+    }
+
+    /// Fill a hexagonal area with wave function collapse (see the generate_layout_wfc function)
+    ///
+    /// @returns true if the area was fully collapsed
+    pub fn generate_layout_wfc(&mut self, max_layer: i32, center_q: i32, center_r: i32) -> bool {
+        layout_wfc(&mut self.state, max_layer, center_q, center_r)
+    }
+
+    /// Re-collapse a hexagonal area after edits (see the regenerate_region function)
+    ///
+    /// @returns true if the area was re-collapsed
+    pub fn regenerate_region(&mut self, center_q: i32, center_r: i32, radius: i32) -> bool {
+        regenerate_area(&mut self.state, center_q, center_r, radius)
+    }
+
+    /// Start a step-wise WFC run (see the begin_layout_wfc function)
+    ///
+    /// @returns false if the radius is negative or the pre-constraints contradict the rules
+    pub fn begin_layout_wfc(&mut self, max_layer: i32, center_q: i32, center_r: i32) -> bool {
+        begin_wfc_run(&mut self.state, max_layer, center_q, center_r)
+    }
+
+    /// Collapse up to `count` cells of the step-wise run (see the wfc_step function)
+    ///
+    /// @returns Number of cells collapsed; 0 once the run is finished or has failed
+    pub fn wfc_step(&mut self, count: u32) -> u32 {
+        step_wfc_run(&mut self.state, count)
+    }
+
+    /// Undecided cells of the step-wise run, or -1 if it gave up (see the wfc_remaining function)
+    pub fn wfc_remaining(&self) -> i32 {
+        wfc_run_remaining(&self.state)
+    }
+
+    /// Entropy of a cell in the step-wise run, or -1 if not part of it (see the get_entropy_at function)
+    pub fn get_entropy_at(&self, q: i32, r: i32) -> f64 {
+        wfc_run_entropy(&self.state, q, r).unwrap_or(-1.0)
+    }
+
+    /// Allow or forbid two tile types as neighbors in this map (see the set_adjacency_rule function)
+    ///
+    /// @returns false if either tile type is invalid
+    pub fn set_adjacency_rule(&mut self, tile_a: i32, tile_b: i32, allowed: bool) -> bool {
+        apply_adjacency_rule(&mut self.state, tile_a, tile_b, allowed)
+    }
+
+    /// Set many adjacency rules (see the set_adjacency_rules function)
+    ///
+    /// @returns Number of rules applied, or -1 if the JSON or any tile type is invalid
+    pub fn set_adjacency_rules(&mut self, rules_json: &str) -> i32 {
+        apply_adjacency_rules(&mut self.state, rules_json).unwrap_or(-1)
+    }
+
+    /// Whether two tile types may be neighbors in this map
+    pub fn is_adjacency_allowed(&self, tile_a: i32, tile_b: i32) -> bool {
+        match (TileType::from_i32(tile_a), TileType::from_i32(tile_b)) {
+            (Some(a), Some(b)) => self.state.rules().allows(a, b),
+            _ => false,
+        }
+    }
+
+    /// Set a tile type's collapse weight for this map (see the set_tile_weight function)
+    ///
+    /// @returns false if the tile type or weight is invalid
+    pub fn set_tile_weight(&mut self, tile_type: i32, weight: f64) -> bool {
+        apply_tile_weight(&mut self.state, tile_type, weight)
+    }
+
+    /// Set tile count limits for generation in this map (see the set_tile_quotas function)
+    ///
+    /// @returns Number of quotas set, or -1 if the JSON, a tile name or a bound is invalid
+    pub fn set_tile_quotas(&mut self, quotas_json: &str) -> i32 {
+        apply_tile_quotas(&mut self.state, quotas_json).unwrap_or(-1)
+    }
+
+    /// Check this map's grid against its tile quotas (see the check_tile_quotas function)
+    pub fn check_tile_quotas(&self) -> String {
+        quota_report_json(&self.state)
+    }
+
+    /// Learn WFC rules for this map from an example (see the learn_rules_from_example function)
+    ///
+    /// @returns JSON summary, or "null" if the JSON is invalid or has no valid tiles
+    pub fn learn_rules_from_example(&mut self, tiles_json: &str) -> String {
+        match example_stats(tiles_json) {
+            Some(stats) => learn_rules(&mut self.state, &stats),
+            None => "null".to_string(),
+        }
+    }
+
+    /// Neighboring tiles that break the adjacency rules (see the validate_adjacency function)
+    pub fn validate_adjacency(&self) -> String {
+        adjacency_violations_json(&self.state)
+    }
+
+    /// Empty the grid (pre-constraints are kept)
+    pub fn clear_layout(&mut self) {
+        self.state.clear();
+    }
+
+    /// Tile type at a hex
+    ///
+    /// @returns Tile type as i32, or -1 if the hex is empty
+    pub fn get_tile_at(&self, q: i32, r: i32) -> i32 {
+        self.state.get_tile(q, r).map(|tile| tile.id()).unwrap_or(-1)
+    }
+
+    /// Closest tile of a type (see the find_nearest_tile_of_type function)
+    ///
+    /// @returns JSON object {"q":3,"r":-1,"distance":2}, or "null" if none is within max_radius
+    pub fn find_nearest_tile_of_type(&self, q: i32, r: i32, tile_type: i32, max_radius: i32) -> String {
+        let Some(tile) = TileType::from_i32(tile_type) else {
+            return "null".to_string();
+        };
+        nearest_tile_json(nearest_tile_of_type(&self.state, (q, r), tile, max_radius))
+    }
+
+    /// Write a tile straight into the grid
+    ///
+    /// @returns false if the tile type is invalid
+    pub fn set_tile(&mut self, q: i32, r: i32, tile_type: i32) -> bool {
+        match TileType::from_i32(tile_type) {
+            Some(tile) => {
+                self.state.insert_tile(q, r, tile);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of hexes in the grid
+    pub fn tile_count(&self) -> u32 {
+        self.state.grid_values().count() as u32
+    }
+
+    /// Tile counts (see the get_stats function)
+    ///
+    /// @returns JSON string with tile counts: {"grass":X,"building":Y,...,"total":C}
+    pub fn get_stats(&self) -> String {
+        stats_json(&self.state)
+    }
+
+    /// Select hexes with a filter expression (see the query function)
+    ///
+    /// @returns JSON array of matching hexes sorted by coordinates, or an error describing the syntax problem
+    pub fn query(&self, expression: &str) -> Result<String, JsValue> {
+        let parsed = parse_query(expression).map_err(|e| JsValue::from_str(&e))?;
+        Ok(query_hexes(parsed, &self.state))
+    }
+
+    /// Exchange this map's state with the global state used by the free functions
+    ///
+    /// Call it once to make the map current, run any free-function generator, then
+    /// call it again to take the result back and restore the previous global map.
+    pub fn swap_with_global(&mut self) {
+        std::mem::swap(&mut self.state, &mut WFC_STATE.lock().unwrap());
+    }
+}
+
+/// Registry of map states keyed by handle
+///
+/// **Learning Point**: The handle API is the function-style alternative to
+/// HexMap for callers that prefer plain numbers over JS objects (or that keep map
+/// references in data structures that outlive a single JS object). The state
+/// stays in WASM until destroy_map, so forgetting to destroy a map leaks it the
+/// same way forgetting HexMap.free() does.
+pub struct MapRegistry {
+    maps: HashMap<u32, WfcState>,
+    next_handle: u32,
+}
+
+impl MapRegistry {
+    pub fn new() -> Self {
+        MapRegistry {
+            maps: HashMap::new(),
+            next_handle: 1,
+        }
+    }
+
+    /// Store a state and return its handle (handles start at 1 and are never reused)
+    pub fn insert(&mut self, state: WfcState) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.maps.insert(handle, state);
+        handle
+    }
+
+    pub fn get(&self, handle: u32) -> Option<&WfcState> {
+        self.maps.get(&handle)
+    }
+
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut WfcState> {
+        self.maps.get_mut(&handle)
+    }
+
+    pub fn remove(&mut self, handle: u32) -> bool {
+        self.maps.remove(&handle).is_some()
+    }
+}
+
+impl Default for MapRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global map registry (thread-safe)
+pub static MAPS: LazyLock<Mutex<MapRegistry>> = LazyLock::new(|| Mutex::new(MapRegistry::new()));
+
+/// Create an empty map and get its handle
+///
+/// @returns Handle for the new map (never 0)
+#[wasm_bindgen]
+pub fn create_map() -> u32 {
+    MAPS.lock().unwrap().insert(WfcState::new())
+}
+
+/// Release a map
+///
+/// @param handle - Handle returned by create_map
+/// @returns true if the handle existed
+#[wasm_bindgen]
+pub fn destroy_map(handle: u32) -> bool {
+    MAPS.lock().unwrap().remove(handle)
+}
+
+/// Set a pre-constraint on a map (see set_pre_constraint)
+///
+/// @returns true if set, false if the tile type is invalid or the handle is unknown
+#[wasm_bindgen]
+pub fn map_set_pre_constraint(handle: u32, q: i32, r: i32, tile_type: i32) -> bool {
+    let mut maps = MAPS.lock().unwrap();
+    match (maps.get_mut(handle), TileType::from_i32(tile_type)) {
+        (Some(state), Some(tile)) => state.set_pre_constraint(q, r, tile),
+        _ => false,
+    }
+}
+
+/// Pre-constrain a filled hexagon on a map (see set_pre_constraint_area)
+///
+/// @returns Number of hexes constrained, or -1 if the tile type, radius or handle is invalid
+#[wasm_bindgen]
+pub fn map_set_pre_constraint_area(handle: u32, center_q: i32, center_r: i32, radius: i32, tile_type: i32) -> i32 {
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => pre_constrain_area(state, center_q, center_r, radius, tile_type).unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Pre-constrain a ring on a map (see set_pre_constraint_ring)
+///
+/// @returns Number of hexes constrained, or -1 if the tile type, radius or handle is invalid
+#[wasm_bindgen]
+pub fn map_set_pre_constraint_ring(handle: u32, center_q: i32, center_r: i32, radius: i32, tile_type: i32) -> i32 {
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => pre_constrain_ring(state, center_q, center_r, radius, tile_type).unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Remove all pre-constraints of a map
+///
+/// @returns false if the handle is unknown
+#[wasm_bindgen]
+pub fn map_clear_pre_constraints(handle: u32) -> bool {
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => {
+            state.clear_pre_constraints();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Apply a map's pre-constraints, filling an area or mask with WFC if given (see generate_layout)
+///
+/// @returns false if the area (or mask) could not be collapsed or the handle is unknown
+#[wasm_bindgen]
+pub fn map_generate_layout(handle: u32, max_layer: Option<i32>, center_q: Option<i32>, center_r: Option<i32>, mask_json: Option<String>) -> bool {
+    let mask = mask_json.map(|json| parse_valid_terrain_json(&json).into_iter().collect());
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => layout_default(state, max_layer, (center_q.unwrap_or(0), center_r.unwrap_or(0)), mask),
+        None => false,
+    }
+}
+
+/// Fill a hexagonal area of a map with wave function collapse (see generate_layout_wfc)
+///
+/// @returns true if the area was fully collapsed, false if it could not be or the handle is unknown
+#[wasm_bindgen]
+pub fn map_generate_layout_wfc(handle: u32, max_layer: i32, center_q: i32, center_r: i32) -> bool {
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => layout_wfc(state, max_layer, center_q, center_r),
+        None => false,
+    }
+}
+
+/// Empty a map's grid (pre-constraints are kept)
+///
+/// @returns false if the handle is unknown
+#[wasm_bindgen]
+pub fn map_clear_layout(handle: u32) -> bool {
+    match MAPS.lock().unwrap().get_mut(handle) {
+        Some(state) => {
+            state.clear();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Tile type at a hex of a map
+///
+/// @returns Tile type as i32, or -1 if the hex is empty or the handle is unknown
+#[wasm_bindgen]
+pub fn map_get_tile_at(handle: u32, q: i32, r: i32) -> i32 {
+    MAPS.lock().unwrap()
+        .get(handle)
+        .and_then(|state| state.get_tile(q, r))
+        .map(|tile| tile.id())
+        .unwrap_or(-1)
+}
+
+/// map_get_tile_at with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":2,"error":null} with the tile type, or {"ok":null,"error":{...}} with
+///   code "unknown_handle" or "no_tile"
+#[wasm_bindgen]
+pub fn map_get_tile_at_result(handle: u32, q: i32, r: i32) -> String {
+    let maps = MAPS.lock().unwrap();
+    envelope(maps.get(handle)
+        .ok_or_else(|| unknown_handle("map", handle))
+        .and_then(|state| state.get_tile(q, r)
+            .ok_or_else(|| ApiError::new(ErrorCode::NoTile, format!("no tile at ({}, {})", q, r))))
+        .map(|tile| tile.id().to_string()))
+}
+
+/// Tile counts of a map (see get_stats)
+///
+/// @returns JSON string with tile counts, or "null" if the handle is unknown
+#[wasm_bindgen]
+pub fn map_get_stats(handle: u32) -> String {
+    match MAPS.lock().unwrap().get(handle) {
+        Some(state) => stats_json(state),
+        None => "null".to_string(),
+    }
+}
+
+/// Exchange a map's state with the global state used by the free functions
+/// (see HexMap.swap_with_global)
+///
+/// @returns false if the handle is unknown
+#[wasm_bindgen]
+pub fn map_swap_with_global(handle: u32) -> bool {
+    let mut maps = MAPS.lock().unwrap();
+    match maps.get_mut(handle) {
+        Some(state) => {
+            std::mem::swap(state, &mut WFC_STATE.lock().unwrap());
+            true
+        }
+        None => false,
+    }
+}
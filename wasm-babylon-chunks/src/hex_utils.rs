@@ -1,6 +1,7 @@
 /// Hex coordinate utilities module
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
 use crate::types::{HexCoord, CubeCoord};
 
 /// Cube directions for hex grid navigation
@@ -128,9 +129,185 @@ pub fn generate_hex_grid(max_layer: i32, center_q: i32, center_r: i32) -> Vec<He
         }
     }
     
+    // Sort so callers that index into the grid (e.g. Voronoi seeding) are deterministic
+    grid.sort_by_key(|hex| (hex.q, hex.r));
+    
     grid
 }
 
+/// Find the center of the super-hex cell containing (q, r)
+/// 
+/// Super-hexes of radius `radius` tile the plane with centers on the lattice spanned by
+/// (2R+1, -R) and (R, R+1) - the same packing chunks use. Every hex lies within
+/// `radius` of exactly one lattice point, so we solve for fractional lattice
+/// coordinates and test the surrounding lattice points.
+pub fn super_hex_center(q: i32, r: i32, radius: i32) -> (i32, i32) {
+    if radius <= 0 {
+        return (q, r);
+    }
+    
+    let (aq, ar) = (2 * radius + 1, -radius);
+    let (bq, br) = (radius, radius + 1);
+    let det = (aq * br - ar * bq) as f64;
+    let fa = ((q * br - r * bq) as f64 / det).floor() as i32;
+    let fb = ((r * aq - q * ar) as f64 / det).floor() as i32;
+    
+    let mut best = (q, r);
+    let mut best_distance = i32::MAX;
+    for da in -1..=2 {
+        for db in -1..=2 {
+            let cq = (fa + da) * aq + (fb + db) * bq;
+            let cr = (fa + da) * ar + (fb + db) * br;
+            let distance = hex_distance(q, r, cq, cr);
+            if distance < best_distance {
+                best_distance = distance;
+                best = (cq, cr);
+            }
+        }
+    }
+    
+    best
+}
+
+/// Hex distance from the nearest source for every hex within max_distance
+/// Multi-source BFS over the hex plane, so distance is geometric hex distance
+pub fn hex_distance_field(sources: &HashSet<(i32, i32)>, max_distance: i32) -> HashMap<(i32, i32), i32> {
+    let mut distances: HashMap<(i32, i32), i32> = sources.iter().map(|hex| (*hex, 0)).collect();
+    let mut frontier: Vec<(i32, i32)> = sources.iter().cloned().collect();
+    
+    for depth in 1..=max_distance {
+        let mut next_frontier = Vec::new();
+        for (q, r) in frontier {
+            for neighbor in get_hex_neighbors(q, r) {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(depth);
+                    next_frontier.push(neighbor);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    
+    distances
+}
+
+/// Collect every hex within max_distance of any source hex (sources included)
+pub fn hexes_within_distance(sources: &HashSet<(i32, i32)>, max_distance: i32) -> HashSet<(i32, i32)> {
+    hex_distance_field(sources, max_distance).into_keys().collect()
+}
+
+/// Flood fill from a start hex over hexes accepted by `passable`
+/// Returns every hex reachable from start (including start itself if passable)
+pub fn flood_fill<F>(start: (i32, i32), passable: F) -> HashSet<(i32, i32)>
+where
+    F: Fn((i32, i32)) -> bool,
+{
+    let mut visited = HashSet::new();
+    if !passable(start) {
+        return visited;
+    }
+    
+    let mut queue = VecDeque::new();
+    visited.insert(start);
+    queue.push_back(start);
+    
+    while let Some((q, r)) = queue.pop_front() {
+        for neighbor in get_hex_neighbors(q, r) {
+            if !visited.contains(&neighbor) && passable(neighbor) {
+                visited.insert(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    
+    visited
+}
+
+/// Round fractional cube coordinates to the nearest hex
+/// The component with the largest rounding error is recomputed so q + r + s = 0 holds
+pub fn cube_round(q: f64, r: f64, s: f64) -> (i32, i32) {
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    (rq as i32, rr as i32)
+}
+
+/// World position of (possibly fractional) axial coordinates
+/// Pointy-top layout matching the TypeScript renderer:
+/// x = size * (2√3 * q + √3 * r), z = size * 3r, with size = hex_size / 1.34
+pub fn hex_to_world(q: f64, r: f64, hex_size: f64) -> (f64, f64) {
+    let size = hex_size / 1.34;
+    let sqrt3 = 3.0_f64.sqrt();
+    (size * (sqrt3 * 2.0 * q + sqrt3 * r), size * (3.0 * r))
+}
+
+/// hex_size at which hex_to_world puts neighboring hex centers 1 world unit apart
+/// (x = q + r/2, z = r·√3/2), the frame used by the noise-driven fields
+pub const UNIT_HEX_SIZE: f64 = 1.34 / (2.0 * 1.732_050_807_568_877_2);
+
+/// Hexes whose centers fall inside a world-space rectangle (see hex_to_world)
+/// Returned sorted by r, then q.
+pub fn hexes_in_world_rect(min_x: f64, min_z: f64, max_x: f64, max_z: f64, hex_size: f64) -> Vec<(i32, i32)> {
+    let size = hex_size / 1.34;
+    let column_width = 3.0_f64.sqrt() * size;
+    let mut hexes = Vec::new();
+    let first_r = (min_z / (3.0 * size)).ceil() as i32;
+    let last_r = (max_z / (3.0 * size)).floor() as i32;
+    for r in first_r..=last_r {
+        // x = column_width * (2q + r)
+        let first_q = ((min_x / column_width - r as f64) / 2.0).ceil() as i32;
+        let last_q = ((max_x / column_width - r as f64) / 2.0).floor() as i32;
+        for q in first_q..=last_q {
+            hexes.push((q, r));
+        }
+    }
+    hexes
+}
+
+/// Hexes on the straight line from a to b (both included)
+/// 
+/// **Learning Point**: Sample the line at hex_distance + 1 evenly spaced points and
+/// round each to a hex. The endpoints are nudged by a tiny epsilon so points that
+/// land exactly on an edge between two hexes always round the same way.
+pub fn hex_line(a: (i32, i32), b: (i32, i32)) -> Vec<(i32, i32)> {
+    let distance = hex_distance(a.0, a.1, b.0, b.1);
+    if distance == 0 {
+        return vec![a];
+    }
+    const EPSILON: f64 = 1e-6;
+    let (aq, ar) = (a.0 as f64 + EPSILON, a.1 as f64 + EPSILON);
+    let (bq, br) = (b.0 as f64 + EPSILON, b.1 as f64 + EPSILON);
+    (0..=distance)
+        .map(|step| {
+            let t = step as f64 / distance as f64;
+            let q = aq + (bq - aq) * t;
+            let r = ar + (br - ar) * t;
+            cube_round(q, r, -q - r)
+        })
+        .collect()
+}
+
+/// Build a hex set from interleaved coordinates [q0, r0, q1, r1, ...]
+/// A trailing unpaired value is ignored
+pub fn hex_set_from_flat(coords: &[i32]) -> HashSet<(i32, i32)> {
+    coords.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Hex list from interleaved coordinates, keeping order and duplicates
+/// A trailing unpaired value is ignored
+pub fn hex_list_from_flat(coords: &[i32]) -> Vec<(i32, i32)> {
+    coords.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Interleave hexes into a flat buffer [q0, r0, q1, r1, ...]
+pub fn hexes_to_flat(hexes: &[(i32, i32)]) -> Vec<i32> {
+    hexes.iter().flat_map(|&(q, r)| [q, r]).collect()
+}
+
 /// Parse valid terrain JSON string into HashSet
 /// Format: [{"q":0,"r":0},{"q":1,"r":0},...]
 /// Returns empty HashSet if parsing fails
@@ -0,0 +1,199 @@
+//! Layered settlement history module
+
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeMap;
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::json::parse_json_object;
+use crate::rng::SeededRng;
+use crate::growth::{GrowthParams, GrowthSim};
+
+/// What is left of an older era's tile
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Remnant {
+    /// Foundations and rubble, visible on open ground
+    Ruin,
+    /// Remains swallowed by forest
+    Overgrowth,
+    /// Old road still in use
+    Road,
+}
+
+impl Remnant {
+    fn name(self) -> &'static str {
+        match self {
+            Remnant::Ruin => "ruin",
+            Remnant::Overgrowth => "overgrowth",
+            Remnant::Road => "road",
+        }
+    }
+}
+
+/// Decay settings between eras
+struct DecayParams {
+    seed: u64,
+    ticks_per_era: u32,
+    ruin_chance: f64,
+    overgrowth_chance: f64,
+    road_survival: f64,
+}
+
+impl DecayParams {
+    fn from_json(params_json: &str) -> Self {
+        let params = parse_json_object(params_json);
+        let number = |key: &str, default: f64| params.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+        DecayParams {
+            seed: number("seed", 0.0) as u64,
+            ticks_per_era: number("ticksPerEra", 30.0).max(1.0) as u32,
+            ruin_chance: number("ruinChance", 0.5).clamp(0.0, 1.0),
+            overgrowth_chance: number("overgrowthChance", 0.3).clamp(0.0, 1.0),
+            road_survival: number("roadSurvival", 0.4).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Age everything built before the current era by one era
+///
+/// Buildings become ruins (on grass) or overgrowth (forest) or vanish; roads
+/// either stay in use or decay the same way. Existing ruins can be overgrown.
+fn decay_era(
+    state: &mut WfcState,
+    layers: &mut BTreeMap<(i32, i32), (u32, Remnant)>,
+    era: u32,
+    params: &DecayParams,
+    rng: &mut SeededRng,
+) {
+    for (_, remnant) in layers.values_mut() {
+        if *remnant == Remnant::Ruin && rng.next_f64() < params.overgrowth_chance {
+            *remnant = Remnant::Overgrowth;
+        }
+    }
+
+    let mut built: Vec<((i32, i32), TileType)> = state.grid_tiles()
+        .filter(|(_, tile)| matches!(*tile, TileType::Building | TileType::Road))
+        .collect();
+    built.sort_by_key(|(hex, _)| *hex);
+
+    for (hex, tile) in built {
+        if tile == TileType::Road && rng.next_f64() < params.road_survival {
+            layers.entry(hex).or_insert((era, Remnant::Road));
+            continue;
+        }
+        let remnant = if rng.next_f64() < params.overgrowth_chance {
+            Some(Remnant::Overgrowth)
+        } else if rng.next_f64() < params.ruin_chance {
+            Some(Remnant::Ruin)
+        } else {
+            None
+        };
+        // A road in use since an earlier era keeps that era's date
+        let built_era = match layers.get(&hex) {
+            Some((road_era, Remnant::Road)) => *road_era,
+            _ => era,
+        };
+        match remnant {
+            Some(remnant) => {
+                layers.insert(hex, (built_era, remnant));
+            }
+            None => {
+                layers.remove(&hex);
+            }
+        }
+        let decayed = if remnant == Some(Remnant::Overgrowth) { TileType::Forest } else { TileType::Grass };
+        state.insert_tile(hex.0, hex.1, decayed);
+    }
+
+    // Ruins overgrown this era are forest now too
+    for (hex, (_, remnant)) in layers.iter() {
+        if *remnant == Remnant::Overgrowth && state.get_tile(hex.0, hex.1) == Some(TileType::Grass) {
+            state.insert_tile(hex.0, hex.1, TileType::Forest);
+        }
+    }
+}
+
+/// Generate successive settlements on the current grid, aging older ones into ruins
+///
+/// **Learning Point**: Each era founds a settlement at a random grass hex and grows
+/// it with the agent simulation (see simulate_growth), with every agent starting
+/// at the new center so the town grows from its own site. When the era ends, its
+/// buildings decay: some leave ruins on open ground, some are swallowed by forest,
+/// some vanish, and a share of the roads stays in use for the next era to build
+/// along. Older ruins keep decaying as eras pass. Only the last era is left
+/// intact, so the final map shows a living town on top of older layers.
+///
+/// The grid holds the blended result (ruins are grass, overgrowth is forest); the
+/// returned layers say which hexes carry remains of which era, for decals or props.
+///
+/// @param eras - Number of settlement eras (the last one stays intact)
+/// @param decay_params_json - JSON object (all optional):
+///   {"seed":0,"ticksPerEra":30,"ruinChance":0.5,"overgrowthChance":0.3,"roadSurvival":0.4}
+///   plus any simulate_growth parameter ("agents", "buildThreshold", ...)
+/// @returns JSON object:
+///   {"eras":[{"era":0,"center":{"q":0,"r":0},"roads":12,"buildings":20},...],
+///    "layers":[{"q":0,"r":0,"era":0,"kind":"ruin"|"overgrowth"|"road"},...]}
+///   an era with no free grass to settle on reports "center":null
+#[wasm_bindgen]
+pub fn generate_history(eras: u32, decay_params_json: String) -> String {
+    let params = DecayParams::from_json(&decay_params_json);
+    let growth_params = GrowthParams::from_json(&decay_params_json);
+    let mut rng = SeededRng::new(params.seed);
+    let mut state = WFC_STATE.lock().unwrap();
+
+    let mut layers: BTreeMap<(i32, i32), (u32, Remnant)> = BTreeMap::new();
+    let mut era_parts = Vec::new();
+
+    for era in 0..eras {
+        let mut grass: Vec<(i32, i32)> = state.grid_tiles()
+            .filter(|(_, tile)| *tile == TileType::Grass)
+            .map(|(hex, _)| hex)
+            .collect();
+        grass.sort();
+
+        let mut roads = 0;
+        let mut buildings = 0;
+        let center = if grass.is_empty() {
+            None
+        } else {
+            let center = grass[rng.next_below(grass.len())];
+            state.insert_tile(center.0, center.1, TileType::Road);
+            roads += 1;
+
+            // This era's agents start from its own center, not from older roads
+            let era_params = GrowthParams { spawn: vec![center], ..growth_params.clone() };
+            let mut sim = GrowthSim::new(rng.next_u64(), era_params);
+            for _ in 0..params.ticks_per_era {
+                let delta = sim.step(&mut state);
+                roads += delta.roads.len();
+                buildings += delta.buildings.len();
+            }
+            Some(center)
+        };
+
+        let center_json = match center {
+            Some((q, r)) => format!(r#"{{"q":{},"r":{}}}"#, q, r),
+            None => "null".to_string(),
+        };
+        era_parts.push(format!(
+            r#"{{"era":{},"center":{},"roads":{},"buildings":{}}}"#,
+            era, center_json, roads, buildings
+        ));
+
+        if era + 1 < eras {
+            decay_era(&mut state, &mut layers, era, &params, &mut rng);
+        }
+    }
+
+    // Remains built over by a later era are gone
+    let layer_parts: Vec<String> = layers.iter()
+        .filter(|(hex, (_, remnant))| {
+            let tile = state.get_tile(hex.0, hex.1);
+            *remnant == Remnant::Road || !matches!(tile, Some(TileType::Building) | Some(TileType::Road))
+        })
+        .map(|((q, r), (era, remnant))| format!(
+            r#"{{"q":{},"r":{},"era":{},"kind":"{}"}}"#,
+            q, r, era, remnant.name()
+        ))
+        .collect();
+
+    format!(r#"{{"eras":[{}],"layers":[{}]}}"#, era_parts.join(","), layer_parts.join(","))
+}
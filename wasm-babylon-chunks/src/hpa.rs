@@ -0,0 +1,321 @@
+//! Hierarchical pathfinding (HPA*) over chunks
+//!
+//! **Learning Point**: Flat A* across a large world explores every hex between the
+//! endpoints. HPA* splits the world into the same chunks the renderer uses and
+//! precomputes a small abstract graph:
+//! - portals: for every open stretch of border between two neighboring chunks, one
+//!   pair of adjacent hexes (one on each side) connected by a step of cost 1
+//! - intra-chunk edges: the walking distance between every two portals of a chunk
+//!
+//! A long query only connects the start and goal to the portals of their own
+//! chunks, runs A* over the portals, and then refines each abstract step with a
+//! local A* confined to one chunk. Paths are near-optimal (they pass through portal
+//! hexes) and a terrain change only rebuilds the chunk it happened in.
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::types::WeightedNode;
+use crate::errors::{envelope, ApiError, ErrorCode};
+use crate::astar::find_path;
+use crate::chunks::{chunk_border_pairs, chunk_neighbor_centers, BorderPair};
+use crate::fields::distance_field;
+use crate::hex_utils::{generate_hex_grid, get_hex_neighbors, hex_distance, parse_valid_terrain_json, super_hex_center};
+
+/// Centers of two neighboring chunks, lower first
+type ChunkPair = ((i32, i32), (i32, i32));
+
+/// Portal to (portal, walking distance) within one chunk
+type PortalEdges = HashMap<(i32, i32), Vec<((i32, i32), f64)>>;
+
+/// Hierarchical path planner over chunked terrain
+///
+/// Used from JS as an object:
+///   const planner = new HierarchicalPathfinder(rings); planner.set_terrain(json); planner.find_path(0, 0, 90, -40);
+#[wasm_bindgen]
+pub struct HierarchicalPathfinder {
+    rings: i32,
+    terrain: HashSet<(i32, i32)>,
+    /// Portal pairs per pair of neighboring chunks (pair.0 in the lower chunk)
+    borders: HashMap<ChunkPair, Vec<BorderPair>>,
+    /// Walking distances between the portals of each chunk
+    intra: HashMap<(i32, i32), PortalEdges>,
+}
+
+impl HierarchicalPathfinder {
+    fn chunk_of(&self, hex: (i32, i32)) -> (i32, i32) {
+        super_hex_center(hex.0, hex.1, self.rings)
+    }
+
+    /// Traversable hexes of a chunk
+    fn chunk_terrain(&self, chunk: (i32, i32)) -> HashSet<(i32, i32)> {
+        generate_hex_grid(self.rings, chunk.0, chunk.1)
+            .iter()
+            .map(|hex| (hex.q, hex.r))
+            .filter(|hex| self.terrain.contains(hex))
+            .collect()
+    }
+
+    /// Border key and whether `chunk` is its lower side
+    fn border_key(chunk: (i32, i32), neighbor: (i32, i32)) -> (ChunkPair, bool) {
+        if chunk < neighbor {
+            ((chunk, neighbor), true)
+        } else {
+            ((neighbor, chunk), false)
+        }
+    }
+
+    /// Portal hexes of a chunk with the hexes they step to across a border
+    fn crossings(&self, chunk: (i32, i32)) -> Vec<((i32, i32), (i32, i32))> {
+        let mut crossings = Vec::new();
+        for neighbor in chunk_neighbor_centers(chunk.0, chunk.1, self.rings) {
+            let (key, lower) = Self::border_key(chunk, neighbor);
+            for &(a, b) in self.borders.get(&key).into_iter().flatten() {
+                crossings.push(if lower { (a, b) } else { (b, a) });
+            }
+        }
+        crossings
+    }
+
+    /// Pick one portal pair per open stretch of the border between two chunks
+    fn rebuild_border(&mut self, lower: (i32, i32), higher: (i32, i32)) {
+        let open: Vec<BorderPair> = chunk_border_pairs(lower, higher, self.rings)
+            .into_iter()
+            .filter(|(a, b)| self.terrain.contains(a) && self.terrain.contains(b))
+            .collect();
+
+        // Group pairs into entrances: runs whose hexes touch on both sides
+        let touches = |x: (i32, i32), y: (i32, i32)| hex_distance(x.0, x.1, y.0, y.1) <= 1;
+        let mut entrances: Vec<Vec<BorderPair>> = Vec::new();
+        for pair in open {
+            let joined: Vec<usize> = entrances.iter()
+                .enumerate()
+                .filter(|(_, entrance)| entrance.iter().any(|other| touches(pair.0, other.0) && touches(pair.1, other.1)))
+                .map(|(index, _)| index)
+                .collect();
+            let mut merged = vec![pair];
+            for index in joined.into_iter().rev() {
+                merged.extend(entrances.swap_remove(index));
+            }
+            entrances.push(merged);
+        }
+
+        let mut portals: Vec<BorderPair> = entrances.into_iter()
+            .map(|mut entrance| {
+                entrance.sort();
+                entrance[entrance.len() / 2]
+            })
+            .collect();
+        portals.sort();
+        if portals.is_empty() {
+            self.borders.remove(&(lower, higher));
+        } else {
+            self.borders.insert((lower, higher), portals);
+        }
+    }
+
+    /// Recompute the walking distances between a chunk's portals
+    fn rebuild_intra(&mut self, chunk: (i32, i32)) {
+        let local = self.chunk_terrain(chunk);
+        let mut portals: Vec<(i32, i32)> = self.crossings(chunk).into_iter().map(|(portal, _)| portal).collect();
+        portals.sort();
+        portals.dedup();
+
+        let mut edges = HashMap::new();
+        for &portal in &portals {
+            let distances = distance_field(&[portal], &local, |_, _| 1.0);
+            let links: Vec<((i32, i32), f64)> = portals.iter()
+                .filter(|other| **other != portal)
+                .filter_map(|other| Some((*other, *distances.get(other)?)))
+                .collect();
+            edges.insert(portal, links);
+        }
+        if edges.is_empty() {
+            self.intra.remove(&chunk);
+        } else {
+            self.intra.insert(chunk, edges);
+        }
+    }
+
+    /// Walking distance from a hex to every portal of its chunk it can reach locally
+    fn portal_links(&self, hex: (i32, i32)) -> HashMap<(i32, i32), f64> {
+        let chunk = self.chunk_of(hex);
+        let distances = distance_field(&[hex], &self.chunk_terrain(chunk), |_, _| 1.0);
+        self.crossings(chunk)
+            .into_iter()
+            .filter_map(|(portal, _)| Some((portal, *distances.get(&portal)?)))
+            .collect()
+    }
+
+    /// Plan a path: A* over portals, then local refinement inside each chunk
+    ///
+    /// @returns Path from start to goal (both included), or None if unreachable
+    pub fn plan(&self, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        if !self.terrain.contains(&start) || !self.terrain.contains(&goal) {
+            return None;
+        }
+        // Short trips inside one chunk don't need the abstract graph
+        if self.chunk_of(start) == self.chunk_of(goal) {
+            if let Some(path) = find_path(start.0, start.1, goal.0, goal.1, &self.chunk_terrain(self.chunk_of(start))) {
+                return Some(path);
+            }
+        }
+
+        let start_links = self.portal_links(start);
+        let goal_links = self.portal_links(goal);
+        let heuristic = |(q, r): (i32, i32)| hex_distance(q, r, goal.0, goal.1) as f64;
+
+        let mut open_set = BinaryHeap::new();
+        let mut g_scores: HashMap<(i32, i32), f64> = HashMap::new();
+        let mut parents: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        let mut closed_set = HashSet::new();
+        g_scores.insert(start, 0.0);
+        open_set.push(WeightedNode { q: start.0, r: start.1, g: 0.0, f: heuristic(start) });
+
+        while let Some(current) = open_set.pop() {
+            let node = (current.q, current.r);
+            if !closed_set.insert(node) {
+                continue;
+            }
+            if node == goal {
+                break;
+            }
+
+            let chunk = self.chunk_of(node);
+            let mut edges: Vec<((i32, i32), f64)> = Vec::new();
+            if node == start {
+                edges.extend(start_links.iter().map(|(portal, cost)| (*portal, *cost)));
+            }
+            edges.extend(self.intra.get(&chunk).and_then(|links| links.get(&node)).into_iter().flatten().copied());
+            edges.extend(self.crossings(chunk).into_iter().filter(|(portal, _)| *portal == node).map(|(_, across)| (across, 1.0)));
+            if let Some(cost) = goal_links.get(&node) {
+                edges.push((goal, *cost));
+            }
+
+            for (next, cost) in edges {
+                let tentative = current.g + cost;
+                if tentative < g_scores.get(&next).copied().unwrap_or(f64::INFINITY) {
+                    g_scores.insert(next, tentative);
+                    parents.insert(next, node);
+                    open_set.push(WeightedNode { q: next.0, r: next.1, g: tentative, f: tentative + heuristic(next) });
+                }
+            }
+        }
+
+        if !closed_set.contains(&goal) {
+            return None;
+        }
+        let mut waypoints = vec![goal];
+        while let Some(parent) = parents.get(waypoints.last()?) {
+            waypoints.push(*parent);
+        }
+        waypoints.reverse();
+
+        // Refine: steps inside a chunk get a local A*, border crossings are single steps
+        let mut path = vec![start];
+        for step in waypoints.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            if from == to {
+                continue;
+            }
+            if get_hex_neighbors(from.0, from.1).contains(&to) {
+                path.push(to);
+                continue;
+            }
+            let local = find_path(from.0, from.1, to.0, to.1, &self.chunk_terrain(self.chunk_of(from)))?;
+            path.extend_from_slice(&local[1..]);
+        }
+        Some(path)
+    }
+}
+
+#[wasm_bindgen]
+impl HierarchicalPathfinder {
+    /// Create a planner for chunks of `rings` rings (the ChunkManager lattice)
+    #[wasm_bindgen(constructor)]
+    pub fn new(rings: i32) -> HierarchicalPathfinder {
+        HierarchicalPathfinder {
+            rings: rings.max(1),
+            terrain: HashSet::new(),
+            borders: HashMap::new(),
+            intra: HashMap::new(),
+        }
+    }
+
+    /// Replace the traversable terrain and precompute portals and their distances
+    ///
+    /// @param valid_terrain_json - JSON array of traversable hexes: [{"q":0,"r":0},...]
+    /// @returns Number of portal pairs
+    pub fn set_terrain(&mut self, valid_terrain_json: String) -> u32 {
+        self.terrain = parse_valid_terrain_json(&valid_terrain_json);
+        self.borders.clear();
+        self.intra.clear();
+
+        let chunks: HashSet<(i32, i32)> = self.terrain.iter().map(|hex| self.chunk_of(*hex)).collect();
+        for &chunk in &chunks {
+            for neighbor in chunk_neighbor_centers(chunk.0, chunk.1, self.rings) {
+                if chunk < neighbor && chunks.contains(&neighbor) {
+                    self.rebuild_border(chunk, neighbor);
+                }
+            }
+        }
+        for &chunk in &chunks {
+            self.rebuild_intra(chunk);
+        }
+        self.portal_count()
+    }
+
+    /// Mark a single hex traversable or blocked, rebuilding only the affected chunks
+    ///
+    /// @returns true if the hex changed
+    pub fn set_traversable(&mut self, q: i32, r: i32, traversable: bool) -> bool {
+        let changed = if traversable { self.terrain.insert((q, r)) } else { self.terrain.remove(&(q, r)) };
+        if !changed {
+            return false;
+        }
+        let chunk = self.chunk_of((q, r));
+        let neighbors = chunk_neighbor_centers(chunk.0, chunk.1, self.rings);
+        for &neighbor in &neighbors {
+            let (key, _) = Self::border_key(chunk, neighbor);
+            self.rebuild_border(key.0, key.1);
+        }
+        self.rebuild_intra(chunk);
+        for neighbor in neighbors {
+            self.rebuild_intra(neighbor);
+        }
+        true
+    }
+
+    /// Number of portal pairs in the abstract graph
+    pub fn portal_count(&self) -> u32 {
+        self.borders.values().map(|portals| portals.len() as u32).sum()
+    }
+
+    /// Find a path between two hexes
+    ///
+    /// @returns JSON array [{"q":0,"r":0},...] from start to goal (both included), or "null" if no path
+    pub fn find_path(&self, start_q: i32, start_r: i32, goal_q: i32, goal_r: i32) -> String {
+        match self.plan((start_q, start_r), (goal_q, goal_r)) {
+            Some(path) => {
+                let parts: Vec<String> = path.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+                format!("[{}]", parts.join(","))
+            }
+            None => "null".to_string(),
+        }
+    }
+
+    /// find_path with a structured result (see the errors module)
+    ///
+    /// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or
+    ///   {"ok":null,"error":{"code":"no_path","message":"..."}} if no path connects the hexes
+    pub fn find_path_result(&self, start_q: i32, start_r: i32, goal_q: i32, goal_r: i32) -> String {
+        envelope(self.plan((start_q, start_r), (goal_q, goal_r))
+            .map(|path| {
+                let parts: Vec<String> = path.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+                format!("[{}]", parts.join(","))
+            })
+            .ok_or_else(|| ApiError::new(
+                ErrorCode::NoPath,
+                format!("no path from ({}, {}) to ({}, {})", start_q, start_r, goal_q, goal_r),
+            )))
+    }
+}
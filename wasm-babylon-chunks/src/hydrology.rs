@@ -0,0 +1,433 @@
+//! Rivers, lakes and erosion over the elevation layer
+//!
+//! **Learning Point**: Water runs downhill, so a river is just the path a drop takes
+//! from a high source: at every hex, step to the lowest neighbour that is not higher
+//! than the current one. Because every step is non-increasing, a traced river can
+//! never flow uphill. It ends where it meets water, the edge of the elevation data,
+//! or another river (becoming a tributary). When every neighbour is higher the drop
+//! is stuck in a pit; filling such depressions into lakes removes those dead ends.
+//! A depression fills up to its spill level, the height of the lowest point on its
+//! rim, where the lake overflows and the water continues downhill.
+//!
+//! Rivers are kept in an overlay layer instead of the tile grid, so they can run
+//! through grass and forest tiles alike.
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+use crate::elevation::ELEVATION;
+use crate::hex_utils::{get_hex_neighbors, hex_distance};
+use crate::connectivity::Connectivity;
+use crate::json::{parse_json_object, JsonValue};
+use crate::errors::{envelope, number_field, parse_object_param, string_field, ApiError, ErrorCode};
+use crate::rng::{content_rng, seed_from_coords};
+use crate::state::{TileLayer, WfcState, WFC_STATE};
+use crate::types::{TileType, WeightedNode};
+
+/// Closest two river sources may be, in hex steps
+const MIN_SOURCE_SPACING: i32 = 4;
+
+/// Traced sources per requested river before generate_rivers gives up
+const SOURCE_ATTEMPTS_PER_RIVER: usize = 20;
+
+/// World RNG stream for erosion droplet starts (see set_seed)
+const WORLD_STREAM_EROSION: u64 = 7;
+
+/// River overlay: hexes carrying water and where it flows next
+#[derive(Default)]
+pub struct RiverLayer {
+    /// Number of sources whose water passes through each river hex
+    pub flow: HashMap<(i32, i32), u32>,
+    /// Next hex downstream (missing at a river's mouth)
+    pub downstream: HashMap<(i32, i32), (i32, i32)>,
+}
+
+/// Global river overlay (thread-safe)
+pub static RIVERS: LazyLock<Mutex<RiverLayer>> = LazyLock::new(|| Mutex::new(RiverLayer::default()));
+
+/// Where a traced river ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RiverOutlet {
+    /// Next to a water tile
+    Water,
+    /// At the edge of the elevation data
+    Edge,
+    /// Joins an earlier river
+    River,
+    /// Stuck in a local minimum
+    Pit,
+}
+
+impl RiverOutlet {
+    pub fn name(self) -> &'static str {
+        match self {
+            RiverOutlet::Water => "water",
+            RiverOutlet::Edge => "edge",
+            RiverOutlet::River => "river",
+            RiverOutlet::Pit => "pit",
+        }
+    }
+}
+
+/// A traced river: its hexes from source to mouth and how it ends
+pub struct River {
+    pub hexes: Vec<(i32, i32)>,
+    pub outlet: RiverOutlet,
+    /// River hex it flows into (tributaries only)
+    pub joins: Option<(i32, i32)>,
+}
+
+/// Water hexes of the grid and the pre-constraints (e.g. from import_heightmap)
+pub fn water_hexes(state: &WfcState) -> HashSet<(i32, i32)> {
+    state.grid_tiles()
+        .chain(state.pre_constraints())
+        .filter(|(_, tile)| *tile == TileType::Water)
+        .map(|(hex, _)| hex)
+        .collect()
+}
+
+/// Follow the elevation downhill from a source (see the module docs)
+///
+/// Flat steps are allowed, but a hex is never revisited. `rivers` holds hexes of
+/// earlier rivers; stepping onto one ends the trace as a tributary.
+pub fn trace_river(
+    source: (i32, i32),
+    elevation: &HashMap<(i32, i32), f64>,
+    water: &HashSet<(i32, i32)>,
+    rivers: &HashMap<(i32, i32), u32>,
+) -> River {
+    let mut hexes = vec![source];
+    let mut visited: HashSet<(i32, i32)> = HashSet::from([source]);
+    let mut current = source;
+    loop {
+        let neighbors = get_hex_neighbors(current.0, current.1);
+        if neighbors.iter().any(|neighbor| water.contains(neighbor)) {
+            return River { hexes, outlet: RiverOutlet::Water, joins: None };
+        }
+        if neighbors.iter().any(|neighbor| !elevation.contains_key(neighbor)) {
+            return River { hexes, outlet: RiverOutlet::Edge, joins: None };
+        }
+        let height = elevation[&current];
+        let next = neighbors.into_iter()
+            .filter(|neighbor| !visited.contains(neighbor) && elevation[neighbor] <= height)
+            .min_by(|a, b| elevation[a].total_cmp(&elevation[b]).then(a.cmp(b)));
+        let Some(next) = next else {
+            return River { hexes, outlet: RiverOutlet::Pit, joins: None };
+        };
+        if rivers.contains_key(&next) {
+            return River { hexes, outlet: RiverOutlet::River, joins: Some(next) };
+        }
+        visited.insert(next);
+        hexes.push(next);
+        current = next;
+    }
+}
+
+/// Trace rivers down the elevation layer from its highest points
+///
+/// Sources are tried from the highest land hex down, at least 4 hexes apart and off
+/// existing rivers. A river shorter than min_length is dropped and the next source
+/// tried. Rivers that reach an earlier river join it, and the water they carry is
+/// added to its flow downstream. Water is read from the grid tiles and
+/// pre-constraints, so place lakes and seas before tracing. Needs an elevation layer
+/// (generate_elevation or import_heightmap). The river overlay is replaced.
+///
+/// @param source_count - Number of rivers to generate
+/// @param min_length - Shortest river to keep, in hexes
+/// @returns JSON array of rivers, in the order they were traced:
+///   [{"source":{"q":0,"r":0},"outlet":"water","hexes":[{"q":0,"r":0},...]},...]
+///   outlet is "water", "edge" (elevation data ends), "river" (a tributary) or "pit" (a local minimum)
+#[wasm_bindgen]
+pub fn generate_rivers(source_count: i32, min_length: i32) -> String {
+    let elevation = ELEVATION.lock().unwrap();
+    let water = water_hexes(&WFC_STATE.lock().unwrap());
+    let mut layer = RIVERS.lock().unwrap();
+    *layer = RiverLayer::default();
+
+    let mut candidates: Vec<(i32, i32)> = elevation.keys().copied().filter(|hex| !water.contains(hex)).collect();
+    candidates.sort_by(|a, b| elevation[b].total_cmp(&elevation[a]).then(a.cmp(b)));
+
+    let wanted = source_count.max(0) as usize;
+    let mut rivers: Vec<River> = Vec::new();
+    let mut attempts = 0;
+    for source in candidates {
+        if rivers.len() >= wanted || attempts >= wanted * SOURCE_ATTEMPTS_PER_RIVER {
+            break;
+        }
+        let crowded = rivers.iter().any(|river| {
+            hex_distance(source.0, source.1, river.hexes[0].0, river.hexes[0].1) < MIN_SOURCE_SPACING
+        });
+        if crowded || layer.flow.contains_key(&source) {
+            continue;
+        }
+        attempts += 1;
+        let river = trace_river(source, &elevation, &water, &layer.flow);
+        if (river.hexes.len() as i32) < min_length {
+            continue;
+        }
+
+        for pair in river.hexes.windows(2) {
+            layer.downstream.insert(pair[0], pair[1]);
+        }
+        for &hex in &river.hexes {
+            *layer.flow.entry(hex).or_insert(0) += 1;
+        }
+        if let (Some(join), Some(&mouth)) = (river.joins, river.hexes.last()) {
+            // A tributary's water flows on down the river it joins
+            layer.downstream.insert(mouth, join);
+            let mut current = Some(join);
+            while let Some(hex) = current {
+                *layer.flow.entry(hex).or_insert(0) += 1;
+                current = layer.downstream.get(&hex).copied();
+            }
+        }
+        rivers.push(river);
+    }
+
+    let parts: Vec<String> = rivers.iter()
+        .map(|river| {
+            let hexes: Vec<String> = river.hexes.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+            let (q, r) = river.hexes[0];
+            format!(
+                r#"{{"source":{{"q":{},"r":{}}},"outlet":"{}","hexes":[{}]}}"#,
+                q, r, river.outlet.name(), hexes.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Water flow through a hex of the river overlay
+///
+/// @returns Number of river sources upstream of the hex (tributaries add up), or 0 if it has no river
+#[wasm_bindgen]
+pub fn get_river_flow(q: i32, r: i32) -> u32 {
+    RIVERS.lock().unwrap().flow.get(&(q, r)).copied().unwrap_or(0)
+}
+
+/// Remove all rivers from the overlay
+#[wasm_bindgen]
+pub fn clear_rivers() {
+    *RIVERS.lock().unwrap() = RiverLayer::default();
+}
+
+/// Water surface height of every hex with elevation: its own height, or the spill
+/// level of the depression it lies in
+///
+/// Priority-flood: starting from the hexes water can leave by (the data edge and
+/// existing water), always expand the lowest hex reached so far. A neighbour lower
+/// than the level it is reached at sits in a depression and fills to that level.
+pub fn spill_levels(elevation: &HashMap<(i32, i32), f64>, water: &HashSet<(i32, i32)>) -> HashMap<(i32, i32), f64> {
+    let mut levels: HashMap<(i32, i32), f64> = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    let mut outlets: Vec<(i32, i32)> = elevation.keys()
+        .copied()
+        .filter(|&(q, r)| {
+            water.contains(&(q, r)) || get_hex_neighbors(q, r).iter().any(|neighbor| !elevation.contains_key(neighbor))
+        })
+        .collect();
+    outlets.sort();
+    for hex in outlets {
+        let height = elevation[&hex];
+        levels.insert(hex, height);
+        open_set.push(WeightedNode { q: hex.0, r: hex.1, g: height, f: height });
+    }
+
+    while let Some(node) = open_set.pop() {
+        for neighbor in get_hex_neighbors(node.q, node.r) {
+            let Some(&height) = elevation.get(&neighbor) else {
+                continue;
+            };
+            if levels.contains_key(&neighbor) {
+                continue;
+            }
+            let level = height.max(node.f);
+            levels.insert(neighbor, level);
+            open_set.push(WeightedNode { q: neighbor.0, r: neighbor.1, g: level, f: level });
+        }
+    }
+    levels
+}
+
+/// Fill depressions of the elevation layer into lakes
+///
+/// Every hex below its spill level (see spill_levels) is under water. Each connected
+/// group of such hexes is a lake with a flat surface at the spill level; lakes that
+/// are large and deep enough are written as Water tiles and their elevation is raised
+/// to the surface, so later rivers end in them instead of dead-ending in pits.
+///
+/// @param params_json - JSON object (all optional):
+///   {"minDepth":0,"minSize":1,"layer":"constraint"|"grid"}
+///   minDepth is the smallest surface-to-bottom depth and minSize the fewest hexes of a kept lake;
+///   tiles go to the pre-constraints by default, or straight into the grid
+/// @returns JSON array of lakes, largest first: [{"id":0,"level":0.42,"depth":0.05,"size":12,"hexes":[{"q":0,"r":0},...]},...]
+///   or "null" if the layer is unknown
+#[wasm_bindgen]
+pub fn fill_depressions(params_json: String) -> String {
+    fill_lakes(&parse_json_object(&params_json)).unwrap_or_else(|_| "null".to_string())
+}
+
+/// fill_depressions with a structured result (see the errors module)
+///
+/// Unlike fill_depressions, a parameter of the wrong type is an error instead of falling back to its default.
+///
+/// @returns JSON envelope: {"ok":[{"id":0,...},...],"error":null}, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json" or "invalid_argument" (unknown layer)
+#[wasm_bindgen]
+pub fn fill_depressions_result(params_json: String) -> String {
+    envelope(parse_object_param(&params_json, "params_json").and_then(|params| {
+        number_field(&params, "minDepth", "params_json")?;
+        string_field(&params, "layer", "params_json")?;
+        if params.get("minSize").is_some_and(|value| value.as_i32().is_none()) {
+            return Err(ApiError::new(ErrorCode::InvalidJson, "params_json.minSize: expected an integer"));
+        }
+        fill_lakes(&params)
+    }))
+}
+
+/// Lake filling shared by fill_depressions and its `_result` variant
+fn fill_lakes(params: &JsonValue) -> Result<String, ApiError> {
+    let min_depth = params.get("minDepth").and_then(|value| value.as_f64()).unwrap_or(0.0);
+    let min_size = params.get("minSize").and_then(|value| value.as_i32()).unwrap_or(1).max(1) as usize;
+    let Some(layer) = TileLayer::from_name(params.get("layer").and_then(|value| value.as_str()).unwrap_or("constraint")) else {
+        return Err(ApiError::new(ErrorCode::InvalidArgument, r#"params_json.layer: expected "constraint" or "grid""#));
+    };
+
+    let mut elevation = ELEVATION.lock().unwrap();
+    let mut state = WFC_STATE.lock().unwrap();
+    let levels = spill_levels(&elevation, &water_hexes(&state));
+    let flooded: HashSet<(i32, i32)> = levels.iter()
+        .filter(|(hex, level)| **level > elevation[*hex])
+        .map(|(hex, _)| *hex)
+        .collect();
+
+    let mut parts = Vec::new();
+    for lake in Connectivity::from_hexes(&flooded).components() {
+        let level = lake.iter().map(|hex| levels[hex]).fold(f64::MIN, f64::max);
+        let depth = level - lake.iter().map(|hex| elevation[hex]).fold(f64::MAX, f64::min);
+        if lake.len() < min_size || depth < min_depth {
+            continue;
+        }
+        for &(q, r) in &lake {
+            elevation.insert((q, r), level);
+            state.set_layer_tile(layer, q, r, TileType::Water);
+        }
+        let hexes: Vec<String> = lake.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+        parts.push(format!(
+            r#"{{"id":{},"level":{},"depth":{},"size":{},"hexes":[{}]}}"#,
+            parts.len(), level, depth, lake.len(), hexes.join(",")
+        ));
+    }
+    Ok(format!("[{}]", parts.join(",")))
+}
+
+/// Droplet erosion settings (see erode)
+struct ErosionParams {
+    max_lifetime: u32,
+    capacity: f64,
+    erode_rate: f64,
+    deposit_rate: f64,
+    evaporation: f64,
+    gravity: f64,
+    min_slope: f64,
+}
+
+/// Run one droplet from a start hex, moving height between hexes
+///
+/// @returns (height eroded, height deposited)
+fn run_droplet(elevation: &mut HashMap<(i32, i32), f64>, start: (i32, i32), params: &ErosionParams) -> (f64, f64) {
+    let (mut eroded, mut deposited) = (0.0, 0.0);
+    let (mut current, mut speed, mut water, mut sediment) = (start, 1.0f64, 1.0f64, 0.0f64);
+    for step in 0..params.max_lifetime {
+        let height = elevation[&current];
+        let neighbors = get_hex_neighbors(current.0, current.1);
+        if neighbors.iter().any(|neighbor| !elevation.contains_key(neighbor)) {
+            // Flows off the map, taking its sediment with it
+            break;
+        }
+        let next = neighbors.into_iter()
+            .min_by(|a, b| elevation[a].total_cmp(&elevation[b]).then(a.cmp(b)))
+            .unwrap();
+        let drop = height - elevation[&next];
+        if drop <= 0.0 {
+            // In a pit: fill it up to the lowest neighbour and stop
+            let amount = sediment.min(-drop);
+            *elevation.get_mut(&current).unwrap() += amount;
+            deposited += amount;
+            break;
+        }
+
+        let capacity = drop.max(params.min_slope) * speed * water * params.capacity;
+        if sediment > capacity {
+            let amount = (sediment - capacity) * params.deposit_rate;
+            sediment -= amount;
+            *elevation.get_mut(&current).unwrap() += amount;
+            deposited += amount;
+        } else {
+            // Never dig below the next hex, or the droplet would carve a pit
+            let amount = ((capacity - sediment) * params.erode_rate).min(drop);
+            sediment += amount;
+            *elevation.get_mut(&current).unwrap() -= amount;
+            eroded += amount;
+        }
+
+        speed = (speed * speed + drop * params.gravity).sqrt();
+        water *= 1.0 - params.evaporation;
+        current = next;
+        if step + 1 == params.max_lifetime {
+            // Dried up: everything still carried settles here
+            *elevation.get_mut(&current).unwrap() += sediment;
+            deposited += sediment;
+        }
+    }
+    (eroded, deposited)
+}
+
+/// Erode the elevation layer with simulated rain droplets
+///
+/// **Learning Point**: Each droplet lands on a random hex and runs downhill. Its
+/// sediment capacity grows with slope, speed and remaining water: on steep ground
+/// it picks up height from the hex it leaves, and where the ground flattens (or
+/// the droplet evaporates) it drops what it can no longer carry. Thousands of
+/// droplets following the same paths carve valleys into mountains and spread the
+/// material as plains at their feet. Erosion never digs below the next hex, so
+/// droplets don't create pits.
+///
+/// @param iterations - Number of droplets
+/// @param params_json - JSON object (all optional):
+///   {"maxLifetime":30,"capacity":4,"erodeRate":0.3,"depositRate":0.3,"evaporation":0.02,"gravity":4,"minSlope":0.01}
+///   droplet starts are seeded from the elevation layer and the world seed (see set_seed)
+/// @returns JSON object: {"droplets":1000,"eroded":3.2,"deposited":2.9}
+///   (height moved in total; the difference left the map or stayed suspended when droplets expired)
+#[wasm_bindgen]
+pub fn erode(iterations: u32, params_json: String) -> String {
+    let json = parse_json_object(&params_json);
+    let number = |key: &str, default: f64| json.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+    let params = ErosionParams {
+        max_lifetime: number("maxLifetime", 30.0).max(0.0) as u32,
+        capacity: number("capacity", 4.0).max(0.0),
+        erode_rate: number("erodeRate", 0.3).clamp(0.0, 1.0),
+        deposit_rate: number("depositRate", 0.3).clamp(0.0, 1.0),
+        evaporation: number("evaporation", 0.02).clamp(0.0, 1.0),
+        gravity: number("gravity", 4.0).max(0.0),
+        min_slope: number("minSlope", 0.01).max(0.0),
+    };
+
+    let mut elevation = ELEVATION.lock().unwrap();
+    let mut hexes: Vec<(i32, i32)> = elevation.keys().copied().collect();
+    hexes.sort();
+    let (mut eroded, mut deposited) = (0.0, 0.0);
+    let mut droplets = 0;
+    if !hexes.is_empty() {
+        let content_seed = seed_from_coords(&hexes);
+        let mut rng = content_rng(content_seed, WORLD_STREAM_EROSION);
+        for _ in 0..iterations {
+            let start = hexes[rng.next_below(hexes.len())];
+            let (droplet_eroded, droplet_deposited) = run_droplet(&mut elevation, start, &params);
+            eroded += droplet_eroded;
+            deposited += droplet_deposited;
+            droplets += 1;
+        }
+    }
+    format!(r#"{{"droplets":{},"eroded":{},"deposited":{}}}"#, droplets, eroded, deposited)
+}
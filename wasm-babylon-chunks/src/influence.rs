@@ -0,0 +1,91 @@
+//! Influence map module
+
+use wasm_bindgen::prelude::*;
+use std::sync::{LazyLock, Mutex};
+use std::collections::HashMap;
+use crate::json::parse_json;
+use crate::hex_utils::parse_path_json;
+
+/// Registry of influence maps (per-hex scalar fields such as threat) keyed by handle
+/// 
+/// **Learning Point**: Influence maps can be large, so JS uploads one once and then
+/// refers to it by a small integer handle in later calls instead of re-sending it.
+pub struct InfluenceRegistry {
+    maps: HashMap<u32, HashMap<(i32, i32), f64>>,
+    next_handle: u32,
+}
+
+impl InfluenceRegistry {
+    pub fn new() -> Self {
+        InfluenceRegistry {
+            maps: HashMap::new(),
+            next_handle: 1,
+        }
+    }
+    
+    /// Store a map and return its handle (handles start at 1 and are never reused)
+    pub fn insert(&mut self, values: HashMap<(i32, i32), f64>) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.maps.insert(handle, values);
+        handle
+    }
+    
+    pub fn get(&self, handle: u32) -> Option<&HashMap<(i32, i32), f64>> {
+        self.maps.get(&handle)
+    }
+    
+    pub fn remove(&mut self, handle: u32) -> bool {
+        self.maps.remove(&handle).is_some()
+    }
+}
+
+/// Global influence map registry (thread-safe)
+pub static INFLUENCE_MAPS: LazyLock<Mutex<InfluenceRegistry>> = LazyLock::new(|| Mutex::new(InfluenceRegistry::new()));
+
+/// Upload an influence map and get a handle for later queries
+/// Accepts the same format as the threat field from generate_enemy_camps.
+/// Hexes that are not listed have influence 0.
+/// 
+/// @param values_json - JSON array of per-hex values: [{"q":0,"r":0,"value":0.8},...]
+/// @returns Handle for the stored map (never 0)
+#[wasm_bindgen]
+pub fn create_influence_map(values_json: String) -> u32 {
+    let mut values: HashMap<(i32, i32), f64> = HashMap::new();
+    if let Some(items) = parse_json(&values_json).as_ref().and_then(|v| v.as_array()) {
+        for item in items {
+            if let (Some(hex), Some(value)) = (item.as_hex(), item.get("value").and_then(|v| v.as_f64())) {
+                *values.entry(hex).or_insert(0.0) += value;
+            }
+        }
+    }
+    
+    INFLUENCE_MAPS.lock().unwrap().insert(values)
+}
+
+/// Release an influence map
+/// 
+/// @param handle - Handle returned by create_influence_map
+/// @returns true if the handle existed
+#[wasm_bindgen]
+pub fn destroy_influence_map(handle: u32) -> bool {
+    INFLUENCE_MAPS.lock().unwrap().remove(handle)
+}
+
+/// Score a path's danger as the sum of influence values of the hexes it enters
+/// The starting hex is not counted (the traveller is already there).
+/// 
+/// @param path_json - JSON array of path hexes in order: [{"q":0,"r":0},...]
+/// @param influence_map_handle - Handle returned by create_influence_map
+/// @returns Total danger along the path (negative influence can make it negative), or undefined if the handle is unknown
+#[wasm_bindgen]
+pub fn score_path_danger(path_json: String, influence_map_handle: u32) -> Option<f64> {
+    let path = parse_path_json(&path_json);
+    let maps = INFLUENCE_MAPS.lock().unwrap();
+    let influence = maps.get(influence_map_handle)?;
+    
+    Some(path.iter()
+        .skip(1)
+        .map(|hex| influence.get(hex).copied().unwrap_or(0.0))
+        .sum())
+}
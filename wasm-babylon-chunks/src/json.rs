@@ -0,0 +1,352 @@
+//! Minimal JSON reader for structured parameters
+//!
+//! **Learning Point**: The flat `[{"q":0,"r":0},...]` inputs are handled by the
+//! hand-written scanners in hex_utils. Parameters with nested options (rules,
+//! constraints, weights) need a real tree, so this module provides a tiny
+//! recursive-descent parser instead of pulling in serde and growing the WASM size.
+
+use std::collections::HashMap;
+use crate::types::TileType;
+
+/// Parsed JSON value
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Look up a key in an object (None for missing keys or non-objects)
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Read a number as an integer (None for fractions and values outside the i32 range)
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_f64()
+            .filter(|n| n.fract() == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64)
+            .map(|n| n as i32)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(items) => Some(items.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(entries) => Some(entries.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Read a `{"q":X,"r":Y}` object as an axial coordinate
+    pub fn as_hex(&self) -> Option<(i32, i32)> {
+        Some((self.get("q")?.as_i32()?, self.get("r")?.as_i32()?))
+    }
+
+    /// Read an array of `{"q":X,"r":Y}` objects, skipping malformed entries
+    pub fn as_hex_list(&self) -> Vec<(i32, i32)> {
+        self.as_array()
+            .map(|items| items.iter().filter_map(|item| item.as_hex()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Read an array of `{"q":X,"r":Y,"value":V}` objects as a per-hex scalar map, skipping malformed entries
+    pub fn as_hex_values(&self) -> HashMap<(i32, i32), f64> {
+        self.as_array()
+            .map(|items| items.iter()
+                .filter_map(|item| Some((item.as_hex()?, item.get("value")?.as_f64()?)))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Read an array of tile type ids, skipping unknown ids
+    pub fn as_tile_types(&self) -> Vec<TileType> {
+        self.as_array()
+            .map(|items| items.iter().filter_map(|item| item.as_i32().and_then(TileType::from_i32)).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for JsonValue {
+    /// Serialize back to compact JSON text
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => write!(f, "{}", n),
+            JsonValue::String(s) => write!(f, "\"{}\"", escape_json_string(s)),
+            JsonValue::Array(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", escape_json_string(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Parse a JSON document
+/// Returns None if the input is not valid JSON or nests deeper than MAX_DEPTH
+pub fn parse_json(input: &str) -> Option<JsonValue> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut pos = 0;
+    let value = parse_value(&chars, &mut pos, 0)?;
+    skip_whitespace(&chars, &mut pos);
+    if pos == chars.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Parse a JSON object parameter, treating empty input as `{}`
+/// Malformed input also yields an empty object so callers fall back to defaults
+pub fn parse_json_object(input: &str) -> JsonValue {
+    match parse_json(input) {
+        Some(value @ JsonValue::Object(_)) => value,
+        _ => JsonValue::Object(Vec::new()),
+    }
+}
+
+/// Escape a string for embedding in a JSON string literal
+pub fn escape_json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while *pos < chars.len() && chars[*pos].is_whitespace() {
+        *pos += 1;
+    }
+}
+
+/// Deepest nesting of arrays and objects accepted (deeper input would overflow the WASM stack)
+const MAX_DEPTH: usize = 128;
+
+fn parse_value(chars: &[char], pos: &mut usize, depth: usize) -> Option<JsonValue> {
+    skip_whitespace(chars, pos);
+    match chars.get(*pos)? {
+        '{' | '[' if depth >= MAX_DEPTH => None,
+        '{' => parse_object(chars, pos, depth + 1),
+        '[' => parse_array(chars, pos, depth + 1),
+        '"' => parse_string(chars, pos).map(JsonValue::String),
+        't' => parse_literal(chars, pos, "true", JsonValue::Bool(true)),
+        'f' => parse_literal(chars, pos, "false", JsonValue::Bool(false)),
+        'n' => parse_literal(chars, pos, "null", JsonValue::Null),
+        _ => parse_number(chars, pos),
+    }
+}
+
+fn parse_literal(chars: &[char], pos: &mut usize, literal: &str, value: JsonValue) -> Option<JsonValue> {
+    for expected in literal.chars() {
+        if chars.get(*pos) != Some(&expected) {
+            return None;
+        }
+        *pos += 1;
+    }
+    Some(value)
+}
+
+fn parse_number(chars: &[char], pos: &mut usize) -> Option<JsonValue> {
+    let start = *pos;
+    while *pos < chars.len() && (chars[*pos].is_ascii_digit() || matches!(chars[*pos], '-' | '+' | '.' | 'e' | 'E')) {
+        *pos += 1;
+    }
+    let num_str: String = chars[start..*pos].iter().collect();
+    num_str.parse::<f64>().ok().map(JsonValue::Number)
+}
+
+fn parse_string(chars: &[char], pos: &mut usize) -> Option<String> {
+    // Skip opening quote
+    *pos += 1;
+    let mut result = String::new();
+    while *pos < chars.len() {
+        let c = chars[*pos];
+        *pos += 1;
+        match c {
+            '"' => return Some(result),
+            '\\' => {
+                let escaped = *chars.get(*pos)?;
+                *pos += 1;
+                match escaped {
+                    'n' => result.push('\n'),
+                    't' => result.push('\t'),
+                    'r' => result.push('\r'),
+                    'b' => result.push('\u{0008}'),
+                    'f' => result.push('\u{000C}'),
+                    'u' => {
+                        let hex: String = chars.get(*pos..*pos + 4)?.iter().collect();
+                        *pos += 4;
+                        result.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                    }
+                    other => result.push(other),
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    None
+}
+
+fn parse_array(chars: &[char], pos: &mut usize, depth: usize) -> Option<JsonValue> {
+    // Skip opening bracket
+    *pos += 1;
+    let mut items = Vec::new();
+    skip_whitespace(chars, pos);
+    if chars.get(*pos) == Some(&']') {
+        *pos += 1;
+        return Some(JsonValue::Array(items));
+    }
+    loop {
+        items.push(parse_value(chars, pos, depth)?);
+        skip_whitespace(chars, pos);
+        match chars.get(*pos)? {
+            ',' => *pos += 1,
+            ']' => {
+                *pos += 1;
+                return Some(JsonValue::Array(items));
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn parse_object(chars: &[char], pos: &mut usize, depth: usize) -> Option<JsonValue> {
+    // Skip opening brace
+    *pos += 1;
+    let mut entries = Vec::new();
+    skip_whitespace(chars, pos);
+    if chars.get(*pos) == Some(&'}') {
+        *pos += 1;
+        return Some(JsonValue::Object(entries));
+    }
+    loop {
+        skip_whitespace(chars, pos);
+        if chars.get(*pos) != Some(&'"') {
+            return None;
+        }
+        let key = parse_string(chars, pos)?;
+        skip_whitespace(chars, pos);
+        if chars.get(*pos) != Some(&':') {
+            return None;
+        }
+        *pos += 1;
+        let value = parse_value(chars, pos, depth)?;
+        entries.push((key, value));
+        skip_whitespace(chars, pos);
+        match chars.get(*pos)? {
+            ',' => *pos += 1,
+            '}' => {
+                *pos += 1;
+                return Some(JsonValue::Object(entries));
+            }
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_values() {
+        let value = parse_json(r#" {"a":[1,-2.5e1,true,null],"b":{"c":"x\"yA"}} "#).unwrap();
+        assert_eq!(value.get("a").and_then(JsonValue::as_array).map(|items| items.len()), Some(4));
+        assert_eq!(value.get("a").unwrap().as_array().unwrap()[1].as_f64(), Some(-25.0));
+        assert_eq!(value.get("b").and_then(|b| b.get("c")).and_then(JsonValue::as_str), Some("x\"yA"));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in ["", "[1,", "{\"a\" 1}", "[1] x", "tru", "{\"a\":}"] {
+            assert_eq!(parse_json(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let input = r#"{"name":"a\"b","list":[1,2.5,false,null]}"#;
+        let value = parse_json(input).unwrap();
+        assert_eq!(parse_json(&value.to_string()), Some(value));
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse_json(&nested(MAX_DEPTH)).is_some());
+        assert_eq!(parse_json(&nested(MAX_DEPTH + 1)), None);
+        // Deep input fails cleanly instead of overflowing the stack
+        assert_eq!(parse_json(&"[".repeat(100_000)), None);
+    }
+
+    #[test]
+    fn as_i32_requires_an_integer_in_range() {
+        let number = |n: f64| JsonValue::Number(n);
+        assert_eq!(number(3.0).as_i32(), Some(3));
+        assert_eq!(number(-7.0).as_i32(), Some(-7));
+        assert_eq!(number(i32::MAX as f64).as_i32(), Some(i32::MAX));
+        assert_eq!(number(i32::MIN as f64).as_i32(), Some(i32::MIN));
+        assert_eq!(number(2.5).as_i32(), None);
+        assert_eq!(number(i32::MAX as f64 + 1.0).as_i32(), None);
+        assert_eq!(number(1e20).as_i32(), None);
+        assert_eq!(JsonValue::String("3".to_string()).as_i32(), None);
+    }
+
+    #[test]
+    fn hex_lists_skip_malformed_entries() {
+        let value = parse_json(r#"[{"q":1,"r":2},{"q":1.5,"r":0},{"q":3},{"q":-4,"r":5}]"#).unwrap();
+        assert_eq!(value.as_hex_list(), vec![(1, 2), (-4, 5)]);
+    }
+}
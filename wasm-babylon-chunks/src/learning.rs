@@ -0,0 +1,157 @@
+//! Learning WFC rules from an example map
+//!
+//! **Learning Point**: Writing adjacency rules and weights by hand is tedious and
+//! rarely captures a style. Instead an artist paints a small reference chunk and
+//! the statistics are read off it:
+//! - weights: how often each tile type occurs
+//! - adjacency: which tile types ever touch (pairs never seen become forbidden)
+//! - neighbor likelihood: how often each tile type sits next to each other one
+//!
+//! The solver uses the likelihoods when collapsing a cell: a tile's weight is
+//! multiplied by P(neighbor | tile) for every neighbor already decided (a naive
+//! Bayes estimate), so forests grow in clumps if the example has clumps and
+//! rivers stay thin if the example's water is thin. This is the N=2 case of the
+//! overlapping model: statistics over neighbor pairs instead of NxN windows.
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::hex_utils::get_hex_neighbors;
+use crate::json::parse_json;
+use crate::pattern::parse_pattern_cells;
+use crate::errors::{envelope, parse_json_param, tile_type_param, ApiError, ErrorCode};
+
+/// Counts read from an example map
+pub struct ExampleStats {
+    /// Tiles per tile id
+    pub counts: Vec<usize>,
+    /// Ordered neighbor pairs, indexed tile * TileType::COUNT + neighbor
+    pub pair_counts: Vec<usize>,
+}
+
+impl ExampleStats {
+    pub fn from_tiles(tiles: &HashMap<(i32, i32), TileType>) -> Self {
+        let mut counts = vec![0; TileType::COUNT];
+        let mut pair_counts = vec![0; TileType::COUNT * TileType::COUNT];
+        for (&(q, r), tile) in tiles {
+            counts[tile.index()] += 1;
+            for neighbor in get_hex_neighbors(q, r) {
+                if let Some(neighbor_tile) = tiles.get(&neighbor) {
+                    pair_counts[tile.index() * TileType::COUNT + neighbor_tile.index()] += 1;
+                }
+            }
+        }
+        ExampleStats { counts, pair_counts }
+    }
+
+    fn pair(&self, a: usize, b: usize) -> usize {
+        self.pair_counts[a * TileType::COUNT + b]
+    }
+}
+
+/// Replace a state's WFC rules with the statistics of an example
+///
+/// Tile types missing from the example get weight 0 and keep their adjacency rules.
+///
+/// @returns JSON summary: {"samples":N,"pairs":P,"allowedPairs":K}
+pub fn learn_rules(state: &mut WfcState, stats: &ExampleStats) -> String {
+    let present: Vec<usize> = (0..TileType::COUNT).filter(|id| stats.counts[*id] > 0).collect();
+    let rules = state.rules_mut();
+
+    for id in 0..TileType::COUNT {
+        rules.weights[id] = Some(stats.counts[id] as f64);
+    }
+
+    let mut allowed_pairs = 0;
+    for (index, &a) in present.iter().enumerate() {
+        for &b in &present[index..] {
+            let seen = stats.pair(a, b) > 0;
+            rules.set_allowed(TileType::from_index(a), TileType::from_index(b), seen);
+            if seen {
+                allowed_pairs += 1;
+            }
+        }
+    }
+
+    let mut likelihood = vec![0.0; TileType::COUNT * TileType::COUNT];
+    for &a in &present {
+        let total: usize = (0..TileType::COUNT).map(|b| stats.pair(a, b)).sum();
+        if total == 0 {
+            continue;
+        }
+        for b in 0..TileType::COUNT {
+            likelihood[a * TileType::COUNT + b] = stats.pair(a, b) as f64 / total as f64;
+        }
+    }
+    rules.neighbor_likelihood = Some(likelihood);
+
+    format!(
+        r#"{{"samples":{},"pairs":{},"allowedPairs":{}}}"#,
+        stats.counts.iter().sum::<usize>(),
+        stats.pair_counts.iter().sum::<usize>() / 2,
+        allowed_pairs
+    )
+}
+
+/// Learn WFC weights, adjacency rules and neighbor statistics from an example map
+///
+/// The learned rules replace the current weights (set_tile_weight) and the
+/// adjacency rules between the tile types in the example, and are then used by
+/// generate_layout_wfc and the step-wise run. reset_adjacency_rules and
+/// reset_tile_weights undo them.
+///
+/// @param tiles_json - JSON array of example tiles: [{"q":0,"r":0,"tileType":3},...]
+/// @returns JSON summary: {"samples":N,"pairs":P,"allowedPairs":K}, or "null" if the JSON
+///          is invalid or has no valid tiles
+#[wasm_bindgen]
+pub fn learn_rules_from_example(tiles_json: String) -> String {
+    match example_stats(&tiles_json) {
+        Some(stats) => learn_rules(&mut WFC_STATE.lock().unwrap(), &stats),
+        None => "null".to_string(),
+    }
+}
+
+/// learn_rules_from_example with a structured result (see the errors module)
+///
+/// Unlike learn_rules_from_example, a malformed tile is an error instead of being skipped.
+///
+/// @returns JSON envelope: {"ok":{"samples":N,"pairs":P,"allowedPairs":K},"error":null}, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument" (unknown tile type, no tiles)
+#[wasm_bindgen]
+pub fn learn_rules_from_example_result(tiles_json: String) -> String {
+    envelope(checked_example_stats(&tiles_json).map(|stats| learn_rules(&mut WFC_STATE.lock().unwrap(), &stats)))
+}
+
+/// Read example tiles from JSON, rejecting malformed entries and empty examples
+fn checked_example_stats(tiles_json: &str) -> Result<ExampleStats, ApiError> {
+    let value = parse_json_param(tiles_json, "tiles_json")?;
+    let items = value.as_array()
+        .ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, "tiles_json: expected an array"))?;
+    let mut tiles: HashMap<(i32, i32), TileType> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        let (Some(hex), Some(tile_type)) = (item.as_hex(), item.get("tileType").and_then(|value| value.as_i32())) else {
+            return Err(ApiError::new(
+                ErrorCode::InvalidJson,
+                format!("tiles_json[{}]: expected {{\"q\":integer,\"r\":integer,\"tileType\":integer}}", index),
+            ));
+        };
+        tiles.entry(hex).or_insert(tile_type_param(tile_type)?);
+    }
+    if tiles.is_empty() {
+        return Err(ApiError::new(ErrorCode::InvalidArgument, "tiles_json: the example has no tiles"));
+    }
+    Ok(ExampleStats::from_tiles(&tiles))
+}
+
+/// Read example tiles from JSON, None if invalid or empty
+pub fn example_stats(tiles_json: &str) -> Option<ExampleStats> {
+    let tiles: HashMap<(i32, i32), TileType> = parse_pattern_cells(&parse_json(tiles_json)?)
+        .into_iter()
+        .filter_map(|(hex, tile)| Some((hex, tile?)))
+        .collect();
+    if tiles.is_empty() {
+        return None;
+    }
+    Some(ExampleStats::from_tiles(&tiles))
+}
@@ -10,6 +10,40 @@
 /// - roads: Road network generation
 /// - chunks: Chunk management
 /// - utils: Utility functions
+/// - json: Minimal JSON parsing for structured parameters
+/// - rng: Seeded random number generation
+/// - sampling: Random hex sampling
+/// - settlements: Settlement cluster planning
+/// - placement: Point-of-interest placement (landmarks, quest locations, enemy camps)
+/// - influence: Influence map storage and danger scoring
+/// - road_graph: Road network graph analysis
+/// - connectivity: Incremental connected-component tracking
+/// - stats: Map composition statistics and QA checks
+/// - noise: Coherent value noise
+/// - events: Tile change tracking
+/// - scripting: JS callbacks over grid tiles
+/// - query: Hex filter expression language
+/// - pattern: Hex pattern matching with rotations
+/// - rewrite: Pattern rewrite rules over the grid
+/// - lsystem: L-system road generation
+/// - growth: Agent-based city growth simulation
+/// - history: Layered settlement history with ruins
+/// - traffic: Foot traffic accumulation and emergent trails
+/// - weather: Time-evolving rain and wind fields
+/// - farms: Farmland plots, crops and row orientation
+/// - movement: Movement profiles, sea routes and flight paths
+/// - elevation: Per-hex elevation layer, heightmap import, slope and contour lines
+/// - biomes: Moisture and temperature fields with biome classification
+/// - hydrology: Rivers, lakes and droplet erosion over the elevation layer
+/// - shore: Shore annotation layer for land next to water
+/// - errors: Structured API errors and result envelopes
+/// - hex_map: Independent map states (HexMap class and handle registry)
+/// - tiles: Runtime-registered tile types
+/// - learning: Learning WFC rules from an example map
+/// - fields: Distance fields over hex terrain
+/// - hpa: Hierarchical pathfinding (HPA*) over chunks
+/// - dstar: Incremental replanning (D* Lite) over the grid
+/// - serde_api: JsValue variants of the JSON API (`serde` feature)
 
 // Module declarations
 mod types;
@@ -21,24 +55,160 @@ mod layout;
 mod roads;
 mod chunks;
 mod utils;
+mod json;
+mod rng;
+mod sampling;
+mod settlements;
+mod placement;
+mod influence;
+mod road_graph;
+mod connectivity;
+mod stats;
+mod noise;
+mod events;
+mod scripting;
+mod query;
+mod pattern;
+mod rewrite;
+mod lsystem;
+mod growth;
+mod history;
+mod traffic;
+mod weather;
+mod farms;
+mod movement;
+mod elevation;
+mod biomes;
+mod hydrology;
+mod shore;
+mod errors;
+mod hex_map;
+mod tiles;
+mod learning;
+mod fields;
+mod hpa;
+mod dstar;
+#[cfg(feature = "serde")]
+mod serde_api;
 
 // Re-export all public functions from sub-modules
 // This maintains the same public API as before the refactoring
 
 // From layout module
-pub use layout::{init, get_wasm_version, generate_layout, get_tile_at, clear_layout, set_pre_constraint, clear_pre_constraints, get_stats};
+pub use layout::{init, get_wasm_version, generate_layout, generate_layout_wfc, regenerate_region, get_tile_at, get_tile_at_result, clear_layout, set_pre_constraint, set_pre_constraint_result, set_pre_constraint_area, set_pre_constraint_area_result, set_pre_constraint_ring, set_pre_constraint_ring_result, clear_pre_constraints, get_stats, set_adjacency_rule, set_adjacency_rules, set_adjacency_rules_result, is_adjacency_allowed, reset_adjacency_rules, validate_adjacency, set_tile_weight, reset_tile_weights, begin_layout_wfc, wfc_step, wfc_remaining, get_entropy_at, get_entropy_at_result, set_tile_quotas, set_tile_quotas_result, clear_tile_quotas, check_tile_quotas};
 
 // From astar module
-pub use astar::{hex_astar, build_path_between_roads, validate_road_connectivity};
+pub use astar::{hex_astar, hex_astar_result, hex_astar_buf, hex_astar_partial, hex_astar_partial_result, hex_astar_danger_aware, hex_astar_danger_aware_result, build_path_between_roads, build_path_between_roads_result, build_path_between_roads_buf, smooth_path, validate_road_connectivity, validate_road_connectivity_buf, diagnose_road_connectivity, diagnose_road_connectivity_buf};
 
 // From voronoi module
-pub use voronoi::generate_voronoi_regions;
+pub use voronoi::{generate_voronoi_regions, generate_voronoi_regions_with_metric, generate_balanced, generate_voronoi_regions_balanced, extract_region_borders};
 
 // From roads module
-pub use roads::generate_road_network_growing_tree;
+pub use roads::{generate_road_network_growing_tree, generate_road_network_growing_tree_result, generate_road_network_growing_tree_buf, generate_road_network_mst, generate_road_network_hierarchy, generate_road_network_grid, stitch_chunk_roads, widen_road_network};
 
 // From chunks module
-pub use chunks::{calculate_chunk_radius, calculate_chunk_neighbors, find_nearest_neighbor_chunk, disable_distant_chunks, calculate_chunk_for_tile};
+pub use chunks::{calculate_chunk_radius, calculate_chunk_neighbors, find_nearest_neighbor_chunk, find_nearest_neighbor_chunk_result, disable_distant_chunks, calculate_chunk_for_tile, calculate_chunk_for_tile_result, generate_border_contracts, build_world_overview, ChunkManager};
 
 // From utils module
-pub use utils::{batch_get_tile_types, shuffle_array, count_adjacent_roads, get_adjacent_valid_terrain, generate_building_placement, batch_hex_to_world};
+pub use utils::{batch_get_tile_types, batch_get_tile_types_buf, find_nearest_tile_of_type, find_nearest_tile_of_type_result, shuffle_array, shuffle_json_array, shuffle_indices, sample_without_replacement, count_adjacent_roads, count_adjacent_roads_batch, count_adjacent_roads_batch_buf, get_adjacent_valid_terrain, get_valid_terrain_near_roads, get_valid_terrain_near_roads_buf, generate_building_placement, batch_hex_to_world, rasterize_polygon, rasterize_polygon_result, import_tilemap_image, import_tilemap_image_result};
+
+// From sampling module
+pub use sampling::{random_walkable_hex, random_walkable_hex_result, sample_hexes_weighted, stratified_sample, create_reservoir, reservoir_push, reservoir_sample, reservoir_sample_result, destroy_reservoir};
+
+// From settlements module
+pub use settlements::plan_settlements;
+
+// From placement module
+pub use placement::{place_landmarks, find_locations, generate_enemy_camps, place_harbors};
+
+// From influence module
+pub use influence::{create_influence_map, destroy_influence_map, score_path_danger};
+
+// From road_graph module
+pub use road_graph::{label_road_intersections, extract_road_graph, simplify_road_graph, find_critical_road_segments, classify_road_tiles, analyze_road_network};
+
+// From connectivity module
+pub use connectivity::{Connectivity, compute_connected_components};
+
+// From stats module
+pub use stats::{compare_stats, check_targets};
+
+// From events module
+pub use events::{set_change_tracking, poll_tile_changes, poll_tile_changes_buf};
+
+// From scripting module
+pub use scripting::map_tiles;
+
+// From query module
+pub use query::{query, query_result};
+
+// From pattern module
+pub use pattern::find_pattern;
+
+// From rewrite module
+pub use rewrite::apply_rewrite_rules;
+
+// From lsystem module
+pub use lsystem::generate_road_network_lsystem;
+
+// From growth module
+pub use growth::{configure_growth, simulate_growth};
+
+// From history module
+pub use history::generate_history;
+
+// From traffic module
+pub use traffic::{set_path_threshold, record_agent_path, get_traffic, clear_traffic};
+
+// From weather module
+pub use weather::{configure_weather, sample_weather, sample_weather_batch};
+
+// From farms module
+pub use farms::assign_farm_plots;
+
+// From movement module
+pub use movement::{register_movement_profile, find_sea_route, find_sea_route_result, find_flight_path, find_flight_path_result};
+
+// From elevation module
+pub use elevation::{set_elevation, get_elevation, clear_elevation, generate_elevation, get_elevation_layer, import_heightmap, import_heightmap_result, compute_slope_map, extract_contours, detect_cliffs};
+
+// From biomes module
+pub use biomes::generate_biomes;
+
+// From hydrology module
+pub use hydrology::{generate_rivers, get_river_flow, clear_rivers, fill_depressions, fill_depressions_result, erode};
+
+// From shore module
+pub use shore::{annotate_shores, get_shore_mask, get_shore_layer};
+
+// From hex_map module
+pub use hex_map::{
+    HexMap, create_map, destroy_map, map_set_pre_constraint, map_set_pre_constraint_area, map_set_pre_constraint_ring,
+    map_clear_pre_constraints, map_generate_layout, map_generate_layout_wfc, map_clear_layout, map_get_tile_at, map_get_tile_at_result, map_get_stats, map_swap_with_global,
+};
+
+// From tiles module
+pub use tiles::{register_tile_type, register_tile_set, register_tile_set_result, get_tile_types};
+
+// From learning module
+pub use learning::{learn_rules_from_example, learn_rules_from_example_result};
+
+// From fields module
+pub use fields::{generate_distance_field, generate_distance_field_result, compute_reachable, generate_flow_field, generate_flow_field_result};
+
+// From hpa module
+pub use hpa::HierarchicalPathfinder;
+
+// From dstar module
+pub use dstar::PathSession;
+
+// From rng module
+pub use rng::{set_seed, clear_seed};
+
+// From serde_api module
+#[cfg(feature = "serde")]
+pub use serde_api::{
+    hex_astar_js, hex_astar_partial_js, build_path_between_roads_js, generate_road_network_growing_tree_js,
+    get_valid_terrain_near_roads_js, generate_building_placement_js, batch_get_tile_types_js, get_stats_js,
+    query_js, find_pattern_js,
+};
@@ -0,0 +1,170 @@
+//! L-system road generation module
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashSet;
+use crate::hex_utils::CUBE_DIRECTIONS;
+use crate::json::{parse_json_object, JsonValue};
+use crate::roads::{apply_road_width, widened_roads_json};
+
+/// Upper bound on the expanded string, so runaway rules can't exhaust memory
+const MAX_LSYSTEM_LENGTH: usize = 100_000;
+
+/// Upper bound on rewriting passes (growing rules hit MAX_LSYSTEM_LENGTH long before)
+const MAX_LSYSTEM_ITERATIONS: u32 = 32;
+
+/// Upper bound on hexes drawn per F/f command
+const MAX_STEP_LENGTH: i32 = 64;
+
+/// Rewrite the axiom `iterations` times with the production rules
+///
+/// Symbols without a rule are copied unchanged. At most MAX_LSYSTEM_ITERATIONS
+/// passes run, and expansion stops early once the string would exceed
+/// MAX_LSYSTEM_LENGTH symbols.
+pub fn expand_lsystem(axiom: &str, rules: &[(char, String)], iterations: u32) -> String {
+    let mut current = axiom.to_string();
+    'passes: for _ in 0..iterations.min(MAX_LSYSTEM_ITERATIONS) {
+        let mut next = String::with_capacity(current.len() * 2);
+        let mut length = 0;
+        for symbol in current.chars() {
+            match rules.iter().find(|(from, _)| *from == symbol) {
+                Some((_, replacement)) => {
+                    length += replacement.chars().count();
+                    if length > MAX_LSYSTEM_LENGTH {
+                        break 'passes;
+                    }
+                    next.push_str(replacement);
+                }
+                None => {
+                    length += 1;
+                    if length > MAX_LSYSTEM_LENGTH {
+                        break 'passes;
+                    }
+                    next.push(symbol);
+                }
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+/// Snap a heading in degrees to the nearest hex direction (index into CUBE_DIRECTIONS)
+fn snap_heading(heading: f64) -> usize {
+    (heading / 60.0).round().rem_euclid(6.0) as usize % 6
+}
+
+/// Walk the turtle over an expanded L-system string and collect the hexes it draws
+///
+/// Turtle commands:
+/// - `F` draw forward `step_length` hexes
+/// - `f` move forward without drawing
+/// - `+` / `-` turn by `angle` degrees (counter-clockwise / clockwise)
+/// - `[` / `]` push / pop position and heading (branches)
+///
+/// With `valid_terrain`, a branch that would draw onto a hex outside it stops
+/// drawing there until the enclosing `]`, so roads never leave the terrain and
+/// stay connected to the origin.
+pub fn interpret_lsystem(
+    commands: &str,
+    origin: (i32, i32),
+    angle: f64,
+    step_length: i32,
+    initial_heading: f64,
+    valid_terrain: Option<&HashSet<(i32, i32)>>,
+) -> HashSet<(i32, i32)> {
+    let allowed = |hex: &(i32, i32)| valid_terrain.is_none_or(|terrain| terrain.contains(hex));
+    let mut roads = HashSet::new();
+    let mut position = origin;
+    let mut heading = initial_heading;
+    let mut blocked = !allowed(&origin);
+    let mut stack: Vec<((i32, i32), f64, bool)> = Vec::new();
+    if !blocked {
+        roads.insert(origin);
+    }
+
+    for command in commands.chars() {
+        match command {
+            'F' | 'f' => {
+                let direction = CUBE_DIRECTIONS[snap_heading(heading)];
+                for _ in 0..step_length {
+                    position = (position.0 + direction.q, position.1 + direction.r);
+                    if command == 'F' && !blocked {
+                        if allowed(&position) {
+                            roads.insert(position);
+                        } else {
+                            blocked = true;
+                        }
+                    }
+                }
+            }
+            '+' => heading += angle,
+            '-' => heading -= angle,
+            '[' => stack.push((position, heading, blocked)),
+            ']' => {
+                if let Some((saved_position, saved_heading, saved_blocked)) = stack.pop() {
+                    position = saved_position;
+                    heading = saved_heading;
+                    blocked = saved_blocked;
+                }
+            }
+            _ => {}
+        }
+    }
+    roads
+}
+
+/// Generate a road network from an L-system
+///
+/// **Learning Point**: An L-system grows a string by rewriting every symbol in
+/// parallel (e.g. `X -> F[+X][-X]FX`), then a "turtle" walks the result. Branch
+/// brackets give the radial, tree-like layouts of organic towns that the growing
+/// tree (nearest-neighbor A*) generator can't produce. The turtle keeps an exact
+/// heading so any turn angle works, but each step is snapped to the nearest of the
+/// six hex directions, so roads always run hex-to-hex.
+///
+/// @param axiom - Start string, e.g. "X"
+/// @param rules_json - JSON object of productions keyed by single symbol: {"X":"F[+X][-X]FX","F":"FF"}
+/// @param iterations - Number of rewriting passes (at most 32; expansion also stops at 100000 symbols)
+/// @param params_json - JSON object (all optional):
+///   {"origin":{"q":0,"r":0},"angle":60,"stepLength":2,"heading":0,"validTerrain":[{"q":0,"r":0},...],"roadWidth":0}
+///   heading and angle are in degrees; heading 0 points along direction 0 (q+1); stepLength is
+///   rounded and capped at 64 hexes; with validTerrain the roads are clipped to it (a branch stops
+///   where it would leave the terrain); roadWidth widens the roads, within validTerrain if given
+///   (see widen_road_network)
+/// @returns JSON array of road coordinates sorted by (q, r): [{"q":0,"r":0},...], with "part" and
+///   "offset" when widened, or "null" if stepLength is not a positive finite number
+#[wasm_bindgen]
+pub fn generate_road_network_lsystem(axiom: String, rules_json: String, iterations: u32, params_json: String) -> String {
+    let rules: Vec<(char, String)> = parse_json_object(&rules_json)
+        .as_object()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(key, value)| {
+            let mut symbols = key.chars();
+            match (symbols.next(), symbols.next(), value) {
+                (Some(symbol), None, JsonValue::String(replacement)) => Some((symbol, replacement.clone())),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let params = parse_json_object(&params_json);
+    let origin = params.get("origin").and_then(|value| value.as_hex()).unwrap_or((0, 0));
+    let angle = params.get("angle").and_then(|value| value.as_f64()).unwrap_or(60.0);
+    let step_length = params.get("stepLength").and_then(|value| value.as_f64()).unwrap_or(2.0);
+    if !step_length.is_finite() || step_length <= 0.0 {
+        return "null".to_string();
+    }
+    let step_length = (step_length.round() as i32).clamp(1, MAX_STEP_LENGTH);
+    let heading = params.get("heading").and_then(|value| value.as_f64()).unwrap_or(0.0);
+    let valid_terrain: Option<HashSet<(i32, i32)>> = params.get("validTerrain")
+        .map(|value| value.as_hex_list().into_iter().collect());
+    let road_width = params.get("roadWidth").and_then(|value| value.as_i32()).unwrap_or(0);
+
+    let commands = expand_lsystem(&axiom, &rules, iterations);
+    let roads: Vec<(i32, i32)> = interpret_lsystem(&commands, origin, angle, step_length, heading, valid_terrain.as_ref())
+        .into_iter()
+        .collect();
+
+    widened_roads_json(&apply_road_width(&roads, valid_terrain.as_ref(), road_width), road_width > 0)
+}
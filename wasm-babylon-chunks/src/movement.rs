@@ -0,0 +1,639 @@
+//! Movement profiles module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use std::sync::{LazyLock, Mutex};
+use crate::astar::{find_path_weighted, search_path_weighted, straighten_path, WeightedPath};
+use crate::hex_utils::{flood_fill, generate_hex_grid, get_hex_neighbors, hex_distance, hex_line};
+use crate::json::{parse_json, parse_json_object, JsonValue};
+use crate::errors::{envelope, hex_set_from_value, hex_values_from_value, number_field, parse_object_param, profile_param, ApiError, ErrorCode};
+
+/// Movement layer a hex belongs to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementLayer {
+    /// Any land tile
+    Land,
+    /// Water tiles
+    Water,
+    /// Hexes without a tile (outside the generated map); flyers may also cross any tile
+    Air,
+}
+
+impl MovementLayer {
+    pub fn name(self) -> &'static str {
+        match self {
+            MovementLayer::Land => "land",
+            MovementLayer::Water => "water",
+            MovementLayer::Air => "air",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MovementLayer> {
+        [MovementLayer::Land, MovementLayer::Water, MovementLayer::Air]
+            .into_iter()
+            .find(|layer| layer.name() == name)
+    }
+
+    /// Layer of a hex given its tile (None = no tile)
+    pub fn of(tile: Option<TileType>) -> MovementLayer {
+        match tile {
+            None => MovementLayer::Air,
+            Some(TileType::Water) => MovementLayer::Water,
+            Some(_) => MovementLayer::Land,
+        }
+    }
+}
+
+/// Which hexes a transition rule side matches
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HexMatch {
+    Any,
+    Tile(TileType),
+    Layer(MovementLayer),
+}
+
+impl HexMatch {
+    fn from_name(name: &str) -> Option<HexMatch> {
+        if name == "*" {
+            return Some(HexMatch::Any);
+        }
+        TileType::from_name(name)
+            .map(HexMatch::Tile)
+            .or_else(|| MovementLayer::from_name(name).map(HexMatch::Layer))
+    }
+
+    fn matches(self, tile: Option<TileType>) -> bool {
+        match self {
+            HexMatch::Any => true,
+            HexMatch::Tile(expected) => tile == Some(expected),
+            HexMatch::Layer(layer) => MovementLayer::of(tile) == layer,
+        }
+    }
+}
+
+/// Extra cost (or a ban) for stepping between two kinds of hex, e.g. boarding a boat
+#[derive(Clone, Debug)]
+pub struct TransitionRule {
+    pub from: HexMatch,
+    pub to: HexMatch,
+    pub cost: f64,
+    pub allowed: bool,
+}
+
+/// What a mover can enter and what it costs
+///
+/// **Learning Point**: Walkers, vehicles, boats and flyers differ only in which
+/// tiles they may enter, how expensive each one is, and what happens when they
+/// cross from one kind of hex to another. Keeping that in one table lets the same
+/// search code serve every kind of traffic on the same generated map.
+#[derive(Clone, Debug)]
+pub struct MovementProfile {
+    /// Entry cost per tile type (indexed by TileType id); None = impassable
+    pub costs: [Option<f64>; TileType::COUNT],
+    /// Layers the mover may be in
+    pub layers: Vec<MovementLayer>,
+    pub transitions: Vec<TransitionRule>,
+}
+
+impl MovementProfile {
+    fn with_costs(costs: &[(TileType, f64)], layers: Vec<MovementLayer>) -> Self {
+        let mut table = [None; TileType::COUNT];
+        for (tile, cost) in costs {
+            table[tile.index()] = Some(*cost);
+        }
+        MovementProfile { costs: table, layers, transitions: Vec::new() }
+    }
+
+    /// People on foot: any walkable tile at the same cost
+    pub fn walk() -> Self {
+        let costs: Vec<(TileType, f64)> = TileType::ALL.into_iter()
+            .filter(|tile| tile.is_walkable())
+            .map(|tile| (tile, 1.0))
+            .collect();
+        Self::with_costs(&costs, vec![MovementLayer::Land])
+    }
+
+    /// Carts and cars: fast on roads, slow off them, no forest
+    pub fn vehicle() -> Self {
+        Self::with_costs(
+            &[(TileType::Road, 1.0), (TileType::Path, 2.0), (TileType::Grass, 4.0)],
+            vec![MovementLayer::Land],
+        )
+    }
+
+    /// Boats move on water only
+    pub fn boat() -> Self {
+        Self::with_costs(&[(TileType::Water, 1.0)], vec![MovementLayer::Water])
+    }
+
+    /// Flyers ignore terrain: every tile costs the same
+    pub fn air() -> Self {
+        let costs: Vec<(TileType, f64)> = TileType::ALL.into_iter().map(|tile| (tile, 1.0)).collect();
+        Self::with_costs(&costs, vec![MovementLayer::Air])
+    }
+
+    /// Build a profile from the register_movement_profile arguments
+    pub fn from_json(cost_table_json: &str, allowed_layers_json: &str, transition_rules_json: &str) -> Self {
+        let mut costs = [None; TileType::COUNT];
+        for (name, value) in parse_json_object(cost_table_json).as_object().unwrap_or_default() {
+            if let (Some(tile), Some(cost)) = (TileType::from_name(name), value.as_f64()) {
+                if cost.is_finite() && cost > 0.0 {
+                    costs[tile.index()] = Some(cost);
+                }
+            }
+        }
+
+        let mut layers: Vec<MovementLayer> = parse_json(allowed_layers_json)
+            .map(|value| value.as_array().unwrap_or_default()
+                .iter()
+                .filter_map(|item| item.as_str().and_then(MovementLayer::from_name))
+                .collect())
+            .unwrap_or_default();
+        if layers.is_empty() {
+            layers = vec![MovementLayer::Land, MovementLayer::Water];
+        }
+
+        let transitions = parse_json(transition_rules_json)
+            .map(|value| value.as_array().unwrap_or_default()
+                .iter()
+                .filter_map(|rule| Some(TransitionRule {
+                    from: HexMatch::from_name(rule.get("from")?.as_str()?)?,
+                    to: HexMatch::from_name(rule.get("to")?.as_str()?)?,
+                    cost: rule.get("cost").and_then(|value| value.as_f64()).unwrap_or(0.0).max(0.0),
+                    allowed: !matches!(rule.get("allowed"), Some(JsonValue::Bool(false))),
+                }))
+                .collect())
+            .unwrap_or_default();
+
+        MovementProfile { costs, layers, transitions }
+    }
+
+    pub fn cost(&self, tile: TileType) -> Option<f64> {
+        self.costs[tile.index()]
+    }
+
+    /// Cost of entering a hex with this tile (None = no tile), ignoring transitions
+    pub fn entry_cost(&self, tile: Option<TileType>) -> Option<f64> {
+        let flying = self.layers.contains(&MovementLayer::Air);
+        match tile {
+            None => flying.then_some(1.0),
+            Some(tile) if flying || self.layers.contains(&MovementLayer::of(Some(tile))) => self.cost(tile),
+            Some(_) => None,
+        }
+    }
+
+    /// Cost of stepping between two hexes, including transition rules (None = not allowed)
+    pub fn step_cost(&self, from: Option<TileType>, to: Option<TileType>) -> Option<f64> {
+        let mut cost = self.entry_cost(to)?;
+        for rule in &self.transitions {
+            if rule.from.matches(from) && rule.to.matches(to) {
+                if !rule.allowed {
+                    return None;
+                }
+                cost += rule.cost;
+            }
+        }
+        Some(cost)
+    }
+
+    /// Cheapest entry cost, used to scale costs so the hex-distance heuristic stays admissible
+    pub fn min_cost(&self) -> f64 {
+        let mut cheapest = self.costs.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+        if self.layers.contains(&MovementLayer::Air) {
+            cheapest = cheapest.min(1.0);
+        }
+        if cheapest.is_finite() { cheapest } else { 1.0 }
+    }
+
+    /// Grid hexes this profile can enter
+    pub fn passable_hexes(&self, state: &WfcState) -> HashSet<(i32, i32)> {
+        state.grid_tiles()
+            .filter(|(_, tile)| self.entry_cost(Some(*tile)).is_some())
+            .map(|(hex, _)| hex)
+            .collect()
+    }
+
+    /// Search a path with this profile's costs
+    ///
+    /// Without `valid_terrain` the search covers the grid hexes the profile can
+    /// enter; with it, only those valid hexes the profile can enter. `extra_cost`
+    /// adds a per-hex cost on top (e.g. danger), and must not be negative. Hexes that
+    /// can't lie on a path costing at most `max_cost` are not explored.
+    ///
+    /// @returns Path, cost in units of the cheapest tile, and whether it reaches the goal
+    #[allow(clippy::too_many_arguments)]
+    pub fn search<F>(
+        &self,
+        state: &WfcState,
+        start: (i32, i32),
+        goal: (i32, i32),
+        valid_terrain: Option<&HashSet<(i32, i32)>>,
+        extra_cost: F,
+        allow_partial: bool,
+        max_cost: f64,
+    ) -> Option<WeightedPath>
+    where
+        F: Fn((i32, i32)) -> f64,
+    {
+        let passable: HashSet<(i32, i32)> = match valid_terrain {
+            Some(valid) => valid.iter()
+                .filter(|(q, r)| self.entry_cost(state.get_tile(*q, *r)).is_some())
+                .cloned()
+                .collect(),
+            None => self.passable_hexes(state),
+        };
+        let scale = self.min_cost();
+        let step_cost = |from: (i32, i32), to: (i32, i32)| {
+            match self.step_cost(state.get_tile(from.0, from.1), state.get_tile(to.0, to.1)) {
+                Some(cost) => cost / scale + extra_cost(to),
+                None => f64::INFINITY,
+            }
+        };
+        search_path_weighted(start, goal, &passable, step_cost, allow_partial, max_cost)
+    }
+}
+
+/// Registered movement profiles by name, preloaded with "walk", "vehicle", "boat" and "air"
+pub static MOVEMENT_PROFILES: LazyLock<Mutex<HashMap<String, MovementProfile>>> = LazyLock::new(|| {
+    let mut profiles = HashMap::new();
+    profiles.insert("walk".to_string(), MovementProfile::walk());
+    profiles.insert("vehicle".to_string(), MovementProfile::vehicle());
+    profiles.insert("boat".to_string(), MovementProfile::boat());
+    profiles.insert("air".to_string(), MovementProfile::air());
+    Mutex::new(profiles)
+});
+
+/// Look up a registered profile by name
+pub fn movement_profile(name: &str) -> Option<MovementProfile> {
+    MOVEMENT_PROFILES.lock().unwrap().get(name).cloned()
+}
+
+/// Register (or replace) a movement profile
+///
+/// **Learning Point**: Pathfinding entry points (hex_astar, hex_astar_partial,
+/// hex_astar_danger_aware, build_path_between_roads, find_sea_route,
+/// find_flight_path, generate_distance_field) take an optional profile name, so a new kind of mover is one
+/// registration instead of a new family of exported functions. The built-in
+/// "walk", "vehicle", "boat" and "air" profiles can be replaced the same way.
+///
+/// @param name - Profile name
+/// @param cost_table_json - Entry cost per tile name; unlisted tiles are impassable: {"road":1,"grass":3}
+/// @param allowed_layers_json - Layers the mover may use: ["land"], ["water"], ["land","water"] or ["air"]
+///   (air also crosses every tile and hexes outside the map); defaults to ["land","water"]
+/// @param transition_rules_json - JSON array of rules for stepping between hexes:
+///   [{"from":"water","to":"land","cost":5},{"from":"water","to":"forest","allowed":false}]
+///   from/to are tile names, layer names or "*"; matching costs add up, and any banned match forbids the step
+/// @returns false if the profile can't enter any tile
+#[wasm_bindgen]
+pub fn register_movement_profile(
+    name: String,
+    cost_table_json: String,
+    allowed_layers_json: String,
+    transition_rules_json: String,
+) -> bool {
+    let profile = MovementProfile::from_json(&cost_table_json, &allowed_layers_json, &transition_rules_json);
+    if profile.costs.iter().all(|cost| cost.is_none()) {
+        return false;
+    }
+    MOVEMENT_PROFILES.lock().unwrap().insert(name, profile);
+    true
+}
+
+/// Kind of a connected body of water
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WaterBodyKind {
+    Lake,
+    Ocean,
+}
+
+impl WaterBodyKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WaterBodyKind::Lake => "lake",
+            WaterBodyKind::Ocean => "ocean",
+        }
+    }
+}
+
+/// Connected water bodies of the grid, keyed by hex: (body id, hex count)
+///
+/// Body ids follow the smallest hex of each body, so they are stable for a given grid.
+pub fn water_bodies(state: &WfcState) -> HashMap<(i32, i32), (usize, usize)> {
+    let water: HashSet<(i32, i32)> = state.grid_tiles()
+        .filter(|(_, tile)| *tile == TileType::Water)
+        .map(|(hex, _)| hex)
+        .collect();
+    let mut sorted: Vec<(i32, i32)> = water.iter().cloned().collect();
+    sorted.sort();
+
+    let mut bodies = HashMap::new();
+    let mut next_id = 0;
+    for start in sorted {
+        if bodies.contains_key(&start) {
+            continue;
+        }
+        let body = flood_fill(start, |hex| water.contains(&hex));
+        for hex in &body {
+            bodies.insert(*hex, (next_id, body.len()));
+        }
+        next_id += 1;
+    }
+    bodies
+}
+
+/// Find a boat route between two ports over the current grid
+///
+/// **Learning Point**: Boats use the water-only "boat" movement profile. A port may be a
+/// water hex or a coastal land hex (the dock); a land port is only used as the
+/// route's end point, so routes never cut across land. Water bodies of at least
+/// `oceanMinSize` hexes count as ocean, smaller ones as lakes, and the route can
+/// be restricted to one kind (e.g. sea trade that must not use inland lakes).
+///
+/// @param port_a_q - First port q coordinate
+/// @param port_a_r - First port r coordinate
+/// @param port_b_q - Second port q coordinate
+/// @param port_b_r - Second port r coordinate
+/// @param constraints_json - JSON object (all optional): {"waterBody":"any"|"ocean"|"lake","oceanMinSize":100}
+/// @param profile - Movement profile name (default "boat")
+/// @returns JSON object {"path":[{"q":0,"r":0},...],"length":12,"waterBody":"ocean"}, or "null" if no route
+///   exists or the profile is unknown
+#[wasm_bindgen]
+pub fn find_sea_route(
+    port_a_q: i32,
+    port_a_r: i32,
+    port_b_q: i32,
+    port_b_r: i32,
+    constraints_json: String,
+    profile: Option<String>,
+) -> String {
+    let Some(profile) = movement_profile(profile.as_deref().unwrap_or("boat")) else {
+        return "null".to_string();
+    };
+    let constraints = parse_json_object(&constraints_json);
+    let ocean_min_size = constraints.get("oceanMinSize").and_then(|value| value.as_f64()).unwrap_or(100.0).max(0.0) as usize;
+    let allowed = match constraints.get("waterBody").and_then(|value| value.as_str()) {
+        Some("ocean") => Some(WaterBodyKind::Ocean),
+        Some("lake") => Some(WaterBodyKind::Lake),
+        _ => None,
+    };
+    sea_route(&profile, (port_a_q, port_a_r), (port_b_q, port_b_r), allowed, ocean_min_size)
+        .unwrap_or_else(|_| "null".to_string())
+}
+
+/// find_sea_route with a structured result (see the errors module)
+///
+/// Unlike find_sea_route, an unknown waterBody or a negative oceanMinSize is an error
+/// instead of falling back to the default.
+///
+/// @returns JSON envelope: {"ok":{"path":[...],"length":12,"waterBody":"ocean"},"error":null}, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json", "invalid_argument", "unknown_profile" or "no_path"
+#[wasm_bindgen]
+pub fn find_sea_route_result(
+    port_a_q: i32,
+    port_a_r: i32,
+    port_b_q: i32,
+    port_b_r: i32,
+    constraints_json: String,
+    profile: Option<String>,
+) -> String {
+    envelope(checked_sea_route((port_a_q, port_a_r), (port_b_q, port_b_r), &constraints_json, profile))
+}
+
+/// Validate the inputs of find_sea_route_result and run the search
+fn checked_sea_route(
+    port_a: (i32, i32),
+    port_b: (i32, i32),
+    constraints_json: &str,
+    profile: Option<String>,
+) -> Result<String, ApiError> {
+    let profile = profile_param(profile.as_deref().unwrap_or("boat"))?;
+    let constraints = parse_object_param(constraints_json, "constraints_json")?;
+    let ocean_min_size = match number_field(&constraints, "oceanMinSize", "constraints_json")? {
+        Some(size) if size < 0.0 => return Err(ApiError::new(
+            ErrorCode::InvalidArgument,
+            "constraints_json.oceanMinSize: must be 0 or more",
+        )),
+        Some(size) => size as usize,
+        None => 100,
+    };
+    let allowed = match constraints.get("waterBody").map(|value| value.as_str()) {
+        None | Some(Some("any")) => None,
+        Some(Some("ocean")) => Some(WaterBodyKind::Ocean),
+        Some(Some("lake")) => Some(WaterBodyKind::Lake),
+        Some(_) => return Err(ApiError::new(
+            ErrorCode::InvalidArgument,
+            r#"constraints_json.waterBody: expected "any", "ocean" or "lake""#,
+        )),
+    };
+    sea_route(&profile, port_a, port_b, allowed, ocean_min_size)
+}
+
+/// Boat route search shared by find_sea_route and its `_result` variant
+fn sea_route(
+    profile: &MovementProfile,
+    port_a: (i32, i32),
+    port_b: (i32, i32),
+    allowed: Option<WaterBodyKind>,
+    ocean_min_size: usize,
+) -> Result<String, ApiError> {
+    let kind_of = |size: usize| if size >= ocean_min_size { WaterBodyKind::Ocean } else { WaterBodyKind::Lake };
+
+    let state = WFC_STATE.lock().unwrap();
+    let bodies = water_bodies(&state);
+    let mut navigable: HashSet<(i32, i32)> = profile
+        .passable_hexes(&state)
+        .into_iter()
+        .filter(|hex| allowed.is_none_or(|kind| bodies.get(hex).is_some_and(|(_, size)| kind_of(*size) == kind)))
+        .collect();
+
+    // Land ports join the route only if they touch navigable water
+    for port in [port_a, port_b] {
+        let touches_water = get_hex_neighbors(port.0, port.1).iter().any(|hex| navigable.contains(hex));
+        if state.get_tile(port.0, port.1).is_some() && touches_water {
+            navigable.insert(port);
+        }
+    }
+
+    // Docking at a land port costs one step; everything else follows the profile
+    let scale = profile.min_cost();
+    let step_cost = |from: (i32, i32), to: (i32, i32)| {
+        if to == port_a || to == port_b {
+            return 1.0;
+        }
+        match profile.step_cost(state.get_tile(from.0, from.1), state.get_tile(to.0, to.1)) {
+            Some(cost) => cost / scale,
+            None => f64::INFINITY,
+        }
+    };
+    let Some((path, _)) = find_path_weighted(port_a, port_b, &navigable, step_cost) else {
+        return Err(ApiError::new(
+            ErrorCode::NoPath,
+            format!("no sea route from ({}, {}) to ({}, {})", port_a.0, port_a.1, port_b.0, port_b.1),
+        ));
+    };
+
+    let body_kind = path.iter()
+        .find_map(|hex| bodies.get(hex))
+        .map(|(_, size)| kind_of(*size).name());
+    let json_parts: Vec<String> = path.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    let body_json = match body_kind {
+        Some(name) => format!("\"{}\"", name),
+        None => "null".to_string(),
+    };
+
+    Ok(format!(
+        r#"{{"path":[{}],"length":{},"waterBody":{}}}"#,
+        json_parts.join(","), path.len() - 1, body_json
+    ))
+}
+
+/// Flight altitude per path hex: terrain plus clearance, with limited climb and descent
+///
+/// A forward pass limits descent and a backward pass starts climbs early enough to
+/// clear the next ridge, so the altitude never changes by more than max_climb per hex.
+fn flight_altitudes(path: &[(i32, i32)], elevation: &HashMap<(i32, i32), f64>, clearance: f64, max_climb: f64) -> Vec<f64> {
+    let ground: Vec<f64> = path.iter()
+        .map(|hex| elevation.get(hex).copied().unwrap_or(0.0) + clearance)
+        .collect();
+    let mut altitudes = ground.clone();
+    for i in 1..altitudes.len() {
+        altitudes[i] = altitudes[i].max(altitudes[i - 1] - max_climb);
+    }
+    for i in (0..altitudes.len().saturating_sub(1)).rev() {
+        altitudes[i] = altitudes[i].max(altitudes[i + 1] - max_climb);
+    }
+    altitudes
+}
+
+/// Find a flight path for birds, dragons or drones
+///
+/// **Learning Point**: Flyers use the "air" movement profile, where every tile costs
+/// the same, so the only obstacles are no-fly hexes (towers, restricted airspace).
+/// The route is the straight hex line when that is clear; otherwise A* finds a way
+/// around the no-fly zones and the result is straightened back into line segments.
+/// Altitude follows the terrain at a fixed clearance, with climb and descent
+/// limited per hex so the flyer rises before a ridge instead of hitting it.
+///
+/// @param start_q - Start q coordinate
+/// @param start_r - Start r coordinate
+/// @param goal_q - Goal q coordinate
+/// @param goal_r - Goal r coordinate
+/// @param params_json - JSON object (all optional):
+///   {"noFly":[{"q":0,"r":0},...],"maxRange":30,"elevation":[{"q":0,"r":0,"value":1.5},...],"clearance":1,"maxClimb":1}
+///   hexes missing from elevation count as 0
+/// @param profile - Movement profile name (default "air")
+/// @returns JSON object {"path":[{"q":0,"r":0,"altitude":1.0},...],"length":12}, or "null" if the goal is
+///   unreachable, further than maxRange steps, or the profile is unknown
+#[wasm_bindgen]
+pub fn find_flight_path(start_q: i32, start_r: i32, goal_q: i32, goal_r: i32, params_json: String, profile: Option<String>) -> String {
+    let Some(profile) = movement_profile(profile.as_deref().unwrap_or("air")) else {
+        return "null".to_string();
+    };
+    let params = parse_json_object(&params_json);
+    let flight = FlightParams {
+        no_fly: params.get("noFly")
+            .map(|value| value.as_hex_list().into_iter().collect())
+            .unwrap_or_default(),
+        max_range: params.get("maxRange").and_then(|value| value.as_i32()),
+        elevation: params.get("elevation").map(|value| value.as_hex_values()).unwrap_or_default(),
+        clearance: params.get("clearance").and_then(|value| value.as_f64()).unwrap_or(1.0),
+        max_climb: params.get("maxClimb").and_then(|value| value.as_f64()).unwrap_or(1.0).max(0.01),
+    };
+    flight_path(&profile, (start_q, start_r), (goal_q, goal_r), &flight).unwrap_or_else(|_| "null".to_string())
+}
+
+/// find_flight_path with a structured result (see the errors module)
+///
+/// Unlike find_flight_path, malformed params fields are errors instead of being ignored.
+///
+/// @returns JSON envelope: {"ok":{"path":[...],"length":12},"error":null}, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json", "unknown_profile" or "no_path" (also for a no-fly endpoint or a goal beyond maxRange)
+#[wasm_bindgen]
+pub fn find_flight_path_result(start_q: i32, start_r: i32, goal_q: i32, goal_r: i32, params_json: String, profile: Option<String>) -> String {
+    envelope(checked_flight_path((start_q, start_r), (goal_q, goal_r), &params_json, profile))
+}
+
+/// Validate the inputs of find_flight_path_result and run the search
+fn checked_flight_path(start: (i32, i32), goal: (i32, i32), params_json: &str, profile: Option<String>) -> Result<String, ApiError> {
+    let profile = profile_param(profile.as_deref().unwrap_or("air"))?;
+    let params = parse_object_param(params_json, "params_json")?;
+    let max_range = params.get("maxRange")
+        .map(|value| value.as_i32().ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, "params_json.maxRange: expected an integer")))
+        .transpose()?;
+    let flight = FlightParams {
+        no_fly: params.get("noFly").map(|value| hex_set_from_value(value, "params_json.noFly")).transpose()?.unwrap_or_default(),
+        max_range,
+        elevation: params.get("elevation")
+            .map(|value| hex_values_from_value(value, "params_json.elevation"))
+            .transpose()?
+            .unwrap_or_default(),
+        clearance: number_field(&params, "clearance", "params_json")?.unwrap_or(1.0),
+        max_climb: number_field(&params, "maxClimb", "params_json")?.unwrap_or(1.0).max(0.01),
+    };
+    flight_path(&profile, start, goal, &flight)
+}
+
+/// Options of a flight path search (see find_flight_path)
+struct FlightParams {
+    no_fly: HashSet<(i32, i32)>,
+    max_range: Option<i32>,
+    elevation: HashMap<(i32, i32), f64>,
+    clearance: f64,
+    max_climb: f64,
+}
+
+/// Flight path search shared by find_flight_path and its `_result` variant
+fn flight_path(profile: &MovementProfile, start: (i32, i32), goal: (i32, i32), params: &FlightParams) -> Result<String, ApiError> {
+    let FlightParams { no_fly, max_range, elevation, clearance, max_climb } = params;
+    let (max_range, clearance, max_climb) = (*max_range, *clearance, *max_climb);
+    let no_path = |reason: &str| ApiError::new(
+        ErrorCode::NoPath,
+        format!("no flight path from ({}, {}) to ({}, {}): {}", start.0, start.1, goal.0, goal.1, reason),
+    );
+
+    let distance = hex_distance(start.0, start.1, goal.0, goal.1);
+    if no_fly.contains(&start) || no_fly.contains(&goal) {
+        return Err(no_path("an endpoint is a no-fly hex"));
+    }
+    if max_range.is_some_and(|range| distance > range) {
+        return Err(no_path("the goal is beyond maxRange"));
+    }
+
+    // Hexes outside the generated grid are open sky for profiles that fly
+    let state = WFC_STATE.lock().unwrap();
+    let open = |hex: (i32, i32)| !no_fly.contains(&hex) && profile.entry_cost(state.get_tile(hex.0, hex.1)).is_some();
+
+    let line = hex_line(start, goal);
+    let path = if line.iter().all(|hex| open(*hex)) {
+        line
+    } else {
+        // Sky around the start, large enough for any route within range
+        let radius = max_range.unwrap_or(2 * distance + 8);
+        let sky: HashSet<(i32, i32)> = generate_hex_grid(radius, start.0, start.1)
+            .into_iter()
+            .map(|hex| (hex.q, hex.r))
+            .filter(|hex| open(*hex))
+            .collect();
+        let Some((detour, _, _)) = profile.search(&state, start, goal, Some(&sky), |_| 0.0, false, f64::INFINITY) else {
+            return Err(no_path("no-fly hexes block every route"));
+        };
+        straighten_path(&detour, open)
+    };
+
+    let length = path.len() as i32 - 1;
+    if max_range.is_some_and(|range| length > range) {
+        return Err(no_path("the detour is longer than maxRange"));
+    }
+
+    let altitudes = flight_altitudes(&path, elevation, clearance, max_climb);
+    let json_parts: Vec<String> = path.iter()
+        .zip(altitudes)
+        .map(|((q, r), altitude)| format!(r#"{{"q":{},"r":{},"altitude":{:.2}}}"#, q, r, altitude))
+        .collect();
+
+    Ok(format!(r#"{{"path":[{}],"length":{}}}"#, json_parts.join(","), length))
+}
@@ -0,0 +1,52 @@
+//! Coherent noise module
+
+use crate::rng::hash_values;
+
+/// Pseudo-random value in [-1, 1] for an integer lattice point
+fn lattice_value(x: i64, y: i64, seed: u64) -> f64 {
+    let hash = hash_values(seed, &[x, y]);
+    (hash >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+}
+
+/// Smoothstep fade curve so the noise has no visible lattice creases
+fn fade(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// 2D value noise in [-1, 1]
+///
+/// **Learning Point**: Value noise assigns a random value to every integer lattice
+/// point (hashed from the coordinates, so any point can be sampled independently)
+/// and blends the four surrounding values with a smooth fade. Nearby inputs get
+/// similar outputs, which is what makes borders, coastlines and terrain look organic
+/// instead of speckled.
+pub fn value_noise_2d(x: f64, y: f64, seed: u64) -> f64 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = fade(x - x0);
+    let ty = fade(y - y0);
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    let top = lattice_value(ix, iy, seed) * (1.0 - tx) + lattice_value(ix + 1, iy, seed) * tx;
+    let bottom = lattice_value(ix, iy + 1, seed) * (1.0 - tx) + lattice_value(ix + 1, iy + 1, seed) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Fractal (fBm) value noise in roughly [-1, 1]
+///
+/// **Learning Point**: Summing octaves of noise at doubling frequency and halving
+/// amplitude adds fine detail on top of broad shapes. Each octave uses its own seed
+/// so the layers don't line up.
+pub fn fractal_noise_2d(x: f64, y: f64, seed: u64, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_amplitude = 0.0;
+    for octave in 0..octaves.max(1) {
+        total += value_noise_2d(x * frequency, y * frequency, seed.wrapping_add(octave as u64)) * amplitude;
+        max_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / max_amplitude
+}
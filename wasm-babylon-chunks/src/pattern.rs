@@ -0,0 +1,102 @@
+//! Hex pattern matching module
+
+use wasm_bindgen::prelude::*;
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::json::{parse_json, JsonValue};
+
+/// One cell of a pattern: offset from the anchor and required type (None = wildcard)
+pub type PatternCell = ((i32, i32), Option<TileType>);
+
+/// Rotate an axial offset by `steps` 60° steps
+///
+/// **Learning Point**: In cube coordinates (x, y, z) a 60° rotation is
+/// (x, y, z) -> (-z, -x, -y). With q = x and r = z that is (q, r) -> (-r, q + r).
+pub fn rotate_hex(q: i32, r: i32, steps: u32) -> (i32, i32) {
+    (0..steps % 6).fold((q, r), |(q, r), _| (-r, q + r))
+}
+
+/// Parse pattern cells from a JSON array, skipping malformed entries
+pub fn parse_pattern_cells(value: &JsonValue) -> Vec<PatternCell> {
+    let mut cells: Vec<PatternCell> = value.as_array()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|item| {
+            let hex = item.as_hex()?;
+            let tile = item.get("tileType").and_then(|value| value.as_i32()).and_then(TileType::from_i32);
+            Some((hex, tile))
+        })
+        .collect();
+    cells.sort_by_key(|(hex, _)| *hex);
+    cells.dedup_by_key(|(hex, _)| *hex);
+    cells
+}
+
+/// Distinct rotated copies of a pattern, each with the rotation that produced it
+///
+/// Rotations that map the pattern onto an earlier copy are dropped.
+pub fn pattern_rotations(cells: &[PatternCell], allow_rotation: bool) -> Vec<(u32, Vec<PatternCell>)> {
+    let mut rotations: Vec<(u32, Vec<PatternCell>)> = Vec::new();
+    for steps in 0..if allow_rotation { 6 } else { 1 } {
+        let mut rotated: Vec<PatternCell> = cells.iter()
+            .map(|((q, r), tile)| (rotate_hex(*q, *r, steps), *tile))
+            .collect();
+        rotated.sort_by_key(|(hex, _)| *hex);
+        if !rotations.iter().any(|(_, existing)| *existing == rotated) {
+            rotations.push((steps, rotated));
+        }
+    }
+    rotations
+}
+
+/// True if every cell of the (already rotated) pattern matches the grid at the anchor
+pub fn matches_at(state: &WfcState, cells: &[PatternCell], anchor: (i32, i32)) -> bool {
+    cells.iter().all(|((dq, dr), required)| {
+        match (state.get_tile(anchor.0 + dq, anchor.1 + dr), required) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(tile), Some(required)) => tile == *required,
+        }
+    })
+}
+
+/// Find all placements of a small hex pattern in the current grid
+///
+/// **Learning Point**: The pattern is a set of offsets from its anchor (0,0). For
+/// each rotation, every grid hex is tried as the anchor. Rotations that map the
+/// pattern onto itself (e.g. a symmetric ring) are skipped, so each physical
+/// placement is reported once, with the smallest rotation that produces it.
+///
+/// @param pattern_tiles_json - JSON array of pattern cells: [{"q":0,"r":0,"tileType":2},{"q":1,"r":0,"tileType":-1},...]
+///   tileType -1 (or missing) is a wildcard matching any placed tile
+/// @param allow_rotation - Also try the 5 other 60° rotations
+/// @returns JSON array of placements sorted by anchor: [{"q":3,"r":-1,"rotation":0},...]
+///   rotation is the number of 60° steps (see rotate_hex) applied to the pattern
+#[wasm_bindgen]
+pub fn find_pattern(pattern_tiles_json: String, allow_rotation: bool) -> String {
+    let cells = parse_json(&pattern_tiles_json)
+        .map(|value| parse_pattern_cells(&value))
+        .unwrap_or_default();
+    if cells.is_empty() {
+        return "[]".to_string();
+    }
+    let rotations = pattern_rotations(&cells, allow_rotation);
+
+    let state = WFC_STATE.lock().unwrap();
+    let mut anchors: Vec<(i32, i32)> = state.grid_tiles().map(|(hex, _)| hex).collect();
+    anchors.sort();
+
+    let mut json_parts = Vec::new();
+    for anchor in anchors {
+        for (steps, rotated) in &rotations {
+            if matches_at(&state, rotated, anchor) {
+                json_parts.push(format!(
+                    r#"{{"q":{},"r":{},"rotation":{}}}"#,
+                    anchor.0, anchor.1, steps
+                ));
+            }
+        }
+    }
+
+    format!("[{}]", json_parts.join(","))
+}
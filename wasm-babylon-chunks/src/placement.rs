@@ -0,0 +1,355 @@
+//! Point-of-interest placement module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::json::{parse_json, parse_json_object, JsonValue};
+use crate::rng::{SeededRng, seed_from_coords};
+use crate::sampling::seed_or_content;
+use crate::hex_utils::{flood_fill, generate_hex_grid, CUBE_DIRECTIONS, get_hex_neighbors, hex_distance, hex_distance_field, hexes_within_distance};
+
+/// Road hexes connected to a reference hex
+/// If the reference is not itself a road, every road touching it is used as an entry point.
+fn road_component(state: &WfcState, reference: (i32, i32)) -> HashSet<(i32, i32)> {
+    let is_road = |(q, r): (i32, i32)| state.get_tile(q, r) == Some(TileType::Road);
+    let mut entries = vec![reference];
+    entries.extend(get_hex_neighbors(reference.0, reference.1));
+    
+    let mut component = HashSet::new();
+    for entry in entries {
+        if is_road(entry) && !component.contains(&entry) {
+            component.extend(flood_fill(entry, is_road));
+        }
+    }
+    component
+}
+
+/// Place landmarks far apart from each other, all reachable by road from a reference hex
+/// 
+/// **Learning Point**: Candidates are walkable, non-road hexes touching the road network
+/// component that contains (or touches) the reference hex, so every landmark can be
+/// reached by road. Picks use greedy farthest-point sampling: the first landmark is the
+/// candidate farthest from the reference, then each next pick maximizes its distance to
+/// the landmarks already placed, stopping once no candidate is at least
+/// `min_pairwise_distance` away from all of them.
+/// 
+/// @param count - Maximum number of landmarks to place
+/// @param min_pairwise_distance - Minimum hex distance between any two landmarks
+/// @param reference_q - Reference hex q coordinate (e.g. the player spawn or town center)
+/// @param reference_r - Reference hex r coordinate
+/// @returns JSON array of landmark positions with their access road: [{"q":0,"r":0,"road":{"q":1,"r":0}},...]
+#[wasm_bindgen]
+pub fn place_landmarks(count: i32, min_pairwise_distance: i32, reference_q: i32, reference_r: i32) -> String {
+    let state = WFC_STATE.lock().unwrap();
+    let roads = road_component(&state, (reference_q, reference_r));
+    
+    // Walkable non-road hexes touching the reachable road network
+    let mut candidates: Vec<((i32, i32), (i32, i32))> = Vec::new();
+    for ((q, r), tile_type) in state.grid_tiles() {
+        if tile_type == TileType::Road || !tile_type.is_walkable() {
+            continue;
+        }
+        if let Some(road) = get_hex_neighbors(q, r).into_iter().find(|n| roads.contains(n)) {
+            candidates.push(((q, r), road));
+        }
+    }
+    candidates.sort();
+    
+    let mut landmarks: Vec<((i32, i32), (i32, i32))> = Vec::new();
+    while (landmarks.len() as i32) < count {
+        // Distance used for ranking: to the reference for the first pick, then to the nearest landmark
+        let spacing = |hex: (i32, i32)| -> i32 {
+            if landmarks.is_empty() {
+                hex_distance(hex.0, hex.1, reference_q, reference_r)
+            } else {
+                landmarks.iter()
+                    .map(|(placed, _)| hex_distance(hex.0, hex.1, placed.0, placed.1))
+                    .min()
+                    .unwrap_or(i32::MAX)
+            }
+        };
+        
+        let best = candidates.iter()
+            .map(|candidate| (spacing(candidate.0), *candidate))
+            .filter(|(distance, _)| landmarks.is_empty() || *distance >= min_pairwise_distance)
+            .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)));
+        
+        match best {
+            Some((_, candidate)) => landmarks.push(candidate),
+            None => break,
+        }
+    }
+    
+    let json_parts: Vec<String> = landmarks.iter()
+        .map(|((q, r), (road_q, road_r))| {
+            format!(r#"{{"q":{},"r":{},"road":{{"q":{},"r":{}}}}}"#, q, r, road_q, road_r)
+        })
+        .collect();
+    
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Composable spatial predicate evaluated per hex
+enum Predicate {
+    TileTypes(Vec<TileType>),
+    /// Distance to a target set: precomputed field (capped), optional min and max
+    Distance {
+        field: HashMap<(i32, i32), i32>,
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Compile a JSON predicate against the current grid
+    /// Returns None for unrecognized predicates so malformed queries match nothing
+    fn from_json(value: &JsonValue, state: &WfcState) -> Option<Predicate> {
+        if let Some(items) = value.get("and").and_then(|v| v.as_array()) {
+            return items.iter().map(|item| Predicate::from_json(item, state)).collect::<Option<Vec<_>>>().map(Predicate::And);
+        }
+        if let Some(items) = value.get("or").and_then(|v| v.as_array()) {
+            return items.iter().map(|item| Predicate::from_json(item, state)).collect::<Option<Vec<_>>>().map(Predicate::Or);
+        }
+        if let Some(inner) = value.get("not") {
+            return Predicate::from_json(inner, state).map(|p| Predicate::Not(Box::new(p)));
+        }
+        if let Some(types) = value.get("tileTypes") {
+            return Some(Predicate::TileTypes(types.as_tile_types()));
+        }
+        if let Some(target) = value.get("distanceTo") {
+            let min = value.get("min").and_then(|v| v.as_i32());
+            let max = value.get("max").and_then(|v| v.as_i32());
+            let targets: HashSet<(i32, i32)> = match target.get("tileTypes") {
+                Some(types) => {
+                    let types = types.as_tile_types();
+                    state.grid_tiles()
+                        .filter(|(_, tile_type)| types.contains(tile_type))
+                        .map(|(hex, _)| hex)
+                        .collect()
+                }
+                None => target.get("hexes").map(|v| v.as_hex_list().into_iter().collect()).unwrap_or_default(),
+            };
+            // Only distances up to the largest bound matter
+            let cap = min.unwrap_or(0).max(max.unwrap_or(0));
+            let field = hex_distance_field(&targets, cap);
+            return Some(Predicate::Distance { field, min, max });
+        }
+        None
+    }
+    
+    fn matches(&self, hex: (i32, i32), tile_type: TileType) -> bool {
+        match self {
+            Predicate::TileTypes(types) => types.contains(&tile_type),
+            Predicate::Distance { field, min, max } => {
+                // Hexes missing from the capped field are farther than every bound
+                let distance = field.get(&hex).copied();
+                let min_ok = min.is_none_or(|min| distance.is_none_or(|d| d >= min));
+                let max_ok = max.is_none_or(|max| distance.is_some_and(|d| d <= max));
+                min_ok && max_ok
+            }
+            Predicate::And(items) => items.iter().all(|p| p.matches(hex, tile_type)),
+            Predicate::Or(items) => items.iter().any(|p| p.matches(hex, tile_type)),
+            Predicate::Not(inner) => !inner.matches(hex, tile_type),
+        }
+    }
+}
+
+/// Find grid hexes matching composable spatial predicates
+/// 
+/// **Learning Point**: Quest systems describe where something should happen
+/// ("in a forest, at least 10 hexes from any road, within 20 of a landmark") and
+/// let WASM scan the grid, instead of exporting every tile to JS. Matches are
+/// shuffled deterministically so repeated queries spread across the map.
+/// 
+/// Predicate forms:
+/// - {"tileTypes":[3]} - tile is one of the listed types
+/// - {"distanceTo":{"tileTypes":[2]},"min":10} - at least 10 hexes from any road
+/// - {"distanceTo":{"hexes":[{"q":0,"r":0}]},"max":20} - within 20 hexes of a listed hex
+/// - {"and":[...]}, {"or":[...]}, {"not":{...}} - combinators
+/// 
+/// @param predicates_json - JSON predicate tree, e.g. {"and":[{"tileTypes":[3]},{"distanceTo":{"tileTypes":[2]},"min":10}]}
+/// @param count - Maximum number of locations to return
+/// @returns JSON array of matching hexes: [{"q":0,"r":0},...] (empty if the predicate is malformed)
+#[wasm_bindgen]
+pub fn find_locations(predicates_json: String, count: i32) -> String {
+    let state = WFC_STATE.lock().unwrap();
+    let Some(predicate) = parse_json(&predicates_json).and_then(|value| Predicate::from_json(&value, &state)) else {
+        return "[]".to_string();
+    };
+    
+    let mut matches: Vec<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile_type)| predicate.matches(*hex, *tile_type))
+        .map(|(hex, _)| hex)
+        .collect();
+    matches.sort();
+    
+    let mut rng = SeededRng::new(seed_from_coords(&matches));
+    for i in (1..matches.len()).rev() {
+        let j = rng.next_below(i + 1);
+        matches.swap(i, j);
+    }
+    matches.truncate(count.max(0) as usize);
+    
+    let json_parts: Vec<String> = matches.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Place enemy camps away from roads and buildings and export their combined threat field
+/// 
+/// **Learning Point**: Camps go on walkable, non-road terrain at least
+/// `minDistanceFromRoads` hexes from any road or building. Each camp gets a random
+/// threat radius, and no camp's threat may reach into the safe zone, so players
+/// always start somewhere quiet. Threat falls off linearly from 1.0 at the camp to
+/// 1/(radius+1) at the edge of its radius; overlapping camps add up.
+/// 
+/// Parameters (all optional):
+/// - count: number of camps (default 5)
+/// - minDistanceFromRoads: minimum hex distance from roads and buildings (default 6)
+/// - minThreatRadius / maxThreatRadius: threat radius range (default 3-6)
+/// - minSpacing: minimum hex distance between camps (default 2 * maxThreatRadius)
+/// - safeZone: {"q":0,"r":0,"radius":10} kept free of threat (default radius 10 at the origin)
+/// - seed: RNG seed (defaults to a seed derived from the candidate set)
+/// 
+/// @param params_json - JSON object with the parameters above
+/// @returns JSON object: {"camps":[{"q":0,"r":0,"threatRadius":4},...],"threat":[{"q":0,"r":0,"value":0.8},...]}
+#[wasm_bindgen]
+pub fn generate_enemy_camps(params_json: String) -> String {
+    let params = parse_json_object(&params_json);
+    let param = |name: &str, default: i32| params.get(name).and_then(|v| v.as_i32()).unwrap_or(default);
+    let count = param("count", 5);
+    let min_distance_from_roads = param("minDistanceFromRoads", 6);
+    let min_threat_radius = param("minThreatRadius", 3).max(0);
+    let max_threat_radius = param("maxThreatRadius", 6).max(min_threat_radius);
+    let min_spacing = param("minSpacing", 2 * max_threat_radius);
+    let safe_zone = params.get("safeZone");
+    let safe_center = safe_zone.and_then(|v| v.as_hex()).unwrap_or((0, 0));
+    let safe_radius = safe_zone.and_then(|v| v.get("radius")).and_then(|v| v.as_i32()).unwrap_or(10);
+    
+    let state = WFC_STATE.lock().unwrap();
+    
+    // Hexes too close to civilization
+    let settled: HashSet<(i32, i32)> = state.grid_tiles()
+        .filter(|(_, tile_type)| matches!(*tile_type, TileType::Road | TileType::Building))
+        .map(|(hex, _)| hex)
+        .collect();
+    let near_settled = hexes_within_distance(&settled, (min_distance_from_roads - 1).max(0));
+    
+    let mut candidates: Vec<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile_type)| tile_type.is_walkable() && *tile_type != TileType::Road && !near_settled.contains(hex))
+        .map(|(hex, _)| hex)
+        .collect();
+    candidates.sort();
+    
+    let mut rng = SeededRng::new(seed_or_content(&params, &candidates));
+    for i in (1..candidates.len()).rev() {
+        let j = rng.next_below(i + 1);
+        candidates.swap(i, j);
+    }
+    
+    // Greedily accept shuffled candidates that respect spacing and the safe zone
+    let mut camps: Vec<((i32, i32), i32)> = Vec::new();
+    for hex in candidates {
+        if camps.len() as i32 >= count {
+            break;
+        }
+        let radius = min_threat_radius + rng.next_below((max_threat_radius - min_threat_radius + 1) as usize) as i32;
+        if hex_distance(hex.0, hex.1, safe_center.0, safe_center.1) <= safe_radius + radius {
+            continue;
+        }
+        if camps.iter().any(|(camp, _)| hex_distance(hex.0, hex.1, camp.0, camp.1) < min_spacing) {
+            continue;
+        }
+        camps.push((hex, radius));
+    }
+    
+    // Combined threat field: linear falloff per camp, summed
+    let mut threat: HashMap<(i32, i32), f64> = HashMap::new();
+    for ((camp_q, camp_r), radius) in &camps {
+        for hex in generate_hex_grid(*radius, *camp_q, *camp_r) {
+            let distance = hex_distance(hex.q, hex.r, *camp_q, *camp_r);
+            *threat.entry((hex.q, hex.r)).or_insert(0.0) += (radius - distance + 1) as f64 / (radius + 1) as f64;
+        }
+    }
+    let mut threat_vec: Vec<((i32, i32), f64)> = threat.into_iter().collect();
+    threat_vec.sort_by_key(|a| a.0);
+    
+    let camp_parts: Vec<String> = camps.iter()
+        .map(|((q, r), radius)| format!(r#"{{"q":{},"r":{},"threatRadius":{}}}"#, q, r, radius))
+        .collect();
+    let threat_parts: Vec<String> = threat_vec.iter()
+        .map(|((q, r), value)| format!(r#"{{"q":{},"r":{},"value":{}}}"#, q, r, value))
+        .collect();
+    
+    format!(
+        r#"{{"camps":[{}],"threat":[{}]}}"#,
+        camp_parts.join(","),
+        threat_parts.join(",")
+    )
+}
+
+/// Find harbor sites where roads end at a large enough body of water
+/// 
+/// **Learning Point**: Water hexes are grouped into connected bodies first, so a
+/// pond next to a road can be told apart from the sea. A harbor goes on a road
+/// dead end (a road hex with at most one road neighbor) that touches a body of at
+/// least `min_water_size` hexes. The dock faces the adjacent water hex with the most
+/// water around it, i.e. toward open water rather than along the shore.
+/// 
+/// @param roads_json - JSON array of road hexes: [{"q":0,"r":0},...]
+/// @param water_regions_json - JSON array of water hexes: [{"q":0,"r":0},...]
+/// @param min_water_size - Minimum number of hexes in the water body
+/// @returns JSON array of docks sorted by (q, r):
+///   [{"q":0,"r":0,"waterQ":1,"waterR":0,"direction":0,"waterBodySize":120},...]
+///   direction is the CUBE_DIRECTIONS index pointing from the dock into the water
+#[wasm_bindgen]
+pub fn place_harbors(roads_json: String, water_regions_json: String, min_water_size: i32) -> String {
+    let roads: HashSet<(i32, i32)> = parse_json(&roads_json).map(|value| value.as_hex_list()).unwrap_or_default().into_iter().collect();
+    let water: HashSet<(i32, i32)> = parse_json(&water_regions_json).map(|value| value.as_hex_list()).unwrap_or_default().into_iter().collect();
+    
+    // Size of the water body each water hex belongs to
+    let mut body_size: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut sorted_water: Vec<(i32, i32)> = water.iter().cloned().collect();
+    sorted_water.sort();
+    for start in sorted_water {
+        if body_size.contains_key(&start) {
+            continue;
+        }
+        let body = flood_fill(start, |hex| water.contains(&hex));
+        for hex in &body {
+            body_size.insert(*hex, body.len());
+        }
+    }
+    
+    let mut dead_ends: Vec<(i32, i32)> = roads.iter()
+        .filter(|(q, r)| get_hex_neighbors(*q, *r).iter().filter(|n| roads.contains(n)).count() <= 1)
+        .cloned()
+        .collect();
+    dead_ends.sort();
+    
+    let mut json_parts = Vec::new();
+    for (q, r) in dead_ends {
+        let best = CUBE_DIRECTIONS.iter()
+            .enumerate()
+            .map(|(direction, offset)| (direction, (q + offset.q, r + offset.r)))
+            .filter(|(_, hex)| body_size.get(hex).is_some_and(|size| *size as i32 >= min_water_size))
+            .max_by_key(|(direction, (wq, wr))| {
+                let open = get_hex_neighbors(*wq, *wr).iter().filter(|n| water.contains(n)).count();
+                (open, std::cmp::Reverse(*direction))
+            });
+        if let Some((direction, (wq, wr))) = best {
+            json_parts.push(format!(
+                r#"{{"q":{},"r":{},"waterQ":{},"waterR":{},"direction":{},"waterBodySize":{}}}"#,
+                q, r, wq, wr, direction, body_size[&(wq, wr)]
+            ));
+        }
+    }
+    
+    format!("[{}]", json_parts.join(","))
+}
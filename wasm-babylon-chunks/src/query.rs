@@ -0,0 +1,389 @@
+//! Hex query expression module
+//!
+//! **Learning Point**: One-off selections ("roads next to water within 15 of the
+//! origin") used to need a new export each. This module parses a tiny filter
+//! language once and evaluates it per hex:
+//!
+//! ```text
+//! expr    := and ("||" and)*
+//! and     := unary ("&&" unary)*
+//! unary   := "!" unary | "(" expr ")" | call | value op value
+//! value   := number | TypeName | "type" | "q" | "r" | "dist(q, r)" | "count(TypeName)"
+//! call    := "adjacent(TypeName)" | "near(TypeName, n)"
+//! op      := "==" | "!=" | "<" | "<=" | ">" | ">="
+//! ```
+//!
+//! Type names are Grass, Building, Road, Forest, Water and Path (case-insensitive).
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::state::{WfcState, WFC_STATE};
+use crate::errors::{envelope, ApiError, ErrorCode};
+use crate::types::TileType;
+use crate::hex_utils::{get_hex_neighbors, hex_distance, hexes_within_distance};
+
+/// Numeric operand of a comparison
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// Tile type literal (compares as its id)
+    Type(TileType),
+    /// Type id of the hex being tested
+    TileType,
+    Q,
+    R,
+    /// Hex distance from the tested hex to a fixed hex
+    Dist(i32, i32),
+    /// Number of neighbors (0-6) of the given type
+    Count(TileType),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Parsed query expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Compare(Value, CompareOp, Value),
+    /// At least one neighbor of the type
+    Adjacent(TileType),
+    /// A tile of the type within the distance (the hex itself included)
+    Near(TileType, i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    LParen,
+    RParen,
+    Comma,
+    And,
+    Or,
+    Not,
+    Op(CompareOp),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let c = chars[pos];
+        let next = chars.get(pos + 1).copied();
+        match c {
+            _ if c.is_whitespace() => pos += 1,
+            '(' => { tokens.push(Token::LParen); pos += 1; }
+            ')' => { tokens.push(Token::RParen); pos += 1; }
+            ',' => { tokens.push(Token::Comma); pos += 1; }
+            '&' if next == Some('&') => { tokens.push(Token::And); pos += 2; }
+            '|' if next == Some('|') => { tokens.push(Token::Or); pos += 2; }
+            '=' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Eq)); pos += 2; }
+            '!' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Ne)); pos += 2; }
+            '!' => { tokens.push(Token::Not); pos += 1; }
+            '<' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Le)); pos += 2; }
+            '<' => { tokens.push(Token::Op(CompareOp::Lt)); pos += 1; }
+            '>' if next == Some('=') => { tokens.push(Token::Op(CompareOp::Ge)); pos += 2; }
+            '>' => { tokens.push(Token::Op(CompareOp::Gt)); pos += 1; }
+            _ if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = pos;
+                pos += 1;
+                while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '.') {
+                    pos += 1;
+                }
+                let text: String = chars[start..pos].iter().collect();
+                let number = text.parse::<f64>().map_err(|_| format!("Invalid number '{}'", text))?;
+                tokens.push(Token::Number(number));
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let start = pos;
+                while pos < chars.len() && (chars[pos].is_ascii_alphanumeric() || chars[pos] == '_') {
+                    pos += 1;
+                }
+                tokens.push(Token::Ident(chars[start..pos].iter().collect()));
+            }
+            _ => return Err(format!("Unexpected character '{}' at {}", c, pos)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token list
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(format!("Expected {:?}, found {:?}", expected, other)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Query::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = Query::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Query::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if name == "adjacent" || name == "near" => {
+                let name = name.clone();
+                self.pos += 1;
+                self.expect(Token::LParen)?;
+                let tile = self.parse_type_name()?;
+                let query = if name == "near" {
+                    self.expect(Token::Comma)?;
+                    Query::Near(tile, self.parse_integer()?)
+                } else {
+                    Query::Adjacent(tile)
+                };
+                self.expect(Token::RParen)?;
+                Ok(query)
+            }
+            _ => {
+                let left = self.parse_value()?;
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    other => return Err(format!("Expected comparison operator, found {:?}", other)),
+                };
+                let right = self.parse_value()?;
+                Ok(Query::Compare(left, op, right))
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Value::Number(number)),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "type" => Ok(Value::TileType),
+                "q" => Ok(Value::Q),
+                "r" => Ok(Value::R),
+                "dist" => {
+                    self.expect(Token::LParen)?;
+                    let q = self.parse_integer()?;
+                    self.expect(Token::Comma)?;
+                    let r = self.parse_integer()?;
+                    self.expect(Token::RParen)?;
+                    Ok(Value::Dist(q, r))
+                }
+                "count" => {
+                    self.expect(Token::LParen)?;
+                    let tile = self.parse_type_name()?;
+                    self.expect(Token::RParen)?;
+                    Ok(Value::Count(tile))
+                }
+                _ => tile_type_from_name(&name)
+                    .map(Value::Type)
+                    .ok_or_else(|| format!("Unknown name '{}'", name)),
+            },
+            other => Err(format!("Expected value, found {:?}", other)),
+        }
+    }
+
+    fn parse_type_name(&mut self) -> Result<TileType, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => tile_type_from_name(&name).ok_or_else(|| format!("Unknown tile type '{}'", name)),
+            Some(Token::Number(id)) => TileType::from_i32(id as i32).ok_or_else(|| format!("Unknown tile type {}", id)),
+            other => Err(format!("Expected tile type, found {:?}", other)),
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<i32, String> {
+        match self.next() {
+            Some(Token::Number(number)) if number.fract() == 0.0 => Ok(number as i32),
+            other => Err(format!("Expected integer, found {:?}", other)),
+        }
+    }
+}
+
+fn tile_type_from_name(name: &str) -> Option<TileType> {
+    TileType::from_name(&name.to_ascii_lowercase())
+}
+
+/// Parse a query expression
+pub fn parse_query(input: &str) -> Result<Query, String> {
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+    if parser.tokens.is_empty() {
+        return Err("Empty query".to_string());
+    }
+    let query = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(query),
+        Some(token) => Err(format!("Unexpected {:?} after end of query", token)),
+    }
+}
+
+/// Query plus the per-grid data its `near` predicates need
+pub struct PreparedQuery {
+    query: Query,
+    near_sets: HashMap<(TileType, i32), HashSet<(i32, i32)>>,
+}
+
+impl PreparedQuery {
+    /// Precompute the neighborhoods used by `near` against the given grid
+    pub fn new(query: Query, state: &WfcState) -> Self {
+        let mut wanted = Vec::new();
+        collect_near(&query, &mut wanted);
+        let mut near_sets = HashMap::new();
+        for (tile, distance) in wanted {
+            near_sets.entry((tile, distance)).or_insert_with(|| {
+                let sources: HashSet<(i32, i32)> = state.grid_tiles()
+                    .filter(|(_, tile_type)| *tile_type == tile)
+                    .map(|(hex, _)| hex)
+                    .collect();
+                hexes_within_distance(&sources, distance)
+            });
+        }
+        PreparedQuery { query, near_sets }
+    }
+
+    pub fn matches(&self, hex: (i32, i32), tile_type: TileType, state: &WfcState) -> bool {
+        self.eval(&self.query, hex, tile_type, state)
+    }
+
+    fn eval(&self, query: &Query, hex: (i32, i32), tile_type: TileType, state: &WfcState) -> bool {
+        match query {
+            Query::And(a, b) => self.eval(a, hex, tile_type, state) && self.eval(b, hex, tile_type, state),
+            Query::Or(a, b) => self.eval(a, hex, tile_type, state) || self.eval(b, hex, tile_type, state),
+            Query::Not(inner) => !self.eval(inner, hex, tile_type, state),
+            Query::Adjacent(tile) => neighbor_count(hex, *tile, state) > 0,
+            Query::Near(tile, distance) => self.near_sets
+                .get(&(*tile, *distance))
+                .is_some_and(|set| set.contains(&hex)),
+            Query::Compare(left, op, right) => {
+                let a = value_of(left, hex, tile_type, state);
+                let b = value_of(right, hex, tile_type, state);
+                match op {
+                    CompareOp::Eq => a == b,
+                    CompareOp::Ne => a != b,
+                    CompareOp::Lt => a < b,
+                    CompareOp::Le => a <= b,
+                    CompareOp::Gt => a > b,
+                    CompareOp::Ge => a >= b,
+                }
+            }
+        }
+    }
+}
+
+fn collect_near(query: &Query, wanted: &mut Vec<(TileType, i32)>) {
+    match query {
+        Query::And(a, b) | Query::Or(a, b) => {
+            collect_near(a, wanted);
+            collect_near(b, wanted);
+        }
+        Query::Not(inner) => collect_near(inner, wanted),
+        Query::Near(tile, distance) => wanted.push((*tile, *distance)),
+        Query::Adjacent(_) | Query::Compare(..) => {}
+    }
+}
+
+fn neighbor_count(hex: (i32, i32), tile: TileType, state: &WfcState) -> usize {
+    get_hex_neighbors(hex.0, hex.1)
+        .iter()
+        .filter(|(q, r)| state.get_tile(*q, *r) == Some(tile))
+        .count()
+}
+
+fn value_of(value: &Value, hex: (i32, i32), tile_type: TileType, state: &WfcState) -> f64 {
+    match value {
+        Value::Number(number) => *number,
+        Value::Type(tile) => tile.id() as f64,
+        Value::TileType => tile_type.id() as f64,
+        Value::Q => hex.0 as f64,
+        Value::R => hex.1 as f64,
+        Value::Dist(q, r) => hex_distance(hex.0, hex.1, *q, *r) as f64,
+        Value::Count(tile) => neighbor_count(hex, *tile, state) as f64,
+    }
+}
+
+/// Select hexes of the current grid with a filter expression
+///
+/// Example: `type==Road && adjacent(Water) && dist(0,0) < 15`
+///
+/// @param expression - Query in the grammar described in the module docs
+/// @returns JSON array of matching hexes sorted by coordinates: [{"q":0,"r":0},...], or an error describing the syntax problem
+#[wasm_bindgen]
+pub fn query(expression: &str) -> Result<String, JsValue> {
+    let parsed = parse_query(expression).map_err(|e| JsValue::from_str(&e))?;
+    Ok(query_hexes(parsed, &WFC_STATE.lock().unwrap()))
+}
+
+/// query with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or {"ok":null,"error":{"code":"invalid_argument","message":"..."}}
+///   describing the syntax problem
+#[wasm_bindgen]
+pub fn query_result(expression: &str) -> String {
+    envelope(
+        parse_query(expression)
+            .map(|parsed| query_hexes(parsed, &WFC_STATE.lock().unwrap()))
+            .map_err(|e| ApiError::new(ErrorCode::InvalidArgument, e)),
+    )
+}
+
+/// Evaluate a parsed query over a grid and format the matches as JSON
+pub fn query_hexes(parsed: Query, state: &WfcState) -> String {
+    let prepared = PreparedQuery::new(parsed, state);
+
+    let mut matches: Vec<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile_type)| prepared.matches(*hex, *tile_type, state))
+        .map(|(hex, _)| hex)
+        .collect();
+    matches.sort();
+
+    let json_parts: Vec<String> = matches.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
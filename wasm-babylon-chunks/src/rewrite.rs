@@ -0,0 +1,125 @@
+//! Grid rewrite rules module
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashSet;
+use crate::state::WFC_STATE;
+use crate::json::{parse_json, JsonValue};
+use crate::types::TileType;
+use crate::pattern::{matches_at, parse_pattern_cells, pattern_rotations, rotate_hex, PatternCell};
+use crate::rng::hash_values;
+
+/// A parsed rewrite rule
+struct RewriteRule {
+    /// Distinct rotations of the match pattern
+    rotations: Vec<(u32, Vec<PatternCell>)>,
+    /// Replacement cells in pattern space (wildcards leave the tile unchanged)
+    replace: Vec<PatternCell>,
+    /// Probability that a match is rewritten
+    chance: f64,
+    seed: u64,
+}
+
+fn parse_rules(rules_json: &str) -> Vec<RewriteRule> {
+    let parsed = parse_json(rules_json);
+    let items = parsed.as_ref().and_then(|value| value.as_array()).unwrap_or_default();
+    items.iter()
+        .filter_map(|item| {
+            let pattern = parse_pattern_cells(item.get("match")?);
+            let replace = parse_pattern_cells(item.get("replace")?);
+            if pattern.is_empty() {
+                return None;
+            }
+            let allow_rotation = matches!(item.get("allowRotation"), Some(JsonValue::Bool(true)));
+            Some(RewriteRule {
+                rotations: pattern_rotations(&pattern, allow_rotation),
+                replace,
+                chance: item.get("chance").and_then(|value| value.as_f64()).unwrap_or(1.0),
+                seed: item.get("seed").and_then(|value| value.as_f64()).unwrap_or(0.0) as u64,
+            })
+        })
+        .collect()
+}
+
+/// Repeatedly replace matched patterns in the grid with replacement patterns
+///
+/// **Learning Point**: This is a graph grammar over the hex grid. Each iteration
+/// runs every rule in order over every anchor (sorted, so results are
+/// deterministic) and rewrites matches immediately. A hex rewritten in this
+/// iteration can't take part in another match until the next iteration, so
+/// growth rules spread one step per iteration instead of sweeping across the map
+/// in a single pass. Iteration stops early once nothing changes.
+///
+/// A rule's "chance" is decided by hashing (seed, iteration, rule, anchor), so the
+/// same rules on the same grid always produce the same map.
+///
+/// @param rules_json - JSON array of rules:
+///   [{"match":[{"q":0,"r":0,"tileType":0},{"q":1,"r":0,"tileType":1}],
+///     "replace":[{"q":0,"r":0,"tileType":2}],
+///     "allowRotation":true,"chance":0.5,"seed":7},...]
+///   match cells use find_pattern's format (tileType -1 = any tile); replace cells
+///   are offsets in the same pattern space and are rotated with the match
+/// @param max_iterations - Maximum number of passes over the rules
+/// @returns JSON object: {"iterations":3,"rewrites":42,"perRule":[30,12]}
+#[wasm_bindgen]
+pub fn apply_rewrite_rules(rules_json: String, max_iterations: u32) -> String {
+    let rules = parse_rules(&rules_json);
+    let mut per_rule = vec![0u32; rules.len()];
+    let mut iterations = 0;
+
+    let mut state = WFC_STATE.lock().unwrap();
+    while iterations < max_iterations {
+        iterations += 1;
+        let mut touched: HashSet<(i32, i32)> = HashSet::new();
+
+        for (rule_index, rule) in rules.iter().enumerate() {
+            let mut anchors: Vec<(i32, i32)> = state.grid_tiles().map(|(hex, _)| hex).collect();
+            anchors.sort();
+
+            for anchor in anchors {
+                for (steps, cells) in &rule.rotations {
+                    let overlaps = cells.iter().any(|((dq, dr), _)| touched.contains(&(anchor.0 + dq, anchor.1 + dr)));
+                    if overlaps || !matches_at(&state, cells, anchor) {
+                        continue;
+                    }
+                    if rule.chance < 1.0 {
+                        let key = [iterations as i64, rule_index as i64, anchor.0 as i64, anchor.1 as i64, *steps as i64];
+                        let roll = (hash_values(rule.seed, &key) >> 11) as f64 / (1u64 << 53) as f64;
+                        if roll >= rule.chance {
+                            continue;
+                        }
+                    }
+
+                    // Matches whose replacement changes nothing don't count as rewrites
+                    let writes: Vec<((i32, i32), TileType)> = rule.replace.iter()
+                        .filter_map(|((q, r), tile)| {
+                            let (dq, dr) = rotate_hex(*q, *r, *steps);
+                            Some(((anchor.0 + dq, anchor.1 + dr), (*tile)?))
+                        })
+                        .filter(|((q, r), tile)| state.get_tile(*q, *r) != Some(*tile))
+                        .collect();
+                    if writes.is_empty() {
+                        continue;
+                    }
+
+                    for ((q, r), tile) in writes {
+                        state.insert_tile(q, r, tile);
+                        touched.insert((q, r));
+                    }
+                    per_rule[rule_index] += 1;
+                    break;
+                }
+            }
+        }
+
+        if touched.is_empty() {
+            break;
+        }
+    }
+
+    let rewrites: u32 = per_rule.iter().sum();
+    let per_rule_parts: Vec<String> = per_rule.iter().map(|count| count.to_string()).collect();
+    format!(
+        r#"{{"iterations":{},"rewrites":{},"perRule":[{}]}}"#,
+        iterations, rewrites, per_rule_parts.join(",")
+    )
+}
@@ -0,0 +1,62 @@
+//! Seeded random number generation module
+
+use wasm_bindgen::prelude::*;
+use wasm_rng::{clear_world_seed, set_world_seed};
+
+pub use wasm_rng::{content_rng, hash_values, seed_from_coords, world_rng, Pcg32};
+
+/// Deterministic pseudo-random number generator for explicitly seeded simulations
+///
+/// **Learning Point**: A thin wrapper over the shared Pcg32 (stream 0), so the
+/// simulations that take a seed parameter (growth, history, sampling) draw from
+/// the same generator as everything else, with a 64-bit draw for seeding
+/// sub-simulations. Content- and world-seeded decisions use content_rng instead.
+#[derive(Clone, Debug)]
+pub struct SeededRng(Pcg32);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng(Pcg32::new(seed, 0))
+    }
+
+    /// Uniform 64-bit value (two 32-bit draws)
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.0.next_u32() as u64) << 32) | self.0.next_u32() as u64
+    }
+
+    /// Uniform integer in 0..bound (returns 0 when bound is 0)
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        self.0.next_below(bound)
+    }
+
+    /// Uniform float in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        self.0.next_f64()
+    }
+
+    /// Fisher-Yates shuffle of a slice in place
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        self.0.shuffle(items);
+    }
+}
+
+/// Set the world seed used by voronoi seeding, shuffle_array, building placement and WFC
+///
+/// **Learning Point**: Those functions derive their randomness from the input
+/// content, so the same input always gives the same output, but every world with
+/// the same grid looks alike. With a world seed set they draw from a PCG32 seeded
+/// by (world seed, content) instead: still fully reproducible, but each seed gives
+/// a different world, so a saved seed regenerates the same world exactly. The
+/// generator and this contract are shared with wasm-babylon-wfc (see wasm-rng).
+///
+/// @param seed - World seed (a BigInt on the JavaScript side)
+#[wasm_bindgen]
+pub fn set_seed(seed: u64) {
+    set_world_seed(seed);
+}
+
+/// Forget the world seed, returning to purely content-based randomness
+#[wasm_bindgen]
+pub fn clear_seed() {
+    clear_world_seed();
+}
@@ -0,0 +1,732 @@
+//! Road graph analysis module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::connectivity::Connectivity;
+use crate::hex_utils::{parse_valid_terrain_json, get_hex_neighbors, CUBE_DIRECTIONS};
+use crate::json::{escape_json_string, parse_json_object, JsonValue};
+
+/// How a traced road segment ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentEnd {
+    /// Reached a hex with 3+ road neighbors
+    Junction,
+    /// Reached a hex with a single road neighbor
+    DeadEnd,
+    /// Came back to the hex the trace started from
+    Loop,
+}
+
+impl SegmentEnd {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SegmentEnd::Junction => "junction",
+            SegmentEnd::DeadEnd => "deadEnd",
+            SegmentEnd::Loop => "loop",
+        }
+    }
+}
+
+/// Road neighbors of a hex, in get_hex_neighbors direction order
+pub fn road_neighbors(hex: (i32, i32), roads: &HashSet<(i32, i32)>) -> Vec<(i32, i32)> {
+    get_hex_neighbors(hex.0, hex.1)
+        .into_iter()
+        .filter(|neighbor| roads.contains(neighbor))
+        .collect()
+}
+
+/// Compass name of the step from one hex to an adjacent hex
+/// Matches the renderer's layout (x grows with q, z grows with r; +z is north)
+pub fn direction_name(from: (i32, i32), to: (i32, i32)) -> &'static str {
+    match (to.0 - from.0, to.1 - from.1) {
+        (1, 0) => "E",
+        (-1, 0) => "W",
+        (0, 1) => "NE",
+        (0, -1) => "SW",
+        (1, -1) => "SE",
+        (-1, 1) => "NW",
+        _ => "?",
+    }
+}
+
+/// Walk along a road from `start` through `first_step` until a junction, dead end,
+/// or the start is reached again. Degree-2 hexes are passed through.
+///
+/// @returns Hexes visited after start (ending with the end hex) and how the segment ends
+pub fn trace_segment(
+    start: (i32, i32),
+    first_step: (i32, i32),
+    roads: &HashSet<(i32, i32)>,
+) -> (Vec<(i32, i32)>, SegmentEnd) {
+    let mut path = vec![first_step];
+    let mut previous = start;
+    let mut current = first_step;
+
+    loop {
+        if current == start {
+            return (path, SegmentEnd::Loop);
+        }
+        let neighbors = road_neighbors(current, roads);
+        match neighbors.len() {
+            0 | 1 => return (path, SegmentEnd::DeadEnd),
+            2 => {
+                let next = if neighbors[0] == previous { neighbors[1] } else { neighbors[0] };
+                previous = current;
+                current = next;
+                path.push(current);
+            }
+            _ => return (path, SegmentEnd::Junction),
+        }
+    }
+}
+
+/// Stable id for a road node, derived from its coordinates
+pub fn node_id(hex: (i32, i32)) -> String {
+    format!("j{}_{}", hex.0, hex.1)
+}
+
+/// Label every road intersection for navigation UI
+///
+/// **Learning Point**: An intersection is any road hex with 3 or more road neighbors.
+/// Ids are derived from coordinates so they stay stable when unrelated parts of the
+/// network change. For each connected direction the segment is followed to the next
+/// intersection (or dead end), giving signage text and turn-by-turn distances.
+///
+/// @param roads_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON array of intersections:
+///   [{"id":"j0_0","q":0,"r":0,"label":"3-way junction (E, NE, W)","segments":[{"direction":"E","distance":4,"to":"j4_0","end":"junction","label":"E: 4 hexes to next junction"},...]},...]
+#[wasm_bindgen]
+pub fn label_road_intersections(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+
+    let mut junctions: Vec<(i32, i32)> = roads.iter()
+        .filter(|hex| road_neighbors(**hex, &roads).len() >= 3)
+        .cloned()
+        .collect();
+    junctions.sort();
+
+    let mut junction_parts = Vec::new();
+    for junction in junctions {
+        let neighbors = road_neighbors(junction, &roads);
+        let directions: Vec<&str> = neighbors.iter().map(|n| direction_name(junction, *n)).collect();
+
+        let mut segment_parts = Vec::new();
+        for neighbor in &neighbors {
+            let (path, end) = trace_segment(junction, *neighbor, &roads);
+            let end_hex = *path.last().unwrap_or(neighbor);
+            let direction = direction_name(junction, *neighbor);
+            let distance = path.len();
+            let hexes = if distance == 1 { "hex" } else { "hexes" };
+            let (to, label) = match end {
+                SegmentEnd::Junction => (
+                    format!(r#""{}""#, node_id(end_hex)),
+                    format!("{}: {} {} to next junction", direction, distance, hexes),
+                ),
+                SegmentEnd::DeadEnd => (
+                    "null".to_string(),
+                    format!("{}: dead end after {} {}", direction, distance, hexes),
+                ),
+                SegmentEnd::Loop => (
+                    format!(r#""{}""#, node_id(junction)),
+                    format!("{}: loops back after {} {}", direction, distance, hexes),
+                ),
+            };
+            segment_parts.push(format!(
+                r#"{{"direction":"{}","distance":{},"to":{},"end":"{}","label":"{}"}}"#,
+                direction, distance, to, end.as_str(), label
+            ));
+        }
+
+        junction_parts.push(format!(
+            r#"{{"id":"{}","q":{},"r":{},"label":"{}-way junction ({})","segments":[{}]}}"#,
+            node_id(junction),
+            junction.0,
+            junction.1,
+            neighbors.len(),
+            directions.join(", "),
+            segment_parts.join(",")
+        ));
+    }
+
+    format!("[{}]", junction_parts.join(","))
+}
+
+/// Road graph: nodes are hexes whose road degree is not 2, edges are the chains between them
+pub struct RoadGraph {
+    /// Node hexes with their road degree (sorted)
+    pub nodes: Vec<((i32, i32), usize)>,
+    /// Edges as full hex paths including both end nodes (path[0] is the smaller end)
+    pub edges: Vec<Vec<(i32, i32)>>,
+}
+
+/// Build the road graph for a set of road hexes
+/// Components that are pure cycles (every hex has degree 2) get their smallest hex as a node.
+pub fn build_road_graph(roads: &HashSet<(i32, i32)>) -> RoadGraph {
+    let mut node_set: HashSet<(i32, i32)> = roads.iter()
+        .filter(|hex| road_neighbors(**hex, roads).len() != 2)
+        .cloned()
+        .collect();
+
+    // Hexes reachable from a node; anything else belongs to a pure cycle
+    let mut covered: HashSet<(i32, i32)> = node_set.clone();
+    let mut edges: Vec<Vec<(i32, i32)>> = Vec::new();
+    let mut seen_edges: HashSet<((i32, i32), (i32, i32))> = HashSet::new();
+
+    let mut sorted_roads: Vec<(i32, i32)> = roads.iter().cloned().collect();
+    sorted_roads.sort();
+
+    let mut pending: Vec<(i32, i32)> = node_set.iter().cloned().collect();
+    pending.sort();
+    let mut cycle_index = 0;
+    loop {
+        for node in pending.drain(..) {
+            for first_step in road_neighbors(node, roads) {
+                // Each edge is traced from both ends; keep it once, keyed by its two end steps
+                if seen_edges.contains(&(node, first_step)) {
+                    continue;
+                }
+                let (trace, _) = trace_segment(node, first_step, roads);
+                let mut path = vec![node];
+                path.extend(trace);
+                covered.extend(path.iter().cloned());
+                let end = path[path.len() - 1];
+                let before_end = path[path.len() - 2];
+                seen_edges.insert((node, first_step));
+                seen_edges.insert((end, before_end));
+                if end < node {
+                    path.reverse();
+                }
+                edges.push(path);
+            }
+        }
+
+        // Promote the smallest uncovered hex of a pure cycle to a node and keep going
+        while cycle_index < sorted_roads.len() && covered.contains(&sorted_roads[cycle_index]) {
+            cycle_index += 1;
+        }
+        match sorted_roads.get(cycle_index) {
+            Some(&hex) => {
+                node_set.insert(hex);
+                covered.insert(hex);
+                pending.push(hex);
+            }
+            None => break,
+        }
+    }
+
+    let mut nodes: Vec<((i32, i32), usize)> = node_set.iter()
+        .map(|hex| (*hex, road_neighbors(*hex, roads).len()))
+        .collect();
+    nodes.sort();
+    edges.sort();
+
+    RoadGraph { nodes, edges }
+}
+
+/// Extract the road network as a graph of nodes and segment edges
+///
+/// **Learning Point**: Junctions (3+ road neighbors), endpoints (1) and isolated road
+/// hexes (0) become nodes; every chain of degree-2 hexes between two nodes becomes an
+/// edge carrying its hex path and length (number of steps). Node ids match the ids
+/// used by label_road_intersections.
+///
+/// @param roads_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON object:
+///   {"nodes":[{"id":"j0_0","q":0,"r":0,"kind":"junction","degree":3},...],
+///    "edges":[{"from":"j0_0","to":"j4_0","length":4,"hexes":[{"q":0,"r":0},...]},...]}
+///   kind is "junction", "endpoint", "isolated", or "loop" (a node chosen on a pure cycle)
+#[wasm_bindgen]
+pub fn extract_road_graph(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+    let graph = build_road_graph(&roads);
+
+    let node_parts: Vec<String> = graph.nodes.iter()
+        .map(|((q, r), degree)| {
+            let kind = match degree {
+                0 => "isolated",
+                1 => "endpoint",
+                2 => "loop",
+                _ => "junction",
+            };
+            format!(
+                r#"{{"id":"{}","q":{},"r":{},"kind":"{}","degree":{}}}"#,
+                node_id((*q, *r)), q, r, kind, degree
+            )
+        })
+        .collect();
+
+    let edge_parts: Vec<String> = graph.edges.iter()
+        .map(|path| {
+            let hex_parts: Vec<String> = path.iter()
+                .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+                .collect();
+            format!(
+                r#"{{"from":"{}","to":"{}","length":{},"hexes":[{}]}}"#,
+                node_id(path[0]),
+                node_id(path[path.len() - 1]),
+                path.len() - 1,
+                hex_parts.join(",")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"nodes":[{}],"edges":[{}]}}"#,
+        node_parts.join(","),
+        edge_parts.join(",")
+    )
+}
+
+/// Edge of a road graph read back from JSON
+struct GraphEdge {
+    from: String,
+    to: String,
+    length: f64,
+    weight: f64,
+    hexes: Vec<(i32, i32)>,
+}
+
+impl GraphEdge {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        let from = value.get("from")?.as_str()?.to_string();
+        let to = value.get("to")?.as_str()?.to_string();
+        let hexes = value.get("hexes").map(|hexes| hexes.as_hex_list()).unwrap_or_default();
+        let length = value.get("length")
+            .and_then(|length| length.as_f64())
+            .unwrap_or(hexes.len().saturating_sub(1) as f64);
+        let weight = value.get("weight").and_then(|weight| weight.as_f64()).unwrap_or(length);
+        Some(GraphEdge { from, to, length, weight, hexes })
+    }
+
+    /// Id of the endpoint opposite `node`
+    fn other_end(&self, node: &str) -> &str {
+        if self.from == node { &self.to } else { &self.from }
+    }
+
+    /// Hex path oriented so that it starts at `node`
+    fn hexes_from(&self, node: &str) -> Vec<(i32, i32)> {
+        let mut hexes = self.hexes.clone();
+        if self.from != node {
+            hexes.reverse();
+        }
+        hexes
+    }
+}
+
+/// Collapse chains of degree-2 nodes into single weighted edges
+///
+/// **Learning Point**: Graphs built hex by hex (or edited after extraction) contain
+/// long runs of nodes with exactly two edges. They carry no routing decisions, so
+/// merging each run into one edge (summing length and weight, concatenating the hex
+/// path) shrinks the graph to its junctions and endpoints without losing geometry.
+/// A cycle made only of degree-2 nodes keeps its smallest id as a "loop" node.
+///
+/// @param graph_json - Graph in the extract_road_graph format; edges may carry an optional "weight" (defaults to length)
+/// @returns JSON object in the same format, with "weight" on every edge:
+///   {"nodes":[{"id":"j0_0","q":0,"r":0,"kind":"junction","degree":3},...],
+///    "edges":[{"from":"j0_0","to":"j4_0","length":4,"weight":4,"hexes":[{"q":0,"r":0},...]},...]}
+#[wasm_bindgen]
+pub fn simplify_road_graph(graph_json: String) -> String {
+    let graph = parse_json_object(&graph_json);
+
+    let mut positions: BTreeMap<String, (i32, i32)> = BTreeMap::new();
+    for node in graph.get("nodes").and_then(|nodes| nodes.as_array()).unwrap_or_default() {
+        if let (Some(id), Some(hex)) = (node.get("id").and_then(|id| id.as_str()), node.as_hex()) {
+            positions.insert(id.to_string(), hex);
+        }
+    }
+    let edges: Vec<GraphEdge> = graph.get("edges")
+        .and_then(|edges| edges.as_array())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(GraphEdge::from_json)
+        .filter(|edge| positions.contains_key(&edge.from) && positions.contains_key(&edge.to))
+        .collect();
+
+    // Incident edge indices per node (a self-loop is listed twice)
+    let mut incident: BTreeMap<&str, Vec<usize>> = positions.keys().map(|id| (id.as_str(), Vec::new())).collect();
+    for (index, edge) in edges.iter().enumerate() {
+        incident.entry(edge.from.as_str()).or_default().push(index);
+        incident.entry(edge.to.as_str()).or_default().push(index);
+    }
+    let collapsible = |id: &str| {
+        incident.get(id).is_some_and(|list| list.len() == 2 && list[0] != list[1])
+    };
+
+    let mut kept: Vec<&str> = incident.keys().cloned().filter(|id| !collapsible(id)).collect();
+    let mut used = vec![false; edges.len()];
+    let mut merged: Vec<GraphEdge> = Vec::new();
+    let mut pending: Vec<&str> = kept.clone();
+
+    loop {
+        for start in pending.drain(..) {
+            for &first in &incident[start] {
+                if used[first] {
+                    continue;
+                }
+                used[first] = true;
+                let mut path = edges[first].hexes_from(start);
+                let mut length = edges[first].length;
+                let mut weight = edges[first].weight;
+                let mut current = edges[first].other_end(start);
+                let mut came_by = first;
+
+                // Follow the chain until a kept node (or the start of a pure cycle)
+                while current != start && collapsible(current) {
+                    let list = &incident[current];
+                    let next = if list[0] == came_by { list[1] } else { list[0] };
+                    used[next] = true;
+                    let hexes = edges[next].hexes_from(current);
+                    if path.last().is_some() && path.last() == hexes.first() {
+                        path.extend(hexes.into_iter().skip(1));
+                    } else {
+                        path.extend(hexes);
+                    }
+                    length += edges[next].length;
+                    weight += edges[next].weight;
+                    current = edges[next].other_end(current);
+                    came_by = next;
+                }
+
+                let (from, to) = if current < start {
+                    path.reverse();
+                    (current, start)
+                } else {
+                    (start, current)
+                };
+                merged.push(GraphEdge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    length,
+                    weight,
+                    hexes: path,
+                });
+            }
+        }
+
+        // Any edge left over belongs to a cycle of degree-2 nodes only
+        let leftover = (0..edges.len()).find(|index| !used[*index]);
+        match leftover {
+            Some(index) => {
+                let mut cycle_nodes: Vec<&str> = Vec::new();
+                let mut current = edges[index].from.as_str();
+                let mut came_by = index;
+                while !cycle_nodes.contains(&current) {
+                    cycle_nodes.push(current);
+                    let list = &incident[current];
+                    came_by = if list[0] == came_by { list[1] } else { list[0] };
+                    current = edges[came_by].other_end(current);
+                }
+                let anchor = cycle_nodes.iter().cloned().min().unwrap_or(current);
+                kept.push(anchor);
+                pending.push(anchor);
+            }
+            None => break,
+        }
+    }
+    kept.sort();
+    merged.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+    let node_parts: Vec<String> = kept.iter()
+        .map(|id| {
+            let (q, r) = positions[*id];
+            let degree = merged.iter()
+                .map(|edge| (edge.from == *id) as usize + (edge.to == *id) as usize)
+                .sum::<usize>();
+            let kind = match degree {
+                0 => "isolated",
+                1 => "endpoint",
+                2 => "loop",
+                _ => "junction",
+            };
+            format!(
+                r#"{{"id":"{}","q":{},"r":{},"kind":"{}","degree":{}}}"#,
+                escape_json_string(id), q, r, kind, degree
+            )
+        })
+        .collect();
+
+    let edge_parts: Vec<String> = merged.iter()
+        .map(|edge| {
+            let hex_parts: Vec<String> = edge.hexes.iter()
+                .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+                .collect();
+            format!(
+                r#"{{"from":"{}","to":"{}","length":{},"weight":{},"hexes":[{}]}}"#,
+                escape_json_string(&edge.from),
+                escape_json_string(&edge.to),
+                edge.length,
+                edge.weight,
+                hex_parts.join(",")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"nodes":[{}],"edges":[{}]}}"#,
+        node_parts.join(","),
+        edge_parts.join(",")
+    )
+}
+
+/// Articulation hexes and bridge steps of a road network
+pub struct CriticalRoads {
+    /// Road hexes whose removal splits their component (sorted)
+    pub articulation: Vec<(i32, i32)>,
+    /// Adjacent road hex pairs whose link is the only connection between two parts (sorted, smaller hex first)
+    pub bridges: Vec<((i32, i32), (i32, i32))>,
+}
+
+/// DFS stack frame: (hex, parent, road neighbors, next neighbor index)
+type DfsFrame = ((i32, i32), Option<(i32, i32)>, Vec<(i32, i32)>, usize);
+
+/// Find articulation points and bridges with Tarjan's low-link DFS
+/// The DFS is iterative so long roads cannot overflow the (small) WASM stack.
+pub fn find_critical_roads(roads: &HashSet<(i32, i32)>) -> CriticalRoads {
+    let mut discovery: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut low: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut articulation: HashSet<(i32, i32)> = HashSet::new();
+    let mut bridges: Vec<((i32, i32), (i32, i32))> = Vec::new();
+    let mut time = 0;
+
+    let mut sorted_roads: Vec<(i32, i32)> = roads.iter().cloned().collect();
+    sorted_roads.sort();
+
+    for &root in &sorted_roads {
+        if discovery.contains_key(&root) {
+            continue;
+        }
+        discovery.insert(root, time);
+        low.insert(root, time);
+        time += 1;
+        let mut root_children = 0;
+
+        let mut stack: Vec<DfsFrame> = vec![(root, None, road_neighbors(root, roads), 0)];
+
+        while let Some(frame) = stack.last_mut() {
+            let (hex, parent) = (frame.0, frame.1);
+            if frame.3 < frame.2.len() {
+                let neighbor = frame.2[frame.3];
+                frame.3 += 1;
+                if Some(neighbor) == parent {
+                    continue;
+                }
+                match discovery.get(&neighbor) {
+                    Some(&neighbor_time) => {
+                        let hex_low = low[&hex];
+                        low.insert(hex, hex_low.min(neighbor_time));
+                    }
+                    None => {
+                        discovery.insert(neighbor, time);
+                        low.insert(neighbor, time);
+                        time += 1;
+                        if hex == root {
+                            root_children += 1;
+                        }
+                        stack.push((neighbor, Some(hex), road_neighbors(neighbor, roads), 0));
+                    }
+                }
+            } else {
+                stack.pop();
+                if let Some(parent) = parent {
+                    let child_low = low[&hex];
+                    let parent_low = low[&parent];
+                    low.insert(parent, parent_low.min(child_low));
+                    if child_low > discovery[&parent] {
+                        bridges.push((parent.min(hex), parent.max(hex)));
+                    }
+                    if parent != root && child_low >= discovery[&parent] {
+                        articulation.insert(parent);
+                    }
+                }
+            }
+        }
+
+        if root_children > 1 {
+            articulation.insert(root);
+        }
+    }
+
+    let mut articulation: Vec<(i32, i32)> = articulation.into_iter().collect();
+    articulation.sort();
+    bridges.sort();
+
+    CriticalRoads { articulation, bridges }
+}
+
+/// Find road hexes and links whose removal disconnects the network
+///
+/// **Learning Point**: An articulation hex is a road tile whose loss splits its
+/// component in two; a bridge is a single hex-to-hex link with the same property.
+/// Both come out of one DFS that tracks the earliest discovery time reachable from
+/// each subtree (Tarjan's low-link), so the cost is linear in the number of roads.
+/// Bridges are grouped into the road graph segments containing them, which is the
+/// unit gameplay usually targets (a toll on a road, a collapsed pass).
+///
+/// @param roads_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON object:
+///   {"articulationHexes":[{"q":0,"r":0},...],
+///    "bridges":[{"a":{"q":0,"r":0},"b":{"q":1,"r":0}},...],
+///    "segments":[{"from":"j0_0","to":"j4_0","length":4,"hexes":[...]},...]}
+///   segments lists the extract_road_graph edges that contain at least one bridge
+#[wasm_bindgen]
+pub fn find_critical_road_segments(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+    let critical = find_critical_roads(&roads);
+    let graph = build_road_graph(&roads);
+
+    let bridge_set: HashSet<((i32, i32), (i32, i32))> = critical.bridges.iter().cloned().collect();
+    let hex_json = |(q, r): (i32, i32)| format!(r#"{{"q":{},"r":{}}}"#, q, r);
+
+    let articulation_parts: Vec<String> = critical.articulation.iter().map(|hex| hex_json(*hex)).collect();
+    let bridge_parts: Vec<String> = critical.bridges.iter()
+        .map(|(a, b)| format!(r#"{{"a":{},"b":{}}}"#, hex_json(*a), hex_json(*b)))
+        .collect();
+    let segment_parts: Vec<String> = graph.edges.iter()
+        .filter(|path| path.windows(2).any(|step| bridge_set.contains(&(step[0].min(step[1]), step[0].max(step[1])))))
+        .map(|path| {
+            let hex_parts: Vec<String> = path.iter().map(|hex| hex_json(*hex)).collect();
+            format!(
+                r#"{{"from":"{}","to":"{}","length":{},"hexes":[{}]}}"#,
+                node_id(path[0]),
+                node_id(path[path.len() - 1]),
+                path.len() - 1,
+                hex_parts.join(",")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"articulationHexes":[{}],"bridges":[{}],"segments":[{}]}}"#,
+        articulation_parts.join(","),
+        bridge_parts.join(","),
+        segment_parts.join(",")
+    )
+}
+
+/// Shape of a road hex, from the directions of its road neighbors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoadTileKind {
+    /// No road neighbors
+    Isolated,
+    /// One road neighbor
+    DeadEnd,
+    /// Two road neighbors on opposite sides
+    Straight,
+    /// Two road neighbors that are not opposite
+    Bend,
+    /// Three road neighbors
+    TJunction,
+    /// Four or more road neighbors
+    Crossroads,
+}
+
+impl RoadTileKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoadTileKind::Isolated => "isolated",
+            RoadTileKind::DeadEnd => "deadEnd",
+            RoadTileKind::Straight => "straight",
+            RoadTileKind::Bend => "bend",
+            RoadTileKind::TJunction => "tJunction",
+            RoadTileKind::Crossroads => "crossroads",
+        }
+    }
+}
+
+/// Road neighbor bitmask of a hex: bit i is set when the neighbor in CUBE_DIRECTIONS[i] is a road
+pub fn road_mask(hex: (i32, i32), roads: &HashSet<(i32, i32)>) -> u8 {
+    CUBE_DIRECTIONS.iter()
+        .enumerate()
+        .filter(|(_, offset)| roads.contains(&(hex.0 + offset.q, hex.1 + offset.r)))
+        .fold(0, |mask, (direction, _)| mask | (1 << direction))
+}
+
+/// Rotate a 6-bit neighbor mask by a number of 60° steps (direction i moves to i + steps)
+fn rotate_mask(mask: u8, steps: u32) -> u8 {
+    let steps = steps % 6;
+    ((mask << steps) | (mask >> ((6 - steps) % 6))) & 0b11_1111
+}
+
+/// Classify a neighbor mask
+///
+/// @returns Kind and rotation: the number of 60° steps that turns the canonical mask of
+///   this shape (the smallest of its six rotations) into `mask`
+pub fn classify_road_mask(mask: u8) -> (RoadTileKind, u32) {
+    let kind = match mask.count_ones() {
+        0 => RoadTileKind::Isolated,
+        1 => RoadTileKind::DeadEnd,
+        2 if rotate_mask(mask, 3) == mask => RoadTileKind::Straight,
+        2 => RoadTileKind::Bend,
+        3 => RoadTileKind::TJunction,
+        _ => RoadTileKind::Crossroads,
+    };
+    let rotation = (0..6).min_by_key(|&steps| rotate_mask(mask, 6 - steps)).unwrap_or(0);
+    (kind, rotation)
+}
+
+/// Classify every road hex for mesh selection
+///
+/// **Learning Point**: A road tile's mesh depends only on which of its six neighbors
+/// are roads. Encoding that as a 6-bit mask (bit i = CUBE_DIRECTIONS[i]) and reducing
+/// it to the smallest of its six rotations leaves a handful of canonical shapes; the
+/// renderer keeps one mesh per canonical mask and turns it by rotation × 60°. Hexes
+/// have two kinds of bend (60° and 120°) and several three-way shapes, which the mask
+/// tells apart.
+///
+/// @param roads_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON array sorted by q, then r: [{"q":0,"r":0,"kind":"bend","mask":5,"rotation":0},...]
+///   where kind is "isolated", "deadEnd", "straight", "bend", "tJunction" or "crossroads", and turning the
+///   canonical mask by rotation steps (bit i moves to bit i + rotation) gives mask
+#[wasm_bindgen]
+pub fn classify_road_tiles(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+    let mut sorted_roads: Vec<(i32, i32)> = roads.iter().cloned().collect();
+    sorted_roads.sort();
+
+    let parts: Vec<String> = sorted_roads.iter()
+        .map(|&(q, r)| {
+            let mask = road_mask((q, r), &roads);
+            let (kind, rotation) = classify_road_mask(mask);
+            format!(r#"{{"q":{},"r":{},"kind":"{}","mask":{},"rotation":{}}}"#, q, r, kind.as_str(), mask, rotation)
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Summarize a road network with tuning metrics
+///
+/// **Learning Point**: Judging generator parameters by eye is slow and subjective.
+/// A few numbers capture the character of a network: intersections versus dead ends
+/// (grid-like or tree-like), the average branch length between them (dense blocks
+/// or long runs), and the largest component (whether everything is actually
+/// reachable). Branches are the edges of extract_road_graph.
+///
+/// @param roads_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON object:
+///   {"totalLength":120,"intersections":9,"deadEnds":14,"branchCount":25,"averageBranchLength":4.8,
+///    "componentCount":1,"largestComponentSize":120}
+///   where totalLength is the number of road hexes, intersections have 3+ road neighbors, dead ends
+///   have exactly one, and averageBranchLength is in steps (0 without branches)
+#[wasm_bindgen]
+pub fn analyze_road_network(roads_json: String) -> String {
+    let roads = parse_valid_terrain_json(&roads_json);
+    let graph = build_road_graph(&roads);
+    let components = Connectivity::from_hexes(&roads).components();
+
+    let intersections = graph.nodes.iter().filter(|(_, degree)| *degree >= 3).count();
+    let dead_ends = graph.nodes.iter().filter(|(_, degree)| *degree == 1).count();
+    let branch_steps: usize = graph.edges.iter().map(|path| path.len() - 1).sum();
+    let average_branch_length = if graph.edges.is_empty() { 0.0 } else { branch_steps as f64 / graph.edges.len() as f64 };
+
+    format!(
+        r#"{{"totalLength":{},"intersections":{},"deadEnds":{},"branchCount":{},"averageBranchLength":{},"componentCount":{},"largestComponentSize":{}}}"#,
+        roads.len(),
+        intersections,
+        dead_ends,
+        graph.edges.len(),
+        average_branch_length,
+        components.len(),
+        components.first().map(|members| members.len()).unwrap_or(0),
+    )
+}
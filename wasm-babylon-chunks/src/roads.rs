@@ -1,13 +1,36 @@
 /// Road network generation module
 
 use wasm_bindgen::prelude::*;
-use std::collections::HashSet;
-use crate::astar::hex_astar;
-use crate::hex_utils::{parse_valid_terrain_json, parse_path_json, hex_distance};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::types::{TileType, WeightedNode};
+use crate::astar::{find_path, find_path_with_turns};
+use crate::errors::{envelope, parse_hex_set_param, ApiError, ErrorCode};
+use crate::fields::reachable_within;
+use crate::hex_utils::{axial_to_cube, cube_ring, parse_valid_terrain_json, get_hex_neighbors, hex_distance, hex_set_from_flat, hexes_to_flat, hexes_within_distance, CUBE_DIRECTIONS};
+use crate::json::{parse_json, parse_json_object};
+use crate::movement::MovementProfile;
+use crate::rng::{content_rng, seed_from_coords, Pcg32};
+use crate::state::WFC_STATE;
+
+/// World RNG stream for the loop pass (see set_seed)
+const WORLD_STREAM_ROAD_LOOPS: u64 = 5;
+
+/// World RNG stream for random and newest frontier picks in the growing tree
+const WORLD_STREAM_ROAD_GROWTH: u64 = 6;
+
+/// Longest new road segment (in steps) the loop pass builds to join two branches
+const MAX_LOOP_GAP: i32 = 3;
+
+/// Loop length limit when the caller gives a loop probability but no max_loop_length
+const DEFAULT_MAX_LOOP_LENGTH: i32 = 12;
+
+/// Bridge defaults for generate_road_network_mst
+const DEFAULT_MAX_BRIDGE_SPAN: i32 = 3;
+const DEFAULT_BRIDGE_DETOUR: f64 = 10.0;
 
 /// Find nearest point in connected set to a given point
 /// Returns the nearest point and its distance
-fn find_nearest_in_set(
+pub fn find_nearest_in_set(
     point: (i32, i32),
     connected_set: &HashSet<(i32, i32)>,
 ) -> Option<((i32, i32), i32)> {
@@ -29,51 +52,295 @@ fn find_nearest_in_set(
     nearest.map(|n| (n, min_distance))
 }
 
-/// Generate road network using true growing tree algorithm
-/// 
-/// Algorithm:
-/// 1. Start with first seed point
-/// 2. For each remaining seed: find nearest connected road, build A* path, add path
-/// 3. For expansion: repeatedly find nearest unconnected valid terrain to any connected road,
-///    build A* path, add path. Continue until target count reached.
+/// Road path between two hexes, with an optional turn penalty (0 = plain A*, see find_path_with_turns)
+fn road_path(from: (i32, i32), to: (i32, i32), valid_terrain: &HashSet<(i32, i32)>, turn_penalty: f64) -> Option<Vec<(i32, i32)>> {
+    if turn_penalty > 0.0 {
+        find_path_with_turns(from, to, valid_terrain, |_, _| 1.0, turn_penalty).map(|(path, _)| path)
+    } else {
+        find_path(from.0, from.1, to.0, to.1, valid_terrain)
+    }
+}
+
+/// Growing tree frontier selection (see generate_road_network_growing_tree)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FrontierPick {
+    /// Connect the unconnected hex closest to the whole network
+    Nearest,
+    /// Carve from a random road hex of the network
+    Random,
+    /// Carve from the most recently added road hex
+    Newest,
+}
+
+/// Relative weights of the frontier picks; each expansion step draws one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrontierWeights {
+    pub nearest: f64,
+    pub random: f64,
+    pub newest: f64,
+}
+
+impl Default for FrontierWeights {
+    fn default() -> Self {
+        FrontierWeights { nearest: 1.0, random: 0.0, newest: 0.0 }
+    }
+}
+
+impl FrontierWeights {
+    /// Parse a strategy name ("nearest", "random", "newest") or a JSON object of weights
+    /// ({"newest":3,"random":1}); None if it is neither or no weight is positive
+    pub fn parse(strategy: &str) -> Option<FrontierWeights> {
+        let weights = match strategy.trim() {
+            "nearest" => FrontierWeights::default(),
+            "random" => FrontierWeights { nearest: 0.0, random: 1.0, newest: 0.0 },
+            "newest" => FrontierWeights { nearest: 0.0, random: 0.0, newest: 1.0 },
+            text => {
+                let json = parse_json(text)?;
+                if json.as_object()?.iter().any(|(key, _)| !matches!(key.as_str(), "nearest" | "random" | "newest")) {
+                    return None;
+                }
+                let weight = |key: &str| json.get(key).and_then(|value| value.as_f64()).unwrap_or(0.0).max(0.0);
+                FrontierWeights { nearest: weight("nearest"), random: weight("random"), newest: weight("newest") }
+            }
+        };
+        (weights.nearest + weights.random + weights.newest > 0.0).then_some(weights)
+    }
+
+    fn pick(&self, rng: &mut Pcg32) -> FrontierPick {
+        if self.random <= 0.0 && self.newest <= 0.0 {
+            return FrontierPick::Nearest;
+        }
+        let roll = rng.next_f64() * (self.nearest + self.random + self.newest);
+        if roll < self.nearest {
+            FrontierPick::Nearest
+        } else if roll < self.nearest + self.random {
+            FrontierPick::Random
+        } else {
+            FrontierPick::Newest
+        }
+    }
+}
+
+/// Growing tree carve step: take a road hex from the active list (newest or random) and
+/// extend it by one free neighbor that touches no other road, so branches stay one hex
+/// apart like maze corridors. Road hexes with no such neighbor leave the active list.
+///
+/// @returns The new road hex, or None once the active list is exhausted
+fn carve_step(
+    connected: &HashSet<(i32, i32)>,
+    unconnected: &HashSet<(i32, i32)>,
+    active: &mut Vec<(i32, i32)>,
+    pick: FrontierPick,
+    rng: &mut Pcg32,
+) -> Option<(i32, i32)> {
+    while !active.is_empty() {
+        let index = if pick == FrontierPick::Newest { active.len() - 1 } else { rng.next_below(active.len()) };
+        let anchor = active[index];
+        let candidates: Vec<(i32, i32)> = get_hex_neighbors(anchor.0, anchor.1)
+            .into_iter()
+            .filter(|hex| {
+                unconnected.contains(hex)
+                    && get_hex_neighbors(hex.0, hex.1).into_iter().all(|next| next == anchor || !connected.contains(&next))
+            })
+            .collect();
+        if candidates.is_empty() {
+            active.remove(index);
+            continue;
+        }
+        return Some(candidates[rng.next_below(candidates.len())]);
+    }
+    None
+}
+
+/// Growing tree expansion: repeatedly add to the network, picking where to grow by the
+/// frontier weights, until it has target_count hexes or nothing else is reachable
+fn expand_road_network(
+    connected: &mut HashSet<(i32, i32)>,
+    unconnected: &mut HashSet<(i32, i32)>,
+    valid_terrain_set: &HashSet<(i32, i32)>,
+    target_count: i32,
+    turn_penalty: f64,
+    frontier: &FrontierWeights,
+) {
+    // Road hexes in the order they joined the network (the growing tree's active list)
+    let mut active: Vec<(i32, i32)> = connected.iter().copied().collect();
+    active.sort();
+    let content_seed = seed_from_coords(&active);
+    let mut rng = content_rng(content_seed, WORLD_STREAM_ROAD_GROWTH);
+
+    while (connected.len() as i32) < target_count && !unconnected.is_empty() {
+        let pick = frontier.pick(&mut rng);
+        if pick != FrontierPick::Nearest {
+            match carve_step(connected, unconnected, &mut active, pick, &mut rng) {
+                Some(hex) => {
+                    connected.insert(hex);
+                    unconnected.remove(&hex);
+                    active.push(hex);
+                    continue;
+                }
+                // Every branch is boxed in; without nearest picks the network is done
+                None if frontier.nearest <= 0.0 => break,
+                None => {}
+            }
+        }
+
+        let mut best_unconnected: Option<(i32, i32)> = None;
+        let mut best_connected: Option<(i32, i32)> = None;
+        let mut min_distance = i32::MAX;
+        
+        // Find nearest unconnected point to any connected road
+        for &unconnected_point in unconnected.iter() {
+            if let Some((nearest_road, distance)) = find_nearest_in_set(unconnected_point, connected) {
+                if distance < min_distance {
+                    min_distance = distance;
+                    best_unconnected = Some(unconnected_point);
+                    best_connected = Some(nearest_road);
+                }
+            }
+        }
+        
+        // Build path and add to network
+        if let (Some(unconnected_point), Some(connected_road)) = (best_unconnected, best_connected) {
+            match road_path(connected_road, unconnected_point, valid_terrain_set, turn_penalty) {
+                Some(path) => {
+                    // Add all path hexes to connected
+                    for path_hex in path {
+                        if connected.insert(path_hex) {
+                            active.push(path_hex);
+                        }
+                        unconnected.remove(&path_hex);
+                    }
+                }
+                None => {
+                    // Can't reach this point, remove it from unconnected
+                    unconnected.remove(&unconnected_point);
+                }
+            }
+        } else {
+            // No more reachable points
+            break;
+        }
+    }
+}
+
+/// Road steps between two road hexes, or None if it is more than max_steps
+fn road_distance(roads: &HashSet<(i32, i32)>, from: (i32, i32), to: (i32, i32), max_steps: i32) -> Option<i32> {
+    let mut steps: HashMap<(i32, i32), i32> = HashMap::new();
+    let mut queue = VecDeque::new();
+    steps.insert(from, 0);
+    queue.push_back(from);
+    while let Some(hex) = queue.pop_front() {
+        let step = steps[&hex];
+        if hex == to {
+            return Some(step);
+        }
+        if step >= max_steps {
+            continue;
+        }
+        for neighbor in get_hex_neighbors(hex.0, hex.1) {
+            if roads.contains(&neighbor) && !steps.contains_key(&neighbor) {
+                steps.insert(neighbor, step + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    None
+}
+
+/// Close loops in a road tree by joining nearby branches (see generate_road_network_growing_tree)
+///
+/// A pair of road hexes at most MAX_LOOP_GAP apart is joined by a short new segment
+/// over free terrain when the roads between them are at least twice as long as the
+/// segment (so it is a real shortcut, not a thickening) and the loop it closes has
+/// at most max_loop_length hexes. Each qualifying pair is joined with probability
+/// loop_probability.
+fn add_road_loops(
+    roads: &mut HashSet<(i32, i32)>,
+    valid_terrain_set: &HashSet<(i32, i32)>,
+    loop_probability: f64,
+    max_loop_length: i32,
+) {
+    if loop_probability <= 0.0 {
+        return;
+    }
+    let mut tree: Vec<(i32, i32)> = roads.iter().copied().collect();
+    tree.sort();
+    let content_seed = seed_from_coords(&tree);
+    let mut rng = content_rng(content_seed, WORLD_STREAM_ROAD_LOOPS);
+
+    for &a in &tree {
+        let center = axial_to_cube(a.0, a.1);
+        let ring_hexes = |radius: i32| cube_ring(center, radius).into_iter().map(|cube| (cube.q, cube.r));
+        for b in (2..=MAX_LOOP_GAP).flat_map(ring_hexes) {
+            if b <= a || !roads.contains(&b) {
+                continue;
+            }
+            // The new segment may only cross free terrain near the pair
+            let mut free: HashSet<(i32, i32)> = (1..=MAX_LOOP_GAP)
+                .flat_map(ring_hexes)
+                .filter(|hex| valid_terrain_set.contains(hex) && !roads.contains(hex))
+                .collect();
+            free.insert(a);
+            free.insert(b);
+            let Some(segment) = find_path(a.0, a.1, b.0, b.1, &free) else {
+                continue;
+            };
+            let gap = segment.len() as i32 - 1;
+            if gap > MAX_LOOP_GAP {
+                continue;
+            }
+            let Some(around) = road_distance(roads, a, b, max_loop_length - gap) else {
+                continue;
+            };
+            if around < 2 * gap || rng.next_f64() >= loop_probability {
+                continue;
+            }
+            roads.extend(segment);
+        }
+    }
+}
+
+/// Optional knobs of grow_road_network (see generate_road_network_growing_tree)
+pub struct GrowthOptions {
+    pub loop_probability: f64,
+    pub max_loop_length: i32,
+    pub turn_penalty: f64,
+    pub frontier: FrontierWeights,
+}
+
+impl GrowthOptions {
+    /// Fill in defaults for the export parameters the caller left out
+    pub fn new(loop_probability: Option<f64>, max_loop_length: Option<i32>, turn_penalty: Option<f64>, frontier: FrontierWeights) -> Self {
+        GrowthOptions {
+            loop_probability: loop_probability.unwrap_or(0.0),
+            max_loop_length: max_loop_length.unwrap_or(DEFAULT_MAX_LOOP_LENGTH),
+            turn_penalty: turn_penalty.unwrap_or(0.0),
+            frontier,
+        }
+    }
+}
+
+/// Grow a road network tree over valid terrain (see generate_road_network_growing_tree)
 /// 
-/// This creates a true tree structure where every road is connected via a path,
-/// not just adjacent (which would be flood fill).
+/// A loop_probability of 0 keeps the pure tree; a turn_penalty of 0 builds paths with plain A*.
 /// 
-/// @param seeds_json - JSON array of seed points: [{"q":0,"r":0},...]
-/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
-/// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
-/// @param target_count - Target number of roads to generate
-/// @returns JSON array of road coordinates: [{"q":0,"r":0},...]
-#[wasm_bindgen]
-pub fn generate_road_network_growing_tree(
-    seeds_json: String,
-    valid_terrain_json: String,
-    occupied_json: String,
+/// @returns Road hexes, sorted
+pub fn grow_road_network(
+    seeds: &HashSet<(i32, i32)>,
+    valid_terrain: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
     target_count: i32,
-) -> String {
-    // Parse inputs
-    let seeds = parse_valid_terrain_json(&seeds_json);
-    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
-    let occupied = parse_valid_terrain_json(&occupied_json);
-    
+    options: &GrowthOptions,
+) -> Vec<(i32, i32)> {
+    let turn_penalty = options.turn_penalty;
     // Build valid terrain set (valid terrain minus occupied)
     let mut valid_terrain_set = HashSet::new();
-    for &hex in &valid_terrain {
+    for &hex in valid_terrain {
         if !occupied.contains(&hex) {
             valid_terrain_set.insert(hex);
         }
     }
     
-    // Convert valid terrain to JSON for hex_astar calls
-    let mut valid_terrain_vec: Vec<(i32, i32)> = valid_terrain_set.iter().cloned().collect();
-    valid_terrain_vec.sort();
-    let mut valid_terrain_json_parts = Vec::new();
-    for (q, r) in &valid_terrain_vec {
-        valid_terrain_json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
-    }
-    let valid_terrain_json_for_astar = format!("[{}]", valid_terrain_json_parts.join(","));
-    
     // Connected set: roads in the network
     let mut connected: HashSet<(i32, i32)> = HashSet::new();
     
@@ -106,16 +373,7 @@ pub fn generate_road_network_growing_tree(
             // Find nearest connected road
             if let Some((nearest_road, _)) = find_nearest_in_set(*seed, &connected) {
                 // Build path from nearest road to seed
-                let path_json = hex_astar(
-                    nearest_road.0,
-                    nearest_road.1,
-                    seed.0,
-                    seed.1,
-                    valid_terrain_json_for_astar.clone(),
-                );
-                
-                if path_json != "null" && !path_json.is_empty() {
-                    let path = parse_path_json(&path_json);
+                if let Some(path) = road_path(nearest_road, *seed, &valid_terrain_set, turn_penalty) {
                     // Add all path hexes to connected
                     for path_hex in path {
                         connected.insert(path_hex);
@@ -127,57 +385,700 @@ pub fn generate_road_network_growing_tree(
     }
     
     // Phase 2: Expand to target density using growing tree
-    while (connected.len() as i32) < target_count && !unconnected.is_empty() {
-        let mut best_unconnected: Option<(i32, i32)> = None;
-        let mut best_connected: Option<(i32, i32)> = None;
-        let mut min_distance = i32::MAX;
-        
-        // Find nearest unconnected point to any connected road
-        for &unconnected_point in &unconnected {
-            if let Some((nearest_road, distance)) = find_nearest_in_set(unconnected_point, &connected) {
-                if distance < min_distance {
-                    min_distance = distance;
-                    best_unconnected = Some(unconnected_point);
-                    best_connected = Some(nearest_road);
-                }
+    expand_road_network(&mut connected, &mut unconnected, &valid_terrain_set, target_count, turn_penalty, &options.frontier);
+    
+    // Phase 3: Optional cross-connections between nearby branches
+    add_road_loops(&mut connected, &valid_terrain_set, options.loop_probability, options.max_loop_length);
+    
+    let mut road_vec: Vec<(i32, i32)> = connected.into_iter().collect();
+    road_vec.sort();
+    road_vec
+}
+
+/// Generate road network using true growing tree algorithm
+/// 
+/// Algorithm:
+/// 1. Start with first seed point
+/// 2. For each remaining seed: find nearest connected road, build A* path, add path
+/// 3. For expansion: repeatedly find nearest unconnected valid terrain to any connected road,
+///    build A* path, add path (or carve from the frontier, see below). Continue until target count reached.
+/// 
+/// This creates a true tree structure where every road is connected via a path,
+/// not just adjacent (which would be flood fill).
+/// 
+/// **Learning Point**: A pure tree has exactly one route between any two places and
+/// dead ends everywhere. The optional loop pass afterwards joins branches that are
+/// close in space but far apart along the roads with a short new segment, turning
+/// cul-de-sacs into city blocks.
+/// 
+/// The frontier strategy decides where the tree grows next. Nearest picks fill space
+/// evenly around the seeds; newest picks follow one branch until it is boxed in and
+/// then back up (long winding corridors, like a depth-first maze); random picks
+/// sprout short branches all over the network. Blended weights give anything in between.
+/// 
+/// @param seeds_json - JSON array of seed points: [{"q":0,"r":0},...]
+/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
+/// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
+/// @param target_count - Target number of roads to generate (before loops are added)
+/// @param loop_probability - Optional chance (0-1) of joining each pair of nearby branches (default 0 = pure tree);
+///   joining segments are at most 3 steps long and seeded like the rest of the world (see set_seed)
+/// @param max_loop_length - Optional longest loop to close, in road hexes around it (default 12)
+/// @param turn_penalty - Optional extra path cost per 60° of heading change (see build_path_between_roads);
+///   favors straight roads and gentle curves (default 0)
+/// @param frontier - Optional frontier strategy: "nearest" (default) connects the free hex closest to the
+///   network; "random" and "newest" carve one hex at a time from a random or the latest road hex, keeping
+///   branches apart; or a JSON object of weights blending them per step, e.g. {"newest":3,"random":1}.
+///   Unknown values fall back to "nearest"
+/// @param road_width - Optional hexes of extra roadway on each side of the centerline, grown over the
+///   free valid terrain after the network is built (default 0 = one hex wide, see widen_road_network)
+/// @returns JSON array of road coordinates: [{"q":0,"r":0},...]; when widened, every corridor hex with
+///   its "part" and "offset" as in widen_road_network
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn generate_road_network_growing_tree(
+    seeds_json: String,
+    valid_terrain_json: String,
+    occupied_json: String,
+    target_count: i32,
+    loop_probability: Option<f64>,
+    max_loop_length: Option<i32>,
+    turn_penalty: Option<f64>,
+    frontier: Option<String>,
+    road_width: Option<i32>,
+) -> String {
+    // Parse inputs
+    let seeds = parse_valid_terrain_json(&seeds_json);
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let occupied = parse_valid_terrain_json(&occupied_json);
+    let frontier = frontier.as_deref().and_then(FrontierWeights::parse).unwrap_or_default();
+    let options = GrowthOptions::new(loop_probability, max_loop_length, turn_penalty, frontier);
+    let road_width = road_width.unwrap_or(0);
+    
+    let roads = grow_road_network(&seeds, &valid_terrain, &occupied, target_count, &options);
+    let free_terrain: HashSet<(i32, i32)> = valid_terrain.difference(&occupied).copied().collect();
+    widened_roads_json(&apply_road_width(&roads, Some(&free_terrain), road_width), road_width > 0)
+}
+
+/// generate_road_network_growing_tree with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or {"ok":null,"error":{"code":"invalid_json","message":"..."}}
+///   naming the malformed parameter ("invalid_argument" for an unknown frontier strategy); "ok" holds
+///   widened roads as in generate_road_network_growing_tree when road_width is given
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn generate_road_network_growing_tree_result(
+    seeds_json: String,
+    valid_terrain_json: String,
+    occupied_json: String,
+    target_count: i32,
+    loop_probability: Option<f64>,
+    max_loop_length: Option<i32>,
+    turn_penalty: Option<f64>,
+    frontier: Option<String>,
+    road_width: Option<i32>,
+) -> String {
+    let parsed = parse_hex_set_param(&seeds_json, "seeds_json").and_then(|seeds| {
+        let valid_terrain = parse_hex_set_param(&valid_terrain_json, "valid_terrain_json")?;
+        let occupied = parse_hex_set_param(&occupied_json, "occupied_json")?;
+        let frontier = match frontier.as_deref() {
+            None => FrontierWeights::default(),
+            Some(strategy) => FrontierWeights::parse(strategy).ok_or_else(|| {
+                ApiError::new(ErrorCode::InvalidArgument, format!("frontier: unknown strategy {}", strategy))
+            })?,
+        };
+        Ok((seeds, valid_terrain, occupied, frontier))
+    });
+    envelope(parsed.map(|(seeds, valid_terrain, occupied, frontier)| {
+        let options = GrowthOptions::new(loop_probability, max_loop_length, turn_penalty, frontier);
+        let road_width = road_width.unwrap_or(0);
+        let roads = grow_road_network(&seeds, &valid_terrain, &occupied, target_count, &options);
+        let free_terrain: HashSet<(i32, i32)> = valid_terrain.difference(&occupied).copied().collect();
+        widened_roads_json(&apply_road_width(&roads, Some(&free_terrain), road_width), road_width > 0)
+    }))
+}
+
+/// Generate a growing-tree road network from flat coordinate buffers
+/// 
+/// Same algorithm as generate_road_network_growing_tree without building and parsing JSON.
+/// 
+/// @param seeds - Int32Array of interleaved seed coordinates: [q0, r0, q1, r1, ...]
+/// @param valid_terrain - Int32Array of interleaved valid terrain coordinates
+/// @param occupied - Int32Array of interleaved occupied coordinates
+/// @param target_count - Target number of roads to generate
+/// @param loop_probability - Optional loop chance (see generate_road_network_growing_tree)
+/// @param max_loop_length - Optional longest loop to close (see generate_road_network_growing_tree)
+/// @param turn_penalty - Optional heading change penalty (see generate_road_network_growing_tree)
+/// @param frontier - Optional frontier strategy (see generate_road_network_growing_tree)
+/// @param road_width - Optional extra roadway per side (see generate_road_network_growing_tree)
+/// @returns Int32Array of interleaved road coordinates, sorted: [q0, r0, q1, r1, ...] (every corridor
+///   hex when widened)
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn generate_road_network_growing_tree_buf(
+    seeds: &[i32],
+    valid_terrain: &[i32],
+    occupied: &[i32],
+    target_count: i32,
+    loop_probability: Option<f64>,
+    max_loop_length: Option<i32>,
+    turn_penalty: Option<f64>,
+    frontier: Option<String>,
+    road_width: Option<i32>,
+) -> Vec<i32> {
+    let frontier = frontier.as_deref().and_then(FrontierWeights::parse).unwrap_or_default();
+    let valid_terrain = hex_set_from_flat(valid_terrain);
+    let occupied = hex_set_from_flat(occupied);
+    let roads = grow_road_network(
+        &hex_set_from_flat(seeds),
+        &valid_terrain,
+        &occupied,
+        target_count,
+        &GrowthOptions::new(loop_probability, max_loop_length, turn_penalty, frontier),
+    );
+    let free_terrain: HashSet<(i32, i32)> = valid_terrain.difference(&occupied).copied().collect();
+    let corridor: Vec<(i32, i32)> = apply_road_width(&roads, Some(&free_terrain), road_width.unwrap_or(0))
+        .into_iter()
+        .map(|(hex, _)| hex)
+        .collect();
+    hexes_to_flat(&corridor)
+}
+
+/// Bridge settings for mst_road_network (see generate_road_network_mst)
+pub struct BridgeParams {
+    /// Hexes a bridge may span
+    pub water: HashSet<(i32, i32)>,
+    /// Most water hexes in one bridge
+    pub max_span: i32,
+    /// Extra cost of a bridge: the land detour it replaces must be at least this much longer
+    pub detour_threshold: f64,
+}
+
+/// Straight crossing from a land hex over water to another land hex
+struct BridgeSpan {
+    to: (i32, i32),
+    water: Vec<(i32, i32)>,
+}
+
+/// Bridges a road may take from each land hex: straight runs of at most max_span water hexes
+fn bridge_spans(valid_terrain: &HashSet<(i32, i32)>, params: &BridgeParams) -> HashMap<(i32, i32), Vec<BridgeSpan>> {
+    let mut spans: HashMap<(i32, i32), Vec<BridgeSpan>> = HashMap::new();
+    for &from in valid_terrain {
+        for offset in CUBE_DIRECTIONS.iter() {
+            let mut hex = (from.0 + offset.q, from.1 + offset.r);
+            let mut water = Vec::new();
+            while params.water.contains(&hex) && (water.len() as i32) < params.max_span {
+                water.push(hex);
+                hex = (hex.0 + offset.q, hex.1 + offset.r);
+            }
+            if !water.is_empty() && valid_terrain.contains(&hex) && !params.water.contains(&hex) {
+                spans.entry(from).or_default().push(BridgeSpan { to: hex, water });
             }
         }
-        
-        // Build path and add to network
-        if let (Some(unconnected_point), Some(connected_road)) = (best_unconnected, best_connected) {
-            let path_json = hex_astar(
-                connected_road.0,
-                connected_road.1,
-                unconnected_point.0,
-                unconnected_point.1,
-                valid_terrain_json_for_astar.clone(),
-            );
-            
-            if path_json != "null" && !path_json.is_empty() {
-                let path = parse_path_json(&path_json);
-                // Add all path hexes to connected
-                for path_hex in path {
-                    connected.insert(path_hex);
-                    unconnected.remove(&path_hex);
-                }
-            } else {
-                // Can't reach this point, remove it from unconnected
-                unconnected.remove(&unconnected_point);
+    }
+    spans
+}
+
+/// How a route enters a hex: the previous land hex and the bridge taken from it, if any
+type RouteStep = ((i32, i32), Option<usize>);
+
+/// Cheapest routes from a set of sources
+struct Routes {
+    /// Cost from the nearest source per reached hex
+    costs: HashMap<(i32, i32), f64>,
+    steps: HashMap<(i32, i32), RouteStep>,
+}
+
+/// Multi-source Dijkstra over land steps and bridges
+fn cheapest_routes<F>(
+    sources: &[(i32, i32)],
+    valid_terrain: &HashSet<(i32, i32)>,
+    hex_cost: &F,
+    spans: &HashMap<(i32, i32), Vec<BridgeSpan>>,
+    bridge_penalty: f64,
+) -> Routes
+where
+    F: Fn((i32, i32)) -> f64,
+{
+    let mut costs: HashMap<(i32, i32), f64> = HashMap::new();
+    let mut steps: HashMap<(i32, i32), RouteStep> = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    for &source in sources {
+        if valid_terrain.contains(&source) && costs.insert(source, 0.0).is_none() {
+            open_set.push(WeightedNode { q: source.0, r: source.1, g: 0.0, f: 0.0 });
+        }
+    }
+
+    while let Some(current) = open_set.pop() {
+        let from = (current.q, current.r);
+        // Skip stale heap entries
+        if current.g > costs[&from] {
+            continue;
+        }
+        let walks = get_hex_neighbors(from.0, from.1)
+            .into_iter()
+            .filter(|neighbor| valid_terrain.contains(neighbor))
+            .map(|neighbor| (neighbor, hex_cost(neighbor), None));
+        let bridges = spans.get(&from).into_iter().flatten().enumerate()
+            .map(|(index, span)| (span.to, span.water.len() as f64 + bridge_penalty + hex_cost(span.to), Some(index)));
+        for (next, cost, bridge) in walks.chain(bridges).collect::<Vec<_>>() {
+            let tentative = current.g + cost;
+            if tentative < costs.get(&next).copied().unwrap_or(f64::INFINITY) {
+                costs.insert(next, tentative);
+                steps.insert(next, (from, bridge));
+                open_set.push(WeightedNode { q: next.0, r: next.1, g: tentative, f: tentative });
             }
-        } else {
-            // No more reachable points
+        }
+    }
+
+    Routes { costs, steps }
+}
+
+/// Road hexes and the water hexes under bridges, both sorted
+pub struct RoadNetwork {
+    pub roads: Vec<(i32, i32)>,
+    pub bridges: Vec<(i32, i32)>,
+}
+
+/// Connect points with a minimum spanning tree of cheapest paths (see generate_road_network_mst)
+///
+/// `hex_cost` is the cost of building road on a hex and must be positive. Points
+/// outside the valid terrain or unreachable from the first point are left out.
+pub fn mst_road_network<F>(
+    points: &[(i32, i32)],
+    valid_terrain: &HashSet<(i32, i32)>,
+    hex_cost: F,
+    bridges: Option<&BridgeParams>,
+) -> RoadNetwork
+where
+    F: Fn((i32, i32)) -> f64,
+{
+    let mut remaining: Vec<(i32, i32)> = points.iter().copied().filter(|point| valid_terrain.contains(point)).collect();
+    if remaining.is_empty() {
+        return RoadNetwork { roads: Vec::new(), bridges: Vec::new() };
+    }
+    let spans = bridges.map(|params| bridge_spans(valid_terrain, params)).unwrap_or_default();
+    let bridge_penalty = bridges.map(|params| params.detour_threshold.max(0.0)).unwrap_or(0.0);
+    let mut network: HashSet<(i32, i32)> = HashSet::new();
+    let mut bridge_hexes: HashSet<(i32, i32)> = HashSet::new();
+    network.insert(remaining.remove(0));
+
+    while !remaining.is_empty() {
+        // Cheapest way from the whole network (not just the points) to every hex
+        let mut sources: Vec<(i32, i32)> = network.iter().copied().collect();
+        sources.sort();
+        let routes = cheapest_routes(&sources, valid_terrain, &hex_cost, &spans, bridge_penalty);
+
+        let nearest = remaining.iter()
+            .enumerate()
+            .filter_map(|(index, point)| Some((index, *routes.costs.get(point)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        let Some((index, _)) = nearest else {
             break;
+        };
+
+        // Follow the route back to the network
+        let mut current = remaining.remove(index);
+        while !network.contains(&current) {
+            network.insert(current);
+            let Some(&(previous, bridge)) = routes.steps.get(&current) else {
+                break;
+            };
+            if let Some(bridge) = bridge {
+                bridge_hexes.extend(spans[&previous][bridge].water.iter().copied());
+            }
+            current = previous;
         }
+        remaining.retain(|point| !network.contains(point));
     }
-    
-    // Convert connected set to JSON array
-    let mut road_vec: Vec<(i32, i32)> = connected.iter().cloned().collect();
+
+    let mut road_vec: Vec<(i32, i32)> = network.into_iter().collect();
     road_vec.sort();
-    let mut json_parts = Vec::new();
-    for (q, r) in road_vec {
-        json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
+    let mut bridge_vec: Vec<(i32, i32)> = bridge_hexes.into_iter().collect();
+    bridge_vec.sort();
+    RoadNetwork { roads: road_vec, bridges: bridge_vec }
+}
+
+/// Generate a road network connecting settlement points with a minimum spanning tree
+///
+/// **Learning Point**: Prim's algorithm grows a tree by repeatedly adding the point
+/// that is cheapest to connect. Here the cost of connecting a point is the cheapest
+/// path to any road hex already built, not just to another point, so new roads can
+/// branch off the middle of existing ones. That makes the result a Steiner-like tree:
+/// nearby settlements share a trunk road instead of each getting its own spoke to a
+/// hub, avoiding the star-shaped sprawl of the growing tree.
+///
+/// With bridge_params, a route may also cross narrow water on a straight bridge. A
+/// bridge costs its length plus detourThreshold, so it is only built when the land
+/// route around the water is longer by more than that.
+///
+/// @param points_json - JSON array of settlement points: [{"q":0,"r":0},...]; the first valid one is the root
+/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
+/// @param cost_table_json - Building cost per tile name of the current grid: {"grass":1,"forest":3}; hexes
+///   with no tile or an unlisted tile cost 1, and costs must be positive (e.g. cheap "road" reuses existing roads)
+/// @param bridge_params_json - Optional bridge settings: {"maxSpan":3,"detourThreshold":10,"water":[{"q":0,"r":0},...]}
+///   (defaults 3 and 10; water defaults to the grid's water tiles); without it no bridges are built
+/// @returns JSON object {"roads":[{"q":0,"r":0},...],"bridges":[{"q":0,"r":0},...]}, both sorted, where bridges are
+///   the water hexes under bridges (not included in roads); points that can't be reached from the first point
+///   are left unconnected
+/// @param road_width - Optional hexes of extra roadway on each side, grown over the valid terrain (bridges stay
+///   one hex wide; default 0, see widen_road_network); widened roads carry "part" and "offset"
+#[wasm_bindgen]
+pub fn generate_road_network_mst(
+    points_json: String,
+    valid_terrain_json: String,
+    cost_table_json: String,
+    bridge_params_json: Option<String>,
+    road_width: Option<i32>,
+) -> String {
+    let points = parse_json(&points_json).map(|value| value.as_hex_list()).unwrap_or_default();
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let costs = MovementProfile::from_json(&cost_table_json, "[]", "[]");
+
+    let state = WFC_STATE.lock().unwrap();
+    let bridges = bridge_params_json.map(|json| {
+        let params = parse_json_object(&json);
+        BridgeParams {
+            water: match params.get("water") {
+                Some(water) => water.as_hex_list().into_iter().collect(),
+                None => state.grid_tiles().filter(|(_, tile)| *tile == TileType::Water).map(|(hex, _)| hex).collect(),
+            },
+            max_span: params.get("maxSpan").and_then(|value| value.as_i32()).unwrap_or(DEFAULT_MAX_BRIDGE_SPAN),
+            detour_threshold: params.get("detourThreshold").and_then(|value| value.as_f64()).unwrap_or(DEFAULT_BRIDGE_DETOUR),
+        }
+    });
+    let network = mst_road_network(&points, &valid_terrain, |(q, r)| {
+        state.get_tile(q, r).and_then(|tile| costs.cost(tile)).unwrap_or(1.0)
+    }, bridges.as_ref());
+
+    let road_width = road_width.unwrap_or(0);
+    let roads = apply_road_width(&network.roads, Some(&valid_terrain), road_width);
+    let bridge_parts: Vec<String> = network.bridges.iter().map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r)).collect();
+    format!(r#"{{"roads":{},"bridges":[{}]}}"#, widened_roads_json(&roads, road_width > 0), bridge_parts.join(","))
+}
+
+/// Tier of a road hex in a two-tier network
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoadTier {
+    /// Trunk road between major seeds
+    Highway,
+    /// Local street branching off the trunk
+    Street,
+}
+
+impl RoadTier {
+    pub fn name(self) -> &'static str {
+        match self {
+            RoadTier::Highway => "highway",
+            RoadTier::Street => "street",
+        }
     }
-    
+}
+
+/// Build a two-tier road network (see generate_road_network_hierarchy)
+///
+/// @returns Road hexes with their tier, sorted
+pub fn road_hierarchy(
+    major_seeds: &[(i32, i32)],
+    valid_terrain: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
+    target_count: i32,
+) -> Vec<((i32, i32), RoadTier)> {
+    let valid_terrain_set: HashSet<(i32, i32)> = valid_terrain.difference(occupied).copied().collect();
+
+    // Trunk: cheapest tree through the major seeds
+    let trunk: HashSet<(i32, i32)> = mst_road_network(major_seeds, &valid_terrain_set, |_| 1.0, None).roads.into_iter().collect();
+
+    // Streets: growing tree from the trunk up to the target density
+    let mut connected = trunk.clone();
+    let mut unconnected: HashSet<(i32, i32)> = valid_terrain_set.difference(&trunk).copied().collect();
+    expand_road_network(&mut connected, &mut unconnected, &valid_terrain_set, target_count, 0.0, &FrontierWeights::default());
+
+    let mut roads: Vec<((i32, i32), RoadTier)> = connected.into_iter()
+        .map(|hex| (hex, if trunk.contains(&hex) { RoadTier::Highway } else { RoadTier::Street }))
+        .collect();
+    roads.sort_by_key(|(hex, _)| *hex);
+    roads
+}
+
+/// Generate a two-tier road network: highways between major seeds and streets branching off them
+///
+/// **Learning Point**: Real road networks are hierarchical. A few trunk roads link
+/// the important places by the most direct routes, and a dense mesh of local streets
+/// hangs off them. Building the trunk first (a spanning tree over the major seeds)
+/// and then growing streets from it gives that shape, and recording the tier per hex
+/// lets the renderer use wider meshes for highways.
+///
+/// @param major_seeds_json - JSON array of major seed points (towns, gates): [{"q":0,"r":0},...]
+/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
+/// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
+/// @param target_count - Target number of road hexes of both tiers together (the trunk alone may exceed it)
+/// @param road_width - Optional hexes of extra roadway on each side of every road, grown over the free valid
+///   terrain (default 0, see widen_road_network); corridor hexes around a highway are highway, the rest street
+/// @returns JSON array of road hexes, sorted: [{"q":0,"r":0,"tier":"highway"},{"q":1,"r":0,"tier":"street"},...]
+///   with "part" and "offset" added when widened
+#[wasm_bindgen]
+pub fn generate_road_network_hierarchy(
+    major_seeds_json: String,
+    valid_terrain_json: String,
+    occupied_json: String,
+    target_count: i32,
+    road_width: Option<i32>,
+) -> String {
+    let major_seeds = parse_json(&major_seeds_json).map(|value| value.as_hex_list()).unwrap_or_default();
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let occupied = parse_valid_terrain_json(&occupied_json);
+    let road_width = road_width.unwrap_or(0);
+
+    let roads = road_hierarchy(&major_seeds, &valid_terrain, &occupied, target_count);
+    let free_terrain: HashSet<(i32, i32)> = valid_terrain.difference(&occupied).copied().collect();
+    let highways: Vec<(i32, i32)> = roads.iter().filter(|(_, tier)| *tier == RoadTier::Highway).map(|(hex, _)| *hex).collect();
+    let highway_corridor: HashSet<(i32, i32)> = apply_road_width(&highways, Some(&free_terrain), road_width)
+        .into_iter()
+        .map(|(hex, _)| hex)
+        .collect();
+    let centerline: Vec<(i32, i32)> = roads.iter().map(|(hex, _)| *hex).collect();
+
+    let json_parts: Vec<String> = apply_road_width(&centerline, Some(&free_terrain), road_width)
+        .iter()
+        .map(|(hex, offset)| {
+            let tier = if highway_corridor.contains(hex) { RoadTier::Highway } else { RoadTier::Street };
+            format!(r#"{{{},"tier":"{}"}}"#, road_hex_fields(*hex, *offset, road_width > 0), tier.name())
+        })
+        .collect();
     format!("[{}]", json_parts.join(","))
 }
 
+/// Lay roads on evenly spaced hex lines within a region (see generate_road_network_grid)
+///
+/// Axis 0 lines have constant r, axis 1 constant q, axis 2 constant s = -q - r; a
+/// hex is a road when, on one of the chosen axes, its coordinate is a multiple of
+/// `spacing` away from the origin's.
+///
+/// @returns Road hexes, sorted
+pub fn grid_road_network(region: &HashSet<(i32, i32)>, origin: (i32, i32), spacing: i32, axes: &[usize]) -> Vec<(i32, i32)> {
+    let spacing = spacing.max(1);
+    let coordinate = |(q, r): (i32, i32), axis: usize| match axis {
+        0 => r,
+        1 => q,
+        _ => -q - r,
+    };
+    let mut roads: Vec<(i32, i32)> = region.iter()
+        .copied()
+        .filter(|&hex| axes.iter().any(|&axis| (coordinate(hex, axis) - coordinate(origin, axis)).rem_euclid(spacing) == 0))
+        .collect();
+    roads.sort();
+    roads
+}
+
+/// Generate a grid-city road layout: regularly spaced straight roads along two or three hex axes
+///
+/// **Learning Point**: Planned districts use a regular street grid. On hexes there
+/// are three axes of straight lines; two of them give rhombic blocks (the hex
+/// version of Manhattan) and all three give triangular blocks. Every road runs
+/// straight, which contrasts with the organic growing-tree and L-system networks.
+/// Lines are clipped to the region, so an irregular region can cut a line in two.
+///
+/// @param region_json - JSON array of hexes the district may use: [{"q":0,"r":0},...]
+/// @param params_json - JSON object (all optional): {"spacing":4,"axes":2,"orientation":0,"origin":{"q":0,"r":0},"roadWidth":0}
+///   spacing is the distance between parallel roads (default 4); axes is 2 or 3 (default 2); with 2 axes,
+///   orientation 0-2 picks the pair (0 = rows of constant r and q, 1 = q and s, 2 = s and r); origin is a hex
+///   every road line passes through (a grid intersection); roadWidth widens every road within the region
+///   (see widen_road_network)
+/// @returns JSON array of road coordinates, sorted: [{"q":0,"r":0},...], with "part" and "offset" when widened
+#[wasm_bindgen]
+pub fn generate_road_network_grid(region_json: String, params_json: String) -> String {
+    let region = parse_valid_terrain_json(&region_json);
+    let params = parse_json_object(&params_json);
+    let spacing = params.get("spacing").and_then(|value| value.as_i32()).unwrap_or(4);
+    let origin = params.get("origin").and_then(|value| value.as_hex()).unwrap_or((0, 0));
+    let orientation = params.get("orientation").and_then(|value| value.as_i32()).unwrap_or(0).rem_euclid(3) as usize;
+    let axes: Vec<usize> = match params.get("axes").and_then(|value| value.as_i32()) {
+        Some(3) => vec![0, 1, 2],
+        _ => vec![orientation, (orientation + 1) % 3],
+    };
+
+    let road_width = params.get("roadWidth").and_then(|value| value.as_i32()).unwrap_or(0);
+
+    let roads = grid_road_network(&region, origin, spacing, &axes);
+    widened_roads_json(&apply_road_width(&roads, Some(&region), road_width), road_width > 0)
+}
+
+/// Road hexes of one chunk where a connector may start or end: its roads on the border,
+/// or its road hex closest to the border if none touch it
+fn border_ends(roads: &HashSet<(i32, i32)>, border: &HashSet<(i32, i32)>) -> Vec<(i32, i32)> {
+    let mut ends: Vec<(i32, i32)> = roads.intersection(border).copied().collect();
+    if ends.is_empty() {
+        let distance_to_border = |hex: &(i32, i32)| border.iter().map(|b| hex_distance(hex.0, hex.1, b.0, b.1)).min();
+        ends.extend(roads.iter().copied().filter_map(|hex| Some((distance_to_border(&hex)?, hex))).min().map(|(_, hex)| hex));
+    }
+    ends.sort();
+    ends
+}
+
+/// New road joining a road end of one chunk to a road end of the other
+pub struct RoadConnector {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    /// New road hexes from next to `from` up to `to` (existing roads left out)
+    pub hexes: Vec<(i32, i32)>,
+}
+
+/// Connector paths that join two neighboring chunks' road networks across their border
+/// (see stitch_chunk_roads)
+pub fn stitch_roads(
+    roads_a: &HashSet<(i32, i32)>,
+    roads_b: &HashSet<(i32, i32)>,
+    border: &HashSet<(i32, i32)>,
+    valid_terrain: &HashSet<(i32, i32)>,
+) -> Vec<RoadConnector> {
+    let allowed: HashSet<(i32, i32)> = valid_terrain.union(roads_a).chain(roads_b.iter()).copied().collect();
+    let mut added: HashSet<(i32, i32)> = HashSet::new();
+    let mut connectors = Vec::new();
+
+    // Every road end on one side is joined to the nearest road end on the other side
+    for (from_roads, to_roads) in [(roads_a, roads_b), (roads_b, roads_a)] {
+        let targets = border_ends(to_roads, border);
+        let routes = cheapest_routes(&targets, &allowed, &|_| 1.0, &HashMap::new(), 0.0);
+        for end in border_ends(from_roads, border) {
+            // Already continues into the other chunk (directly or over an earlier connector)
+            let joined = |hex: &(i32, i32)| to_roads.contains(hex) || added.contains(hex);
+            if joined(&end) || get_hex_neighbors(end.0, end.1).iter().any(joined) {
+                continue;
+            }
+            let mut current = end;
+            let mut hexes = Vec::new();
+            while let Some(&(previous, _)) = routes.steps.get(&current) {
+                current = previous;
+                if !roads_a.contains(&current) && !roads_b.contains(&current) {
+                    hexes.push(current);
+                }
+            }
+            if routes.costs.get(&current) == Some(&0.0) && current != end {
+                added.extend(hexes.iter().copied());
+                connectors.push(RoadConnector { from: end, to: current, hexes });
+            }
+        }
+    }
+    connectors
+}
+
+/// Join the road networks of two neighboring chunks across their shared border
+///
+/// **Learning Point**: Chunks generated independently each get a road network that
+/// ends somewhere near their edge, so roads stop dead at chunk borders. Stitching
+/// looks at the road ends on (or nearest to) the border on each side and routes the
+/// shortest connector from each to the other side's nearest end, skipping ends that
+/// already continue across, so every road leaving one chunk carries on in the next.
+///
+/// @param roads_a_json - JSON array of the first chunk's road hexes: [{"q":0,"r":0},...]
+/// @param roads_b_json - JSON array of the second chunk's road hexes
+/// @param border_json - JSON array of the hexes along the shared border on both sides
+///   (e.g. the pairs of generate_border_contracts or chunk border rings)
+/// @param valid_terrain_json - JSON array of hexes connectors may use: [{"q":0,"r":0},...]
+/// @returns JSON array of connectors: [{"from":{"q":0,"r":0},"to":{"q":1,"r":0},"hexes":[{"q":0,"r":1},...]},...]
+///   where from is a road end in one chunk, to the road end it joins in the other, and hexes are the new road
+///   hexes from next to `from` up to `to` (existing roads left out); empty if nothing needs joining
+#[wasm_bindgen]
+pub fn stitch_chunk_roads(
+    roads_a_json: String,
+    roads_b_json: String,
+    border_json: String,
+    valid_terrain_json: String,
+) -> String {
+    let roads_a = parse_valid_terrain_json(&roads_a_json);
+    let roads_b = parse_valid_terrain_json(&roads_b_json);
+    let border = parse_valid_terrain_json(&border_json);
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+
+    let hex_json = |(q, r): (i32, i32)| format!(r#"{{"q":{},"r":{}}}"#, q, r);
+    let parts: Vec<String> = stitch_roads(&roads_a, &roads_b, &border, &valid_terrain)
+        .into_iter()
+        .map(|connector| {
+            let hex_parts: Vec<String> = connector.hexes.into_iter().map(hex_json).collect();
+            format!(r#"{{"from":{},"to":{},"hexes":[{}]}}"#, hex_json(connector.from), hex_json(connector.to), hex_parts.join(","))
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Widen road centerlines into corridors (see widen_road_network)
+///
+/// @returns Every corridor hex with its step distance from the centerline (0 = centerline), sorted
+pub fn widen_roads(centerline: &[(i32, i32)], valid_terrain: &HashSet<(i32, i32)>, road_width: i32) -> Vec<((i32, i32), i32)> {
+    let mut traversable = valid_terrain.clone();
+    traversable.extend(centerline.iter().copied());
+    let mut corridor: Vec<((i32, i32), i32)> = reachable_within(centerline, &traversable, road_width.max(0))
+        .into_iter()
+        .collect();
+    corridor.sort();
+    corridor
+}
+
+/// Apply a generator's road width to its centerline (see widen_road_network)
+///
+/// A width of 0 or less keeps the centerline (every offset 0). Without `valid_terrain`
+/// the corridor may grow onto any hex.
+///
+/// @returns Road hexes with their step distance from the centerline, sorted
+pub fn apply_road_width(
+    centerline: &[(i32, i32)],
+    valid_terrain: Option<&HashSet<(i32, i32)>>,
+    road_width: i32,
+) -> Vec<((i32, i32), i32)> {
+    if road_width <= 0 {
+        let mut roads: Vec<((i32, i32), i32)> = centerline.iter().map(|hex| (*hex, 0)).collect();
+        roads.sort();
+        return roads;
+    }
+    match valid_terrain {
+        Some(valid_terrain) => widen_roads(centerline, valid_terrain, road_width),
+        None => {
+            let around = hexes_within_distance(&centerline.iter().copied().collect(), road_width);
+            widen_roads(centerline, &around, road_width)
+        }
+    }
+}
+
+/// JSON fields of a road hex after apply_road_width: just q and r for a one-hex road,
+/// plus "part" and "offset" (as in widen_road_network) when the road was widened
+pub fn road_hex_fields((q, r): (i32, i32), offset: i32, widened: bool) -> String {
+    if !widened {
+        return format!(r#""q":{},"r":{}"#, q, r);
+    }
+    let part = if offset == 0 { "centerline" } else { "edge" };
+    format!(r#""q":{},"r":{},"part":"{}","offset":{}"#, q, r, part, offset)
+}
+
+/// JSON array of road hexes after apply_road_width (see road_hex_fields)
+pub fn widened_roads_json(roads: &[((i32, i32), i32)], widened: bool) -> String {
+    let parts: Vec<String> = roads.iter()
+        .map(|(hex, offset)| format!("{{{}}}", road_hex_fields(*hex, *offset, widened)))
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+/// Expand generated road paths into multi-hex corridors
+///
+/// **Learning Point**: Generators produce one-hex-wide centerlines, which is what
+/// pathing and connectivity need. Avenues and highways look better wider, so the
+/// corridor is grown afterwards: a breadth-first search from the centerline over
+/// valid terrain marks every hex within road_width steps. Growing through valid
+/// terrain only (rather than by plain hex distance) keeps the corridor from jumping
+/// across water or buildings next to the road. Centerline and edge hexes are kept
+/// apart so the renderer can draw lane markings on one and curbs on the other.
+/// The road generators take the same width (roadWidth or road_width) and widen their
+/// own output; this export widens roads from anywhere else.
+///
+/// @param roads_json - JSON array of road centerline hexes: [{"q":0,"r":0},...]
+/// @param valid_terrain_json - JSON array of hexes the corridor may grow into: [{"q":0,"r":0},...]
+/// @param road_width - Hexes of extra roadway on each side of the centerline (0 = unchanged)
+/// @returns JSON array sorted by q, then r: [{"q":0,"r":0,"part":"centerline","offset":0},{"q":1,"r":0,"part":"edge","offset":1},...]
+///   where offset is the distance in steps from the centerline
+#[wasm_bindgen]
+pub fn widen_road_network(roads_json: String, valid_terrain_json: String, road_width: i32) -> String {
+    let mut centerline: Vec<(i32, i32)> = parse_valid_terrain_json(&roads_json).into_iter().collect();
+    centerline.sort();
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+
+    widened_roads_json(&widen_roads(&centerline, &valid_terrain, road_width), true)
+}
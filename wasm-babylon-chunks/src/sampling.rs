@@ -0,0 +1,387 @@
+//! Random hex sampling module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::json::{parse_json_object, JsonValue};
+use crate::errors::{envelope, number_field, parse_object_param, tile_type_param, unknown_handle, ApiError, ErrorCode};
+use crate::rng::{SeededRng, seed_from_coords};
+use crate::hex_utils::{flood_fill, hexes_within_distance, parse_valid_terrain_json, super_hex_center};
+
+/// Spatial filter shared by the sampling functions
+///
+/// Supported filter keys (all optional):
+/// - tileTypes: allowed tile type ids (empty means any type)
+/// - minDistanceFromBuilding: minimum hex distance from any building tile
+/// - reachableFrom: hex that a match must be reachable from by walking
+pub struct HexFilter {
+    allowed_types: Vec<TileType>,
+    near_building: HashSet<(i32, i32)>,
+    reachable: Option<HashSet<(i32, i32)>>,
+}
+
+impl HexFilter {
+    /// Build a filter from its JSON description against the current grid
+    pub fn from_json(filter: &JsonValue, state: &WfcState) -> Self {
+        let allowed_types: Vec<TileType> = filter.get("tileTypes")
+            .map(|value| value.as_tile_types())
+            .unwrap_or_default();
+        let min_building_distance = filter.get("minDistanceFromBuilding")
+            .and_then(|value| value.as_i32())
+            .unwrap_or(0);
+        let reachable_from = filter.get("reachableFrom").and_then(|value| value.as_hex());
+
+        // Mark every hex closer than the minimum distance to a building
+        let near_building: HashSet<(i32, i32)> = if min_building_distance > 0 {
+            let buildings: HashSet<(i32, i32)> = state.grid_tiles()
+                .filter(|(_, tile_type)| *tile_type == TileType::Building)
+                .map(|(hex, _)| hex)
+                .collect();
+            hexes_within_distance(&buildings, min_building_distance - 1)
+        } else {
+            HashSet::new()
+        };
+
+        // Walkable region connected to the reference hex
+        let reachable = reachable_from.map(|start| {
+            flood_fill(start, |(q, r)| state.get_tile(q, r).is_some_and(|tile| tile.is_walkable()))
+        });
+
+        HexFilter {
+            allowed_types,
+            near_building,
+            reachable,
+        }
+    }
+
+    /// Check the filter keys strictly, for the `_result` variants (from_json skips bad values)
+    pub fn check_json(filter: &JsonValue, param: &str) -> Result<(), ApiError> {
+        let invalid = |key: &str, expected: &str| ApiError::new(ErrorCode::InvalidJson, format!("{}.{}: expected {}", param, key, expected));
+        if let Some(value) = filter.get("tileTypes") {
+            let items = value.as_array().ok_or_else(|| invalid("tileTypes", "an array of tile type ids"))?;
+            for item in items {
+                let id = item.as_i32().ok_or_else(|| invalid("tileTypes", "an array of tile type ids"))?;
+                tile_type_param(id)?;
+            }
+        }
+        if filter.get("minDistanceFromBuilding").is_some_and(|value| value.as_i32().is_none()) {
+            return Err(invalid("minDistanceFromBuilding", "an integer"));
+        }
+        if filter.get("reachableFrom").is_some_and(|value| value.as_hex().is_none()) {
+            return Err(invalid("reachableFrom", "{\"q\":integer,\"r\":integer}"));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, hex: (i32, i32), tile_type: TileType) -> bool {
+        (self.allowed_types.is_empty() || self.allowed_types.contains(&tile_type))
+            && !self.near_building.contains(&hex)
+            && self.reachable.as_ref().is_none_or(|set| set.contains(&hex))
+    }
+}
+
+/// Read the optional "seed" key, falling back to a seed derived from the (sorted) candidates
+pub fn seed_or_content(params: &JsonValue, candidates: &[(i32, i32)]) -> u64 {
+    params.get("seed")
+        .and_then(|value| value.as_f64())
+        .map(|seed| seed as u64)
+        .unwrap_or_else(|| seed_from_coords(candidates))
+}
+
+/// Pick a random walkable hex from the current grid that satisfies constraints
+///
+/// **Learning Point**: Spawning logic only needs a single tile, so filtering and
+/// picking inside WASM avoids exporting the whole grid to JS just to choose one.
+/// Constraints use the shared filter keys (tileTypes, minDistanceFromBuilding,
+/// reachableFrom) plus an optional seed; only walkable tiles are ever returned.
+///
+/// @param constraints_json - JSON object: {"tileTypes":[0,3],"minDistanceFromBuilding":3,"reachableFrom":{"q":0,"r":0},"seed":42}
+/// @returns JSON string with the chosen hex: {"q":0,"r":0} or "null" if no hex qualifies
+#[wasm_bindgen]
+pub fn random_walkable_hex(constraints_json: String) -> String {
+    pick_walkable_hex(&parse_json_object(&constraints_json)).unwrap_or_else(|| "null".to_string())
+}
+
+/// random_walkable_hex with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":{"q":0,"r":0},"error":null}, {"ok":null,"error":null} if no hex qualifies,
+///   or {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument" for bad constraints
+#[wasm_bindgen]
+pub fn random_walkable_hex_result(constraints_json: String) -> String {
+    envelope(parse_object_param(&constraints_json, "constraints_json").and_then(|constraints| {
+        HexFilter::check_json(&constraints, "constraints_json")?;
+        number_field(&constraints, "seed", "constraints_json")?;
+        Ok(pick_walkable_hex(&constraints).unwrap_or_else(|| "null".to_string()))
+    }))
+}
+
+/// Pick shared by random_walkable_hex and its `_result` variant
+fn pick_walkable_hex(constraints: &JsonValue) -> Option<String> {
+    let state = WFC_STATE.lock().unwrap();
+    let filter = HexFilter::from_json(constraints, &state);
+
+    let mut candidates: Vec<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile_type)| tile_type.is_walkable() && filter.matches(*hex, *tile_type))
+        .map(|(hex, _)| hex)
+        .collect();
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    // Sort so the pick only depends on the seed, not on hash map iteration order
+    candidates.sort();
+
+    let mut rng = SeededRng::new(seed_or_content(constraints, &candidates));
+    let (q, r) = candidates[rng.next_below(candidates.len())];
+
+    Some(format!(r#"{{"q":{},"r":{}}}"#, q, r))
+}
+
+/// Draw distinct hexes from the current grid with per-tile-type probabilities
+///
+/// **Learning Point**: Uses weighted sampling without replacement (Efraimidis-Spirakis):
+/// each candidate gets the key u^(1/w) for a uniform random u and its type weight w,
+/// and the `count` largest keys win. Higher weights make a hex more likely to be
+/// drawn, and no hex can be drawn twice.
+///
+/// @param count - Number of hexes to draw
+/// @param weights_by_type_json - JSON object mapping tile type id to weight: {"3":8,"0":1} (types without a weight are never drawn)
+/// @param exclusion_json - JSON array of hexes that must not be drawn: [{"q":0,"r":0},...]
+/// @returns JSON array of drawn hexes: [{"q":0,"r":0},...] (fewer than count if not enough candidates)
+#[wasm_bindgen]
+pub fn sample_hexes_weighted(count: i32, weights_by_type_json: String, exclusion_json: String) -> String {
+    let weights_value = parse_json_object(&weights_by_type_json);
+    let mut type_weights: HashMap<TileType, f64> = HashMap::new();
+    for (key, value) in weights_value.as_object().unwrap_or_default() {
+        let tile_type = key.trim().parse::<i32>().ok().and_then(TileType::from_i32);
+        if let (Some(tile_type), Some(weight)) = (tile_type, value.as_f64()) {
+            if weight > 0.0 {
+                type_weights.insert(tile_type, weight);
+            }
+        }
+    }
+    let excluded = parse_valid_terrain_json(&exclusion_json);
+
+    let state = WFC_STATE.lock().unwrap();
+    let mut candidates: Vec<((i32, i32), f64)> = state.grid_tiles()
+        .filter(|(hex, _)| !excluded.contains(hex))
+        .filter_map(|(hex, tile_type)| type_weights.get(&tile_type).map(|weight| (hex, *weight)))
+        .collect();
+    candidates.sort_by_key(|a| a.0);
+
+    let coords: Vec<(i32, i32)> = candidates.iter().map(|(hex, _)| *hex).collect();
+    let mut rng = SeededRng::new(seed_from_coords(&coords));
+
+    // Key each candidate by u^(1/w) and keep the largest keys
+    let mut keyed: Vec<(f64, (i32, i32))> = candidates.iter()
+        .map(|(hex, weight)| (rng.next_f64().powf(1.0 / weight), *hex))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    keyed.truncate(count.max(0) as usize);
+
+    let json_parts: Vec<String> = keyed.iter()
+        .map(|(_, (q, r))| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Pick spatially spread hexes: at most one per super-hex cell
+///
+/// **Learning Point**: The map is tiled by super-hexes of radius `cell_radius`
+/// (the same packing used for chunks). Each cell containing a qualifying hex
+/// contributes one random pick, and a random subset of cells is kept when there
+/// are more cells than requested. This guarantees spread without the cost of
+/// full Poisson-disk sampling.
+///
+/// @param count - Maximum number of hexes to return
+/// @param cell_radius - Radius of each super-hex cell (0 means every hex is its own cell)
+/// @param filter_json - JSON object using the shared filter keys plus optional seed: {"tileTypes":[3],"seed":7}
+/// @returns JSON array of picked hexes: [{"q":0,"r":0},...]
+#[wasm_bindgen]
+pub fn stratified_sample(count: i32, cell_radius: i32, filter_json: String) -> String {
+    let params = parse_json_object(&filter_json);
+    let state = WFC_STATE.lock().unwrap();
+    let filter = HexFilter::from_json(&params, &state);
+
+    let mut candidates: Vec<(i32, i32)> = state.grid_tiles()
+        .filter(|(hex, tile_type)| filter.matches(*hex, *tile_type))
+        .map(|(hex, _)| hex)
+        .collect();
+    candidates.sort();
+
+    // Group candidates by containing cell (BTreeMap keeps cell order deterministic)
+    let radius = cell_radius.max(0);
+    let mut cells: BTreeMap<(i32, i32), Vec<(i32, i32)>> = BTreeMap::new();
+    for &(q, r) in &candidates {
+        cells.entry(super_hex_center(q, r, radius)).or_default().push((q, r));
+    }
+
+    let mut rng = SeededRng::new(seed_or_content(&params, &candidates));
+    let mut picks: Vec<(i32, i32)> = cells.values()
+        .map(|members| members[rng.next_below(members.len())])
+        .collect();
+
+    // Shuffle, then keep the first `count` cells
+    rng.shuffle(&mut picks);
+    picks.truncate(count.max(0) as usize);
+
+    let json_parts: Vec<String> = picks.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Fixed-size uniform sample of a stream of hexes (Algorithm R)
+pub struct Reservoir {
+    capacity: usize,
+    seen: u64,
+    items: Vec<(i32, i32)>,
+    rng: SeededRng,
+}
+
+impl Reservoir {
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Reservoir {
+            capacity,
+            seen: 0,
+            // Grows as candidates arrive: capacity comes from JS and may be far larger than the stream
+            items: Vec::new(),
+            rng: SeededRng::new(seed),
+        }
+    }
+
+    /// Offer one candidate; after n offers every candidate is kept with probability capacity/n
+    pub fn offer(&mut self, hex: (i32, i32)) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(hex);
+        } else {
+            let slot = self.rng.next_below(self.seen as usize);
+            if slot < self.capacity {
+                self.items[slot] = hex;
+            }
+        }
+    }
+
+    /// Uniform sample of up to k of the kept candidates, in random order
+    /// Slots are filled in arrival order, so the kept set is shuffled before truncating.
+    pub fn sample(&mut self, k: usize) -> Vec<(i32, i32)> {
+        let mut picks = self.items.clone();
+        self.rng.shuffle(&mut picks);
+        picks.truncate(k);
+        picks
+    }
+}
+
+/// Registry of open reservoirs keyed by handle (handles start at 1 and are never reused)
+pub struct ReservoirRegistry {
+    reservoirs: HashMap<u32, Reservoir>,
+    next_handle: u32,
+}
+
+impl ReservoirRegistry {
+    pub fn new() -> Self {
+        ReservoirRegistry {
+            reservoirs: HashMap::new(),
+            next_handle: 1,
+        }
+    }
+
+    pub fn insert(&mut self, reservoir: Reservoir) -> u32 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.reservoirs.insert(handle, reservoir);
+        handle
+    }
+
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut Reservoir> {
+        self.reservoirs.get_mut(&handle)
+    }
+
+    pub fn remove(&mut self, handle: u32) -> bool {
+        self.reservoirs.remove(&handle).is_some()
+    }
+}
+
+/// Global reservoir registry (thread-safe)
+pub static RESERVOIRS: LazyLock<Mutex<ReservoirRegistry>> = LazyLock::new(|| Mutex::new(ReservoirRegistry::new()));
+
+/// Open a reservoir for sampling hexes while they are being produced
+///
+/// **Learning Point**: Reservoir sampling (Algorithm R) keeps a uniform random
+/// sample of fixed size from a stream of unknown length using O(capacity) memory:
+/// the n-th candidate replaces a random slot with probability capacity/n. Terrain
+/// iteration can push candidates as it finds them, and the full candidate set is
+/// never materialized or shuffled.
+///
+/// @param capacity - Largest sample that will be requested from this reservoir
+/// @param seed - Random seed
+/// @returns Handle for the reservoir (never 0)
+#[wasm_bindgen]
+pub fn create_reservoir(capacity: u32, seed: u32) -> u32 {
+    RESERVOIRS.lock().unwrap().insert(Reservoir::new(capacity as usize, seed as u64))
+}
+
+/// Offer candidate hexes to a reservoir
+///
+/// @param handle - Handle returned by create_reservoir
+/// @param coords - Int32Array of interleaved coordinates: [q0, r0, q1, r1, ...]
+/// @returns false if the handle is unknown
+#[wasm_bindgen]
+pub fn reservoir_push(handle: u32, coords: &[i32]) -> bool {
+    let mut registry = RESERVOIRS.lock().unwrap();
+    match registry.get_mut(handle) {
+        Some(reservoir) => {
+            for pair in coords.chunks_exact(2) {
+                reservoir.offer((pair[0], pair[1]));
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Draw a uniform sample from everything offered to a reservoir so far
+///
+/// @param handle - Handle returned by create_reservoir
+/// @param k - Sample size (at most the reservoir capacity)
+/// @returns JSON array of sampled hexes: [{"q":0,"r":0},...], or "null" if the handle is unknown
+#[wasm_bindgen]
+pub fn reservoir_sample(handle: u32, k: u32) -> String {
+    draw_reservoir_sample(handle, k).unwrap_or_else(|_| "null".to_string())
+}
+
+/// reservoir_sample with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or
+///   {"ok":null,"error":{"code":"unknown_handle","message":"..."}}
+#[wasm_bindgen]
+pub fn reservoir_sample_result(handle: u32, k: u32) -> String {
+    envelope(draw_reservoir_sample(handle, k))
+}
+
+/// Sample shared by reservoir_sample and its `_result` variant
+fn draw_reservoir_sample(handle: u32, k: u32) -> Result<String, ApiError> {
+    let mut registry = RESERVOIRS.lock().unwrap();
+    let reservoir = registry.get_mut(handle).ok_or_else(|| unknown_handle("reservoir", handle))?;
+
+    let json_parts: Vec<String> = reservoir.sample(k as usize).iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+
+    Ok(format!("[{}]", json_parts.join(",")))
+}
+
+/// Release a reservoir
+///
+/// @param handle - Handle returned by create_reservoir
+/// @returns true if the handle existed
+#[wasm_bindgen]
+pub fn destroy_reservoir(handle: u32) -> bool {
+    RESERVOIRS.lock().unwrap().remove(handle)
+}
@@ -0,0 +1,74 @@
+//! JS callback scripting module
+
+use wasm_bindgen::prelude::*;
+use js_sys::{Array, Function, Int32Array};
+use crate::state::WFC_STATE;
+use crate::types::TileType;
+use crate::json::parse_json_object;
+use crate::sampling::HexFilter;
+
+/// Run a JS function over matching tiles and apply the types it returns
+///
+/// **Learning Point**: Custom post-processing rules usually live in JS, but exporting
+/// the grid, editing it and re-importing it costs three full copies. Here the filter
+/// runs in WASM and the callback receives matching hexes in batches as typed arrays
+/// `(qs, rs, types)`. It returns an array of the same length with the new type per
+/// hex; -1 (or the current type) leaves a hex unchanged, and returning nothing skips
+/// the batch. The grid is not locked while the callback runs, so it may call other
+/// exports. Changes go through the normal grid update (and show up in
+/// poll_tile_changes when tracking is on).
+///
+/// @param filter_json - JSON object using the shared filter keys plus batch size:
+///   {"tileTypes":[0],"minDistanceFromBuilding":2,"reachableFrom":{"q":0,"r":0},"batchSize":4096}
+/// @param callback - JS function (qs: Int32Array, rs: Int32Array, types: Int32Array) => Int32Array | number[] | undefined
+/// @returns Number of tiles changed, or the error thrown by the callback (or an error if it returns anything else)
+#[wasm_bindgen]
+pub fn map_tiles(filter_json: String, callback: &Function) -> Result<u32, JsValue> {
+    let params = parse_json_object(&filter_json);
+    let batch_size = params.get("batchSize")
+        .and_then(|value| value.as_i32())
+        .unwrap_or(4096)
+        .max(1) as usize;
+
+    let mut matches: Vec<((i32, i32), TileType)> = {
+        let state = WFC_STATE.lock().unwrap();
+        let filter = HexFilter::from_json(&params, &state);
+        state.grid_tiles()
+            .filter(|(hex, tile_type)| filter.matches(*hex, *tile_type))
+            .collect()
+    };
+    matches.sort_by_key(|(hex, _)| *hex);
+
+    let mut changed = 0;
+    for batch in matches.chunks(batch_size) {
+        let qs: Vec<i32> = batch.iter().map(|((q, _), _)| *q).collect();
+        let rs: Vec<i32> = batch.iter().map(|((_, r), _)| *r).collect();
+        let types: Vec<i32> = batch.iter().map(|(_, tile_type)| tile_type.id()).collect();
+
+        let result = callback.call3(
+            &JsValue::NULL,
+            &Int32Array::from(qs.as_slice()),
+            &Int32Array::from(rs.as_slice()),
+            &Int32Array::from(types.as_slice()),
+        )?;
+        if result.is_undefined() || result.is_null() {
+            continue;
+        }
+        if !result.is_instance_of::<Int32Array>() && !result.is_instance_of::<Array>() {
+            return Err(JsValue::from_str("map_tiles callback must return an Int32Array, an array or undefined"));
+        }
+        let new_types = Int32Array::new(&result).to_vec();
+
+        let mut state = WFC_STATE.lock().unwrap();
+        for (((q, r), old_type), new_type) in batch.iter().zip(new_types) {
+            if let Some(new_type) = TileType::from_i32(new_type) {
+                if new_type != *old_type {
+                    state.insert_tile(*q, *r, new_type);
+                    changed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(changed)
+}
@@ -0,0 +1,293 @@
+//! JsValue variants of the JSON-string API (enabled by the `serde` feature)
+//!
+//! **Learning Point**: The default build keeps WASM small by passing JSON text and
+//! parsing it with the tiny reader in the json module. With the `serde` feature,
+//! the functions below take and return plain JS objects and arrays instead:
+//! inputs are read through serde-wasm-bindgen into a JsonValue and outputs are
+//! written straight into JS objects, so TypeScript callers never build or parse
+//! JSON strings. Each variant delegates to the string function of the same name,
+//! so both APIs always agree.
+
+use wasm_bindgen::prelude::*;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use crate::json::{parse_json, JsonValue};
+
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(b) => serializer.serialize_bool(*b),
+            JsonValue::Number(n) => serializer.serialize_f64(*n),
+            JsonValue::String(s) => serializer.serialize_str(s),
+            JsonValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a JSON-compatible value")
+    }
+
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(value as f64))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(value as f64))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonValue::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = map.next_entry()? {
+            entries.push((key, value));
+        }
+        Ok(JsonValue::Object(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<JsonValue, D::Error> {
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+/// Read a JS value as JSON text for the string API
+fn to_json_text(value: JsValue) -> Result<String, JsValue> {
+    let json: JsonValue = serde_wasm_bindgen::from_value(value)?;
+    Ok(json.to_string())
+}
+
+/// Turn a string API result into plain JS objects and arrays ("null" becomes null)
+fn from_json_text(text: &str) -> Result<JsValue, JsValue> {
+    let json = parse_json(text).ok_or_else(|| JsValue::from_str("internal error: result is not valid JSON"))?;
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(json.serialize(&serializer)?)
+}
+
+/// JsValue variant of hex_astar
+///
+/// @param valid_terrain - Array of valid terrain coordinates: [{q: 0, r: 0}, ...]
+/// @param penalties - Optional array of soft avoidance costs: [{q: 0, r: 0, value: 5}, ...]
+/// @returns Array of path coordinates, or null if no path found
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_js(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain: JsValue,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties: JsValue,
+) -> Result<JsValue, JsValue> {
+    let penalties = to_json_text(penalties)?;
+    let result = crate::astar::hex_astar(start_q, start_r, goal_q, goal_r, to_json_text(valid_terrain)?, profile, max_cost, Some(penalties));
+    from_json_text(&result)
+}
+
+/// JsValue variant of hex_astar_partial
+///
+/// @returns {path: [{q, r}, ...], complete: boolean}, or null if the start is not valid terrain
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn hex_astar_partial_js(
+    start_q: i32,
+    start_r: i32,
+    goal_q: i32,
+    goal_r: i32,
+    valid_terrain: JsValue,
+    profile: Option<String>,
+    max_cost: Option<f64>,
+    penalties: JsValue,
+) -> Result<JsValue, JsValue> {
+    let penalties = to_json_text(penalties)?;
+    let result = crate::astar::hex_astar_partial(start_q, start_r, goal_q, goal_r, to_json_text(valid_terrain)?, profile, max_cost, Some(penalties));
+    from_json_text(&result)
+}
+
+/// JsValue variant of build_path_between_roads
+///
+/// @returns Array of path coordinates excluding start, including end, or null if no path found
+#[wasm_bindgen]
+pub fn build_path_between_roads_js(
+    start_q: i32,
+    start_r: i32,
+    end_q: i32,
+    end_r: i32,
+    valid_terrain: JsValue,
+    profile: Option<String>,
+    turn_penalty: Option<f64>,
+) -> Result<JsValue, JsValue> {
+    let result = crate::astar::build_path_between_roads(start_q, start_r, end_q, end_r, to_json_text(valid_terrain)?, profile, turn_penalty);
+    from_json_text(&result)
+}
+
+/// JsValue variant of generate_road_network_growing_tree
+///
+/// @param frontier - Optional strategy name or weights object: "newest", {newest: 3, random: 1}, ...
+/// @param road_width - Optional extra roadway per side (see generate_road_network_growing_tree)
+/// @returns Array of road coordinates
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn generate_road_network_growing_tree_js(
+    seeds: JsValue,
+    valid_terrain: JsValue,
+    occupied: JsValue,
+    target_count: i32,
+    loop_probability: Option<f64>,
+    max_loop_length: Option<i32>,
+    turn_penalty: Option<f64>,
+    frontier: JsValue,
+    road_width: Option<i32>,
+) -> Result<JsValue, JsValue> {
+    // A strategy name, or an object of weights
+    let frontier = match frontier.as_string() {
+        Some(name) => Some(name),
+        None if frontier.is_undefined() || frontier.is_null() => None,
+        None => Some(to_json_text(frontier)?),
+    };
+    let result = crate::roads::generate_road_network_growing_tree(
+        to_json_text(seeds)?,
+        to_json_text(valid_terrain)?,
+        to_json_text(occupied)?,
+        target_count,
+        loop_probability,
+        max_loop_length,
+        turn_penalty,
+        frontier,
+        road_width,
+    );
+    from_json_text(&result)
+}
+
+/// JsValue variant of get_valid_terrain_near_roads
+///
+/// @returns Array of nearby valid terrain coordinates
+#[wasm_bindgen]
+pub fn get_valid_terrain_near_roads_js(
+    road_network: JsValue,
+    valid_terrain: JsValue,
+    occupied: JsValue,
+    max_distance: i32,
+) -> Result<JsValue, JsValue> {
+    let result = crate::utils::get_valid_terrain_near_roads(
+        to_json_text(road_network)?,
+        to_json_text(valid_terrain)?,
+        to_json_text(occupied)?,
+        max_distance,
+    );
+    from_json_text(&result)
+}
+
+/// JsValue variant of generate_building_placement
+///
+/// @param building_rules - Rules object, same keys as the JSON version
+/// @returns Array of building positions: [{q, r, door: {q, r} | null}, ...]
+#[wasm_bindgen]
+pub fn generate_building_placement_js(
+    valid_terrain: JsValue,
+    road_network: JsValue,
+    occupied: JsValue,
+    building_rules: JsValue,
+    target_count: i32,
+) -> Result<JsValue, JsValue> {
+    let result = crate::utils::generate_building_placement(
+        to_json_text(valid_terrain)?,
+        to_json_text(road_network)?,
+        to_json_text(occupied)?,
+        to_json_text(building_rules)?,
+        target_count,
+    );
+    from_json_text(&result)
+}
+
+/// JsValue variant of batch_get_tile_types
+///
+/// @returns Array of {q, r, tileType} for the hexes that have a tile
+#[wasm_bindgen]
+pub fn batch_get_tile_types_js(hex_coords: JsValue) -> Result<JsValue, JsValue> {
+    let result = crate::utils::batch_get_tile_types(to_json_text(hex_coords)?);
+    from_json_text(&result)
+}
+
+/// JsValue variant of get_stats
+///
+/// @returns Object with tile counts per type
+#[wasm_bindgen]
+pub fn get_stats_js() -> Result<JsValue, JsValue> {
+    from_json_text(&crate::layout::get_stats())
+}
+
+/// JsValue variant of query
+///
+/// @returns Array of matching hexes, sorted by (q, r)
+#[wasm_bindgen]
+pub fn query_js(expression: &str) -> Result<JsValue, JsValue> {
+    from_json_text(&crate::query::query(expression)?)
+}
+
+/// JsValue variant of find_pattern
+///
+/// @param pattern_tiles - Array of pattern cells: [{q, r, tileType}, ...]
+/// @returns Array of matches: [{q, r, rotation}, ...]
+#[wasm_bindgen]
+pub fn find_pattern_js(pattern_tiles: JsValue, allow_rotation: bool) -> Result<JsValue, JsValue> {
+    let result = crate::pattern::find_pattern(to_json_text(pattern_tiles)?, allow_rotation);
+    from_json_text(&result)
+}
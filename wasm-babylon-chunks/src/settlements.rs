@@ -0,0 +1,207 @@
+//! Settlement planning module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::utils::{front_door_hex, door_json};
+use crate::json::{parse_json, parse_json_object, escape_json_string};
+use crate::rng::{SeededRng, seed_from_coords};
+use crate::hex_utils::{parse_valid_terrain_json, get_hex_neighbors, hex_distance};
+
+/// Shortest path over terrain from a hex to the nearest hex of a road network
+///
+/// A breadth-first search from the hex stops at the first road hex it reaches, so
+/// a near road cut off by impassable terrain is passed over for a farther one that
+/// can be reached.
+///
+/// @returns Path from the hex to the road (both included), or None if no road can be reached
+fn path_to_network(from: (i32, i32), roads: &HashSet<(i32, i32)>, terrain: &HashSet<(i32, i32)>) -> Option<Vec<(i32, i32)>> {
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    came_from.insert(from, from);
+    while let Some(current) = queue.pop_front() {
+        if roads.contains(&current) {
+            let mut path = vec![current];
+            let mut hex = current;
+            while hex != from {
+                hex = came_from[&hex];
+                path.push(hex);
+            }
+            return Some(path);
+        }
+        for neighbor in get_hex_neighbors(current.0, current.1) {
+            if terrain.contains(&neighbor) && !came_from.contains_key(&neighbor) {
+                came_from.insert(neighbor, current);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    None
+}
+
+/// Size class of a settlement cluster
+struct SizeClass {
+    name: String,
+    buildings: i32,
+    radius: i32,
+}
+
+/// Default size classes: hamlets, villages and towns
+fn default_size_classes() -> Vec<SizeClass> {
+    vec![
+        SizeClass { name: "hamlet".to_string(), buildings: 3, radius: 2 },
+        SizeClass { name: "village".to_string(), buildings: 8, radius: 3 },
+        SizeClass { name: "town".to_string(), buildings: 20, radius: 5 },
+    ]
+}
+
+/// Parse size classes, overriding or extending the defaults
+/// Format: {"village":{"buildings":10,"radius":4},"city":{"buildings":40,"radius":8}}
+fn parse_size_classes(size_classes_json: &str) -> Vec<SizeClass> {
+    let mut size_classes = default_size_classes();
+    let overrides = parse_json_object(size_classes_json);
+    for (name, settings) in overrides.as_object().unwrap_or_default() {
+        let existing = size_classes.iter().position(|class| &class.name == name);
+        let defaults = existing.map(|index| (size_classes[index].buildings, size_classes[index].radius));
+        let (default_buildings, default_radius) = defaults.unwrap_or((1, 1));
+        let class = SizeClass {
+            name: name.clone(),
+            buildings: settings.get("buildings").and_then(|v| v.as_i32()).unwrap_or(default_buildings),
+            radius: settings.get("radius").and_then(|v| v.as_i32()).unwrap_or(default_radius),
+        };
+        match existing {
+            Some(index) => size_classes[index] = class,
+            None => size_classes.push(class),
+        }
+    }
+    size_classes
+}
+
+/// Capitalize the first letter of a size class name for generated settlement names
+fn title_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Plan clustered settlements (hamlets, villages, towns) at chosen anchor points
+/// 
+/// **Learning Point**: Instead of spreading buildings evenly along every road, each
+/// anchor becomes the center of a named cluster. Anchors are linked into a road tree
+/// (each anchor connects to the closest road already built that a path over valid
+/// terrain reaches, like the seed phase of the growing tree generator), then each cluster fills its radius with buildings,
+/// preferring hexes that touch a road and lie close to the center. An anchor that
+/// no road can reach is reported with "connected":false and gets no road hex.
+/// 
+/// @param anchors_json - JSON array of anchors: [{"q":0,"r":0,"sizeClass":"village","name":"Oakford"},...] (name optional, sizeClass defaults to "hamlet")
+/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
+/// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
+/// @param size_classes_json - JSON object overriding/adding size classes: {"town":{"buildings":25,"radius":6}} (empty for defaults)
+/// @returns JSON object: {"settlements":[{"name":"Oakford","sizeClass":"village","center":{"q":0,"r":0},"connected":true,"buildings":[{"q":1,"r":0,"door":{"q":0,"r":0}},...]},...],"roads":[{"q":0,"r":0},...]}
+#[wasm_bindgen]
+pub fn plan_settlements(
+    anchors_json: String,
+    valid_terrain_json: String,
+    occupied_json: String,
+    size_classes_json: String,
+) -> String {
+    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
+    let occupied = parse_valid_terrain_json(&occupied_json);
+    let size_classes = parse_size_classes(&size_classes_json);
+    
+    let terrain: HashSet<(i32, i32)> = valid_terrain.difference(&occupied).cloned().collect();
+    
+    // Parse anchors, skipping anchors outside usable terrain
+    let mut anchors: Vec<((i32, i32), &SizeClass, String)> = Vec::new();
+    if let Some(items) = parse_json(&anchors_json).as_ref().and_then(|v| v.as_array()) {
+        for item in items {
+            let Some(center) = item.as_hex() else { continue };
+            if !terrain.contains(&center) {
+                continue;
+            }
+            let class_name = item.get("sizeClass").and_then(|v| v.as_str()).unwrap_or("hamlet");
+            let Some(class) = size_classes.iter().find(|class| class.name == class_name) else { continue };
+            let name = item.get("name")
+                .and_then(|v| v.as_str())
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("{} {}", title_case(&class.name), anchors.len() + 1));
+            anchors.push((center, class, name));
+        }
+    }
+    
+    // Connect cluster centers: each anchor links to the closest reachable road already built.
+    // The first anchor starts the network; an anchor no path reaches stays off it.
+    let mut roads: HashSet<(i32, i32)> = HashSet::new();
+    let mut connected: Vec<bool> = Vec::with_capacity(anchors.len());
+    for (center, _, _) in &anchors {
+        let linked = if roads.is_empty() {
+            roads.insert(*center);
+            true
+        } else {
+            match path_to_network(*center, &roads, &terrain) {
+                Some(path) => {
+                    roads.extend(path);
+                    true
+                }
+                None => false,
+            }
+        };
+        connected.push(linked);
+    }
+    
+    let anchor_coords: Vec<(i32, i32)> = anchors.iter().map(|(center, _, _)| *center).collect();
+    let mut rng = SeededRng::new(seed_from_coords(&anchor_coords));
+    
+    // Fill each cluster with buildings around its center
+    let mut taken: HashSet<(i32, i32)> = HashSet::new();
+    let mut settlement_parts = Vec::new();
+    for ((center, class, name), linked) in anchors.iter().zip(&connected) {
+        let mut candidates: Vec<(i32, i32)> = terrain.iter()
+            .filter(|hex| !roads.contains(*hex) && !taken.contains(*hex))
+            .filter(|hex| hex_distance(hex.0, hex.1, center.0, center.1) <= class.radius)
+            .cloned()
+            .collect();
+        candidates.sort();
+        
+        // Rank: touching a road first, then closer to the center, random tie-break
+        let mut ranked: Vec<(bool, i32, u64, (i32, i32))> = candidates.into_iter()
+            .map(|hex| {
+                let touches_road = get_hex_neighbors(hex.0, hex.1).iter().any(|n| roads.contains(n));
+                (!touches_road, hex_distance(hex.0, hex.1, center.0, center.1), rng.next_u64() >> 33, hex)
+            })
+            .collect();
+        ranked.sort();
+        
+        let mut building_parts = Vec::new();
+        for (_, _, _, hex) in ranked.into_iter().take(class.buildings.max(0) as usize) {
+            taken.insert(hex);
+            building_parts.push(format!(
+                r#"{{"q":{},"r":{},"door":{}}}"#,
+                hex.0, hex.1, door_json(front_door_hex(hex.0, hex.1, &roads))
+            ));
+        }
+        
+        settlement_parts.push(format!(
+            r#"{{"name":"{}","sizeClass":"{}","center":{{"q":{},"r":{}}},"connected":{},"buildings":[{}]}}"#,
+            escape_json_string(name),
+            escape_json_string(&class.name),
+            center.0,
+            center.1,
+            linked,
+            building_parts.join(",")
+        ));
+    }
+    
+    let mut road_vec: Vec<(i32, i32)> = roads.into_iter().collect();
+    road_vec.sort();
+    let road_parts: Vec<String> = road_vec.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    
+    format!(
+        r#"{{"settlements":[{}],"roads":[{}]}}"#,
+        settlement_parts.join(","),
+        road_parts.join(",")
+    )
+}
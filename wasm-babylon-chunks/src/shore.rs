@@ -0,0 +1,79 @@
+//! Shore annotation layer module
+//!
+//! **Learning Point**: Beaches are a rendering concern, not a tile type: a shore
+//! hex is still grass or forest for pathfinding, WFC rules and stats. Keeping the
+//! classification in a separate metadata layer lets the renderer pick sand
+//! materials (and orient them toward the water) without changing the map.
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use crate::hex_utils::CUBE_DIRECTIONS;
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+
+/// Global shore layer: water-neighbor bitmask per shore hex (thread-safe)
+pub static SHORES: LazyLock<Mutex<HashMap<(i32, i32), u8>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Land hexes of the grid that touch water, with a bitmask of the water sides
+///
+/// Bit i is set when the neighbor in CUBE_DIRECTIONS[i] is a Water tile.
+pub fn shore_masks(state: &WfcState) -> HashMap<(i32, i32), u8> {
+    let mut shores = HashMap::new();
+    for ((q, r), tile) in state.grid_tiles() {
+        if tile == TileType::Water {
+            continue;
+        }
+        let mut mask = 0u8;
+        for (direction, offset) in CUBE_DIRECTIONS.iter().enumerate() {
+            if state.get_tile(q + offset.q, r + offset.r) == Some(TileType::Water) {
+                mask |= 1 << direction;
+            }
+        }
+        if mask != 0 {
+            shores.insert((q, r), mask);
+        }
+    }
+    shores
+}
+
+/// Mark land hexes next to water as shore in the shore layer
+///
+/// Reads the generated grid; run again after the grid changes. The tiles themselves
+/// are not modified.
+///
+/// @returns Number of shore hexes (the layer is replaced)
+#[wasm_bindgen]
+pub fn annotate_shores() -> u32 {
+    let shores = shore_masks(&WFC_STATE.lock().unwrap());
+    let mut layer = SHORES.lock().unwrap();
+    *layer = shores;
+    layer.len() as u32
+}
+
+/// Get the shore classification of a hex
+///
+/// @returns Bitmask of the water sides (bit i = neighbor in direction i, see CUBE_DIRECTIONS),
+///   or 0 if the hex is not shore
+#[wasm_bindgen]
+pub fn get_shore_mask(q: i32, r: i32) -> u32 {
+    SHORES.lock().unwrap().get(&(q, r)).copied().unwrap_or(0) as u32
+}
+
+/// Export the whole shore layer
+///
+/// @returns Int32Array with three values per shore hex, sorted by (q, r): [q0, r0, mask0, q1, r1, mask1, ...]
+#[wasm_bindgen]
+pub fn get_shore_layer() -> Vec<i32> {
+    let shores = SHORES.lock().unwrap();
+    let mut hexes: Vec<(i32, i32)> = shores.keys().cloned().collect();
+    hexes.sort();
+
+    let mut values = Vec::with_capacity(hexes.len() * 3);
+    for hex in hexes {
+        values.push(hex.0);
+        values.push(hex.1);
+        values.push(shores[&hex] as i32);
+    }
+    values
+}
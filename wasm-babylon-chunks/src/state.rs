@@ -2,7 +2,169 @@
 
 use std::sync::{LazyLock, Mutex};
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use crate::types::TileType;
+use crate::tiles::tile_weights;
+use crate::layout::WfcRun;
+
+/// Which map of tiles a change happened in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(i32)]
+pub enum TileLayer {
+    /// Generated grid
+    Grid = 0,
+    /// Pre-constraints set by JS
+    Constraint = 1,
+}
+
+impl TileLayer {
+    pub fn name(self) -> &'static str {
+        match self {
+            TileLayer::Grid => "grid",
+            TileLayer::Constraint => "constraint",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<TileLayer> {
+        match name {
+            "grid" => Some(TileLayer::Grid),
+            "constraint" => Some(TileLayer::Constraint),
+            _ => None,
+        }
+    }
+}
+
+/// One tile mutation (None means no tile)
+#[derive(Clone, Copy, Debug)]
+pub struct TileChange {
+    pub layer: TileLayer,
+    pub hex: (i32, i32),
+    pub old: Option<TileType>,
+    pub new: Option<TileType>,
+}
+
+/// Pending tile changes, coalesced per (layer, hex) as (first old type, latest new type)
+pub type ChangeLog = HashMap<(TileLayer, (i32, i32)), (Option<TileType>, Option<TileType>)>;
+
+/// A count limit, either absolute or as a share of the generated area
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuotaBound {
+    Count(usize),
+    Percent(f64),
+}
+
+impl QuotaBound {
+    /// Smallest count meeting this bound as a minimum over `area` cells
+    pub fn min_count(self, area: usize) -> usize {
+        match self {
+            QuotaBound::Count(count) => count,
+            QuotaBound::Percent(percent) => (percent / 100.0 * area as f64).ceil() as usize,
+        }
+    }
+    
+    /// Largest count meeting this bound as a maximum over `area` cells
+    pub fn max_count(self, area: usize) -> usize {
+        match self {
+            QuotaBound::Count(count) => count,
+            QuotaBound::Percent(percent) => (percent / 100.0 * area as f64).floor() as usize,
+        }
+    }
+}
+
+/// Minimum and/or maximum number of tiles of one type (see set_tile_quotas)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileQuota {
+    pub tile: TileType,
+    pub min: Option<QuotaBound>,
+    pub max: Option<QuotaBound>,
+}
+
+/// Adjacency rules and weight overrides used by the WFC solver
+/// 
+/// **Learning Point**: Both tables are indexed by tile id. A cell's set of still
+/// possible tiles (its "domain") is a bitmask with bit i meaning tile id i, so
+/// "which tiles may sit next to anything in this domain" is the OR of the
+/// adjacency masks of its bits, and narrowing a neighbor is a single AND.
+#[derive(Clone, Debug)]
+pub struct WfcRules {
+    /// Bitmask of tile ids allowed next to each tile id (kept symmetric)
+    pub adjacency: Vec<u64>,
+    /// Collapse weight per tile id for this map (None = the tile type's registered weight)
+    pub weights: Vec<Option<f64>>,
+    /// Learned P(neighbor | tile), indexed tile * TileType::COUNT + neighbor (see learn_rules_from_example)
+    pub neighbor_likelihood: Option<Vec<f64>>,
+    /// Tile count limits for generated areas
+    pub quotas: Vec<TileQuota>,
+}
+
+impl WfcRules {
+    /// Built-in rules: water stays away from buildings, roads and paths;
+    /// registered tile types may sit next to anything
+    pub fn default_rules() -> Self {
+        let mut rules = WfcRules {
+            adjacency: vec![u64::MAX; TileType::COUNT],
+            weights: vec![None; TileType::COUNT],
+            neighbor_likelihood: None,
+            quotas: Vec::new(),
+        };
+        for a in TileType::ALL {
+            for b in TileType::ALL {
+                let forbidden = matches!(
+                    (a, b),
+                    (TileType::Water, TileType::Building | TileType::Road | TileType::Path)
+                        | (TileType::Building | TileType::Road | TileType::Path, TileType::Water)
+                );
+                if forbidden {
+                    rules.adjacency[a.index()] &= !(1 << b.index());
+                }
+            }
+        }
+        rules
+    }
+    
+    /// Allow or forbid two tile types next to each other (in both directions)
+    pub fn set_allowed(&mut self, a: TileType, b: TileType, allowed: bool) {
+        if allowed {
+            self.adjacency[a.index()] |= 1 << b.index();
+            self.adjacency[b.index()] |= 1 << a.index();
+        } else {
+            self.adjacency[a.index()] &= !(1 << b.index());
+            self.adjacency[b.index()] &= !(1 << a.index());
+        }
+    }
+    
+    /// Whether two tile types may be neighbors
+    pub fn allows(&self, a: TileType, b: TileType) -> bool {
+        self.adjacency[a.index()] & (1 << b.index()) != 0
+    }
+    
+    /// Collapse weight per tile id: this map's overrides on top of the registered weights
+    pub fn effective_weights(&self) -> Vec<f64> {
+        tile_weights()
+            .into_iter()
+            .zip(&self.weights)
+            .map(|(registered, weight)| weight.unwrap_or(registered))
+            .collect()
+    }
+    
+    /// Bitmask of the tile ids allowed next to at least one tile of a domain
+    pub fn allowed_next_to(&self, domain: u64) -> u64 {
+        let mut allowed = 0;
+        let mut bits = domain;
+        while bits != 0 {
+            let id = bits.trailing_zeros() as usize;
+            allowed |= self.adjacency.get(id).copied().unwrap_or(0);
+            bits &= bits - 1;
+        }
+        allowed
+    }
+}
+
+impl Default for WfcRules {
+    fn default() -> Self {
+        Self::default_rules()
+    }
+}
 
 /// State structure using hash map for efficient sparse grid storage
 /// 
@@ -11,6 +173,12 @@ use crate::types::TileType;
 pub struct WfcState {
     grid: HashMap<(i32, i32), TileType>,
     pre_constraints: HashMap<(i32, i32), TileType>,
+    /// Change log, present only while change tracking is enabled
+    changes: Option<ChangeLog>,
+    /// Rules for the WFC solver
+    rules: WfcRules,
+    /// Step-wise WFC run in progress (see begin_layout_wfc)
+    wfc_run: Option<WfcRun>,
 }
 
 impl WfcState {
@@ -18,39 +186,147 @@ impl WfcState {
         WfcState {
             grid: HashMap::new(),
             pre_constraints: HashMap::new(),
+            changes: None,
+            rules: WfcRules::default_rules(),
+            wfc_run: None,
         }
     }
     
     pub fn clear(&mut self) {
+        if self.changes.is_some() {
+            let removed: Vec<((i32, i32), TileType)> = self.grid_tiles().collect();
+            for (hex, tile_type) in removed {
+                self.record_change(TileLayer::Grid, hex, Some(tile_type), None);
+            }
+        }
         self.grid.clear();
         // DO NOT clear pre_constraints - they must persist
+        // A step-wise WFC run would keep writing into the emptied grid
+        self.wfc_run = None;
     }
     
     /// Set a pre-constraint at a specific hex position (q, r)
     /// Returns true if the constraint was set successfully
     pub fn set_pre_constraint(&mut self, q: i32, r: i32, tile_type: TileType) -> bool {
-        self.pre_constraints.insert((q, r), tile_type);
+        let old = self.pre_constraints.insert((q, r), tile_type);
+        self.record_change(TileLayer::Constraint, (q, r), old, Some(tile_type));
         true
     }
     
     /// Clear all pre-constraints
     pub fn clear_pre_constraints(&mut self) {
+        if self.changes.is_some() {
+            let removed: Vec<((i32, i32), TileType)> = self.pre_constraints().collect();
+            for (hex, tile_type) in removed {
+                self.record_change(TileLayer::Constraint, hex, Some(tile_type), None);
+            }
+        }
         self.pre_constraints.clear();
     }
     
+    /// Start or stop recording tile changes (stopping discards pending changes)
+    pub fn set_change_tracking(&mut self, enabled: bool) {
+        match (enabled, self.changes.is_some()) {
+            (true, false) => self.changes = Some(HashMap::new()),
+            (false, true) => self.changes = None,
+            _ => {}
+        }
+    }
+    
+    /// Take the pending changes, dropping hexes that ended up unchanged
+    /// Returns changes sorted by layer and coordinates
+    pub fn take_changes(&mut self) -> Vec<TileChange> {
+        let log = match self.changes.as_mut() {
+            Some(log) => std::mem::take(log),
+            None => return Vec::new(),
+        };
+        let mut changes: Vec<TileChange> = log.into_iter()
+            .filter(|(_, (old, new))| old != new)
+            .map(|((layer, hex), (old, new))| TileChange { layer, hex, old, new })
+            .collect();
+        changes.sort_by_key(|change| (change.layer, change.hex));
+        changes
+    }
+    
+    /// Record one tile change, keeping the first old value per (layer, hex)
+    fn record_change(&mut self, layer: TileLayer, hex: (i32, i32), old: Option<TileType>, new: Option<TileType>) {
+        if let Some(log) = self.changes.as_mut() {
+            match log.entry((layer, hex)) {
+                Entry::Occupied(mut entry) => entry.get_mut().1 = new,
+                Entry::Vacant(entry) => {
+                    entry.insert((old, new));
+                }
+            }
+        }
+    }
+    
     /// Get tile at hex coordinate (q, r)
     pub fn get_tile(&self, q: i32, r: i32) -> Option<TileType> {
         self.grid.get(&(q, r)).copied()
     }
     
+    /// Get the pre-constraint at hex coordinate (q, r)
+    pub fn get_pre_constraint(&self, q: i32, r: i32) -> Option<TileType> {
+        self.pre_constraints.get(&(q, r)).copied()
+    }
+    
+    /// WFC solver rules
+    pub fn rules(&self) -> &WfcRules {
+        &self.rules
+    }
+
+    
+    /// WFC solver rules, for editing
+    pub fn rules_mut(&mut self) -> &mut WfcRules {
+        &mut self.rules
+    }
+    
     /// Get pre-constraints iterator
     pub fn pre_constraints(&self) -> impl Iterator<Item = ((i32, i32), TileType)> + '_ {
         self.pre_constraints.iter().map(|((q, r), tile_type)| ((*q, *r), *tile_type))
     }
     
+    /// Step-wise WFC run in progress
+    pub fn wfc_run(&self) -> Option<&WfcRun> {
+        self.wfc_run.as_ref()
+    }
+    
+    /// Take the step-wise WFC run out of the state (to step it while editing the grid)
+    pub fn take_wfc_run(&mut self) -> Option<WfcRun> {
+        self.wfc_run.take()
+    }
+    
+    /// Store (or, with None, cancel) the step-wise WFC run
+    pub fn set_wfc_run(&mut self, run: Option<WfcRun>) {
+        self.wfc_run = run;
+    }
+    
+    /// Remove a tile from the grid
+    pub fn remove_tile(&mut self, q: i32, r: i32) {
+        if let Some(old) = self.grid.remove(&(q, r)) {
+            self.record_change(TileLayer::Grid, (q, r), Some(old), None);
+        }
+    }
+    
     /// Insert tile into grid
     pub fn insert_tile(&mut self, q: i32, r: i32, tile_type: TileType) {
-        self.grid.insert((q, r), tile_type);
+        let old = self.grid.insert((q, r), tile_type);
+        self.record_change(TileLayer::Grid, (q, r), old, Some(tile_type));
+    }
+    
+    /// Write a tile to the grid or as a pre-constraint
+    pub fn set_layer_tile(&mut self, layer: TileLayer, q: i32, r: i32, tile_type: TileType) {
+        match layer {
+            TileLayer::Grid => self.insert_tile(q, r, tile_type),
+            TileLayer::Constraint => {
+                self.set_pre_constraint(q, r, tile_type);
+            }
+        }
+    }
+    
+    /// Get grid entries iterator ((q, r), tile type)
+    pub fn grid_tiles(&self) -> impl Iterator<Item = ((i32, i32), TileType)> + '_ {
+        self.grid.iter().map(|((q, r), tile_type)| ((*q, *r), *tile_type))
     }
     
     /// Get grid values iterator
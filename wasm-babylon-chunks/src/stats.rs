@@ -0,0 +1,201 @@
+//! Map composition statistics and QA checks module
+
+use wasm_bindgen::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use crate::state::{WfcState, WFC_STATE};
+use crate::types::TileType;
+use crate::json::{escape_json_string, parse_json_object, JsonValue};
+use crate::hex_utils::get_hex_neighbors;
+use crate::tiles::registered_tiles;
+
+/// Tile counts per type for a map
+#[derive(Clone, Copy, Debug)]
+pub struct TileCounts {
+    counts: [usize; TileType::COUNT],
+}
+
+impl Default for TileCounts {
+    fn default() -> Self {
+        TileCounts { counts: [0; TileType::COUNT] }
+    }
+}
+
+impl TileCounts {
+    /// Count tiles in the current grid
+    pub fn from_state(state: &WfcState) -> Self {
+        let mut counts = [0usize; TileType::COUNT];
+        for tile_type in state.grid_values() {
+            counts[tile_type.index()] += 1;
+        }
+        TileCounts { counts }
+    }
+
+    /// Read counts from a get_stats JSON object (missing keys count as 0)
+    pub fn from_json(stats: &JsonValue) -> Self {
+        let mut counts = [0usize; TileType::COUNT];
+        for tile in registered_tiles() {
+            counts[tile.index()] = stats.get(&tile.name())
+                .and_then(|value| value.as_f64())
+                .map(|count| count.max(0.0) as usize)
+                .unwrap_or(0);
+        }
+        TileCounts { counts }
+    }
+
+    pub fn add(&mut self, tile: TileType) {
+        self.counts[tile.index()] += 1;
+    }
+
+    pub fn remove(&mut self, tile: TileType) {
+        self.counts[tile.index()] = self.counts[tile.index()].saturating_sub(1);
+    }
+
+    pub fn count(&self, tile: TileType) -> usize {
+        self.counts[tile.index()]
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Share of the map covered by a tile type, in percent (0 for an empty map)
+    pub fn percent(&self, tile: TileType) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.count(tile) as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// Percentage of land tiles (everything but water) that can be reached on foot from a road
+///
+/// Walkable tiles are flooded from every road at once; a building counts as reached
+/// when it touches a reached tile (its door). Returns 100 for a map without land.
+pub fn land_reachable_percent(state: &WfcState) -> f64 {
+    let mut reached: HashSet<(i32, i32)> = HashSet::new();
+    let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
+    for (hex, tile_type) in state.grid_tiles() {
+        if tile_type == TileType::Road {
+            reached.insert(hex);
+            queue.push_back(hex);
+        }
+    }
+    while let Some((q, r)) = queue.pop_front() {
+        for neighbor in get_hex_neighbors(q, r) {
+            let walkable = state.get_tile(neighbor.0, neighbor.1).is_some_and(|tile| tile.is_walkable());
+            if walkable && reached.insert(neighbor) {
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    let mut land = 0;
+    let mut reachable = 0;
+    for ((q, r), tile_type) in state.grid_tiles() {
+        match tile_type {
+            TileType::Water => {}
+            TileType::Building => {
+                land += 1;
+                if get_hex_neighbors(q, r).iter().any(|neighbor| reached.contains(neighbor)) {
+                    reachable += 1;
+                }
+            }
+            _ => {
+                land += 1;
+                if reached.contains(&(q, r)) {
+                    reachable += 1;
+                }
+            }
+        }
+    }
+
+    match land {
+        0 => 100.0,
+        land => reachable as f64 * 100.0 / land as f64,
+    }
+}
+
+/// Compare the composition of two maps
+///
+/// **Learning Point**: Maps of different sizes are compared by share of the map
+/// (percent per tile type) rather than raw counts, so a regenerated map at a new
+/// radius can still be checked against a reference map. Every tile type in the
+/// registry is compared, including custom tiles added with register_tile_type.
+///
+/// @param stats_a_json - Reference stats in get_stats format: {"grass":X,"building":Y,...}
+/// @param stats_b_json - Stats to check, same format
+/// @param tolerances_json - Allowed difference in percentage points per type, plus an optional default: {"water":2,"default":5}
+/// @returns JSON object: {"pass":true,"deltas":[{"type":"grass","a":40.5,"b":42.0,"delta":1.5,"tolerance":5,"pass":true},...]}
+#[wasm_bindgen]
+pub fn compare_stats(stats_a_json: String, stats_b_json: String, tolerances_json: String) -> String {
+    let stats_a = TileCounts::from_json(&parse_json_object(&stats_a_json));
+    let stats_b = TileCounts::from_json(&parse_json_object(&stats_b_json));
+    let tolerances = parse_json_object(&tolerances_json);
+    let default_tolerance = tolerances.get("default").and_then(|value| value.as_f64()).unwrap_or(5.0);
+
+    let mut all_pass = true;
+    let mut delta_parts = Vec::new();
+    for tile in registered_tiles() {
+        let a = stats_a.percent(tile);
+        let b = stats_b.percent(tile);
+        let delta = b - a;
+        let tolerance = tolerances.get(&tile.name()).and_then(|value| value.as_f64()).unwrap_or(default_tolerance);
+        let pass = delta.abs() <= tolerance;
+        all_pass &= pass;
+        delta_parts.push(format!(
+            r#"{{"type":"{}","a":{:.2},"b":{:.2},"delta":{:.2},"tolerance":{},"pass":{}}}"#,
+            escape_json_string(&tile.name()), a, b, delta, tolerance, pass
+        ));
+    }
+
+    format!(r#"{{"pass":{},"deltas":[{}]}}"#, all_pass, delta_parts.join(","))
+}
+
+/// Check the current grid against composition targets
+///
+/// **Learning Point**: Each target is a percentage range. Metrics are the tile type
+/// names (share of the whole map) and "landReachable" (share of land tiles reachable
+/// on foot from a road). The delta is how far the value lies outside its range,
+/// signed (negative = below min, positive = above max, 0 = within range), so QA
+/// tooling can tell which way a failing map needs to move.
+///
+/// @param targets_json - JSON object of ranges: {"water":{"min":10,"max":20},"landReachable":{"min":95}}
+/// @returns JSON object: {"pass":false,"results":[{"metric":"water","value":8.2,"min":10,"max":20,"delta":-1.8,"pass":false},...]}
+///   unknown metrics are reported with "value":null and fail
+#[wasm_bindgen]
+pub fn check_targets(targets_json: String) -> String {
+    let targets = parse_json_object(&targets_json);
+    let state = WFC_STATE.lock().unwrap();
+    let counts = TileCounts::from_state(&state);
+
+    let mut all_pass = true;
+    let mut result_parts = Vec::new();
+    for (metric, range) in targets.as_object().unwrap_or_default() {
+        let min = range.get("min").and_then(|value| value.as_f64());
+        let max = range.get("max").and_then(|value| value.as_f64());
+        let value = match metric.as_str() {
+            "landReachable" => Some(land_reachable_percent(&state)),
+            name => TileType::from_name(name).map(|tile| counts.percent(tile)),
+        };
+
+        let bound_json = |bound: Option<f64>| bound.map(|b| b.to_string()).unwrap_or_else(|| "null".to_string());
+        let (value_json, delta_json, pass) = match value {
+            Some(value) => {
+                let delta = match (min, max) {
+                    (Some(min), _) if value < min => value - min,
+                    (_, Some(max)) if value > max => value - max,
+                    _ => 0.0,
+                };
+                (format!("{:.2}", value), format!("{:.2}", delta), delta == 0.0)
+            }
+            None => ("null".to_string(), "null".to_string(), false),
+        };
+        all_pass &= pass;
+        result_parts.push(format!(
+            r#"{{"metric":"{}","value":{},"min":{},"max":{},"delta":{},"pass":{}}}"#,
+            escape_json_string(metric), value_json, bound_json(min), bound_json(max), delta_json, pass
+        ));
+    }
+
+    format!(r#"{{"pass":{},"results":[{}]}}"#, all_pass, result_parts.join(","))
+}
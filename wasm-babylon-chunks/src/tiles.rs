@@ -0,0 +1,208 @@
+//! Runtime-registered tile types
+//!
+//! **Learning Point**: The six built-in tile types cover the demo, but games want
+//! their own terrain (sand, mountain, swamp, ...). Ids 6-63 can be registered at
+//! runtime with a name, a WFC weight and a walkable flag; from then on they are
+//! ordinary tile types: they can be pre-constrained, stored in the grid, counted
+//! by get_stats, queried by name, and picked by the WFC solver.
+
+use wasm_bindgen::prelude::*;
+use std::sync::{LazyLock, Mutex};
+use crate::types::TileType;
+use crate::json::{escape_json_string, JsonValue};
+use crate::errors::{envelope, parse_json_param, ApiError, ErrorCode};
+
+/// Description of a tile type
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileInfo {
+    pub name: String,
+    /// Relative WFC collapse weight (0 = never picked unless pre-constrained)
+    pub weight: f64,
+    pub walkable: bool,
+}
+
+/// Tile descriptions indexed by tile id (None = id not registered)
+pub struct TileRegistry {
+    tiles: Vec<Option<TileInfo>>,
+}
+
+impl TileRegistry {
+    /// Registry holding only the built-in types
+    pub fn new() -> Self {
+        let mut tiles = vec![None; TileType::COUNT];
+        let builtin_weights = [6.0, 1.0, 1.0, 3.0, 2.0, 0.5];
+        for (tile, weight) in TileType::ALL.into_iter().zip(builtin_weights) {
+            tiles[tile.index()] = Some(TileInfo {
+                name: tile.name(),
+                weight,
+                walkable: tile.is_walkable(),
+            });
+        }
+        TileRegistry { tiles }
+    }
+
+    pub fn get(&self, id: i32) -> Option<&TileInfo> {
+        usize::try_from(id).ok().and_then(|index| self.tiles.get(index)).and_then(|info| info.as_ref())
+    }
+
+    /// Check that a custom tile could be registered under an id
+    pub fn validate(&self, id: i32, info: &TileInfo) -> Result<(), String> {
+        if id < TileType::ALL.len() as i32 || id >= TileType::COUNT as i32 {
+            return Err(format!("tile id {} is outside the custom range {}-{}", id, TileType::ALL.len(), TileType::COUNT - 1));
+        }
+        if info.name.is_empty() || matches!(info.name.as_str(), "total" | "weights") {
+            return Err(format!("invalid tile name \"{}\"", info.name));
+        }
+        if !info.weight.is_finite() || info.weight < 0.0 {
+            return Err(format!("invalid weight {} for tile \"{}\"", info.weight, info.name));
+        }
+        let taken = self.tiles.iter()
+            .enumerate()
+            .any(|(other, existing)| other as i32 != id && existing.as_ref().is_some_and(|e| e.name == info.name));
+        if taken {
+            return Err(format!("tile name \"{}\" is already used", info.name));
+        }
+        Ok(())
+    }
+
+    /// Register (or re-register) a custom tile
+    pub fn register(&mut self, id: i32, info: TileInfo) -> Result<(), String> {
+        self.validate(id, &info)?;
+        self.tiles[id as usize] = Some(info);
+        Ok(())
+    }
+
+    /// Registered tile types with their descriptions, in id order
+    pub fn entries(&self) -> impl Iterator<Item = (TileType, &TileInfo)> + '_ {
+        self.tiles.iter()
+            .enumerate()
+            .filter_map(|(index, info)| info.as_ref().map(|info| (TileType::from_index(index), info)))
+    }
+}
+
+impl Default for TileRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global tile registry (thread-safe)
+///
+/// Never lock another global while holding this one; TileType::from_i32 locks it
+/// while callers may hold WFC_STATE.
+pub static TILE_REGISTRY: LazyLock<Mutex<TileRegistry>> = LazyLock::new(|| Mutex::new(TileRegistry::new()));
+
+/// Whether a tile id is built-in or registered
+pub fn is_registered(id: i32) -> bool {
+    TILE_REGISTRY.lock().unwrap().get(id).is_some()
+}
+
+/// Description of a tile type (None if not registered)
+pub fn tile_info(tile: TileType) -> Option<TileInfo> {
+    TILE_REGISTRY.lock().unwrap().get(tile.id()).cloned()
+}
+
+/// Registered custom tile type with the given name
+pub fn find_by_name(name: &str) -> Option<TileType> {
+    TILE_REGISTRY.lock().unwrap()
+        .entries()
+        .find(|(_, info)| info.name == name)
+        .map(|(tile, _)| tile)
+}
+
+/// All registered tile types (built-in first), in id order
+pub fn registered_tiles() -> Vec<TileType> {
+    TILE_REGISTRY.lock().unwrap().entries().map(|(tile, _)| tile).collect()
+}
+
+/// WFC weight per tile id (0 for unregistered ids), indexed like TileType::index
+pub fn tile_weights() -> Vec<f64> {
+    let registry = TILE_REGISTRY.lock().unwrap();
+    (0..TileType::COUNT as i32)
+        .map(|id| registry.get(id).map(|info| info.weight).unwrap_or(0.0))
+        .collect()
+}
+
+/// Register a custom tile type
+///
+/// **Learning Point**: Registering the same id again replaces its name and
+/// weight, so tile sets can be tuned live. Tiles already placed keep their id.
+///
+/// @param id - Tile id, 6-63 (0-5 are the built-in types)
+/// @param name - Unique lowercase name, used as its get_stats key and in queries ("total" and "weights" are reserved)
+/// @param weight - Relative WFC collapse weight (0 = only placed by pre-constraints)
+/// @returns true if registered, false if the id, name or weight is invalid
+#[wasm_bindgen]
+pub fn register_tile_type(id: i32, name: String, weight: f64) -> bool {
+    let info = TileInfo { name, weight, walkable: true };
+    TILE_REGISTRY.lock().unwrap().register(id, info).is_ok()
+}
+
+/// Register several custom tile types at once
+///
+/// Either every entry is registered or (if any entry is invalid) none is.
+///
+/// @param tiles_json - JSON array: [{"id":6,"name":"sand","weight":2,"walkable":true},...]
+///                     (weight defaults to 1, walkable to true)
+/// @returns Number of tile types registered, or -1 if the JSON or any entry is invalid
+#[wasm_bindgen]
+pub fn register_tile_set(tiles_json: String) -> i32 {
+    register_tiles(&tiles_json).unwrap_or(-1)
+}
+
+/// register_tile_set with a structured result (see the errors module)
+///
+/// @returns JSON envelope: {"ok":2,"error":null} with the number registered, or {"ok":null,"error":{"code","message"}}
+///   with code "invalid_json" (malformed entry) or "invalid_argument" (bad id, name or weight), naming the entry
+#[wasm_bindgen]
+pub fn register_tile_set_result(tiles_json: String) -> String {
+    envelope(register_tiles(&tiles_json).map(|count| count.to_string()))
+}
+
+/// Registration shared by register_tile_set and its `_result` variant
+fn register_tiles(tiles_json: &str) -> Result<i32, ApiError> {
+    let value = parse_json_param(tiles_json, "tiles_json")?;
+    let items = value.as_array()
+        .ok_or_else(|| ApiError::new(ErrorCode::InvalidJson, "tiles_json: expected an array"))?;
+
+    let mut entries = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let (Some(id), Some(name)) = (item.get("id").and_then(JsonValue::as_i32), item.get("name").and_then(JsonValue::as_str)) else {
+            return Err(ApiError::new(
+                ErrorCode::InvalidJson,
+                format!("tiles_json[{}]: expected {{\"id\":integer,\"name\":string}}", index),
+            ));
+        };
+        let info = TileInfo {
+            name: name.to_string(),
+            weight: item.get("weight").and_then(JsonValue::as_f64).unwrap_or(1.0),
+            walkable: !matches!(item.get("walkable"), Some(JsonValue::Bool(false))),
+        };
+        entries.push((id, info));
+    }
+
+    let mut registry = TILE_REGISTRY.lock().unwrap();
+    // Validate against a copy so duplicate names inside the set are caught too
+    let mut staged = TileRegistry { tiles: registry.tiles.clone() };
+    for (index, (id, info)) in entries.iter().enumerate() {
+        staged.register(*id, info.clone())
+            .map_err(|message| ApiError::new(ErrorCode::InvalidArgument, format!("tiles_json[{}]: {}", index, message)))?;
+    }
+    *registry = staged;
+    Ok(entries.len() as i32)
+}
+
+/// List all tile types
+///
+/// @returns JSON array in id order: [{"id":0,"name":"grass","weight":6,"walkable":true,"builtin":true},...]
+#[wasm_bindgen]
+pub fn get_tile_types() -> String {
+    let registry = TILE_REGISTRY.lock().unwrap();
+    let parts: Vec<String> = registry.entries()
+        .map(|(tile, info)| format!(
+            r#"{{"id":{},"name":"{}","weight":{},"walkable":{},"builtin":{}}}"#,
+            tile.id(), escape_json_string(&info.name), info.weight, info.walkable, tile.is_builtin()
+        ))
+        .collect();
+    format!("[{}]", parts.join(","))
+}
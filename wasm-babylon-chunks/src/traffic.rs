@@ -0,0 +1,93 @@
+//! Foot traffic accumulation and emergent trails module
+
+use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use crate::state::WFC_STATE;
+use crate::types::TileType;
+use crate::hex_utils::parse_path_json;
+
+/// Accumulated usage per hex
+pub struct TrafficLayer {
+    usage: HashMap<(i32, i32), f64>,
+    /// Usage at which a grass hex wears into a path
+    threshold: f64,
+}
+
+impl TrafficLayer {
+    pub fn new() -> Self {
+        TrafficLayer {
+            usage: HashMap::new(),
+            threshold: 10.0,
+        }
+    }
+
+    pub fn usage(&self, hex: (i32, i32)) -> f64 {
+        self.usage.get(&hex).copied().unwrap_or(0.0)
+    }
+}
+
+impl Default for TrafficLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global traffic layer (thread-safe)
+pub static TRAFFIC: LazyLock<Mutex<TrafficLayer>> = LazyLock::new(|| Mutex::new(TrafficLayer::new()));
+
+/// Set how much traffic wears a grass hex into a path
+///
+/// @param threshold - Accumulated usage at which grass becomes Path (default 10)
+#[wasm_bindgen]
+pub fn set_path_threshold(threshold: f64) {
+    TRAFFIC.lock().unwrap().threshold = threshold.max(0.0);
+}
+
+/// Record a path walked by an agent and wear trails where traffic is heavy
+///
+/// **Learning Point**: Every walked hex accumulates usage. Once a grass hex's usage
+/// reaches the threshold it becomes a Path tile. Nobody plans these trails: they
+/// appear between whatever destinations agents actually travel between, and the
+/// most popular shortcuts wear in first. Only grass wears, so roads, buildings,
+/// forest and water are never overwritten; traffic on them is still counted.
+///
+/// @param path_json - JSON array of hexes walked: [{"q":0,"r":0},...] (e.g. an A* result)
+/// @param weight - Usage added per hex visit (1 for a single walker)
+/// @returns JSON array of hexes that turned into Path tiles on this call: [{"q":0,"r":0},...]
+#[wasm_bindgen]
+pub fn record_agent_path(path_json: String, weight: f64) -> String {
+    let path = parse_path_json(&path_json);
+    let mut traffic = TRAFFIC.lock().unwrap();
+    let mut state = WFC_STATE.lock().unwrap();
+
+    let mut worn = Vec::new();
+    for hex in path {
+        let usage = traffic.usage.entry(hex).or_insert(0.0);
+        *usage += weight;
+        if *usage >= traffic.threshold && state.get_tile(hex.0, hex.1) == Some(TileType::Grass) {
+            state.insert_tile(hex.0, hex.1, TileType::Path);
+            worn.push(hex);
+        }
+    }
+
+    let json_parts: Vec<String> = worn.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Get accumulated traffic at a hex
+///
+/// @returns Usage recorded at the hex (0 if never walked)
+#[wasm_bindgen]
+pub fn get_traffic(q: i32, r: i32) -> f64 {
+    TRAFFIC.lock().unwrap().usage((q, r))
+}
+
+/// Forget all recorded traffic (worn paths stay in the grid)
+#[wasm_bindgen]
+pub fn clear_traffic() {
+    TRAFFIC.lock().unwrap().usage.clear();
+}
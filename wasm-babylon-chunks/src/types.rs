@@ -1,17 +1,103 @@
 /// Core type definitions for the WASM module
 
-/// Tile type enumeration for 5 simple tile types
+/// Tile type: one of the built-in terrain types or a tile registered at runtime
 /// 
-/// **Learning Point**: Simplified tile types for hex grid layout generation.
-/// Each tile type represents a terrain or structure type.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-#[repr(i32)]
-pub enum TileType {
-    Grass = 0,
-    Building = 1,
-    Road = 2,
-    Forest = 3,
-    Water = 4,
+/// **Learning Point**: Tile types used to be a closed enum, so adding sand or
+/// swamp meant recompiling. TileType is now a small id wrapper: ids 0-5 are the
+/// built-in types below (associated constants, still usable in match patterns
+/// like `TileType::Water => ...`), and ids 6-63 become valid once registered
+/// with register_tile_type (see the tiles module).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileType(u8);
+
+#[allow(non_upper_case_globals)]
+impl TileType {
+    pub const Grass: TileType = TileType(0);
+    pub const Building: TileType = TileType(1);
+    pub const Road: TileType = TileType(2);
+    pub const Forest: TileType = TileType(3);
+    pub const Water: TileType = TileType(4);
+    /// Trail worn into grass by repeated foot traffic
+    pub const Path: TileType = TileType(5);
+
+    /// Every built-in tile type, in id order
+    pub const ALL: [TileType; 6] = [
+        TileType::Grass,
+        TileType::Building,
+        TileType::Road,
+        TileType::Forest,
+        TileType::Water,
+        TileType::Path,
+    ];
+
+    /// Number of possible tile ids (built-in and registered): ids are 0..COUNT
+    pub const COUNT: usize = 64;
+
+    /// Tile id as used across the JS boundary
+    pub fn id(self) -> i32 {
+        self.0 as i32
+    }
+
+    /// Tile id as an index into per-tile tables of length COUNT
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Tile type for an id without checking that it is registered (id must be below COUNT)
+    pub(crate) fn from_index(index: usize) -> TileType {
+        debug_assert!(index < TileType::COUNT);
+        TileType(index as u8)
+    }
+
+    /// Convert an i32 tile id (as used across the JS boundary) to a TileType
+    /// Returns None for ids that are neither built-in (0-5) nor registered
+    pub fn from_i32(value: i32) -> Option<TileType> {
+        match value {
+            0..=5 => Some(TileType(value as u8)),
+            _ if crate::tiles::is_registered(value) => Some(TileType(value as u8)),
+            _ => None,
+        }
+    }
+
+    /// Whether this is one of the built-in types (ids 0-5)
+    pub fn is_builtin(self) -> bool {
+        self.index() < TileType::ALL.len()
+    }
+
+    /// Whether characters can walk on this tile (buildings and water block movement;
+    /// registered tiles say so when they are registered)
+    pub fn is_walkable(self) -> bool {
+        match self {
+            TileType::Grass | TileType::Road | TileType::Forest | TileType::Path => true,
+            TileType::Building | TileType::Water => false,
+            _ => crate::tiles::tile_info(self).is_some_and(|info| info.walkable),
+        }
+    }
+
+    /// Lowercase name, matching the keys used by get_stats
+    pub fn name(self) -> String {
+        let builtin = match self {
+            TileType::Grass => "grass",
+            TileType::Building => "building",
+            TileType::Road => "road",
+            TileType::Forest => "forest",
+            TileType::Water => "water",
+            TileType::Path => "path",
+            _ => {
+                return crate::tiles::tile_info(self)
+                    .map(|info| info.name)
+                    .unwrap_or_else(|| format!("tile{}", self.0));
+            }
+        };
+        builtin.to_string()
+    }
+
+    /// Look up a tile type by its get_stats name
+    pub fn from_name(name: &str) -> Option<TileType> {
+        TileType::ALL.into_iter()
+            .find(|tile| tile.name() == name)
+            .or_else(|| crate::tiles::find_by_name(name))
+    }
 }
 
 /// Hex coordinate structure for Voronoi generation
@@ -79,3 +165,28 @@ impl PartialOrd for AStarNode {
     }
 }
 
+
+/// Node for cost-weighted A* (fractional step costs)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightedNode {
+    pub q: i32,
+    pub r: i32,
+    pub g: f64,
+    pub f: f64,
+}
+
+impl Eq for WeightedNode {}
+
+impl Ord for WeightedNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reverse order for min-heap (lowest f score first), prefer deeper nodes on ties
+        other.f.total_cmp(&self.f)
+            .then_with(|| self.g.total_cmp(&other.g))
+    }
+}
+
+impl PartialOrd for WeightedNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
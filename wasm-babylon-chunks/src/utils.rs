@@ -1,9 +1,17 @@
 /// Utility functions module
 
 use wasm_bindgen::prelude::*;
-use std::collections::HashSet;
-use crate::state::WFC_STATE;
-use crate::hex_utils::{parse_valid_terrain_json, get_hex_neighbors};
+use std::collections::{HashMap, HashSet};
+use crate::state::{TileLayer, WfcState, WFC_STATE};
+use crate::json::{parse_json, parse_json_object, JsonValue};
+use crate::types::TileType;
+use crate::errors::{envelope, parse_object_param, tile_type_param, ApiError, ErrorCode};
+use crate::rng::{content_rng, seed_from_coords, SeededRng};
+use crate::hex_utils::{axial_to_cube, cube_ring, parse_valid_terrain_json, parse_path_json, get_hex_neighbors, hexes_within_distance, hex_distance_field, hex_distance, hex_to_world, hexes_in_world_rect, hex_set_from_flat, hex_list_from_flat, hexes_to_flat};
+
+/// World RNG streams (see set_seed), so shuffles and placement never share a sequence
+const WORLD_STREAM_SHUFFLE: u64 = 1;
+const WORLD_STREAM_BUILDINGS: u64 = 2;
 
 /// Batch query tile types for multiple hex coordinates
 /// Returns JSON array with tile types: [{"q":0,"r":0,"tileType":1},...]
@@ -22,7 +30,7 @@ pub fn batch_get_tile_types(hex_coords_json: String) -> String {
         if let Some(tile) = state.get_tile(q, r) {
             json_parts.push(format!(
                 r#"{{"q":{},"r":{},"tileType":{}}}"#,
-                q, r, tile as i32
+                q, r, tile.id()
             ));
         }
     }
@@ -30,6 +38,75 @@ pub fn batch_get_tile_types(hex_coords_json: String) -> String {
     format!("[{}]", json_parts.join(","))
 }
 
+/// Batch query tile types from a flat coordinate buffer
+/// 
+/// @param hex_coords - Int32Array of interleaved coordinates: [q0, r0, q1, r1, ...]
+/// @returns Int32Array with one tile type per input hex, in input order (-1 for empty hexes)
+#[wasm_bindgen]
+pub fn batch_get_tile_types_buf(hex_coords: &[i32]) -> Vec<i32> {
+    let state = WFC_STATE.lock().unwrap();
+    hex_list_from_flat(hex_coords)
+        .into_iter()
+        .map(|(q, r)| state.get_tile(q, r).map(|tile| tile.id()).unwrap_or(-1))
+        .collect()
+}
+
+/// Nearest grid hex of a tile type within max_radius of an origin
+/// Searched ring by ring outward; ties within a ring go to the lowest (r, q).
+/// 
+/// @returns The hex and its distance, or None if there is none in range
+pub fn nearest_tile_of_type(state: &WfcState, origin: (i32, i32), tile: TileType, max_radius: i32) -> Option<((i32, i32), i32)> {
+    let center = axial_to_cube(origin.0, origin.1);
+    (0..=max_radius).find_map(|radius| {
+        cube_ring(center, radius)
+            .into_iter()
+            .map(|cube| (cube.q, cube.r))
+            .filter(|&(q, r)| state.get_tile(q, r) == Some(tile))
+            .min_by_key(|&(q, r)| (r, q))
+            .map(|hex| (hex, radius))
+    })
+}
+
+/// Find the closest grid tile of a type
+/// 
+/// **Learning Point**: "Nearest water" or "nearest road" only needs the rings
+/// around the origin, checked from the inside out; the first ring holding a match
+/// gives the answer, so the rest of the grid never has to be exported to JS.
+/// 
+/// @param q - Origin q coordinate (axial)
+/// @param r - Origin r coordinate (axial)
+/// @param tile_type - Tile type to look for
+/// @param max_radius - Largest hex distance to search
+/// @returns JSON object {"q":3,"r":-1,"distance":2}, or "null" if none is within max_radius (or the tile type is invalid)
+#[wasm_bindgen]
+pub fn find_nearest_tile_of_type(q: i32, r: i32, tile_type: i32, max_radius: i32) -> String {
+    let Some(tile) = TileType::from_i32(tile_type) else {
+        return "null".to_string();
+    };
+    let state = WFC_STATE.lock().unwrap();
+    nearest_tile_json(nearest_tile_of_type(&state, (q, r), tile, max_radius))
+}
+
+/// find_nearest_tile_of_type with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":{"q":3,"r":-1,"distance":2},"error":null}, {"ok":null,"error":null} if none
+///   is within max_radius, or {"ok":null,"error":{"code":"invalid_argument","message":"..."}} for an unknown tile type
+#[wasm_bindgen]
+pub fn find_nearest_tile_of_type_result(q: i32, r: i32, tile_type: i32, max_radius: i32) -> String {
+    envelope(tile_type_param(tile_type).map(|tile| {
+        let state = WFC_STATE.lock().unwrap();
+        nearest_tile_json(nearest_tile_of_type(&state, (q, r), tile, max_radius))
+    }))
+}
+
+/// Format a nearest_tile_of_type result
+pub fn nearest_tile_json(nearest: Option<((i32, i32), i32)>) -> String {
+    match nearest {
+        Some(((q, r), distance)) => format!(r#"{{"q":{},"r":{},"distance":{}}}"#, q, r, distance),
+        None => "null".to_string(),
+    }
+}
+
 /// Shuffle array in WASM using Fisher-Yates algorithm
 /// Returns shuffled JSON array
 /// 
@@ -99,23 +176,11 @@ pub fn shuffle_array(array_json: String) -> String {
         i += 1;
     }
     
-    // Fisher-Yates shuffle using a simple PRNG
+    // Fisher-Yates shuffle with PCG32 (world-seeded if set_seed was called)
     // Use a deterministic seed based on array content for reproducibility
-    let mut seed: u64 = 0;
-    for (q, r) in &coords {
-        seed = seed.wrapping_mul(31).wrapping_add((*q as u64).wrapping_mul(17).wrapping_add(*r as u64));
-    }
+    let seed = seed_from_coords(&coords);
     
-    let mut rng_state = seed;
-    let mut rng = || {
-        rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-        rng_state
-    };
-    
-    for i in (1..coords.len()).rev() {
-        let j = (rng() % (i as u64 + 1)) as usize;
-        coords.swap(i, j);
-    }
+    content_rng(seed, WORLD_STREAM_SHUFFLE).shuffle(&mut coords);
     
     // Convert back to JSON
     let mut json_parts = Vec::new();
@@ -126,6 +191,58 @@ pub fn shuffle_array(array_json: String) -> String {
     format!("[{}]", json_parts.join(","))
 }
 
+/// Shuffle a JSON array of arbitrary elements with an explicit seed
+/// 
+/// **Learning Point**: shuffle_array only understands {q,r} objects and derives its
+/// seed from the content, so the same input always comes back in the same order.
+/// Taking the seed as a parameter lets callers get a different (but reproducible)
+/// order per run, and elements can be any JSON value (objects, strings, numbers).
+/// 
+/// @param array_json - JSON array to shuffle: [{"id":"a"},"b",3,...]
+/// @param seed - Random seed
+/// @returns Shuffled JSON array, or "[]" if the input is not an array
+#[wasm_bindgen]
+pub fn shuffle_json_array(array_json: String, seed: u32) -> String {
+    let mut items = parse_json_array(&array_json);
+    SeededRng::new(seed as u64).shuffle(&mut items);
+    JsonValue::Array(items).to_string()
+}
+
+/// Shuffled index permutation 0..count, for shuffling data that stays on the JS side
+/// 
+/// @param count - Number of indices
+/// @param seed - Random seed
+/// @returns Uint32Array with a permutation of 0..count
+#[wasm_bindgen]
+pub fn shuffle_indices(count: u32, seed: u32) -> Vec<u32> {
+    let mut indices: Vec<u32> = (0..count).collect();
+    SeededRng::new(seed as u64).shuffle(&mut indices);
+    indices
+}
+
+/// Draw k distinct elements from a JSON array
+/// 
+/// **Learning Point**: Uses a partial Fisher-Yates shuffle: only the first k
+/// positions are drawn, so picking a few items from a large array costs O(k)
+/// swaps instead of shuffling everything.
+/// 
+/// @param array_json - JSON array to sample from (any element type)
+/// @param k - Number of elements to draw (all elements if k exceeds the length)
+/// @param seed - Random seed
+/// @returns JSON array of the drawn elements in draw order
+#[wasm_bindgen]
+pub fn sample_without_replacement(array_json: String, k: u32, seed: u32) -> String {
+    let mut items = parse_json_array(&array_json);
+    let k = (k as usize).min(items.len());
+    let mut rng = SeededRng::new(seed as u64);
+    for i in 0..k {
+        let j = i + rng.next_below(items.len() - i);
+        items.swap(i, j);
+    }
+    items.truncate(k);
+    JsonValue::Array(items).to_string()
+}
+
 /// Count adjacent roads for a given hex coordinate
 /// 
 /// @param hex_q - Hex q coordinate
@@ -149,6 +266,66 @@ pub fn count_adjacent_roads(hex_q: i32, hex_r: i32, road_network_json: String) -
     count
 }
 
+/// Count adjacent roads for many hex coordinates in one call
+/// Parses the road network once and shares it across all queries
+/// 
+/// @param hex_coords_json - JSON array of hexes to score: [{"q":0,"r":0},...]
+/// @param road_network_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @returns JSON array with counts in input order: [{"q":0,"r":0,"count":2},...]
+#[wasm_bindgen]
+pub fn count_adjacent_roads_batch(hex_coords_json: String, road_network_json: String) -> String {
+    let hex_coords = parse_path_json(&hex_coords_json);
+    let roads_set = parse_valid_terrain_json(&road_network_json);
+    
+    let mut json_parts = Vec::new();
+    for (q, r) in hex_coords {
+        let count = get_hex_neighbors(q, r)
+            .iter()
+            .filter(|neighbor| roads_set.contains(neighbor))
+            .count();
+        json_parts.push(format!(r#"{{"q":{},"r":{},"count":{}}}"#, q, r, count));
+    }
+    
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Count adjacent roads for many hexes from flat coordinate buffers
+/// 
+/// @param hex_coords - Int32Array of interleaved hexes to score: [q0, r0, q1, r1, ...]
+/// @param road_network - Int32Array of interleaved road coordinates
+/// @returns Int32Array with one count (0-6) per input hex, in input order
+#[wasm_bindgen]
+pub fn count_adjacent_roads_batch_buf(hex_coords: &[i32], road_network: &[i32]) -> Vec<i32> {
+    let roads_set = hex_set_from_flat(road_network);
+    hex_list_from_flat(hex_coords)
+        .into_iter()
+        .map(|(q, r)| {
+            get_hex_neighbors(q, r)
+                .iter()
+                .filter(|neighbor| roads_set.contains(neighbor))
+                .count() as i32
+        })
+        .collect()
+}
+
+/// Collect valid, unoccupied, non-road terrain within max_distance hexes of any road
+/// Distance is geometric hex distance, not path distance through terrain.
+/// Returns sorted coordinates.
+fn valid_terrain_near_roads(
+    roads: &HashSet<(i32, i32)>,
+    valid_terrain: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
+    max_distance: i32,
+) -> Vec<(i32, i32)> {
+    let mut near_hexes: Vec<(i32, i32)> = hexes_within_distance(roads, max_distance)
+        .into_iter()
+        .filter(|hex| !roads.contains(hex) && !occupied.contains(hex) && valid_terrain.contains(hex))
+        .collect();
+    
+    near_hexes.sort();
+    near_hexes
+}
+
 /// Get all valid terrain hexes adjacent to existing roads
 /// Returns array of hex coordinates that are:
 /// - Adjacent to at least one road in the network
@@ -165,59 +342,90 @@ pub fn get_adjacent_valid_terrain(
     valid_terrain_json: String,
     occupied_json: String,
 ) -> String {
-    let roads = parse_valid_terrain_json(&road_network_json);
-    let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
-    let occupied = parse_valid_terrain_json(&occupied_json);
-    
-    let roads_set: HashSet<(i32, i32)> = roads.iter().cloned().collect();
-    let valid_terrain_set: HashSet<(i32, i32)> = valid_terrain.iter().cloned().collect();
-    let occupied_set: HashSet<(i32, i32)> = occupied.iter().cloned().collect();
-    
-    let mut adjacent_hexes: HashSet<(i32, i32)> = HashSet::new();
+    get_valid_terrain_near_roads(road_network_json, valid_terrain_json, occupied_json, 1)
+}
+
+/// Get all valid terrain hexes within max_distance of existing roads
+/// K-distance variant of get_adjacent_valid_terrain (max_distance = 1 gives the same result).
+/// Returns array of hex coordinates that are:
+/// - Within max_distance hexes of at least one road in the network
+/// - On valid terrain (in valid_terrain_json)
+/// - Not a road and not already occupied
+/// 
+/// @param road_network_json - JSON array of road coordinates: [{"q":0,"r":0},...]
+/// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
+/// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
+/// @param max_distance - Maximum hex distance from the nearest road
+/// @returns JSON array of nearby valid terrain: [{"q":0,"r":0},...]
+#[wasm_bindgen]
+pub fn get_valid_terrain_near_roads(
+    road_network_json: String,
+    valid_terrain_json: String,
+    occupied_json: String,
+    max_distance: i32,
+) -> String {
+    let roads_set = parse_valid_terrain_json(&road_network_json);
+    let valid_terrain_set = parse_valid_terrain_json(&valid_terrain_json);
+    let occupied_set = parse_valid_terrain_json(&occupied_json);
     
-    // For each road, find its neighbors
-    for (road_q, road_r) in roads {
-        let neighbors = get_hex_neighbors(road_q, road_r);
-        for (nq, nr) in neighbors {
-            let neighbor_key = (nq, nr);
-            
-            // Skip if already a road
-            if roads_set.contains(&neighbor_key) {
-                continue;
-            }
-            
-            // Skip if occupied
-            if occupied_set.contains(&neighbor_key) {
-                continue;
-            }
-            
-            // Check if this neighbor is in valid terrain
-            if valid_terrain_set.contains(&neighbor_key) {
-                adjacent_hexes.insert(neighbor_key);
-            }
-        }
-    }
+    let near_hexes = valid_terrain_near_roads(&roads_set, &valid_terrain_set, &occupied_set, max_distance);
     
     // Convert to JSON
-    let mut adjacent_vec: Vec<(i32, i32)> = adjacent_hexes.iter().cloned().collect();
-    adjacent_vec.sort();
-    
     let mut json_parts = Vec::new();
-    for (q, r) in adjacent_vec {
+    for (q, r) in near_hexes {
         json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
     }
     
     format!("[{}]", json_parts.join(","))
 }
 
+/// Get valid terrain within max_distance of roads from flat coordinate buffers
+/// Same result as get_valid_terrain_near_roads without building and parsing JSON.
+/// 
+/// @param road_network - Int32Array of interleaved road coordinates: [q0, r0, q1, r1, ...]
+/// @param valid_terrain - Int32Array of interleaved valid terrain coordinates
+/// @param occupied - Int32Array of interleaved occupied coordinates
+/// @param max_distance - Maximum hex distance from the nearest road
+/// @returns Int32Array of interleaved nearby valid terrain coordinates, sorted
+#[wasm_bindgen]
+pub fn get_valid_terrain_near_roads_buf(
+    road_network: &[i32],
+    valid_terrain: &[i32],
+    occupied: &[i32],
+    max_distance: i32,
+) -> Vec<i32> {
+    let near_hexes = valid_terrain_near_roads(
+        &hex_set_from_flat(road_network),
+        &hex_set_from_flat(valid_terrain),
+        &hex_set_from_flat(occupied),
+        max_distance,
+    );
+    hexes_to_flat(&near_hexes)
+}
+
 /// Generate building placement on valid terrain adjacent to roads
 /// 
+/// Supported rules (all optional):
+/// - minAdjacentRoads: minimum number of adjacent road hexes (default 1)
+/// - maxAdjacentBuildings: maximum number of adjacent buildings, counting existingBuildings
+///   and buildings placed by this call (default unlimited)
+/// - minDistanceFromWater: minimum hex distance from any hex in waterHexes (default 0)
+/// - waterHexes: water positions used by minDistanceFromWater
+/// - existingBuildings: buildings already on the map, used by maxAdjacentBuildings
+/// - keepOut: hexes to keep clear, each with an optional radius: [{"q":0,"r":0,"radius":2},...]
+/// - scoring: switches from pure shuffle selection to ranking candidates by a weighted sum
+///   of features (shuffle order breaks ties):
+///   {"weights":{"adjacentRoads":1,"distanceToWater":0.5,"distanceToCenter":-0.2,"elevation":0},
+///    "center":{"q":0,"r":0},"maxWaterDistance":32,"elevation":[{"q":0,"r":0,"value":1.5},...]}
+///   Water distance is measured to waterHexes and capped at maxWaterDistance; hexes missing
+///   from the elevation list count as 0. Use negative weights to prefer small values.
+/// 
 /// @param valid_terrain_json - JSON array of valid terrain: [{"q":0,"r":0},...]
 /// @param road_network_json - JSON array of road coordinates: [{"q":0,"r":0},...]
 /// @param occupied_json - JSON array of occupied hexes: [{"q":0,"r":0},...]
-/// @param building_rules_json - JSON string with building rules: {"minAdjacentRoads":1}
+/// @param building_rules_json - JSON string with building rules: {"minAdjacentRoads":1,"maxAdjacentBuildings":2,"minDistanceFromWater":2,"waterHexes":[...],"keepOut":[...]}
 /// @param target_count - Target number of buildings to place
-/// @returns JSON array of building positions: [{"q":0,"r":0},...]
+/// @returns JSON array of building positions with their entrance road hex: [{"q":0,"r":0,"door":{"q":1,"r":0}},...] (door is null when no road is adjacent)
 #[wasm_bindgen]
 pub fn generate_building_placement(
     valid_terrain_json: String,
@@ -227,46 +435,34 @@ pub fn generate_building_placement(
     target_count: i32,
 ) -> String {
     let valid_terrain = parse_valid_terrain_json(&valid_terrain_json);
-    let roads = parse_valid_terrain_json(&road_network_json);
-    let occupied = parse_valid_terrain_json(&occupied_json);
-    
-    let roads_set: HashSet<(i32, i32)> = roads.iter().cloned().collect();
-    let occupied_set: HashSet<(i32, i32)> = occupied.iter().cloned().collect();
+    let roads_set = parse_valid_terrain_json(&road_network_json);
+    let occupied_set = parse_valid_terrain_json(&occupied_json);
     
     // Parse building rules
-    let mut min_adjacent_roads = 1;
-    let trimmed_rules = building_rules_json.trim();
-    if !trimmed_rules.is_empty() && trimmed_rules != "{}" {
-        // Simple JSON parsing for minAdjacentRoads
-        let chars: Vec<char> = trimmed_rules.chars().collect();
-        let mut i = 0;
-        while i < chars.len() {
-            if i + 18 < chars.len() && chars[i] == '"' && chars[i + 1] == 'm' && chars[i + 2] == 'i' 
-                && chars[i + 3] == 'n' && chars[i + 4] == 'A' && chars[i + 5] == 'd' 
-                && chars[i + 6] == 'j' && chars[i + 7] == 'a' && chars[i + 8] == 'c' 
-                && chars[i + 9] == 'e' && chars[i + 10] == 'n' && chars[i + 11] == 't' 
-                && chars[i + 12] == 'R' && chars[i + 13] == 'o' && chars[i + 14] == 'a' 
-                && chars[i + 15] == 'd' && chars[i + 16] == 's' && chars[i + 17] == '"' {
-                i += 18;
-                while i < chars.len() && (chars[i] == ':' || chars[i] == ' ' || chars[i] == '\t') {
-                    i += 1;
-                }
-                if i < chars.len() && chars[i].is_ascii_digit() {
-                    let start = i;
-                    i += 1;
-                    while i < chars.len() && chars[i].is_ascii_digit() {
-                        i += 1;
-                    }
-                    let num_str: String = chars[start..i].iter().collect();
-                    if let Ok(num) = num_str.parse::<i32>() {
-                        min_adjacent_roads = num;
-                    }
-                }
-                break;
+    let rules = parse_json_object(&building_rules_json);
+    let min_adjacent_roads = rules.get("minAdjacentRoads").and_then(|v| v.as_i32()).unwrap_or(1);
+    let max_adjacent_buildings = rules.get("maxAdjacentBuildings").and_then(|v| v.as_i32());
+    let min_distance_from_water = rules.get("minDistanceFromWater").and_then(|v| v.as_i32()).unwrap_or(0);
+    let water_hexes: HashSet<(i32, i32)> = rules.get("waterHexes")
+        .map(|v| v.as_hex_list().into_iter().collect())
+        .unwrap_or_default();
+    let mut buildings_set: HashSet<(i32, i32)> = rules.get("existingBuildings")
+        .map(|v| v.as_hex_list().into_iter().collect())
+        .unwrap_or_default();
+    
+    // Expand keep-out zones and water buffers into one blocked set
+    let mut blocked: HashSet<(i32, i32)> = HashSet::new();
+    if let Some(keep_out) = rules.get("keepOut").and_then(|v| v.as_array()) {
+        for zone in keep_out {
+            if let Some(center) = zone.as_hex() {
+                let radius = zone.get("radius").and_then(|v| v.as_i32()).unwrap_or(0);
+                blocked.extend(hexes_within_distance(&HashSet::from([center]), radius));
             }
-            i += 1;
         }
     }
+    if min_distance_from_water > 0 && !water_hexes.is_empty() {
+        blocked.extend(hexes_within_distance(&water_hexes, min_distance_from_water - 1));
+    }
     
     // Find available hexes for buildings
     let mut available_building_hexes: Vec<(i32, i32)> = Vec::new();
@@ -274,8 +470,8 @@ pub fn generate_building_placement(
     for (terrain_q, terrain_r) in &valid_terrain {
         let terrain_key = (*terrain_q, *terrain_r);
         
-        // Skip if occupied
-        if occupied_set.contains(&terrain_key) {
+        // Skip if occupied or inside a keep-out/water buffer
+        if occupied_set.contains(&terrain_key) || blocked.contains(&terrain_key) {
             continue;
         }
         
@@ -294,39 +490,114 @@ pub fn generate_building_placement(
         }
     }
     
+    // Sort so the shuffle only depends on content, not hash set iteration order
+    available_building_hexes.sort();
+    
     // Shuffle available building hexes
     if available_building_hexes.len() > 1 {
         // Use deterministic seed based on content
-        let mut seed: u64 = 0;
-        for (q, r) in &available_building_hexes {
-            seed = seed.wrapping_mul(31).wrapping_add((*q as u64).wrapping_mul(17).wrapping_add(*r as u64));
-        }
+        let seed = seed_from_coords(&available_building_hexes);
         
-        let mut rng_state = seed;
-        let mut rng = || {
-            rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-            rng_state
+        content_rng(seed, WORLD_STREAM_BUILDINGS).shuffle(&mut available_building_hexes);
+    }
+    
+    // Scoring mode: rank the shuffled candidates by weighted features
+    // (stable sort, so equal scores keep their shuffled order)
+    if let Some(scoring) = rules.get("scoring") {
+        let weight = |name: &str| {
+            scoring.get("weights").and_then(|w| w.get(name)).and_then(|v| v.as_f64()).unwrap_or(0.0)
         };
+        let road_weight = weight("adjacentRoads");
+        let water_weight = weight("distanceToWater");
+        let center_weight = weight("distanceToCenter");
+        let elevation_weight = weight("elevation");
+        let center = scoring.get("center").and_then(|v| v.as_hex()).unwrap_or((0, 0));
+        let max_water_distance = scoring.get("maxWaterDistance").and_then(|v| v.as_i32()).unwrap_or(32);
+        let water_distances = hex_distance_field(&water_hexes, max_water_distance);
+        let elevations: HashMap<(i32, i32), f64> = scoring.get("elevation")
+            .map(|v| v.as_hex_values())
+            .unwrap_or_default();
         
-        for i in (1..available_building_hexes.len()).rev() {
-            let j = (rng() % (i as u64 + 1)) as usize;
-            available_building_hexes.swap(i, j);
-        }
+        let score = |hex: &(i32, i32)| -> f64 {
+            let adjacent_roads = get_hex_neighbors(hex.0, hex.1)
+                .iter()
+                .filter(|n| roads_set.contains(n))
+                .count() as f64;
+            let water_distance = water_distances.get(hex).copied().unwrap_or(max_water_distance) as f64;
+            let center_distance = hex_distance(hex.0, hex.1, center.0, center.1) as f64;
+            let elevation = elevations.get(hex).copied().unwrap_or(0.0);
+            road_weight * adjacent_roads
+                + water_weight * water_distance
+                + center_weight * center_distance
+                + elevation_weight * elevation
+        };
+        
+        let mut scored: Vec<(f64, (i32, i32))> = available_building_hexes.iter().map(|hex| (score(hex), *hex)).collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        available_building_hexes = scored.into_iter().map(|(_, hex)| hex).collect();
     }
     
-    // Limit to target count
-    let building_count = target_count.min(available_building_hexes.len() as i32);
-    let selected_buildings = &available_building_hexes[0..(building_count as usize)];
+    // Take shuffled candidates until the target count is reached, enforcing the
+    // adjacency limit against existing and already-placed buildings
+    let mut selected_buildings: Vec<(i32, i32)> = Vec::new();
+    for hex in available_building_hexes {
+        if selected_buildings.len() as i32 >= target_count {
+            break;
+        }
+        if let Some(max_adjacent) = max_adjacent_buildings {
+            let adjacent_buildings = get_hex_neighbors(hex.0, hex.1)
+                .iter()
+                .filter(|neighbor| buildings_set.contains(neighbor))
+                .count() as i32;
+            if adjacent_buildings > max_adjacent {
+                continue;
+            }
+        }
+        buildings_set.insert(hex);
+        selected_buildings.push(hex);
+    }
     
-    // Convert to JSON
+    // Convert to JSON, attaching each building's front door road hex
     let mut json_parts = Vec::new();
     for (q, r) in selected_buildings {
-        json_parts.push(format!(r#"{{"q":{},"r":{}}}"#, q, r));
+        json_parts.push(format!(
+            r#"{{"q":{},"r":{},"door":{}}}"#,
+            q, r, door_json(front_door_hex(q, r, &roads_set))
+        ));
     }
     
     format!("[{}]", json_parts.join(","))
 }
 
+/// Choose the road hex a building's entrance faces
+/// Prefers the adjacent road with the most road neighbors (the better-connected street);
+/// ties are broken by neighbor direction order so the choice is deterministic.
+/// Returns None if the building has no adjacent road.
+pub fn front_door_hex(q: i32, r: i32, roads: &HashSet<(i32, i32)>) -> Option<(i32, i32)> {
+    let mut best: Option<((i32, i32), usize)> = None;
+    for neighbor in get_hex_neighbors(q, r) {
+        if !roads.contains(&neighbor) {
+            continue;
+        }
+        let connectivity = get_hex_neighbors(neighbor.0, neighbor.1)
+            .iter()
+            .filter(|n| roads.contains(n))
+            .count();
+        if best.is_none_or(|(_, best_connectivity)| connectivity > best_connectivity) {
+            best = Some((neighbor, connectivity));
+        }
+    }
+    best.map(|(hex, _)| hex)
+}
+
+/// Format an optional door hex as JSON ({"q":0,"r":0} or null)
+pub fn door_json(door: Option<(i32, i32)>) -> String {
+    match door {
+        Some((q, r)) => format!(r#"{{"q":{},"r":{}}}"#, q, r),
+        None => "null".to_string(),
+    }
+}
+
 /// Batch convert hex coordinates to world positions
 /// 
 /// @param hex_coords_json - JSON array of hex coordinates: [{"q":0,"r":0},...]
@@ -336,19 +607,9 @@ pub fn generate_building_placement(
 pub fn batch_hex_to_world(hex_coords_json: String, hex_size: f64) -> String {
     let hex_coords = parse_valid_terrain_json(&hex_coords_json);
     
-    // Formula for pointy-top hexagons:
-    // x = size * (√3 * q + √3/2 * r)
-    // z = size * (3/2 * r)
-    // Adjusted for the scaling factor used in TypeScript (hexSize / 1.34)
-    let adjusted_hex_size = hex_size / 1.34;
-    let sqrt3 = 3.0_f64.sqrt();
-    
     let mut json_parts = Vec::new();
     for (q, r) in hex_coords {
-        let q_f = q as f64;
-        let r_f = r as f64;
-        let x = adjusted_hex_size * (sqrt3 * 2.0 * q_f + sqrt3 * r_f);
-        let z = adjusted_hex_size * (3.0 * r_f);
+        let (x, z) = hex_to_world(q as f64, r as f64, hex_size);
         
         json_parts.push(format!(
             r#"{{"q":{},"r":{},"x":{},"z":{}}}"#,
@@ -359,3 +620,218 @@ pub fn batch_hex_to_world(hex_coords_json: String, hex_size: f64) -> String {
     format!("[{}]", json_parts.join(","))
 }
 
+
+/// Most hexes a world rectangle may cover in rasterize_polygon and import_tilemap_image
+const MAX_RASTER_HEXES: usize = 4_000_000;
+
+/// Whether a world rectangle holds more hex centers than MAX_RASTER_HEXES (estimated from the row and column spacing)
+fn exceeds_raster_limit(min_x: f64, min_z: f64, max_x: f64, max_z: f64, hex_size: f64) -> bool {
+    // Spacing between hex centers along a row and between rows
+    let (width, _) = hex_to_world(1.0, 0.0, hex_size);
+    let (_, depth) = hex_to_world(0.0, 1.0, hex_size);
+    let estimate = ((max_x - min_x) / width + 1.0) * ((max_z - min_z) / depth + 1.0);
+    // A NaN estimate (e.g. a NaN hex_size) counts as too many
+    estimate.is_nan() || estimate > MAX_RASTER_HEXES as f64
+}
+
+/// Even-odd test of a point against a polygon given as (x, z) vertices
+fn point_in_polygon(x: f64, z: f64, polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon {
+        let ((x1, z1), (x2, z2)) = (previous, current);
+        if (z1 > z) != (z2 > z) && x < x1 + (z - z1) * (x2 - x1) / (z2 - z1) {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Rasterize a world-space polygon to the hexes whose centers fall inside it
+/// 
+/// **Learning Point**: Only hexes whose centers lie in the polygon's bounding box
+/// can be inside, so those are enumerated row by row and each center is tested with
+/// the even-odd rule (a ray from the point crosses the outline an odd number of
+/// times iff the point is inside). Self-intersecting outlines work too: overlapping
+/// lobes cancel out like in SVG's evenodd fill. Passing a tile type turns the
+/// shape straight into pre-constraints, so freeform lakes, districts and exclusion
+/// zones never round-trip through JS hex by hex.
+/// 
+/// @param world_points - Float64Array of polygon vertices: [x0, z0, x1, z1, ...] (at least 3)
+/// @param hex_size - Hex size used by the renderer (see batch_hex_to_world)
+/// @param tile_type - Optional tile type (0-5) to set as a pre-constraint on every covered hex
+/// @returns JSON array of covered hexes sorted by (q, r): [{"q":0,"r":0},...], or "null" if there are
+///   fewer than 3 vertices, the tile type is invalid, or the polygon covers an unreasonably large area
+#[wasm_bindgen]
+pub fn rasterize_polygon(world_points: &[f64], hex_size: f64, tile_type: Option<i32>) -> String {
+    rasterize(world_points, hex_size, tile_type).unwrap_or_else(|_| "null".to_string())
+}
+
+/// rasterize_polygon with a structured result (see the errors module)
+/// 
+/// @returns JSON envelope: {"ok":[{"q":0,"r":0},...],"error":null}, or
+///   {"ok":null,"error":{"code":"invalid_argument","message":"..."}} naming the problem
+#[wasm_bindgen]
+pub fn rasterize_polygon_result(world_points: &[f64], hex_size: f64, tile_type: Option<i32>) -> String {
+    envelope(rasterize(world_points, hex_size, tile_type))
+}
+
+/// Rasterization shared by rasterize_polygon and its `_result` variant
+fn rasterize(world_points: &[f64], hex_size: f64, tile_type: Option<i32>) -> Result<String, ApiError> {
+    let invalid = |message: &str| ApiError::new(ErrorCode::InvalidArgument, message);
+    let polygon: Vec<(f64, f64)> = world_points.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+    if polygon.len() < 3 {
+        return Err(invalid("world_points: a polygon needs at least 3 vertices"));
+    }
+    if polygon.iter().any(|(x, z)| !x.is_finite() || !z.is_finite()) {
+        return Err(invalid("world_points: every coordinate must be finite"));
+    }
+    if hex_size <= 0.0 {
+        return Err(invalid("hex_size: must be positive"));
+    }
+    let tile = tile_type.map(tile_type_param).transpose()?;
+    
+    let (min_x, min_z, max_x, max_z) = polygon.iter().fold(
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        |(min_x, min_z, max_x, max_z), &(x, z)| (min_x.min(x), min_z.min(z), max_x.max(x), max_z.max(z)),
+    );
+    if exceeds_raster_limit(min_x, min_z, max_x, max_z, hex_size) {
+        return Err(invalid("the polygon covers too many hexes"));
+    }
+    
+    let mut covered: Vec<(i32, i32)> = hexes_in_world_rect(min_x, min_z, max_x, max_z, hex_size)
+        .into_iter()
+        .filter(|&(q, r)| {
+            let (x, z) = hex_to_world(q as f64, r as f64, hex_size);
+            point_in_polygon(x, z, &polygon)
+        })
+        .collect();
+    covered.sort();
+    
+    if let Some(tile) = tile {
+        let mut state = WFC_STATE.lock().unwrap();
+        for &(q, r) in &covered {
+            state.set_pre_constraint(q, r, tile);
+        }
+    }
+    
+    let json_parts: Vec<String> = covered.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{}}}"#, q, r))
+        .collect();
+    
+    Ok(format!("[{}]", json_parts.join(",")))
+}
+
+/// Import a tile map drawn as an indexed image
+/// 
+/// **Learning Point**: The image is laid over the world with one pixel per world
+/// unit, pixel (0, 0) covering x in [0, 1) and z in [0, 1). Every hex whose center
+/// falls on the image takes the tile its pixel's palette index maps to, so artists
+/// can draw a map at any resolution in a paint tool; a larger hex_size simply
+/// samples it more coarsely. Palette indices missing from the mapping are treated
+/// as transparent and leave their hexes untouched.
+/// 
+/// @param pixels - Uint8Array of palette indices, one byte per pixel, row-major (row 0 at z = 0)
+/// @param width - Image width in pixels
+/// @param height - Image height in pixels
+/// @param palette_to_tiletype_json - JSON object from palette index to tile name or number:
+///   {"0":"grass","1":"water","2":3}
+/// @param hex_size - Hex size used by the renderer (see batch_hex_to_world)
+/// @param layer - Where tiles are written: "constraint" (default, guides generation) or "grid"
+/// @returns Number of hexes written, or -1 if the sizes, palette or layer are invalid or hex_size
+///   is so small that the image would cover an unreasonable number of hexes
+#[wasm_bindgen]
+pub fn import_tilemap_image(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    palette_to_tiletype_json: String,
+    hex_size: f64,
+    layer: Option<String>,
+) -> i32 {
+    let palette_json = parse_json_object(&palette_to_tiletype_json);
+    apply_tilemap_image(pixels, width, height, &palette_json, hex_size, layer.as_deref()).unwrap_or(-1)
+}
+
+/// import_tilemap_image with a structured result (see the errors module)
+/// 
+/// Unlike import_tilemap_image, a malformed palette is an error instead of an empty mapping.
+/// 
+/// @returns JSON envelope: {"ok":N,"error":null} with the number of hexes written, or
+///   {"ok":null,"error":{"code","message"}} with code "invalid_json" or "invalid_argument"
+#[wasm_bindgen]
+pub fn import_tilemap_image_result(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    palette_to_tiletype_json: String,
+    hex_size: f64,
+    layer: Option<String>,
+) -> String {
+    envelope(parse_object_param(&palette_to_tiletype_json, "palette_to_tiletype_json")
+        .and_then(|palette_json| apply_tilemap_image(pixels, width, height, &palette_json, hex_size, layer.as_deref()))
+        .map(|written| written.to_string()))
+}
+
+/// Import shared by import_tilemap_image and its `_result` variant
+fn apply_tilemap_image(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    palette_json: &JsonValue,
+    hex_size: f64,
+    layer: Option<&str>,
+) -> Result<i32, ApiError> {
+    let invalid = |message: String| ApiError::new(ErrorCode::InvalidArgument, message);
+    let (width, height) = (width as usize, height as usize);
+    if width == 0 || height == 0 || width.checked_mul(height) != Some(pixels.len()) {
+        return Err(invalid("pixels must hold width * height bytes (both non-zero)".to_string()));
+    }
+    if hex_size <= 0.0 || exceeds_raster_limit(0.0, 0.0, width as f64, height as f64, hex_size) {
+        return Err(invalid("hex_size: must be positive and large enough to keep the hex count reasonable".to_string()));
+    }
+    let Some(layer) = TileLayer::from_name(layer.unwrap_or("constraint")) else {
+        return Err(invalid(r#"layer: expected "constraint" or "grid""#.to_string()));
+    };
+    
+    let Some(entries) = palette_json.as_object() else {
+        return Err(invalid("palette_to_tiletype_json: expected an object".to_string()));
+    };
+    let mut palette: [Option<TileType>; 256] = [None; 256];
+    for (key, value) in entries {
+        let Ok(index) = key.parse::<u8>() else {
+            return Err(invalid(format!("palette_to_tiletype_json.{}: keys must be palette indices 0-255", key)));
+        };
+        let tile = match value {
+            JsonValue::String(name) => TileType::from_name(name),
+            _ => value.as_i32().and_then(TileType::from_i32),
+        };
+        let Some(tile) = tile else {
+            return Err(invalid(format!("palette_to_tiletype_json.{}: unknown tile type", key)));
+        };
+        palette[index as usize] = Some(tile);
+    }
+    
+    let mut written = 0;
+    let mut state = WFC_STATE.lock().unwrap();
+    for (q, r) in hexes_in_world_rect(0.0, 0.0, width as f64, height as f64, hex_size) {
+        let (x, z) = hex_to_world(q as f64, r as f64, hex_size);
+        // A center exactly on the far edge belongs to the last pixel
+        let column = (x.floor() as usize).min(width - 1);
+        let row = (z.floor() as usize).min(height - 1);
+        if let Some(tile) = palette[pixels[row * width + column] as usize] {
+            state.set_layer_tile(layer, q, r, tile);
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Parse a JSON array parameter, treating anything else as empty
+fn parse_json_array(input: &str) -> Vec<JsonValue> {
+    match parse_json(input) {
+        Some(JsonValue::Array(items)) => items,
+        _ => Vec::new(),
+    }
+}
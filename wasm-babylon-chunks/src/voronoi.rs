@@ -1,8 +1,148 @@
 /// Voronoi region generation module
 
 use wasm_bindgen::prelude::*;
-use crate::types::{TileType, VoronoiSeed};
-use crate::hex_utils::{generate_hex_grid, hex_distance};
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::types::{HexCoord, TileType, VoronoiSeed};
+use crate::hex_utils::{cube_round, generate_hex_grid, hex_distance, get_hex_neighbors, CUBE_DIRECTIONS};
+use crate::json::{parse_json, parse_json_object, JsonValue};
+use crate::noise::value_noise_2d;
+use crate::stats::TileCounts;
+use crate::rng::{seed_from_coords, world_rng, Pcg32};
+
+/// World RNG stream for seed selection (see set_seed)
+const WORLD_STREAM_VORONOI: u64 = 3;
+
+/// Candidate draws per seed when seeds must keep a minimum distance apart
+const SEED_PLACEMENT_ATTEMPTS: usize = 30;
+
+/// Pick the grid index of the next Voronoi seed
+///
+/// Without a minimum distance the first candidate is used. With one, further
+/// candidates are drawn until one is at least min_seed_distance from every seed
+/// placed so far (dart-throwing Poisson-disk sampling); if none is, the candidate
+/// farthest from its nearest seed is used, so the seed count never drops.
+fn pick_seed_index(
+    hex_vec: &[(i32, i32)],
+    seeds: &[VoronoiSeed],
+    first_candidate: usize,
+    mut world: Option<&mut Pcg32>,
+    min_seed_distance: i32,
+) -> usize {
+    if min_seed_distance <= 0 || seeds.is_empty() {
+        return first_candidate;
+    }
+    let spacing = |index: usize| {
+        let (q, r) = hex_vec[index];
+        seeds.iter().map(|seed| hex_distance(q, r, seed.q, seed.r)).min().unwrap_or(i32::MAX)
+    };
+    let mut best = (spacing(first_candidate), first_candidate);
+    for attempt in 1..SEED_PLACEMENT_ATTEMPTS {
+        if best.0 >= min_seed_distance {
+            break;
+        }
+        let candidate = match world.as_deref_mut() {
+            Some(rng) => rng.next_below(hex_vec.len()),
+            None => (first_candidate + attempt * 7919) % hex_vec.len(),
+        };
+        let distance = spacing(candidate);
+        if distance > best.0 {
+            best = (distance, candidate);
+        }
+    }
+    best.1
+}
+
+/// Lloyd relaxation: move every seed to the centroid of its region and reassign
+///
+/// Stops early once no seed moves. Seeds that own no hexes stay where they are.
+fn relax_seeds(seeds: &mut [VoronoiSeed], hex_grid: &[HexCoord], metric: &VoronoiMetric, iterations: i32) {
+    let grid_hexes: HashSet<(i32, i32)> = hex_grid.iter().map(|hex| (hex.q, hex.r)).collect();
+    for _ in 0..iterations {
+        let mut sums = vec![(0.0f64, 0.0f64, 0usize); seeds.len()];
+        for hex in hex_grid {
+            let nearest = (0..seeds.len()).min_by(|a, b| metric.distance(hex, &seeds[*a]).total_cmp(&metric.distance(hex, &seeds[*b])));
+            if let Some(index) = nearest {
+                sums[index].0 += hex.q as f64;
+                sums[index].1 += hex.r as f64;
+                sums[index].2 += 1;
+            }
+        }
+        let mut moved = false;
+        for (seed, (sum_q, sum_r, count)) in seeds.iter_mut().zip(sums) {
+            if count == 0 {
+                continue;
+            }
+            let (q, r) = (sum_q / count as f64, sum_r / count as f64);
+            let centroid = cube_round(q, r, -q - r);
+            // A weighted or warped region's centroid can fall outside the grid
+            if centroid != (seed.q, seed.r) && grid_hexes.contains(&centroid) {
+                seed.q = centroid.0;
+                seed.r = centroid.1;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+/// Merge regions smaller than min_size hexes into their largest neighboring region
+///
+/// A region is a connected group of hexes of one tile type. The smallest undersized
+/// region is merged first and regions are relabelled after every merge, so a speck
+/// next to another speck ends up in the big region around both. A region with no
+/// neighbors (the whole grid) is left alone.
+fn merge_small_regions(assigned: &mut [((i32, i32), TileType)], min_size: i32) {
+    if min_size <= 1 {
+        return;
+    }
+    let index: HashMap<(i32, i32), usize> = assigned.iter().enumerate().map(|(i, (hex, _))| (*hex, i)).collect();
+    let adjacency: Vec<Vec<usize>> = assigned.iter()
+        .map(|((q, r), _)| get_hex_neighbors(*q, *r).into_iter().filter_map(|hex| index.get(&hex).copied()).collect())
+        .collect();
+    loop {
+        // Label regions in grid order
+        let mut labels = vec![usize::MAX; assigned.len()];
+        let mut members: Vec<Vec<usize>> = Vec::new();
+        for start in 0..assigned.len() {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+            let label = members.len();
+            let mut region = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            labels[start] = label;
+            while let Some(i) = queue.pop_front() {
+                region.push(i);
+                for &j in &adjacency[i] {
+                    if labels[j] == usize::MAX && assigned[j].1 == assigned[start].1 {
+                        labels[j] = label;
+                        queue.push_back(j);
+                    }
+                }
+            }
+            members.push(region);
+        }
+
+        let mut small: Vec<usize> = (0..members.len()).filter(|label| (members[*label].len() as i32) < min_size).collect();
+        small.sort_by_key(|label| (members[*label].len(), *label));
+        let merge = small.into_iter().find_map(|label| {
+            let target = members[label].iter()
+                .flat_map(|i| &adjacency[*i])
+                .map(|j| labels[*j])
+                .filter(|other| *other != label)
+                .max_by_key(|other| (members[*other].len(), std::cmp::Reverse(*other)))?;
+            Some((label, assigned[members[target][0]].1))
+        });
+        let Some((label, tile)) = merge else {
+            return;
+        };
+        for &i in &members[label] {
+            assigned[i].1 = tile;
+        }
+    }
+}
 
 /// Generate Voronoi regions for specified tile types
 /// 
@@ -16,7 +156,15 @@ use crate::hex_utils::{generate_hex_grid, hex_distance};
 /// @param forest_seeds - Number of forest region seeds
 /// @param water_seeds - Number of water region seeds
 /// @param grass_seeds - Number of grass region seeds
+/// @param min_seed_distance - Optional minimum hex distance between any two seeds (default 0 = no spacing);
+///   keeps seeds from clustering into sliver regions. Best effort: a seed that finds no spaced spot
+///   goes to the most isolated candidate it tried
+/// @param iterations - Optional Lloyd relaxation passes (default 0): each moves every seed to the
+///   centroid of its region, giving rounder, more evenly sized regions
+/// @param min_size - Optional smallest region size in hexes (default 0 = keep all); smaller connected
+///   regions are merged into their largest neighboring region, removing one-hex puddles and specks
 /// @returns JSON string with array of pre-constraints: [{"q":0,"r":0,"tileType":3},...]
+#[allow(clippy::too_many_arguments)]
 #[wasm_bindgen]
 pub fn generate_voronoi_regions(
     max_layer: i32,
@@ -25,7 +173,113 @@ pub fn generate_voronoi_regions(
     forest_seeds: i32,
     water_seeds: i32,
     grass_seeds: i32,
+    min_seed_distance: Option<i32>,
+    iterations: Option<i32>,
+    min_size: Option<i32>,
 ) -> String {
+    generate_voronoi_regions_with_metric(
+        max_layer,
+        center_q,
+        center_r,
+        forest_seeds,
+        water_seeds,
+        grass_seeds,
+        String::new(),
+        min_seed_distance,
+        iterations,
+        min_size,
+    )
+}
+
+/// Distance metric used to assign hexes to Voronoi seeds
+enum VoronoiMetric {
+    /// Plain hex distance: crisp, straight-edged cells
+    Hex,
+    /// Per-axis weighted cube distance: cells stretched along cheap axes
+    Weighted { q: f64, r: f64, s: f64 },
+    /// Hex distance measured from a noise-displaced position: ragged, organic borders
+    Noise { amplitude: f64, frequency: f64, seed: u64 },
+}
+
+impl VoronoiMetric {
+    /// Read a metric description; unknown or missing types fall back to plain hex distance
+    fn from_json(metric: &JsonValue) -> Self {
+        let number = |key: &str, default: f64| metric.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+        match metric.get("type").and_then(|value| value.as_str()) {
+            Some("weighted") => {
+                let weights = metric.get("weights");
+                let weight = |axis: &str| {
+                    weights.and_then(|w| w.get(axis)).and_then(|value| value.as_f64()).unwrap_or(1.0).max(0.0)
+                };
+                VoronoiMetric::Weighted { q: weight("q"), r: weight("r"), s: weight("s") }
+            }
+            Some("noise") => VoronoiMetric::Noise {
+                amplitude: number("amplitude", 2.0),
+                frequency: number("frequency", 0.15),
+                seed: number("seed", 0.0) as u64,
+            },
+            _ => VoronoiMetric::Hex,
+        }
+    }
+
+    fn distance(&self, hex: &HexCoord, seed: &VoronoiSeed) -> f64 {
+        let dq = (hex.q - seed.q) as f64;
+        let dr = (hex.r - seed.r) as f64;
+        match self {
+            VoronoiMetric::Hex => hex_distance(hex.q, hex.r, seed.q, seed.r) as f64,
+            VoronoiMetric::Weighted { q, r, s } => {
+                // Cube distance is half the sum of the axis deltas; weight each axis separately
+                (dq.abs() * q + dr.abs() * r + (dq + dr).abs() * s) / 2.0
+            }
+            VoronoiMetric::Noise { amplitude, frequency, seed: noise_seed } => {
+                // Displace the hex by two independent noise fields before measuring
+                let (x, y) = (hex.q as f64 * frequency, hex.r as f64 * frequency);
+                let wq = dq + amplitude * value_noise_2d(x, y, *noise_seed);
+                let wr = dr + amplitude * value_noise_2d(x, y, noise_seed.wrapping_add(1));
+                (wq.abs() + wr.abs() + (wq + wr).abs()) / 2.0
+            }
+        }
+    }
+}
+
+/// Generate Voronoi regions with a configurable distance metric
+/// 
+/// **Learning Point**: Voronoi cells are only as straight as the distance function.
+/// Plain hex distance gives crisp cells; weighting the cube axes stretches cells
+/// along the cheaper directions (ridges, valleys); measuring from a noise-displaced
+/// position (domain warping) turns the borders into ragged organic blobs. Seeds are
+/// placed exactly as in generate_voronoi_regions, so only the shapes change.
+/// 
+/// @param max_layer - Maximum layer of hexagon (determines grid size)
+/// @param center_q - Center q coordinate
+/// @param center_r - Center r coordinate
+/// @param forest_seeds - Number of forest region seeds
+/// @param water_seeds - Number of water region seeds
+/// @param grass_seeds - Number of grass region seeds
+/// @param metric_json - JSON object, empty for plain hex distance:
+///   {"type":"hex"} | {"type":"weighted","weights":{"q":1,"r":2,"s":1}} | {"type":"noise","amplitude":2,"frequency":0.15,"seed":7}
+/// @param min_seed_distance - Optional minimum distance between seeds (see generate_voronoi_regions)
+/// @param iterations - Optional Lloyd relaxation passes (see generate_voronoi_regions); centroids are
+///   taken over the regions the chosen metric produces
+/// @param min_size - Optional smallest region size (see generate_voronoi_regions)
+/// @returns JSON string with array of pre-constraints: [{"q":0,"r":0,"tileType":3},...]
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn generate_voronoi_regions_with_metric(
+    max_layer: i32,
+    center_q: i32,
+    center_r: i32,
+    forest_seeds: i32,
+    water_seeds: i32,
+    grass_seeds: i32,
+    metric_json: String,
+    min_seed_distance: Option<i32>,
+    iterations: Option<i32>,
+    min_size: Option<i32>,
+) -> String {
+    let metric = VoronoiMetric::from_json(&parse_json_object(&metric_json));
+    let min_seed_distance = min_seed_distance.unwrap_or(0);
+    
     // Generate hex grid
     let hex_grid = generate_hex_grid(max_layer, center_q, center_r);
     
@@ -50,8 +304,11 @@ pub fn generate_voronoi_regions(
     // Generate seed points by sampling from actual hex grid coordinates
     // Use deterministic selection with prime multiplier for good distribution
     // This ensures seeds are ALWAYS generated reliably
+    // With a world seed (see set_seed), seeds are drawn from it instead
+    // With min_seed_distance, crowded candidates are redrawn (see pick_seed_index)
     let mut seeds: Vec<VoronoiSeed> = Vec::new();
     let mut seed_counter: usize = 0;
+    let mut world = world_rng(seed_from_coords(&[(max_layer, 0), (center_q, center_r)]), WORLD_STREAM_VORONOI);
     
     // Generate forest seeds
     // Ensure we have at least 0 seeds (handle negative values)
@@ -60,7 +317,11 @@ pub fn generate_voronoi_regions(
         seed_counter += 1;
         // Use deterministic selection: (counter * prime) % count for good distribution
         // Prime 7919 provides good pseudo-random distribution
-        let index = ((seed_counter * 7919) + (i * 997)) % hex_count;
+        let candidate = match world.as_mut() {
+            Some(rng) => rng.next_below(hex_count),
+            None => ((seed_counter * 7919) + (i * 997)) % hex_count,
+        };
+        let index = pick_seed_index(&hex_vec, &seeds, candidate, world.as_mut(), min_seed_distance);
         // Bounds check (should always pass due to modulo, but be safe)
         if index < hex_vec.len() {
             let (q, r) = hex_vec[index];
@@ -76,7 +337,11 @@ pub fn generate_voronoi_regions(
     let water_count = if water_seeds > 0 { water_seeds as usize } else { 0 };
     for i in 0..water_count {
         seed_counter += 1;
-        let index = ((seed_counter * 7919) + (i * 997)) % hex_count;
+        let candidate = match world.as_mut() {
+            Some(rng) => rng.next_below(hex_count),
+            None => ((seed_counter * 7919) + (i * 997)) % hex_count,
+        };
+        let index = pick_seed_index(&hex_vec, &seeds, candidate, world.as_mut(), min_seed_distance);
         if index < hex_vec.len() {
             let (q, r) = hex_vec[index];
             seeds.push(VoronoiSeed {
@@ -91,7 +356,11 @@ pub fn generate_voronoi_regions(
     let grass_count = if grass_seeds > 0 { grass_seeds as usize } else { 0 };
     for i in 0..grass_count {
         seed_counter += 1;
-        let index = ((seed_counter * 7919) + (i * 997)) % hex_count;
+        let candidate = match world.as_mut() {
+            Some(rng) => rng.next_below(hex_count),
+            None => ((seed_counter * 7919) + (i * 997)) % hex_count,
+        };
+        let index = pick_seed_index(&hex_vec, &seeds, candidate, world.as_mut(), min_seed_distance);
         if index < hex_vec.len() {
             let (q, r) = hex_vec[index];
             seeds.push(VoronoiSeed {
@@ -120,6 +389,8 @@ pub fn generate_voronoi_regions(
         _ => {},
     }
     
+    relax_seeds(&mut seeds, &hex_grid, &metric, iterations.unwrap_or(0));
+    
     // Assign each hex to nearest seed and build JSON
     // Ensure seeds is not empty (should be guaranteed by fallback above)
     let seeds_ref = match seeds.as_slice() {
@@ -127,29 +398,30 @@ pub fn generate_voronoi_regions(
         s => s,
     };
     
-    let mut json_parts = Vec::new();
+    let mut assigned: Vec<((i32, i32), TileType)> = Vec::new();
     for hex in &hex_grid {
         let nearest_seed = seeds_ref.iter()
-            .min_by_key(|seed| hex_distance(hex.q, hex.r, seed.q, seed.r));
+            .min_by(|a, b| metric.distance(hex, a).total_cmp(&metric.distance(hex, b)));
         
-        match nearest_seed {
-            Some(seed) => {
-                json_parts.push(format!(
-                    r#"{{"q":{},"r":{},"tileType":{}}}"#,
-                    hex.q, hex.r, seed.tile_type as i32
-                ));
-            },
-            None => {},
+        if let Some(seed) = nearest_seed {
+            assigned.push(((hex.q, hex.r), seed.tile_type));
         }
     }
     
+    // Optional cleanup: fold undersized regions into their neighbors
+    merge_small_regions(&mut assigned, min_size.unwrap_or(0));
+    
+    let json_parts: Vec<String> = assigned.iter()
+        .map(|((q, r), tile)| format!(r#"{{"q":{},"r":{},"tileType":{}}}"#, q, r, tile.id()))
+        .collect();
+    
     // If json_parts is empty (shouldn't happen), return at least one entry from first seed
     let json_parts = match json_parts.as_slice() {
         [] => {
             match (seeds_ref.first(), hex_grid.first()) {
                 (Some(first_seed), _) => vec![format!(
                     r#"{{"q":{},"r":{},"tileType":{}}}"#,
-                    first_seed.q, first_seed.r, first_seed.tile_type as i32
+                    first_seed.q, first_seed.r, first_seed.tile_type.id()
                 )],
                 (None, Some(first_hex)) => vec![format!(
                     r#"{{"q":{},"r":{},"tileType":0}}"#,
@@ -175,3 +447,382 @@ pub fn generate_voronoi_regions(
     }
 }
 
+
+/// Voronoi region types whose seed counts generate_balanced can tune
+const BALANCED_TYPES: [TileType; 3] = [TileType::Forest, TileType::Water, TileType::Grass];
+
+/// Tile type percentages of a generate_voronoi_regions result
+fn voronoi_percentages(max_layer: i32, center_q: i32, center_r: i32, seeds: [i32; 3]) -> TileCounts {
+    let regions = generate_voronoi_regions(max_layer, center_q, center_r, seeds[0], seeds[1], seeds[2], None, None, None);
+    let mut counts = TileCounts::default();
+    for entry in parse_json(&regions).as_ref().and_then(|value| value.as_array()).unwrap_or_default() {
+        if let Some(tile) = entry.get("tileType").and_then(|value| value.as_i32()).and_then(TileType::from_i32) {
+            counts.add(tile);
+        }
+    }
+    counts
+}
+
+/// Generate Voronoi seed counts that hit tile-type percentage targets
+///
+/// **Learning Point**: Region sizes depend on where the seeds land, so the right seed
+/// counts can't be computed up front. Each attempt runs generate_voronoi_regions,
+/// measures the result and scales the seed count of every out-of-range type by
+/// target/actual (at least one seed per step). The best attempt is kept, so even a
+/// failed search returns the closest parameters found. Generation is deterministic:
+/// passing the returned seed counts to generate_voronoi_regions reproduces the map.
+///
+/// @param targets_json - JSON object with percentage ranges for forest/water/grass and generation options:
+///   {"forest":{"min":30,"max":45},"water":{"min":10,"max":20},"maxLayer":10,"center":{"q":0,"r":0},"seeds":{"forest":3,"water":2,"grass":4}}
+/// @param max_attempts - Maximum number of generations to try
+/// @returns JSON object: {"success":true,"attempts":4,"params":{"forestSeeds":5,"waterSeeds":2,"grassSeeds":4},"percentages":{"forest":38.1,"water":14.2,"grass":47.7}}
+#[wasm_bindgen]
+pub fn generate_balanced(targets_json: String, max_attempts: i32) -> String {
+    let targets = parse_json_object(&targets_json);
+    let max_layer = targets.get("maxLayer").and_then(|value| value.as_i32()).unwrap_or(10).max(0);
+    let (center_q, center_r) = targets.get("center").and_then(|value| value.as_hex()).unwrap_or((0, 0));
+    let hex_count = generate_hex_grid(max_layer, center_q, center_r).len() as i32;
+
+    let defaults = [3, 2, 4];
+    let mut seeds = [0i32; 3];
+    let mut ranges = [(0.0f64, 100.0f64); 3];
+    for (index, tile) in BALANCED_TYPES.iter().enumerate() {
+        seeds[index] = targets.get("seeds")
+            .and_then(|value| value.get(&tile.name()))
+            .and_then(|value| value.as_i32())
+            .unwrap_or(defaults[index])
+            .clamp(0, hex_count);
+        if let Some(range) = targets.get(&tile.name()) {
+            let min = range.get("min").and_then(|value| value.as_f64()).unwrap_or(0.0);
+            let max = range.get("max").and_then(|value| value.as_f64()).unwrap_or(100.0);
+            ranges[index] = (min, max);
+        }
+    }
+
+    let mut best: Option<(f64, [i32; 3], TileCounts)> = None;
+    let mut attempts = 0;
+    for _ in 0..max_attempts.max(1) {
+        attempts += 1;
+        let counts = voronoi_percentages(max_layer, center_q, center_r, seeds);
+
+        // Total distance outside the requested ranges (0 means every target is met)
+        let mut error = 0.0;
+        let mut next_seeds = seeds;
+        for (index, tile) in BALANCED_TYPES.iter().enumerate() {
+            let (min, max) = ranges[index];
+            let percent = counts.percent(*tile);
+            if percent >= min && percent <= max {
+                continue;
+            }
+            error += if percent < min { min - percent } else { percent - max };
+
+            // Aim for the middle of the range
+            let aim = (min + max) / 2.0;
+            let count = seeds[index];
+            let scaled = if percent > 0.0 { (count as f64 * aim / percent).round() as i32 } else { count + 1 };
+            next_seeds[index] = if percent < min {
+                scaled.max(count + 1).min(hex_count)
+            } else {
+                let floor = if min > 0.0 { 1 } else { 0 };
+                scaled.min(count - 1).max(floor)
+            };
+        }
+
+        if best.as_ref().is_none_or(|(best_error, _, _)| error < *best_error) {
+            best = Some((error, seeds, counts));
+        }
+        if error == 0.0 || next_seeds == seeds {
+            break;
+        }
+        seeds = next_seeds;
+    }
+
+    let (error, seeds, counts) = best.unwrap_or((f64::MAX, seeds, TileCounts::default()));
+    let percentage_parts: Vec<String> = BALANCED_TYPES.iter()
+        .map(|tile| format!(r#""{}":{:.2}"#, tile.name(), counts.percent(*tile)))
+        .collect();
+
+    format!(
+        r#"{{"success":{},"attempts":{},"params":{{"forestSeeds":{},"waterSeeds":{},"grassSeeds":{}}},"percentages":{{{}}}}}"#,
+        error == 0.0,
+        attempts,
+        seeds[0],
+        seeds[1],
+        seeds[2],
+        percentage_parts.join(",")
+    )
+}
+
+/// Generate Voronoi regions, then grow and shrink them to hit target area fractions
+///
+/// **Learning Point**: Seed counts only loosely control region sizes. This variant
+/// starts from generate_voronoi_regions and repeatedly lets the type with the
+/// largest deficit annex the boundary hexes of neighboring regions that are over
+/// their target, one boundary layer per iteration. Regions keep their organic shape
+/// because only hexes touching the growing region change hands. A region boxed in by
+/// on-target neighbors borrows from them, and they in turn grow into a surplus
+/// region later. A type with no region at all is started from the most interior hex
+/// of the largest surplus region.
+///
+/// @param max_layer - Maximum layer of hexagon (determines grid size)
+/// @param center_q - Center q coordinate
+/// @param center_r - Center r coordinate
+/// @param params_json - JSON object:
+///   {"fractions":{"forest":0.3,"water":0.15,"grass":0.55},"seeds":{"forest":3,"water":2,"grass":4},"tolerance":0.02,"maxIterations":200}
+///   fractions default to the initial Voronoi result for types not listed (they are scaled to fill the rest)
+/// @returns JSON string with array of pre-constraints: [{"q":0,"r":0,"tileType":3},...]
+#[wasm_bindgen]
+pub fn generate_voronoi_regions_balanced(max_layer: i32, center_q: i32, center_r: i32, params_json: String) -> String {
+    let params = parse_json_object(&params_json);
+    let seed_count = |tile: TileType, default: i32| {
+        params.get("seeds")
+            .and_then(|value| value.get(&tile.name()))
+            .and_then(|value| value.as_i32())
+            .unwrap_or(default)
+    };
+    let tolerance = params.get("tolerance").and_then(|value| value.as_f64()).unwrap_or(0.02).max(0.0);
+    let max_iterations = params.get("maxIterations").and_then(|value| value.as_i32()).unwrap_or(200).max(0);
+
+    let regions = generate_voronoi_regions(
+        max_layer,
+        center_q,
+        center_r,
+        seed_count(TileType::Forest, 3),
+        seed_count(TileType::Water, 2),
+        seed_count(TileType::Grass, 4),
+        None,
+        None,
+        None,
+    );
+    let mut assignment: HashMap<(i32, i32), TileType> = HashMap::new();
+    for entry in parse_json(&regions).as_ref().and_then(|value| value.as_array()).unwrap_or_default() {
+        let tile = entry.get("tileType").and_then(|value| value.as_i32()).and_then(TileType::from_i32);
+        if let (Some(hex), Some(tile)) = (entry.as_hex(), tile) {
+            assignment.insert(hex, tile);
+        }
+    }
+    let total = assignment.len();
+    let mut counts = TileCounts::default();
+    for tile in assignment.values() {
+        counts.add(*tile);
+    }
+
+    // Target hex counts; unlisted types share what is left in proportion to their current size
+    let mut targets = [0.0f64; TileType::COUNT];
+    let mut listed_fraction = 0.0;
+    let mut unlisted_count = 0;
+    for tile in BALANCED_TYPES {
+        match params.get("fractions").and_then(|value| value.get(&tile.name())).and_then(|value| value.as_f64()) {
+            Some(fraction) => {
+                targets[tile.index()] = fraction.max(0.0) * total as f64;
+                listed_fraction += fraction.max(0.0);
+            }
+            None => unlisted_count += counts.count(tile),
+        }
+    }
+    for tile in BALANCED_TYPES {
+        let listed = params.get("fractions").and_then(|value| value.get(&tile.name())).is_some();
+        if !listed && unlisted_count > 0 {
+            let rest = (1.0 - listed_fraction).max(0.0) * total as f64;
+            targets[tile.index()] = rest * counts.count(tile) as f64 / unlisted_count as f64;
+        }
+    }
+    let slack = tolerance * total as f64;
+
+    let mut sorted_hexes: Vec<(i32, i32)> = assignment.keys().cloned().collect();
+    sorted_hexes.sort();
+
+    let surplus = |tile: TileType, counts: &TileCounts| counts.count(tile) as f64 - targets[tile.index()];
+    // Hexes of a region accepted by `donor_ok` that touch the region of `grow`
+    let boundary = |assignment: &HashMap<(i32, i32), TileType>, grow: TileType, donor_ok: &dyn Fn(TileType) -> bool| {
+        sorted_hexes.iter()
+            .filter(|hex| {
+                let tile = assignment[*hex];
+                tile != grow
+                    && donor_ok(tile)
+                    && get_hex_neighbors(hex.0, hex.1).iter().any(|n| assignment.get(n) == Some(&grow))
+            })
+            .cloned()
+            .collect::<Vec<(i32, i32)>>()
+    };
+
+    for _ in 0..max_iterations {
+        if BALANCED_TYPES.iter().all(|tile| surplus(*tile, &counts).abs() <= slack) {
+            break;
+        }
+
+        // Types under target, most starved first
+        let mut deficits: Vec<TileType> = BALANCED_TYPES.into_iter().filter(|tile| surplus(*tile, &counts) < 0.0).collect();
+        deficits.sort_by(|a, b| surplus(*a, &counts).total_cmp(&surplus(*b, &counts)));
+
+        // The most starved type that borders an over-target region annexes one boundary layer
+        let growth = deficits.iter()
+            .map(|grow| (*grow, boundary(&assignment, *grow, &|tile| surplus(tile, &counts) > 0.0)))
+            .find(|(_, candidates)| !candidates.is_empty());
+
+        let (grow, candidates, donor_floor) = match (growth, deficits.first()) {
+            (Some((grow, candidates)), _) => (grow, candidates, 0.0),
+            (None, Some(&starved)) if counts.count(starved) == 0 => {
+                // Start the missing region at the most interior hex of the largest surplus region
+                let donor = BALANCED_TYPES.into_iter()
+                    .max_by(|a, b| surplus(*a, &counts).total_cmp(&surplus(*b, &counts)));
+                let start = donor.and_then(|donor| {
+                    sorted_hexes.iter()
+                        .filter(|hex| assignment[*hex] == donor)
+                        .max_by_key(|hex| {
+                            get_hex_neighbors(hex.0, hex.1).iter().filter(|n| assignment.get(*n) == Some(&donor)).count()
+                        })
+                        .cloned()
+                });
+                match start {
+                    Some(hex) => (starved, vec![hex], f64::MIN),
+                    None => break,
+                }
+            }
+            (None, Some(&starved)) => {
+                // Boxed in by regions at or under target: borrow from a less starved neighbor,
+                // which then grows into a surplus region on a later iteration
+                let level = surplus(starved, &counts);
+                let candidates = boundary(&assignment, starved, &|tile| surplus(tile, &counts) > level + 1.0);
+                if candidates.is_empty() {
+                    break;
+                }
+                (starved, candidates, level + 1.0)
+            }
+            (None, None) => break,
+        };
+
+        let mut needed = (-surplus(grow, &counts)).ceil().max(1.0) as usize;
+        for hex in candidates {
+            if needed == 0 {
+                break;
+            }
+            let previous = assignment[&hex];
+            // Earlier reassignments in this layer may have used up the donor's margin
+            if surplus(previous, &counts) <= donor_floor {
+                continue;
+            }
+            assignment.insert(hex, grow);
+            counts.remove(previous);
+            counts.add(grow);
+            needed -= 1;
+        }
+    }
+
+    let json_parts: Vec<String> = sorted_hexes.iter()
+        .map(|(q, r)| format!(r#"{{"q":{},"r":{},"tileType":{}}}"#, q, r, assignment[&(*q, *r)].id()))
+        .collect();
+
+    format!("[{}]", json_parts.join(","))
+}
+
+/// Hex one step from another in CUBE_DIRECTIONS order
+fn step(hex: (i32, i32), direction: usize) -> (i32, i32) {
+    let offset = CUBE_DIRECTIONS[direction % 6];
+    (hex.0 + offset.q, hex.1 + offset.r)
+}
+
+/// A region border edge: the side of a hex facing its neighbor in CUBE_DIRECTIONS[direction]
+type BorderEdge = ((i32, i32), usize);
+
+/// Chain border edges into lines that keep their region on the same side
+///
+/// Walking around a region, the edge after (hex, d) turns the corner between
+/// directions d and d+1: if the hex in direction d+1 is outside the region the walk
+/// stays on this hex, otherwise it continues on that hex along its side d-1 (which
+/// faces the same outside hex). A line is open where it reaches the map edge.
+fn trace_border_lines(tiles: &HashMap<(i32, i32), i32>, edges: &[BorderEdge]) -> Vec<(Vec<BorderEdge>, bool)> {
+    let edge_set: HashSet<BorderEdge> = edges.iter().copied().collect();
+    let next = |(hex, direction): BorderEdge| {
+        let turn = step(hex, direction + 1);
+        let candidate = if tiles.get(&turn) == tiles.get(&hex) { (turn, (direction + 5) % 6) } else { (hex, (direction + 1) % 6) };
+        edge_set.contains(&candidate).then_some(candidate)
+    };
+    let has_previous: HashSet<BorderEdge> = edges.iter().filter_map(|edge| next(*edge)).collect();
+
+    let mut used: HashSet<BorderEdge> = HashSet::new();
+    let mut lines = Vec::new();
+    // Open lines first (they start where the previous edge would be off the map), then loops
+    let starts = edges.iter().filter(|edge| !has_previous.contains(*edge)).chain(edges.iter());
+    for &start in starts {
+        if used.contains(&start) {
+            continue;
+        }
+        let mut line = Vec::new();
+        let mut current = Some(start);
+        while let Some(edge) = current {
+            if !used.insert(edge) {
+                break;
+            }
+            line.push(edge);
+            current = next(edge);
+        }
+        let closed = current == Some(start);
+        lines.push((line, closed));
+    }
+    lines
+}
+
+/// Extract the borders between regions of differing tile types
+///
+/// **Learning Point**: A border hex is one with a neighbor of another type; the map
+/// edge is not a border. For outlines the hexes alone are not enough, so the loop
+/// mode returns the shared hex sides instead, chained in walking order around each
+/// region (counter-clockwise in CUBE_DIRECTIONS order, region on the inside). Every
+/// border is traced once from each side, so both regions get their own outline.
+///
+/// @param tiles_json - JSON array of typed hexes, e.g. a generate_voronoi_regions result:
+///   [{"q":0,"r":0,"tileType":3},...]
+/// @param as_loops - Optional: return ordered border lines instead of border hexes (default false)
+/// @returns JSON array of border hexes sorted by q, then r, with the other types they touch:
+///   [{"q":0,"r":0,"tileType":3,"borders":[0,4]},...]
+///   or, with as_loops, border lines: [{"tileType":3,"closed":true,"edges":[{"q":0,"r":0,"direction":2},...]},...]
+///   where each edge is the side of the hex facing its neighbor in that direction (0-5, see CUBE_DIRECTIONS);
+///   a line that runs into the map edge is not closed
+#[wasm_bindgen]
+pub fn extract_region_borders(tiles_json: String, as_loops: Option<bool>) -> String {
+    let mut tiles: HashMap<(i32, i32), i32> = HashMap::new();
+    for entry in parse_json(&tiles_json).as_ref().and_then(|value| value.as_array()).unwrap_or_default() {
+        if let (Some(hex), Some(tile)) = (entry.as_hex(), entry.get("tileType").and_then(|value| value.as_i32())) {
+            tiles.insert(hex, tile);
+        }
+    }
+    let mut sorted_hexes: Vec<(i32, i32)> = tiles.keys().copied().collect();
+    sorted_hexes.sort();
+
+    let mut edges: Vec<BorderEdge> = Vec::new();
+    let mut border_parts = Vec::new();
+    for &hex in &sorted_hexes {
+        let tile = tiles[&hex];
+        let mut borders: Vec<i32> = Vec::new();
+        for direction in 0..6 {
+            match tiles.get(&step(hex, direction)) {
+                Some(&other) if other != tile => {
+                    edges.push((hex, direction));
+                    borders.push(other);
+                }
+                _ => {}
+            }
+        }
+        if !borders.is_empty() {
+            borders.sort();
+            borders.dedup();
+            let borders: Vec<String> = borders.iter().map(|other| other.to_string()).collect();
+            border_parts.push(format!(r#"{{"q":{},"r":{},"tileType":{},"borders":[{}]}}"#, hex.0, hex.1, tile, borders.join(",")));
+        }
+    }
+
+    if !as_loops.unwrap_or(false) {
+        return format!("[{}]", border_parts.join(","));
+    }
+    let line_parts: Vec<String> = trace_border_lines(&tiles, &edges)
+        .iter()
+        .map(|(line, closed)| {
+            let edge_parts: Vec<String> = line.iter()
+                .map(|((q, r), direction)| format!(r#"{{"q":{},"r":{},"direction":{}}}"#, q, r, direction))
+                .collect();
+            format!(r#"{{"tileType":{},"closed":{},"edges":[{}]}}"#, tiles[&line[0].0], closed, edge_parts.join(","))
+        })
+        .collect();
+    format!("[{}]", line_parts.join(","))
+}
@@ -0,0 +1,209 @@
+//! Time-evolving weather fields module
+
+use wasm_bindgen::prelude::*;
+use std::sync::{LazyLock, Mutex};
+use crate::json::parse_json_object;
+use crate::hex_utils::{hex_to_world, UNIT_HEX_SIZE};
+use crate::noise::{fractal_noise_2d, value_noise_2d};
+
+/// Gauss-Legendre pieces per noise cell (the wind can turn by up to a full circle in one cell)
+const DRIFT_PIECES_PER_CELL: usize = 4;
+
+/// 5-point Gauss-Legendre nodes and weights on [-1, 1]
+const GAUSS_NODES: [f64; 5] = [-0.906_179_845_938_664, -0.538_469_310_105_683, 0.0, 0.538_469_310_105_683, 0.906_179_845_938_664];
+const GAUSS_WEIGHTS: [f64; 5] = [0.236_926_885_056_189, 0.478_628_670_499_366, 0.568_888_888_888_889, 0.478_628_670_499_366, 0.236_926_885_056_189];
+
+/// Integral of the unit wind vector from noise time 0 up to a whole noise cell
+#[derive(Clone, Copy, Debug, Default)]
+struct DriftCheckpoint {
+    cell: i64,
+    drift: (f64, f64),
+}
+
+/// Weather configuration; fields are pure functions of (hex, time)
+#[derive(Clone, Debug)]
+pub struct Weather {
+    seed: u64,
+    /// Noise frequency per hex (smaller = larger storm cells)
+    scale: f64,
+    /// How far clouds drift per time unit, in hexes
+    wind_speed: f64,
+    /// Largest local deviation of the wind from the prevailing direction, in radians
+    wind_variation: f64,
+    /// Rough share of the map under rain (0-1)
+    coverage: f64,
+    /// How fast the prevailing wind direction turns (noise cycles per time unit)
+    change_rate: f64,
+    octaves: u32,
+    /// Furthest whole-cell drift integral computed so far (see drift)
+    checkpoint: DriftCheckpoint,
+}
+
+/// One weather sample at a hex
+#[derive(Clone, Copy, Debug)]
+pub struct WeatherSample {
+    /// Rain intensity, 0 (dry) to 1 (downpour)
+    pub rain: f64,
+    /// Wind direction in radians in the world x/z plane (0 = +x, counter-clockwise toward +z)
+    pub wind_direction: f64,
+    /// Wind strength, 0-1 relative to the configured wind speed
+    pub wind_speed: f64,
+}
+
+impl Weather {
+    pub fn from_json(seed: u64, params_json: &str) -> Self {
+        let params = parse_json_object(params_json);
+        let number = |key: &str, default: f64| params.get(key).and_then(|value| value.as_f64()).unwrap_or(default);
+        Weather {
+            seed,
+            scale: number("scale", 0.08).max(0.0001),
+            wind_speed: number("windSpeed", 0.5),
+            wind_variation: number("windVariation", 0.6),
+            coverage: number("coverage", 0.4).clamp(0.0, 1.0),
+            change_rate: number("changeRate", 0.01),
+            octaves: number("octaves", 3.0).clamp(1.0, 8.0) as u32,
+            checkpoint: DriftCheckpoint::default(),
+        }
+    }
+
+    /// Prevailing wind direction at a time, in radians
+    fn prevailing_direction(&self, time: f64) -> f64 {
+        self.direction_at(time * self.change_rate)
+    }
+
+    /// Prevailing wind direction at a noise time (time × changeRate), in radians
+    fn direction_at(&self, u: f64) -> f64 {
+        value_noise_2d(u, 0.5, self.seed) * std::f64::consts::PI
+    }
+
+    /// Integral of the unit prevailing wind vector over noise time [from, to]
+    ///
+    /// The noise is smooth inside one lattice cell, so a few Gauss-Legendre pieces
+    /// per cell are accurate; callers pass ranges within one cell.
+    fn integrate_direction(&self, from: f64, to: f64) -> (f64, f64) {
+        let piece = (to - from) / DRIFT_PIECES_PER_CELL as f64;
+        let half = piece / 2.0;
+        let mut total = (0.0, 0.0);
+        for index in 0..DRIFT_PIECES_PER_CELL {
+            let middle = from + piece * (index as f64 + 0.5);
+            for (node, weight) in GAUSS_NODES.iter().zip(GAUSS_WEIGHTS) {
+                let direction = self.direction_at(middle + half * node);
+                total.0 += direction.cos() * weight * half;
+                total.1 += direction.sin() * weight * half;
+            }
+        }
+        total
+    }
+
+    /// Sample rain and wind at a hex
+    pub fn sample(&mut self, q: i32, r: i32, time: f64) -> WeatherSample {
+        let drift = self.drift(time);
+        self.sample_drifted(q, r, time, drift)
+    }
+
+    /// Sample with a precomputed drift (see drift), so batches compute it once
+    ///
+    /// The rain field is noise in a frame that drifts with the prevailing wind, so
+    /// storm cells move across the map instead of flickering in place.
+    pub fn sample_drifted(&self, q: i32, r: i32, time: f64, drift: (f64, f64)) -> WeatherSample {
+        let (x, z) = hex_to_world(q as f64, r as f64, UNIT_HEX_SIZE);
+
+        let (drift_x, drift_z) = drift;
+        let noise = fractal_noise_2d((x - drift_x) * self.scale, (z - drift_z) * self.scale, self.seed.wrapping_add(10), self.octaves);
+        // fBm value noise is roughly normal around 0 with spread ~0.2; rain falls where
+        // it is above the level that leaves about `coverage` of the map wet
+        let cutoff = (0.5 - self.coverage) * 0.35;
+        let rain = if self.coverage == 0.0 { 0.0 } else { ((noise - cutoff) * 4.0).clamp(0.0, 1.0) };
+
+        let local = value_noise_2d(x * self.scale, z * self.scale + time * self.change_rate, self.seed.wrapping_add(20));
+        let gust = value_noise_2d(x * self.scale * 2.0 + time * 0.1, z * self.scale * 2.0, self.seed.wrapping_add(30));
+        WeatherSample {
+            rain,
+            wind_direction: self.prevailing_direction(time) + local * self.wind_variation,
+            wind_speed: (0.6 + 0.4 * gust).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Total distance the clouds have drifted by a time
+    ///
+    /// The drift is the exact integral of the turning prevailing wind. Whole noise
+    /// cells are summed outward from time 0 and the furthest sum is kept, so moving
+    /// time forward only integrates the new cells and the result does not depend on
+    /// how time was advanced. Going back toward 0 sums again from the start.
+    pub fn drift(&mut self, time: f64) -> (f64, f64) {
+        if self.change_rate == 0.0 {
+            let direction = self.direction_at(0.0);
+            return (direction.cos() * self.wind_speed * time, direction.sin() * self.wind_speed * time);
+        }
+        let u = time * self.change_rate;
+        let cell = u.trunc() as i64;
+        let outward = self.checkpoint.cell.signum() * cell.signum() >= 0 && self.checkpoint.cell.abs() <= cell.abs();
+        if !outward {
+            self.checkpoint = DriftCheckpoint::default();
+        }
+        let step = cell.signum();
+        while self.checkpoint.cell != cell {
+            let from = self.checkpoint.cell as f64;
+            let (x, z) = self.integrate_direction(from, from + step as f64);
+            self.checkpoint.drift.0 += x;
+            self.checkpoint.drift.1 += z;
+            self.checkpoint.cell += step;
+        }
+        let (x, z) = self.integrate_direction(cell as f64, u);
+        let scale = self.wind_speed / self.change_rate;
+        ((self.checkpoint.drift.0 + x) * scale, (self.checkpoint.drift.1 + z) * scale)
+    }
+}
+
+/// Global weather configuration (thread-safe)
+pub static WEATHER: LazyLock<Mutex<Weather>> = LazyLock::new(|| Mutex::new(Weather::from_json(0, "{}")));
+
+/// Configure the weather fields
+///
+/// @param seed - Random seed
+/// @param params_json - JSON object (all optional):
+///   {"scale":0.08,"windSpeed":0.5,"windVariation":0.6,"coverage":0.4,"changeRate":0.01,"octaves":3}
+#[wasm_bindgen]
+pub fn configure_weather(seed: u32, params_json: String) {
+    *WEATHER.lock().unwrap() = Weather::from_json(seed as u64, &params_json);
+}
+
+/// Sample the weather at one hex
+///
+/// **Learning Point**: Weather is a set of scalar fields that are pure functions of
+/// position and time. Rain is fractal noise advected by the prevailing wind (the
+/// noise is sampled in a frame that drifts downwind), so storm fronts roll across
+/// the map smoothly and the same (hex, time) always gives the same weather - no
+/// per-frame simulation state to keep in sync between clients or chunks.
+///
+/// @param q - Hex q coordinate
+/// @param r - Hex r coordinate
+/// @param time - Game time (same units as windSpeed and changeRate)
+/// @returns JSON object: {"rain":0.42,"windDirection":1.57,"windSpeed":0.8}
+#[wasm_bindgen]
+pub fn sample_weather(q: i32, r: i32, time: f64) -> String {
+    let sample = WEATHER.lock().unwrap().sample(q, r, time);
+    format!(
+        r#"{{"rain":{:.4},"windDirection":{:.4},"windSpeed":{:.4}}}"#,
+        sample.rain, sample.wind_direction, sample.wind_speed
+    )
+}
+
+/// Sample the weather at many hexes for particle systems and gameplay modifiers
+///
+/// @param coords - Int32Array of interleaved coordinates: [q0, r0, q1, r1, ...]
+/// @param time - Game time
+/// @returns Float32Array with three values per hex: [rain0, windDirection0, windSpeed0, rain1, ...]
+#[wasm_bindgen]
+pub fn sample_weather_batch(coords: &[i32], time: f64) -> Vec<f32> {
+    let mut weather = WEATHER.lock().unwrap();
+    let drift = weather.drift(time);
+    let mut values = Vec::with_capacity(coords.len() / 2 * 3);
+    for pair in coords.chunks_exact(2) {
+        let sample = weather.sample_drifted(pair[0], pair[1], time, drift);
+        values.push(sample.rain as f32);
+        values.push(sample.wind_direction as f32);
+        values.push(sample.wind_speed as f32);
+    }
+    values
+}
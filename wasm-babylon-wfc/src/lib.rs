@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 use std::sync::{LazyLock, Mutex};
+use wasm_rng::{clear_world_seed, content_rng, seed_from_coords, set_world_seed};
 use std::collections::{HashMap, HashSet, BinaryHeap};
 use std::cmp::Ordering;
 
@@ -794,11 +795,12 @@ pub fn generate_voronoi_regions(
     
     // Generate seed points
     let mut seeds: Vec<VoronoiSeed> = Vec::new();
+    let mut rng = content_rng(seed_from_coords(&[(max_layer, 0), (center_q, center_r)]), WORLD_STREAM_VORONOI);
     
     // Generate forest seeds
     for _ in 0..forest_seeds {
-        let q = (js_random() * (max_q - min_q + 1) as f64) as i32 + min_q;
-        let r = (js_random() * (max_r - min_r + 1) as f64) as i32 + min_r;
+        let q = (rng.next_f64() * (max_q - min_q + 1) as f64) as i32 + min_q;
+        let r = (rng.next_f64() * (max_r - min_r + 1) as f64) as i32 + min_r;
         seeds.push(VoronoiSeed {
             q,
             r,
@@ -808,8 +810,8 @@ pub fn generate_voronoi_regions(
     
     // Generate water seeds
     for _ in 0..water_seeds {
-        let q = (js_random() * (max_q - min_q + 1) as f64) as i32 + min_q;
-        let r = (js_random() * (max_r - min_r + 1) as f64) as i32 + min_r;
+        let q = (rng.next_f64() * (max_q - min_q + 1) as f64) as i32 + min_q;
+        let r = (rng.next_f64() * (max_r - min_r + 1) as f64) as i32 + min_r;
         seeds.push(VoronoiSeed {
             q,
             r,
@@ -819,8 +821,8 @@ pub fn generate_voronoi_regions(
     
     // Generate grass seeds
     for _ in 0..grass_seeds {
-        let q = (js_random() * (max_q - min_q + 1) as f64) as i32 + min_q;
-        let r = (js_random() * (max_r - min_r + 1) as f64) as i32 + min_r;
+        let q = (rng.next_f64() * (max_q - min_q + 1) as f64) as i32 + min_q;
+        let r = (rng.next_f64() * (max_r - min_r + 1) as f64) as i32 + min_r;
         seeds.push(VoronoiSeed {
             q,
             r,
@@ -1230,14 +1232,28 @@ pub fn generate_road_network_growing_tree(
     format!("[{}]", json_parts.join(","))
 }
 
-/// JavaScript random number generator
+/// World RNG stream for Voronoi seed placement (same stream as in wasm-babylon-chunks)
+const WORLD_STREAM_VORONOI: u64 = 3;
+
+/// Set the world seed used for Voronoi seed placement
+/// 
+/// **Learning Point**: Seed points used to come from JavaScript's Math.random()
+/// through a function attached to globalThis, which failed wherever the callback
+/// wasn't installed (e.g. Web Workers) and could never be reproduced. They are
+/// now drawn from a PCG32 seeded by (world seed, grid), the same generator and
+/// contract as set_seed in wasm-babylon-chunks (see wasm-rng): the same seed and
+/// parameters always reproduce the same world, and a new seed gives a new one.
+/// Without a seed, the grid alone decides.
 /// 
-/// **Learning Point**: WASM can't generate random numbers directly, so we
-/// call back to JavaScript's Math.random(). This is set up in the TypeScript code.
-/// The function is attached to globalThis in the TypeScript route handler.
+/// @param seed - World seed (a BigInt on the JavaScript side)
+#[wasm_bindgen]
+pub fn set_seed(seed: u64) {
+    set_world_seed(seed);
+}
+
+/// Forget the world seed, returning to purely content-based randomness
 #[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_name = "js_random")]
-    fn js_random() -> f64;
+pub fn clear_seed() {
+    clear_world_seed();
 }
 
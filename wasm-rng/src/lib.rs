@@ -0,0 +1,205 @@
+//! Seeded random number generation shared by the WASM crates
+//!
+//! **Learning Point**: Every crate that generates worlds draws its randomness
+//! from here, so one seed means the same thing everywhere: set_world_seed picks
+//! the world, and each decision draws from a PCG32 seeded by (world seed, the
+//! content being decided). Each WASM module links its own copy, so every module
+//! keeps its own world seed. This crate has no wasm_bindgen exports; each module
+//! exposes set_seed/clear_seed itself.
+
+use std::sync::Mutex;
+
+/// PCG32 generator (XSH RR variant, 64-bit state, 32-bit output)
+///
+/// **Learning Point**: An LCG's raw state has weak low bits; PCG advances the same
+/// kind of LCG but outputs a permuted (xorshifted, then rotated by the top bits)
+/// view of the state, which passes statistical tests with the same tiny state.
+/// The stream constant selects one of 2^63 independent sequences for a seed.
+#[derive(Clone, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Pcg32 { state: 0, increment: (stream << 1) | 1 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(6364136223846793005).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Uniform integer in 0..bound (returns 0 when bound is 0; bound is capped at u32::MAX)
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        let bound = bound.min(u32::MAX as usize) as u64;
+        ((self.next_u32() as u64 * bound) >> 32) as usize
+    }
+
+    /// Uniform float in [0, 1) with 53 bits of precision
+    pub fn next_f64(&mut self) -> f64 {
+        let high = (self.next_u32() as u64) << 21;
+        let low = (self.next_u32() >> 11) as u64;
+        (high | low) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Fisher-Yates shuffle of a slice in place
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// World seed set by set_world_seed (None until a seed is set)
+static WORLD_SEED: Mutex<Option<u64>> = Mutex::new(None);
+
+/// Set the world seed (the contract behind each module's set_seed export)
+///
+/// The same world seed and the same input always give the same output, and a
+/// different seed gives a different world.
+pub fn set_world_seed(seed: u64) {
+    *WORLD_SEED.lock().unwrap() = Some(seed);
+}
+
+/// Forget the world seed, returning to purely content-based randomness
+pub fn clear_world_seed() {
+    *WORLD_SEED.lock().unwrap() = None;
+}
+
+/// Generator for one seeded decision, or None if no world seed is set
+///
+/// @param content_seed - Seed derived from the input (e.g. seed_from_coords)
+/// @param stream - Distinguishes the functions that use the world seed
+pub fn world_rng(content_seed: u64, stream: u64) -> Option<Pcg32> {
+    let seed = (*WORLD_SEED.lock().unwrap())?;
+    Some(Pcg32::new(hash_values(seed, &[content_seed as i64]), stream))
+}
+
+/// Generator for one decision: from the world seed if one is set, otherwise from the content alone
+pub fn content_rng(content_seed: u64, stream: u64) -> Pcg32 {
+    world_rng(content_seed, stream).unwrap_or_else(|| Pcg32::new(content_seed, stream))
+}
+
+/// Derive a deterministic seed from a list of coordinates
+pub fn seed_from_coords(coords: &[(i32, i32)]) -> u64 {
+    let mut seed: u64 = 0;
+    for (q, r) in coords {
+        seed = seed.wrapping_mul(31).wrapping_add((*q as u64).wrapping_mul(17).wrapping_add(*r as u64));
+    }
+    seed
+}
+
+/// Stateless 64-bit hash of a seed and a list of values (SplitMix64 finalizer per value)
+///
+/// **Learning Point**: Streaming generators give different results depending on
+/// how many numbers were drawn before. Hashing a key (world seed plus the
+/// coordinates of the thing being decided) instead gives every decision its own
+/// reproducible value, so two chunks generated independently agree on it.
+pub fn hash_values(seed: u64, values: &[i64]) -> u64 {
+    let mut hash = seed ^ 0x9E37_79B9_7F4A_7C15;
+    for value in values {
+        hash = hash.wrapping_add(*value as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(rng: &mut Pcg32, count: usize) -> Vec<u32> {
+        (0..count).map(|_| rng.next_u32()).collect()
+    }
+
+    #[test]
+    fn pcg32_is_deterministic_per_seed_and_stream() {
+        let first = draws(&mut Pcg32::new(42, 1), 16);
+        assert_eq!(first, draws(&mut Pcg32::new(42, 1), 16));
+        assert_ne!(first, draws(&mut Pcg32::new(43, 1), 16));
+        assert_ne!(first, draws(&mut Pcg32::new(42, 2), 16));
+    }
+
+    #[test]
+    fn next_below_stays_in_range() {
+        let mut rng = Pcg32::new(7, 0);
+        assert_eq!(rng.next_below(0), 0);
+        for bound in [1, 2, 3, 6, 1000] {
+            for _ in 0..200 {
+                assert!(rng.next_below(bound) < bound);
+            }
+        }
+        // Every value of a small range shows up
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            seen[rng.next_below(6)] = true;
+        }
+        assert!(seen.iter().all(|hit| *hit));
+    }
+
+    #[test]
+    fn next_f64_is_in_unit_interval() {
+        let mut rng = Pcg32::new(9, 0);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let mut items: Vec<u32> = (0..50).collect();
+        Pcg32::new(3, 0).shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<u32>>());
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<u32>>());
+    }
+
+    // The only test that touches the world seed, so parallel tests can't change it mid-run
+    #[test]
+    fn world_seed_selects_the_generator() {
+        clear_world_seed();
+        assert!(world_rng(5, 1).is_none());
+        let content = draws(&mut content_rng(5, 1), 8);
+        assert_eq!(content, draws(&mut Pcg32::new(5, 1), 8));
+
+        set_world_seed(100);
+        let world = draws(&mut content_rng(5, 1), 8);
+        assert_eq!(world, draws(&mut world_rng(5, 1).unwrap(), 8));
+        assert_ne!(world, content);
+        assert_ne!(world, draws(&mut content_rng(6, 1), 8));
+
+        set_world_seed(101);
+        assert_ne!(world, draws(&mut content_rng(5, 1), 8));
+
+        clear_world_seed();
+        assert_eq!(content, draws(&mut content_rng(5, 1), 8));
+    }
+
+    #[test]
+    fn hash_values_depends_on_every_input() {
+        let base = hash_values(1, &[2, 3]);
+        assert_eq!(base, hash_values(1, &[2, 3]));
+        assert_ne!(base, hash_values(2, &[2, 3]));
+        assert_ne!(base, hash_values(1, &[3, 2]));
+        assert_ne!(base, hash_values(1, &[2, 3, 0]));
+    }
+
+    #[test]
+    fn seed_from_coords_is_order_sensitive() {
+        assert_eq!(seed_from_coords(&[]), 0);
+        assert_eq!(seed_from_coords(&[(1, 2), (3, 4)]), seed_from_coords(&[(1, 2), (3, 4)]));
+        assert_ne!(seed_from_coords(&[(1, 2), (3, 4)]), seed_from_coords(&[(3, 4), (1, 2)]));
+    }
+}